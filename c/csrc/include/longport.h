@@ -1748,6 +1748,123 @@ typedef void (*lb_order_changed_callback_t)(const struct lb_trade_context_t*,
                                             const struct lb_push_order_changed_t*,
                                             void*);
 
+/**
+ * Order
+ */
+typedef struct lb_order_t {
+  /**
+   * Order ID
+   */
+  const char *order_id;
+  /**
+   * Order status
+   */
+  enum lb_order_status_t status;
+  /**
+   * Stock name
+   */
+  const char *stock_name;
+  /**
+   * Submitted quantity
+   */
+  const struct lb_decimal_t *quantity;
+  /**
+   * Executed quantity
+   */
+  const struct lb_decimal_t *executed_quantity;
+  /**
+   * Submitted price (maybe null)
+   */
+  const struct lb_decimal_t *price;
+  /**
+   * Executed price (maybe null)
+   */
+  const struct lb_decimal_t *executed_price;
+  /**
+   * Submitted time
+   */
+  int64_t submitted_at;
+  /**
+   * Order side
+   */
+  enum lb_order_side_t side;
+  /**
+   * Security code
+   */
+  const char *symbol;
+  /**
+   * Order type
+   */
+  enum lb_order_type_t order_type;
+  /**
+   * Last done (maybe null)
+   */
+  const struct lb_decimal_t *last_done;
+  /**
+   * `LIT` / `MIT` Order Trigger Price (maybe null)
+   */
+  const struct lb_decimal_t *trigger_price;
+  /**
+   * Rejected Message or remark
+   */
+  const char *msg;
+  /**
+   * Order tag
+   */
+  enum lb_order_tag_t tag;
+  /**
+   * Time in force type
+   */
+  enum lb_time_in_force_type_t time_in_force;
+  /**
+   * Long term order expire date (maybe null)
+   */
+  const struct lb_date_t *expire_date;
+  /**
+   * Last updated time (maybe null)
+   */
+  const int64_t *updated_at;
+  /**
+   * Conditional order trigger time (maybe null)
+   */
+  const int64_t *trigger_at;
+  /**
+   * `TSMAMT` / `TSLPAMT` order trailing amount (maybe null)
+   */
+  const struct lb_decimal_t *trailing_amount;
+  /**
+   * `TSMPCT` / `TSLPPCT` order trailing percent (maybe null)
+   */
+  const struct lb_decimal_t *trailing_percent;
+  /**
+   * `TSLPAMT` / `TSLPPCT` order limit offset amount (maybe null)
+   */
+  const struct lb_decimal_t *limit_offset;
+  /**
+   * Conditional order trigger status (maybe null)
+   */
+  const enum lb_trigger_status_t *trigger_status;
+  /**
+   * Currency
+   */
+  const char *currency;
+  /**
+   * Enable or disable outside regular trading hours (maybe null)
+   */
+  const enum lb_outside_rth_t *outside_rth;
+  /**
+   * Remark
+   */
+  const char *remark;
+} lb_order_t;
+
+typedef void (*lb_orders_reconciled_callback_t)(const struct lb_trade_context_t*,
+                                                const struct lb_order_t*,
+                                                uintptr_t,
+                                                const struct lb_order_t*,
+                                                uintptr_t,
+                                                void*);
+
 /**
  * Options for get histroy executions request
  */
@@ -2703,116 +2820,6 @@ typedef struct lb_execution_t {
   const struct lb_decimal_t *price;
 } lb_execution_t;
 
-/**
- * Order
- */
-typedef struct lb_order_t {
-  /**
-   * Order ID
-   */
-  const char *order_id;
-  /**
-   * Order status
-   */
-  enum lb_order_status_t status;
-  /**
-   * Stock name
-   */
-  const char *stock_name;
-  /**
-   * Submitted quantity
-   */
-  const struct lb_decimal_t *quantity;
-  /**
-   * Executed quantity
-   */
-  const struct lb_decimal_t *executed_quantity;
-  /**
-   * Submitted price (maybe null)
-   */
-  const struct lb_decimal_t *price;
-  /**
-   * Executed price (maybe null)
-   */
-  const struct lb_decimal_t *executed_price;
-  /**
-   * Submitted time
-   */
-  int64_t submitted_at;
-  /**
-   * Order side
-   */
-  enum lb_order_side_t side;
-  /**
-   * Security code
-   */
-  const char *symbol;
-  /**
-   * Order type
-   */
-  enum lb_order_type_t order_type;
-  /**
-   * Last done (maybe null)
-   */
-  const struct lb_decimal_t *last_done;
-  /**
-   * `LIT` / `MIT` Order Trigger Price (maybe null)
-   */
-  const struct lb_decimal_t *trigger_price;
-  /**
-   * Rejected Message or remark
-   */
-  const char *msg;
-  /**
-   * Order tag
-   */
-  enum lb_order_tag_t tag;
-  /**
-   * Time in force type
-   */
-  enum lb_time_in_force_type_t time_in_force;
-  /**
-   * Long term order expire date (maybe null)
-   */
-  const struct lb_date_t *expire_date;
-  /**
-   * Last updated time (maybe null)
-   */
-  const int64_t *updated_at;
-  /**
-   * Conditional order trigger time (maybe null)
-   */
-  const int64_t *trigger_at;
-  /**
-   * `TSMAMT` / `TSLPAMT` order trailing amount (maybe null)
-   */
-  const struct lb_decimal_t *trailing_amount;
-  /**
-   * `TSMPCT` / `TSLPPCT` order trailing percent (maybe null)
-   */
-  const struct lb_decimal_t *trailing_percent;
-  /**
-   * `TSLPAMT` / `TSLPPCT` order limit offset amount (maybe null)
-   */
-  const struct lb_decimal_t *limit_offset;
-  /**
-   * Conditional order trigger status (maybe null)
-   */
-  const enum lb_trigger_status_t *trigger_status;
-  /**
-   * Currency
-   */
-  const char *currency;
-  /**
-   * Enable or disable outside regular trading hours (maybe null)
-   */
-  const enum lb_outside_rth_t *outside_rth;
-  /**
-   * Remark
-   */
-  const char *remark;
-} lb_order_t;
-
 /**
  * Account balance
  */
@@ -4249,6 +4256,17 @@ void lb_trade_context_set_on_order_changed(const struct lb_trade_context_t *ctx,
                                            void *userdata,
                                            lb_free_userdata_func_t free_userdata);
 
+/**
+ * Set orders reconciled callback, after reconnecting to the trade server or
+ * refreshing today's orders, it will call back to this function with the
+ * orders changed while disconnected and the orders removed from the order
+ * cache.
+ */
+void lb_trade_context_set_on_orders_reconciled(const struct lb_trade_context_t *ctx,
+                                               lb_orders_reconciled_callback_t callback,
+                                               void *userdata,
+                                               lb_free_userdata_func_t free_userdata);
+
 void lb_trade_context_subscribe(const struct lb_trade_context_t *ctx,
                                 const enum lb_topic_type_t *topics,
                                 uintptr_t num_topics,
@@ -253,6 +253,15 @@ pub unsafe extern "C" fn lb_quote_context_new(
                                 );
                             }
                         }
+                        PushEvent {
+                            detail:
+                                PushEventDetail::CustomCandlestick(_)
+                                | PushEventDetail::Bar(_)
+                                | PushEventDetail::Indicator(_)
+                                | PushEventDetail::SequenceGap(_)
+                                | PushEventDetail::Resynced(_),
+                            ..
+                        } => {}
                     }
                 }
             });
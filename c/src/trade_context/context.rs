@@ -23,7 +23,7 @@ use crate::{
             CEstimateMaxPurchaseQuantityResponseOwned, CExecutionOwned,
             CFundPositionsResponseOwned, CGetCashFlowOptions, CGetFundPositionsOptions,
             CGetHistoryExecutionsOptions, CGetHistoryOrdersOptions, CGetStockPositionsOptions,
-            CGetTodayExecutionsOptions, CGetTodayOrdersOptions, CMarginRatioOwned,
+            CGetTodayExecutionsOptions, CGetTodayOrdersOptions, CMarginRatioOwned, COrder,
             COrderDetailOwned, COrderOwned, CPushOrderChanged, CPushOrderChangedOwned,
             CReplaceOrderOptions, CStockPositionsResponseOwned, CSubmitOrderOptions,
             CSubmitOrderResponseOwned,
@@ -35,9 +35,13 @@ use crate::{
 pub type COnOrderChangedCallback =
     extern "C" fn(*const CTradeContext, *const CPushOrderChanged, *mut c_void);
 
+pub type COnOrdersReconciledCallback =
+    extern "C" fn(*const CTradeContext, *const COrder, usize, *const COrder, usize, *mut c_void);
+
 #[derive(Default)]
 struct Callbacks {
     order_changed: Option<Callback<COnOrderChangedCallback>>,
+    orders_reconciled: Option<Callback<COnOrdersReconciledCallback>>,
 }
 
 pub struct CTradeContextState {
@@ -119,6 +123,33 @@ pub unsafe extern "C" fn lb_trade_context_new(
                                 );
                             }
                         }
+                        PushEvent::OrdersReconciled(orders_reconciled) => {
+                            if let Some(callback) = &state.callbacks.orders_reconciled {
+                                let log_subscriber = ctx.ctx.log_subscriber();
+                                let _guard =
+                                    tracing::dispatcher::set_default(&log_subscriber.into());
+
+                                let s = Instant::now();
+                                tracing::info!("begin call on_orders_reconciled callback");
+
+                                let orders: CVec<COrderOwned> = orders_reconciled.orders.into();
+                                let removed: CVec<COrderOwned> = orders_reconciled.removed.into();
+                                (callback.f)(
+                                    Arc::as_ptr(&ctx),
+                                    orders.to_ffi_type(),
+                                    orders.len(),
+                                    removed.to_ffi_type(),
+                                    removed.len(),
+                                    callback.userdata,
+                                );
+
+                                tracing::info!(
+                                    duration = ?s.elapsed(),
+                                    "after call on_orders_reconciled callback"
+                                );
+                            }
+                        }
+                        _ => {}
                     }
                 }
             });
@@ -188,6 +219,24 @@ pub unsafe extern "C" fn lb_trade_context_set_on_order_changed(
     });
 }
 
+/// Set orders reconciled callback, after reconnecting to the trade server or
+/// refreshing today's orders, it will call back to this function with the
+/// orders changed while disconnected and the orders removed from the order
+/// cache.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn lb_trade_context_set_on_orders_reconciled(
+    ctx: *const CTradeContext,
+    callback: COnOrdersReconciledCallback,
+    userdata: *mut c_void,
+    free_userdata: CFreeUserDataFunc,
+) {
+    (*ctx).state.lock().callbacks.orders_reconciled = Some(Callback {
+        f: callback,
+        userdata,
+        free_userdata,
+    });
+}
+
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn lb_trade_context_subscribe(
     ctx: *const CTradeContext,
@@ -155,6 +155,9 @@ public class SdkNative {
 
         public static native void tradeContextSetOnOrderChanged(long context, OrderChangedHandler handler);
 
+        public static native void tradeContextSetOnOrdersReconciled(long context,
+                        OrdersReconciledHandler handler);
+
         public static native void tradeContextSubscribe(long context, TopicType[] topics, AsyncCallback callback);
 
         public static native void tradeContextUnsubscribe(long context, TopicType[] topics, AsyncCallback callback);
@@ -0,0 +1,5 @@
+package com.longport.trade;
+
+public interface OrdersReconciledHandler {
+    void onOrdersReconciled(Order[] orders, Order[] removed);
+}
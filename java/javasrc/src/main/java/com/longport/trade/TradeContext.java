@@ -39,6 +39,18 @@ public class TradeContext implements AutoCloseable {
         SdkNative.tradeContextSetOnOrderChanged(this.raw, handler);
     }
 
+    /**
+     * Set orders reconciled event callback, after reconnecting to the trade
+     * server or refreshing today's orders, it will call back to this handler
+     * with the orders changed while disconnected and the orders removed from
+     * the order cache.
+     * 
+     * @param handler A orders reconciled handler
+     */
+    public void setOnOrdersReconciled(OrdersReconciledHandler handler) {
+        SdkNative.tradeContextSetOnOrdersReconciled(this.raw, handler);
+    }
+
     /**
      * Subscribe
      * 
@@ -106,6 +106,11 @@ fn send_push_event(jvm: &JavaVM, callbacks: &Callbacks, event: PushEvent) -> Res
                 )?;
             }
         }
+        PushEventDetail::CustomCandlestick(_)
+        | PushEventDetail::Bar(_)
+        | PushEventDetail::Indicator(_)
+        | PushEventDetail::SequenceGap(_)
+        | PushEventDetail::Resynced(_) => {}
     }
 
     Ok(())
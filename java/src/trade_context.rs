@@ -29,6 +29,7 @@ use crate::{
 #[derive(Default)]
 struct Callbacks {
     order_changed: Option<GlobalRef>,
+    orders_reconciled: Option<GlobalRef>,
 }
 
 struct ContextObj {
@@ -51,6 +52,19 @@ fn send_push_event(jvm: &JavaVM, callbacks: &Callbacks, event: PushEvent) -> Res
                 )?;
             }
         }
+        PushEvent::OrdersReconciled(orders_reconciled) => {
+            if let Some(handler) = &callbacks.orders_reconciled {
+                let orders = ObjectArray(orders_reconciled.orders).into_jvalue(&mut env)?;
+                let removed = ObjectArray(orders_reconciled.removed).into_jvalue(&mut env)?;
+                env.call_method(
+                    handler,
+                    "onOrdersReconciled",
+                    "([Lcom/longport/trade/Order;[Lcom/longport/trade/Order;)V",
+                    &[orders.borrow(), removed.borrow()],
+                )?;
+            }
+        }
+        _ => {}
     }
 
     Ok(())
@@ -127,6 +141,24 @@ pub unsafe extern "system" fn Java_com_longport_SdkNative_tradeContextSetOnOrder
     })
 }
 
+#[unsafe(no_mangle)]
+pub unsafe extern "system" fn Java_com_longport_SdkNative_tradeContextSetOnOrdersReconciled(
+    mut env: JNIEnv,
+    _class: JClass,
+    ctx: i64,
+    handler: JObject,
+) {
+    let context = &*(ctx as *const ContextObj);
+    jni_result(&mut env, (), |env| {
+        if !handler.is_null() {
+            context.callbacks.lock().orders_reconciled = Some(env.new_global_ref(handler)?);
+        } else {
+            context.callbacks.lock().orders_reconciled = None;
+        }
+        Ok(())
+    })
+}
+
 #[unsafe(no_mangle)]
 pub unsafe extern "system" fn Java_com_longport_SdkNative_tradeContextSubscribe(
     mut env: JNIEnv,
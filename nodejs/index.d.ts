@@ -2313,6 +2313,13 @@ export class TradeContext {
    * will call back to this function.
    */
   setOnOrderChanged(callback: (err: null | Error, event: PushOrderChanged) => void): void
+  /**
+   * Set orders reconciled callback, after reconnecting to the trade server
+   * or refreshing today's orders, it will call back to this function with
+   * the orders changed while disconnected and the orders removed from the
+   * order cache.
+   */
+  setOnOrdersReconciled(callback: (err: null | Error, orders: Order[], removed: Order[]) => void): void
   /**
    * Subscribe
    *
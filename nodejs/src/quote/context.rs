@@ -125,6 +125,11 @@ impl QuoteContext {
                                 }
                             }
                         }
+                        PushEventDetail::CustomCandlestick(_)
+                        | PushEventDetail::Bar(_)
+                        | PushEventDetail::Indicator(_)
+                        | PushEventDetail::SequenceGap(_)
+                        | PushEventDetail::Resynced(_) => {}
                     }
                 }
             }
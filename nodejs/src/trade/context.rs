@@ -25,6 +25,7 @@ use crate::{
 #[derive(Default)]
 struct Callbacks {
     order_changed: Option<JsCallback<PushOrderChanged>>,
+    orders_reconciled: Option<JsCallback<(Vec<Order>, Vec<Order>)>>,
 }
 
 /// Trade context
@@ -61,6 +62,26 @@ impl TradeContext {
                                 }
                             }
                         }
+                        PushEvent::OrdersReconciled(orders_reconciled) => {
+                            if let Some(callback) = &callbacks.orders_reconciled {
+                                let to_orders = |orders: Vec<longport::trade::Order>| {
+                                    orders
+                                        .into_iter()
+                                        .map(TryInto::try_into)
+                                        .collect::<Result<Vec<Order>>>()
+                                };
+                                if let (Ok(orders), Ok(removed)) = (
+                                    to_orders(orders_reconciled.orders),
+                                    to_orders(orders_reconciled.removed),
+                                ) {
+                                    callback.call(
+                                        Ok((orders, removed)),
+                                        ThreadsafeFunctionCallMode::Blocking,
+                                    );
+                                }
+                            }
+                        }
+                        _ => {}
                     }
                 }
             }
@@ -78,6 +99,22 @@ impl TradeContext {
         Ok(())
     }
 
+    /// Set orders reconciled callback, after reconnecting to the trade server
+    /// or refreshing today's orders, it will call back to this function with
+    /// the orders changed while disconnected and the orders removed from the
+    /// order cache.
+    #[napi(
+        ts_args_type = "callback: (err: null | Error, orders: Order[], removed: Order[]) => void"
+    )]
+    pub fn set_on_orders_reconciled(&self, callback: JsFunction) -> Result<()> {
+        self.callbacks.lock().orders_reconciled =
+            Some(callback.create_threadsafe_function(32, |ctx| {
+                let (orders, removed) = ctx.value;
+                Ok(vec![orders, removed])
+            })?);
+        Ok(())
+    }
+
     /// Subscribe
     ///
     /// #### Example
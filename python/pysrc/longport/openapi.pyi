@@ -4831,6 +4831,11 @@ class TradeContext:
         Set order changed callback, after receiving the order changed event, it will call back to this function.
         """
 
+    def set_on_orders_reconciled(self, callback: Callable[[List[Order], List[Order]], None]) -> None:
+        """
+        Set orders reconciled callback, after reconnecting to the trade server or refreshing today's orders, it will call back to this function with the orders changed while disconnected and the orders removed from the order cache.
+        """
+
     def subscribe(self, topics: List[Type[TopicType]]) -> None:
         """
         Subscribe
@@ -14,6 +14,11 @@ pub(crate) fn handle_push_event(callbacks: &Callbacks, event: PushEvent) {
         PushEventDetail::Candlestick(candlestick) => {
             handle_candlesticks(callbacks, event.symbol, candlestick)
         }
+        PushEventDetail::CustomCandlestick(_)
+        | PushEventDetail::Bar(_)
+        | PushEventDetail::Indicator(_)
+        | PushEventDetail::SequenceGap(_)
+        | PushEventDetail::Resynced(_) => {}
     }
 }
 
@@ -31,6 +31,7 @@ use crate::{
 #[derive(Debug, Default)]
 pub(crate) struct Callbacks {
     pub(crate) order_changed: Option<PyObject>,
+    pub(crate) orders_reconciled: Option<PyObject>,
 }
 
 #[pyclass]
@@ -64,6 +65,18 @@ impl TradeContext {
         }
     }
 
+    /// Set orders reconciled callback, after reconnecting to the trade server
+    /// or refreshing today's orders, it will call back to this function with
+    /// the orders changed while disconnected and the orders removed from the
+    /// order cache.
+    fn set_on_orders_reconciled(&self, py: Python<'_>, callback: PyObject) {
+        if callback.is_none(py) {
+            self.callbacks.lock().orders_reconciled = None;
+        } else {
+            self.callbacks.lock().orders_reconciled = Some(callback);
+        }
+    }
+
     /// Subscribe
     fn subscribe(&self, topics: Vec<TopicType>) -> PyResult<()> {
         self.ctx
@@ -1,11 +1,15 @@
-use longport::trade::{PushEvent, PushOrderChanged};
-use pyo3::Python;
+use longport::trade::{PushEvent, PushOrderChanged, PushOrdersReconciled};
+use pyo3::{PyResult, Python};
 
 use crate::trade::context::Callbacks;
 
 pub(crate) fn handle_push_event(callbacks: &Callbacks, event: PushEvent) {
     match event {
         PushEvent::OrderChanged(order_changed) => handle_order_changed(callbacks, order_changed),
+        PushEvent::OrdersReconciled(orders_reconciled) => {
+            handle_orders_reconciled(callbacks, orders_reconciled)
+        }
+        _ => {}
     }
 }
 
@@ -21,3 +25,25 @@ fn handle_order_changed(callbacks: &Callbacks, order_changed: PushOrderChanged)
         });
     }
 }
+
+fn handle_orders_reconciled(callbacks: &Callbacks, orders_reconciled: PushOrdersReconciled) {
+    if let Some(callback) = &callbacks.orders_reconciled {
+        let _ = Python::with_gil(|py| {
+            callback.call1(
+                py,
+                (
+                    orders_reconciled
+                        .orders
+                        .into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<PyResult<Vec<crate::trade::types::Order>>>()?,
+                    orders_reconciled
+                        .removed
+                        .into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<PyResult<Vec<crate::trade::types::Order>>>()?,
+                ),
+            )
+        });
+    }
+}
@@ -0,0 +1,130 @@
+use rust_decimal::{prelude::FromPrimitive, Decimal};
+
+use crate::{Candlestick, Trade, UpdateAction, UpdateFields};
+
+/// Non-time-based bar type
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum BarType {
+    /// A bar is closed after the number of trades
+    Tick(u64),
+    /// A bar is closed after the volume is reached
+    Volume(i64),
+    /// A bar is closed after the turnover is reached
+    Turnover(Decimal),
+    /// A bar is closed before the difference between the high and the low
+    /// price exceeds the range
+    Range(Decimal),
+}
+
+/// Builds the non-time-based bars from the trades
+///
+/// The time of a bar is the time of its first trade. A trade is never split
+/// across the bars, so a volume or turnover bar may exceed the threshold by
+/// the size of its last trade. A bar is confirmed when the next bar is opened,
+/// just like the time-based candlesticks. A trade which does not update the
+/// price opens the next bar at the close of the full bar.
+#[derive(Debug, Clone)]
+pub struct BarBuilder {
+    bar_type: BarType,
+    lot_size: i64,
+    current: Option<Candlestick>,
+    ticks: u64,
+}
+
+impl BarBuilder {
+    /// Create a new `BarBuilder`, the `lot_size` is used to calculate the
+    /// turnover of the trades, see [`Market::lot_size`](crate::Market#structfield.lot_size)
+    pub fn new(bar_type: BarType, lot_size: i64) -> Self {
+        Self {
+            bar_type,
+            lot_size,
+            current: None,
+            ticks: 0,
+        }
+    }
+
+    /// Returns the bar type
+    #[inline]
+    pub fn bar_type(&self) -> BarType {
+        self.bar_type
+    }
+
+    /// Returns the bar which is being built
+    #[inline]
+    pub fn current(&self) -> Option<Candlestick> {
+        self.current
+    }
+
+    fn is_full(&self, current: &Candlestick, trade: &Trade) -> bool {
+        match self.bar_type {
+            BarType::Tick(count) => self.ticks >= count,
+            BarType::Volume(volume) => current.volume >= volume,
+            BarType::Turnover(turnover) => current.turnover >= turnover,
+            BarType::Range(range) => {
+                trade.update_fields.contains(UpdateFields::PRICE)
+                    && current.high.max(trade.price) - current.low.min(trade.price) > range
+            }
+        }
+    }
+
+    /// Merges the trade, the trades must be merged in order of time
+    pub fn merge_trade(&mut self, trade: &Trade) -> UpdateAction {
+        if trade.update_fields.is_empty() {
+            return UpdateAction::None;
+        }
+
+        let turnover =
+            trade.price * Decimal::from_i64(trade.volume * self.lot_size).unwrap_or_default();
+
+        match self.current {
+            Some(current) if !self.is_full(&current, trade) => {
+                let mut candlestick = current;
+                if trade.update_fields.contains(UpdateFields::PRICE) {
+                    candlestick.high = candlestick.high.max(trade.price);
+                    candlestick.low = candlestick.low.min(trade.price);
+                    candlestick.close = trade.price;
+                }
+                if trade.update_fields.contains(UpdateFields::VOLUME) {
+                    candlestick.volume += trade.volume;
+                    candlestick.turnover += turnover;
+                }
+                self.ticks += 1;
+                self.current = Some(candlestick);
+                UpdateAction::UpdateLast(candlestick)
+            }
+            current => {
+                // a trade which does not update the price opens the new bar at
+                // the close of the full bar, so its volume is not lost
+                let price = if trade.update_fields.contains(UpdateFields::PRICE) {
+                    trade.price
+                } else {
+                    match current {
+                        Some(current) => current.close,
+                        // the first bar cannot be opened without a price
+                        None => return UpdateAction::None,
+                    }
+                };
+                let new = Candlestick {
+                    time: trade.time,
+                    open: price,
+                    high: price,
+                    low: price,
+                    close: price,
+                    volume: if trade.update_fields.contains(UpdateFields::VOLUME) {
+                        trade.volume
+                    } else {
+                        0
+                    },
+                    turnover: if trade.update_fields.contains(UpdateFields::VOLUME) {
+                        turnover
+                    } else {
+                        Decimal::ZERO
+                    },
+                };
+                let confirmed = self.current.replace(new);
+                self.ticks = 1;
+                UpdateAction::AppendNew { confirmed, new }
+            }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use time::OffsetDateTime;
+
+use crate::Candlestick;
+
+/// The state of a calculation over a candlestick series
+pub(crate) trait IncrementalState: Clone {
+    /// The output of the calculation at a candlestick
+    type Output;
+
+    /// Advances the state with the candlestick, returns the output at it
+    fn next(&mut self, candlestick: &Candlestick) -> Option<Self::Output>;
+}
+
+/// Feeds the candlesticks to a state incrementally
+///
+/// The candlesticks are fed in order of time. The latest candlestick may be
+/// updated many times before it is confirmed, the output of an unconfirmed
+/// candlestick is calculated without changing the state. The pending
+/// candlestick is committed with its last update when a candlestick with a
+/// later time is fed, so the confirmations can be missed. A late candlestick
+/// between the last committed and the pending one is committed before the
+/// pending one, and the candlesticks older than the last committed one are
+/// ignored.
+#[derive(Debug, Clone)]
+pub(crate) struct Incremental<S> {
+    state: S,
+    pending: Option<Candlestick>,
+    last_time: Option<OffsetDateTime>,
+}
+
+impl<S: IncrementalState> Incremental<S> {
+    pub(crate) fn new(state: S) -> Self {
+        Self {
+            state,
+            pending: None,
+            last_time: None,
+        }
+    }
+
+    fn commit(&mut self, candlestick: &Candlestick) -> Option<S::Output> {
+        self.last_time = Some(candlestick.time);
+        self.state.next(candlestick)
+    }
+
+    /// Feeds a candlestick, returns the output at the candlestick
+    pub(crate) fn update(
+        &mut self,
+        candlestick: &Candlestick,
+        is_confirmed: bool,
+    ) -> Option<S::Output> {
+        if self.last_time.is_some_and(|time| candlestick.time <= time) {
+            return None;
+        }
+
+        if let Some(pending) = self.pending {
+            if pending.time < candlestick.time {
+                self.pending = None;
+                self.commit(&pending);
+            } else if pending.time > candlestick.time {
+                // the pending candlestick is kept
+                return self.commit(candlestick);
+            }
+        }
+
+        if is_confirmed {
+            self.pending = None;
+            self.commit(candlestick)
+        } else {
+            self.pending = Some(*candlestick);
+            self.state.clone().next(candlestick)
+        }
+    }
+}
@@ -0,0 +1,359 @@
+use std::collections::VecDeque;
+
+use rust_decimal::{Decimal, MathematicalOps};
+use time::{Date, OffsetDateTime};
+use time_tz::{OffsetDateTimeExt, Tz};
+
+use crate::{
+    incremental::{Incremental, IncrementalState},
+    Candlestick,
+};
+
+/// Technical indicator
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Indicator {
+    /// Simple moving average of the close prices
+    Sma(usize),
+    /// Exponential moving average of the close prices, seeded with the simple
+    /// moving average of the first `N` close prices
+    Ema(usize),
+    /// Relative strength index with the Wilder's smoothing
+    Rsi(usize),
+    /// Moving average convergence divergence
+    Macd {
+        /// Period of the fast EMA
+        fast: usize,
+        /// Period of the slow EMA
+        slow: usize,
+        /// Period of the signal EMA
+        signal: usize,
+    },
+    /// Bollinger bands
+    BollingerBands {
+        /// Period of the moving average
+        period: usize,
+        /// Multiplier of the standard deviation
+        multiplier: Decimal,
+    },
+    /// Average true range with the Wilder's smoothing
+    Atr(usize),
+    /// Volume weighted average price of the typical prices, reset at the start
+    /// of each day
+    Vwap,
+}
+
+/// Value of a technical indicator
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum IndicatorValue {
+    /// Value of the indicators with a single line
+    Value(Decimal),
+    /// Value of [`Indicator::Macd`]
+    Macd {
+        /// Fast EMA minus slow EMA
+        macd: Decimal,
+        /// EMA of the MACD
+        signal: Decimal,
+        /// MACD minus signal
+        histogram: Decimal,
+    },
+    /// Value of [`Indicator::BollingerBands`]
+    Bands {
+        /// Upper band
+        upper: Decimal,
+        /// Moving average
+        middle: Decimal,
+        /// Lower band
+        lower: Decimal,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Sma {
+    period: usize,
+    window: VecDeque<Decimal>,
+    sum: Decimal,
+}
+
+impl Sma {
+    fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            window: VecDeque::new(),
+            sum: Decimal::ZERO,
+        }
+    }
+
+    fn next(&mut self, value: Decimal) -> Option<Decimal> {
+        self.window.push_back(value);
+        self.sum += value;
+        if self.window.len() > self.period {
+            self.sum -= self.window.pop_front().unwrap_or_default();
+        }
+        (self.window.len() == self.period).then(|| self.sum / Decimal::from(self.period))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Ema {
+    period: usize,
+    count: usize,
+    sum: Decimal,
+    value: Option<Decimal>,
+}
+
+impl Ema {
+    fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            count: 0,
+            sum: Decimal::ZERO,
+            value: None,
+        }
+    }
+
+    fn next(&mut self, value: Decimal) -> Option<Decimal> {
+        match self.value {
+            Some(prev) => {
+                let alpha = Decimal::TWO / Decimal::from(self.period + 1);
+                self.value = Some(alpha * value + (Decimal::ONE - alpha) * prev);
+            }
+            None => {
+                self.count += 1;
+                self.sum += value;
+                if self.count == self.period {
+                    self.value = Some(self.sum / Decimal::from(self.period));
+                }
+            }
+        }
+        self.value
+    }
+}
+
+/// Wilder's smoothing, seeded with the simple average of the first `N` values
+#[derive(Debug, Clone)]
+struct Wilder {
+    period: usize,
+    count: usize,
+    value: Decimal,
+}
+
+impl Wilder {
+    fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            count: 0,
+            value: Decimal::ZERO,
+        }
+    }
+
+    fn next(&mut self, value: Decimal) -> Option<Decimal> {
+        let period = Decimal::from(self.period);
+        if self.count < self.period {
+            self.count += 1;
+            self.value += value / period;
+        } else {
+            self.value = (self.value * (period - Decimal::ONE) + value) / period;
+        }
+        (self.count == self.period).then_some(self.value)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum State {
+    Sma(Sma),
+    Ema(Ema),
+    Rsi {
+        prev_close: Option<Decimal>,
+        gain: Wilder,
+        loss: Wilder,
+    },
+    Macd {
+        fast: Ema,
+        slow: Ema,
+        signal: Ema,
+    },
+    BollingerBands {
+        sma: Sma,
+        multiplier: Decimal,
+    },
+    Atr {
+        prev_close: Option<Decimal>,
+        tr: Wilder,
+    },
+    Vwap {
+        timezone: &'static Tz,
+        date: Option<Date>,
+        amount: Decimal,
+        volume: Decimal,
+    },
+}
+
+impl State {
+    fn new(indicator: Indicator, timezone: &'static Tz) -> Self {
+        match indicator {
+            Indicator::Sma(period) => State::Sma(Sma::new(period)),
+            Indicator::Ema(period) => State::Ema(Ema::new(period)),
+            Indicator::Rsi(period) => State::Rsi {
+                prev_close: None,
+                gain: Wilder::new(period),
+                loss: Wilder::new(period),
+            },
+            Indicator::Macd { fast, slow, signal } => State::Macd {
+                fast: Ema::new(fast),
+                slow: Ema::new(slow),
+                signal: Ema::new(signal),
+            },
+            Indicator::BollingerBands { period, multiplier } => State::BollingerBands {
+                sma: Sma::new(period),
+                multiplier,
+            },
+            Indicator::Atr(period) => State::Atr {
+                prev_close: None,
+                tr: Wilder::new(period),
+            },
+            Indicator::Vwap => State::Vwap {
+                timezone,
+                date: None,
+                amount: Decimal::ZERO,
+                volume: Decimal::ZERO,
+            },
+        }
+    }
+}
+
+impl IncrementalState for State {
+    type Output = IndicatorValue;
+
+    fn next(&mut self, candlestick: &Candlestick) -> Option<IndicatorValue> {
+        let close = candlestick.close;
+
+        match self {
+            State::Sma(sma) => sma.next(close).map(IndicatorValue::Value),
+            State::Ema(ema) => ema.next(close).map(IndicatorValue::Value),
+            State::Rsi {
+                prev_close,
+                gain,
+                loss,
+            } => {
+                let change = close - prev_close.replace(close)?;
+                let avg_gain = gain.next(change.max(Decimal::ZERO));
+                let avg_loss = loss.next((-change).max(Decimal::ZERO));
+                let (avg_gain, avg_loss) = (avg_gain?, avg_loss?);
+                Some(IndicatorValue::Value(if avg_loss.is_zero() {
+                    Decimal::ONE_HUNDRED
+                } else {
+                    Decimal::ONE_HUNDRED
+                        - Decimal::ONE_HUNDRED / (Decimal::ONE + avg_gain / avg_loss)
+                }))
+            }
+            State::Macd { fast, slow, signal } => {
+                let (fast, slow) = (fast.next(close), slow.next(close));
+                let macd = fast? - slow?;
+                let signal = signal.next(macd)?;
+                Some(IndicatorValue::Macd {
+                    macd,
+                    signal,
+                    histogram: macd - signal,
+                })
+            }
+            State::BollingerBands { sma, multiplier } => {
+                let middle = sma.next(close)?;
+                let variance = sma
+                    .window
+                    .iter()
+                    .map(|value| (*value - middle) * (*value - middle))
+                    .sum::<Decimal>()
+                    / Decimal::from(sma.period);
+                let width = variance.sqrt().unwrap_or_default() * *multiplier;
+                Some(IndicatorValue::Bands {
+                    upper: middle + width,
+                    middle,
+                    lower: middle - width,
+                })
+            }
+            State::Atr { prev_close, tr } => {
+                let range = candlestick.high - candlestick.low;
+                let true_range = match prev_close.replace(close) {
+                    Some(prev_close) => range
+                        .max((candlestick.high - prev_close).abs())
+                        .max((candlestick.low - prev_close).abs()),
+                    None => range,
+                };
+                tr.next(true_range).map(IndicatorValue::Value)
+            }
+            State::Vwap {
+                timezone,
+                date,
+                amount,
+                volume,
+            } => {
+                let day = candlestick.time.to_timezone(*timezone).date();
+                if *date != Some(day) {
+                    *date = Some(day);
+                    *amount = Decimal::ZERO;
+                    *volume = Decimal::ZERO;
+                }
+                let typical_price = (candlestick.high + candlestick.low + close) / Decimal::from(3);
+                *amount += typical_price * Decimal::from(candlestick.volume);
+                *volume += Decimal::from(candlestick.volume);
+                (!volume.is_zero()).then(|| IndicatorValue::Value(*amount / *volume))
+            }
+        }
+    }
+}
+
+/// Calculates a technical indicator incrementally
+///
+/// The candlesticks are fed in order of time. The latest candlestick may be
+/// updated many times before it is confirmed, the value of an unconfirmed
+/// candlestick is calculated without changing the state. The previous
+/// candlestick is committed with its last update when a candlestick with a
+/// later time is fed, so the confirmations can be missed. A late candlestick
+/// between the last committed and the pending one is committed before the
+/// pending one, and the candlesticks older than the last committed one are
+/// ignored.
+#[derive(Debug, Clone)]
+pub struct IndicatorCalculator {
+    indicator: Indicator,
+    inner: Incremental<State>,
+    value: Option<(OffsetDateTime, IndicatorValue)>,
+}
+
+impl IndicatorCalculator {
+    /// Create a new `IndicatorCalculator`, the days of [`Indicator::Vwap`] are
+    /// split in the `timezone`
+    pub fn new(indicator: Indicator, timezone: &'static Tz) -> Self {
+        Self {
+            indicator,
+            inner: Incremental::new(State::new(indicator, timezone)),
+            value: None,
+        }
+    }
+
+    /// Returns the indicator
+    #[inline]
+    pub fn indicator(&self) -> Indicator {
+        self.indicator
+    }
+
+    /// Returns the latest value and the time of its candlestick
+    #[inline]
+    pub fn value(&self) -> Option<(OffsetDateTime, IndicatorValue)> {
+        self.value
+    }
+
+    /// Feeds a candlestick, returns the value of the indicator at the
+    /// candlestick
+    ///
+    /// Returns `None` if the candlestick is ignored or there are not enough
+    /// candlesticks to calculate the indicator.
+    pub fn update(
+        &mut self,
+        candlestick: &Candlestick,
+        is_confirmed: bool,
+    ) -> Option<IndicatorValue> {
+        let value = self.inner.update(candlestick, is_confirmed);
+        self.value = value.map(|value| (candlestick.time, value));
+        value
+    }
+}
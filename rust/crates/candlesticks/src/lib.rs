@@ -1,13 +1,21 @@
+mod bars;
 mod candlestick;
 mod find_session;
+mod incremental;
+mod indicators;
 mod market;
 pub mod markets;
+mod resample;
 pub mod testutil;
+mod transform;
 mod types;
 
+pub use bars::{BarBuilder, BarType};
 pub use candlestick::Candlestick;
+pub use indicators::{Indicator, IndicatorCalculator, IndicatorValue};
 pub use market::{
-    Days, Market, TradeSession, TradeSessionType, UpdateAction, TRADE_SESSION_NORMAL,
-    TRADE_SESSION_OVERNIGHT, TRADE_SESSION_POST, TRADE_SESSION_PRE,
+    Days, Market, TradeSession, TradeSessionTable, TradeSessionType, UpdateAction,
+    TRADE_SESSION_NORMAL, TRADE_SESSION_OVERNIGHT, TRADE_SESSION_POST, TRADE_SESSION_PRE,
 };
+pub use transform::{CandlestickTransform, CandlestickTransformer};
 pub use types::{Period, Quote, Trade, UpdateFields};
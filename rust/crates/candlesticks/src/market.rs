@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::{borrow::Cow, collections::HashSet};
 
 use rust_decimal::{prelude::FromPrimitive, Decimal};
 use time::{macros::time, Date, Duration, OffsetDateTime, Time, Weekday};
@@ -70,11 +70,14 @@ pub const TRADE_SESSION_PRE: TradeSessionType = TradeSessionType(1);
 pub const TRADE_SESSION_POST: TradeSessionType = TradeSessionType(2);
 pub const TRADE_SESSION_OVERNIGHT: TradeSessionType = TradeSessionType(3);
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/// Trade sessions of a market, indexed by [`TradeSessionType`]
+pub type TradeSessionTable = Cow<'static, [Cow<'static, [TradeSession]>]>;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Market {
     pub timezone: &'static Tz,
-    pub trade_sessions: &'static [&'static [TradeSession]],
-    pub half_trade_sessions: &'static [&'static [TradeSession]],
+    pub trade_sessions: TradeSessionTable,
+    pub half_trade_sessions: TradeSessionTable,
     pub lot_size: i64,
 }
 
@@ -106,7 +109,113 @@ impl Days for &HashSet<Date> {
     }
 }
 
+fn set_trade_sessions(
+    table: &mut TradeSessionTable,
+    ts: TradeSessionType,
+    trade_sessions: Vec<TradeSession>,
+) {
+    let table = table.to_mut();
+    if table.len() <= ts.0 {
+        table.resize(ts.0 + 1, Cow::Borrowed(&[]));
+    }
+    table[ts.0] = Cow::Owned(trade_sessions);
+}
+
+/// Converts the `(start, end)` time ranges to trade sessions, a range which
+/// ends before it starts wraps to the next day
+fn ranges_to_trade_sessions(ranges: impl IntoIterator<Item = (Time, Time)>) -> Vec<TradeSession> {
+    let mut trade_sessions = Vec::new();
+    for (start, end) in ranges {
+        if end > start {
+            trade_sessions.push(TradeSession::new(start, end));
+        } else {
+            trade_sessions.push(TradeSession::new(Time::MIDNIGHT, end));
+            trade_sessions.push(TradeSession::new(start, time!(23:59:59)).with_inclusive());
+        }
+    }
+    trade_sessions.retain(|trade_session| trade_session.start != trade_session.end);
+    trade_sessions.sort_by_key(|trade_session| trade_session.start);
+    trade_sessions
+}
+
 impl Market {
+    /// Create a market without trade sessions
+    pub fn new(timezone: &'static Tz, lot_size: i64) -> Self {
+        Self {
+            timezone,
+            trade_sessions: Cow::Borrowed(&[]),
+            half_trade_sessions: Cow::Borrowed(&[]),
+            lot_size,
+        }
+    }
+
+    /// Returns the trade sessions of the type
+    pub fn trade_sessions(&self, ts: TradeSessionType) -> &[TradeSession] {
+        self.trade_sessions
+            .get(ts.0)
+            .map(|v| &**v)
+            .unwrap_or_default()
+    }
+
+    /// Returns the trade sessions of the type on the half trading days
+    pub fn half_trade_sessions(&self, ts: TradeSessionType) -> &[TradeSession] {
+        self.half_trade_sessions
+            .get(ts.0)
+            .map(|v| &**v)
+            .unwrap_or_default()
+    }
+
+    /// Replaces the trade sessions of the type
+    ///
+    /// The sessions must be sorted by the start time and must not overlap.
+    #[must_use]
+    pub fn with_trade_sessions(
+        mut self,
+        ts: TradeSessionType,
+        trade_sessions: impl Into<Vec<TradeSession>>,
+    ) -> Self {
+        set_trade_sessions(&mut self.trade_sessions, ts, trade_sessions.into());
+        self
+    }
+
+    /// Replaces the trade sessions of the type on the half trading days
+    ///
+    /// The sessions must be sorted by the start time and must not overlap.
+    #[must_use]
+    pub fn with_half_trade_sessions(
+        mut self,
+        ts: TradeSessionType,
+        trade_sessions: impl Into<Vec<TradeSession>>,
+    ) -> Self {
+        set_trade_sessions(&mut self.half_trade_sessions, ts, trade_sessions.into());
+        self
+    }
+
+    /// Replaces the trade sessions of the type with the `(start, end)` time
+    /// ranges, such as the trading sessions returned by the server
+    ///
+    /// A range which ends before it starts wraps to the next day. The timeout
+    /// of the last session of the type is kept, so the closing auction of the
+    /// built-in markets is still merged into the last candlestick.
+    #[must_use]
+    pub fn with_trade_session_ranges(
+        mut self,
+        ts: TradeSessionType,
+        ranges: impl IntoIterator<Item = (Time, Time)>,
+    ) -> Self {
+        let timeout = self
+            .trade_sessions(ts)
+            .last()
+            .map(|trade_session| trade_session.timeout)
+            .unwrap_or_default();
+        let mut trade_sessions = ranges_to_trade_sessions(ranges);
+        if let Some(last) = trade_sessions.last_mut().filter(|last| !last.inclusive) {
+            last.timeout = timeout;
+        }
+        set_trade_sessions(&mut self.trade_sessions, ts, trade_sessions);
+        self
+    }
+
     pub fn candlestick_time<H>(
         &self,
         ts: TradeSessionType,
@@ -123,7 +232,7 @@ impl Market {
 
         let t = t.to_timezone(self.timezone);
         let time = t.time();
-        let trade_sessions = if !half_days.contains(t.date()) {
+        let trade_sessions: &[TradeSession] = if !half_days.contains(t.date()) {
             self.trade_sessions.get(ts.0)?
         } else {
             self.half_trade_sessions.get(ts.0)?
@@ -144,14 +253,21 @@ impl Market {
         Some(match period {
             Min_1 => t.replace_time(Time::from_hms(time.hour(), time.minute(), 0).ok()?),
             Min_2 | Min_3 | Min_5 | Min_10 | Min_15 | Min_20 | Min_30 | Min_45 | Min_60
-            | Min_120 | Min_180 | Min_240 => {
-                let minutes = period.minutes() as i64;
+            | Min_120 | Min_180 | Min_240 | Minutes(_) => {
+                let minutes = period.seconds() / 60;
                 let TradeSession { start, .. } = &trade_sessions[n];
                 let start_minutes = start.hour() as i64 * 60 + start.minute() as i64;
                 let current_minutes = time.hour() as i64 * 60 + time.minute() as i64;
                 let offset_minutes = ((current_minutes - start_minutes) / minutes) * minutes;
                 t.replace_time(*start + Duration::minutes(offset_minutes))
             }
+            Seconds(_) => {
+                let seconds = period.seconds();
+                let TradeSession { start, .. } = &trade_sessions[n];
+                let elapsed_seconds = (time - *start).whole_seconds();
+                let offset_seconds = (elapsed_seconds / seconds) * seconds;
+                t.replace_time(*start + Duration::seconds(offset_seconds))
+            }
             Day => t.replace_time(time!(00:00:00)),
             Week => {
                 let week = t.iso_week();
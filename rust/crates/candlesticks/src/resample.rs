@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+
+use rust_decimal::{prelude::FromPrimitive, Decimal};
+use time_tz::OffsetDateTimeExt;
+
+use crate::{Candlestick, Days, Market, Period, Trade, TradeSessionType, UpdateFields};
+
+#[derive(Default)]
+struct Buckets {
+    tails: HashMap<TradeSessionType, usize>,
+    candlesticks: Vec<(TradeSessionType, Candlestick)>,
+}
+
+impl Buckets {
+    fn tail(&self, ts: TradeSessionType) -> Option<Candlestick> {
+        self.tails.get(&ts).map(|index| self.candlesticks[*index].1)
+    }
+
+    fn update_tail(&mut self, ts: TradeSessionType, candlestick: Candlestick) {
+        let index = self.tails[&ts];
+        self.candlesticks[index].1 = candlestick;
+    }
+
+    fn push(&mut self, ts: TradeSessionType, candlestick: Candlestick) {
+        self.tails.insert(ts, self.candlesticks.len());
+        self.candlesticks.push((ts, candlestick));
+    }
+
+    fn into_candlesticks(mut self) -> Vec<(TradeSessionType, Candlestick)> {
+        self.candlesticks
+            .sort_by_key(|(_, candlestick)| candlestick.time);
+        self.candlesticks
+    }
+}
+
+impl Market {
+    /// Resamples the trades to the candlesticks of the period
+    ///
+    /// The trades must be sorted by time. The trades are merged in the same
+    /// way as [`Market::merge_trade`], except that the candlesticks of the day
+    /// and longer periods are also built from the trades of the normal
+    /// session. The returned candlesticks are sorted by time.
+    pub fn resample_trades<H, I>(
+        &self,
+        half_days: H,
+        period: Period,
+        trades: I,
+    ) -> Vec<(TradeSessionType, Candlestick)>
+    where
+        H: Days,
+        I: IntoIterator<Item = (TradeSessionType, Trade)>,
+    {
+        let mut buckets = Buckets::default();
+
+        for (ts, trade) in trades {
+            if !period.is_minute() && !ts.is_normal() {
+                continue;
+            }
+            let Some(time) = self.candlestick_time(ts, half_days, period, trade.time) else {
+                continue;
+            };
+            let time = time.to_timezone(time_tz::timezones::db::UTC);
+            let turnover =
+                trade.price * Decimal::from_i64(trade.volume * self.lot_size).unwrap_or_default();
+
+            match buckets.tail(ts) {
+                Some(mut candlestick) if candlestick.time == time => {
+                    if trade.update_fields.contains(UpdateFields::PRICE) {
+                        candlestick.high = candlestick.high.max(trade.price);
+                        candlestick.low = candlestick.low.min(trade.price);
+                        candlestick.close = trade.price;
+                    }
+                    if trade.update_fields.contains(UpdateFields::VOLUME) {
+                        candlestick.volume += trade.volume;
+                        candlestick.turnover += turnover;
+                    }
+                    buckets.update_tail(ts, candlestick);
+                }
+                Some(prev) if time > prev.time => {
+                    let mut candlestick = Candlestick {
+                        time,
+                        open: prev.close,
+                        high: prev.close,
+                        low: prev.close,
+                        close: prev.close,
+                        volume: 0,
+                        turnover: Decimal::ZERO,
+                    };
+                    if trade.update_fields.contains(UpdateFields::PRICE) {
+                        candlestick.open = trade.price;
+                        candlestick.high = trade.price;
+                        candlestick.low = trade.price;
+                        candlestick.close = trade.price;
+                    }
+                    if trade.update_fields.contains(UpdateFields::VOLUME) {
+                        candlestick.volume = trade.volume;
+                        candlestick.turnover = turnover;
+                    }
+                    buckets.push(ts, candlestick);
+                }
+                Some(_) => {}
+                None => {
+                    if trade.update_fields.contains(UpdateFields::PRICE) {
+                        buckets.push(
+                            ts,
+                            Candlestick {
+                                time,
+                                open: trade.price,
+                                high: trade.price,
+                                low: trade.price,
+                                close: trade.price,
+                                volume: trade.volume,
+                                turnover,
+                            },
+                        );
+                    }
+                }
+            }
+        }
+
+        buckets.into_candlesticks()
+    }
+
+    /// Resamples the candlesticks of a shorter period to the candlesticks of
+    /// the period
+    ///
+    /// The candlesticks must be sorted by time, the trade session of each
+    /// candlestick is determined by [`Market::candlestick_trade_session`].
+    /// The candlesticks of the day and longer periods are built from the
+    /// candlesticks of the normal session. The returned candlesticks are
+    /// sorted by time.
+    pub fn resample_candlesticks<H, I>(
+        &self,
+        half_days: H,
+        period: Period,
+        candlesticks: I,
+    ) -> Vec<(TradeSessionType, Candlestick)>
+    where
+        H: Days,
+        I: IntoIterator<Item = Candlestick>,
+    {
+        let mut buckets = Buckets::default();
+
+        for input in candlesticks {
+            let Some(ts) = self.candlestick_trade_session(input.time) else {
+                continue;
+            };
+            if !period.is_minute() && !ts.is_normal() {
+                continue;
+            }
+            let Some(time) = self.candlestick_time(ts, half_days, period, input.time) else {
+                continue;
+            };
+            let time = time.to_timezone(time_tz::timezones::db::UTC);
+
+            match buckets.tail(ts) {
+                Some(mut candlestick) if candlestick.time == time => {
+                    candlestick.high = candlestick.high.max(input.high);
+                    candlestick.low = candlestick.low.min(input.low);
+                    candlestick.close = input.close;
+                    candlestick.volume += input.volume;
+                    candlestick.turnover += input.turnover;
+                    buckets.update_tail(ts, candlestick);
+                }
+                Some(prev) if time < prev.time => {}
+                _ => buckets.push(ts, Candlestick { time, ..input }),
+            }
+        }
+
+        buckets.into_candlesticks()
+    }
+}
@@ -0,0 +1,174 @@
+use crate::{
+    incremental::{Incremental, IncrementalState},
+    Candlestick, UpdateAction,
+};
+use rust_decimal::{Decimal, MathematicalOps};
+
+/// The number of decimal places of the percent changes and the log returns
+const RETURN_SCALE: u32 = 8;
+
+/// Candlestick transform
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum CandlestickTransform {
+    /// Heikin-Ashi candlesticks, the first open price is the average of the
+    /// open and close prices
+    HeikinAshi,
+    /// Percent changes of the prices relative to the previous close price
+    PercentChange,
+    /// Natural logarithm of the prices relative to the previous close price
+    LogReturn,
+}
+
+#[derive(Debug, Clone)]
+enum State {
+    HeikinAshi {
+        prev: Option<(Decimal, Decimal)>,
+    },
+    Return {
+        log: bool,
+        prev_close: Option<Decimal>,
+    },
+}
+
+impl State {
+    fn new(transform: CandlestickTransform) -> Self {
+        match transform {
+            CandlestickTransform::HeikinAshi => State::HeikinAshi { prev: None },
+            CandlestickTransform::PercentChange => State::Return {
+                log: false,
+                prev_close: None,
+            },
+            CandlestickTransform::LogReturn => State::Return {
+                log: true,
+                prev_close: None,
+            },
+        }
+    }
+}
+
+impl IncrementalState for State {
+    type Output = Candlestick;
+
+    fn next(&mut self, candlestick: &Candlestick) -> Option<Candlestick> {
+        match self {
+            State::HeikinAshi { prev } => {
+                let close =
+                    (candlestick.open + candlestick.high + candlestick.low + candlestick.close)
+                        / Decimal::from(4);
+                let open = match prev {
+                    Some((prev_open, prev_close)) => (*prev_open + *prev_close) / Decimal::TWO,
+                    None => (candlestick.open + candlestick.close) / Decimal::TWO,
+                };
+                *prev = Some((open, close));
+                Some(Candlestick {
+                    open,
+                    high: candlestick.high.max(open).max(close),
+                    low: candlestick.low.min(open).min(close),
+                    close,
+                    ..*candlestick
+                })
+            }
+            State::Return { log, prev_close } => {
+                let base = prev_close.replace(candlestick.close)?;
+                if base.is_zero() {
+                    return None;
+                }
+                let to_return = |price: Decimal| {
+                    let ratio = price / base;
+                    if *log {
+                        ratio.checked_ln().map(|value| value.round_dp(RETURN_SCALE))
+                    } else {
+                        Some(((ratio - Decimal::ONE) * Decimal::ONE_HUNDRED).round_dp(RETURN_SCALE))
+                    }
+                };
+                Some(Candlestick {
+                    open: to_return(candlestick.open)?,
+                    high: to_return(candlestick.high)?,
+                    low: to_return(candlestick.low)?,
+                    close: to_return(candlestick.close)?,
+                    ..*candlestick
+                })
+            }
+        }
+    }
+}
+
+/// Transforms a candlestick series incrementally
+///
+/// The candlesticks are fed in order of time with the same rules as
+/// [`IndicatorCalculator`](crate::IndicatorCalculator), the latest candlestick
+/// may be updated many times and is committed when it is confirmed or a
+/// candlestick with a later time is fed. The volumes and the turnovers are
+/// kept.
+#[derive(Debug, Clone)]
+pub struct CandlestickTransformer {
+    transform: CandlestickTransform,
+    inner: Incremental<State>,
+}
+
+impl CandlestickTransformer {
+    /// Create a new `CandlestickTransformer`
+    pub fn new(transform: CandlestickTransform) -> Self {
+        Self {
+            transform,
+            inner: Incremental::new(State::new(transform)),
+        }
+    }
+
+    /// Returns the transform
+    #[inline]
+    pub fn transform(&self) -> CandlestickTransform {
+        self.transform
+    }
+
+    /// Feeds a candlestick, returns the transformed candlestick
+    ///
+    /// Returns `None` if the candlestick is ignored or there are not enough
+    /// candlesticks to transform it, such as the first candlestick of the
+    /// returns.
+    pub fn update(&mut self, candlestick: &Candlestick, is_confirmed: bool) -> Option<Candlestick> {
+        self.inner.update(candlestick, is_confirmed)
+    }
+
+    /// Feeds the action returned by merging a trade or a quote, returns the
+    /// action of the transformed candlesticks
+    ///
+    /// [`UpdateAction::UpdateLast`] updates the pending candlestick, and
+    /// [`UpdateAction::AppendNew`] commits the confirmed candlestick before the
+    /// new one is fed.
+    pub fn apply(&mut self, action: &UpdateAction) -> UpdateAction {
+        match action {
+            UpdateAction::UpdateLast(candlestick) => self
+                .update(candlestick, false)
+                .map(UpdateAction::UpdateLast)
+                .unwrap_or(UpdateAction::None),
+            UpdateAction::AppendNew { confirmed, new } => {
+                let confirmed = confirmed.and_then(|confirmed| self.update(&confirmed, true));
+                match self.update(new, false) {
+                    Some(new) => UpdateAction::AppendNew { confirmed, new },
+                    None => confirmed
+                        .map(UpdateAction::UpdateLast)
+                        .unwrap_or(UpdateAction::None),
+                }
+            }
+            UpdateAction::None => UpdateAction::None,
+        }
+    }
+
+    /// Transforms the candlesticks sorted by time, the last candlestick is
+    /// kept pending if it is not confirmed
+    pub fn transform_candlesticks(
+        &mut self,
+        candlesticks: &[Candlestick],
+        last_confirmed: bool,
+    ) -> Vec<Candlestick> {
+        candlesticks
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, candlestick)| {
+                let is_confirmed = last_confirmed || idx + 1 < candlesticks.len();
+                self.update(candlestick, is_confirmed)
+            })
+            .collect()
+    }
+}
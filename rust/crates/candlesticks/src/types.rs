@@ -1,54 +1,65 @@
 use rust_decimal::Decimal;
 use time::OffsetDateTime;
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 #[allow(non_camel_case_types)]
-#[repr(u8)]
 pub enum Period {
-    Min_1 = 0,
-    Min_2 = 1,
-    Min_3 = 2,
-    Min_5 = 3,
-    Min_10 = 4,
-    Min_15 = 5,
-    Min_20 = 6,
-    Min_30 = 7,
-    Min_45 = 8,
-    Min_60 = 9,
-    Min_120 = 10,
-    Min_180 = 11,
-    Min_240 = 12,
-    Day = 100,
-    Week = 101,
-    Month = 102,
-    Quarter = 103,
-    Year = 104,
+    Min_1,
+    Min_2,
+    Min_3,
+    Min_5,
+    Min_10,
+    Min_15,
+    Min_20,
+    Min_30,
+    Min_45,
+    Min_60,
+    Min_120,
+    Min_180,
+    Min_240,
+    /// Custom period of `N` minutes, the candlesticks are aligned to the start
+    /// of each trade session
+    Minutes(u16),
+    /// Custom period of `N` seconds, the candlesticks are aligned to the start
+    /// of each trade session
+    Seconds(u32),
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
 }
 
 impl Period {
     #[inline]
-    pub(crate) fn minutes(&self) -> u8 {
+    pub(crate) fn seconds(&self) -> i64 {
         match self {
-            Period::Min_1 => 1,
-            Period::Min_2 => 2,
-            Period::Min_3 => 3,
-            Period::Min_5 => 5,
-            Period::Min_10 => 10,
-            Period::Min_15 => 15,
-            Period::Min_20 => 20,
-            Period::Min_30 => 30,
-            Period::Min_45 => 45,
-            Period::Min_60 => 60,
-            Period::Min_120 => 120,
-            Period::Min_180 => 180,
-            Period::Min_240 => 240,
+            Period::Min_1 => 60,
+            Period::Min_2 => 2 * 60,
+            Period::Min_3 => 3 * 60,
+            Period::Min_5 => 5 * 60,
+            Period::Min_10 => 10 * 60,
+            Period::Min_15 => 15 * 60,
+            Period::Min_20 => 20 * 60,
+            Period::Min_30 => 30 * 60,
+            Period::Min_45 => 45 * 60,
+            Period::Min_60 => 60 * 60,
+            Period::Min_120 => 120 * 60,
+            Period::Min_180 => 180 * 60,
+            Period::Min_240 => 240 * 60,
+            Period::Minutes(minutes) => (*minutes).max(1) as i64 * 60,
+            Period::Seconds(seconds) => (*seconds).max(1) as i64,
             _ => unreachable!(),
         }
     }
 
+    /// Returns `true` if the period is shorter than a day
     #[inline]
     pub(crate) fn is_minute(&self) -> bool {
-        (*self as u8) < 100
+        !matches!(
+            self,
+            Period::Day | Period::Week | Period::Month | Period::Quarter | Period::Year
+        )
     }
 }
 
@@ -0,0 +1,120 @@
+use longport_candlesticks::{BarBuilder, BarType, Trade, UpdateAction, UpdateFields};
+use time::{macros::datetime, Duration, OffsetDateTime};
+
+fn trades(prices: &[(i64, i64)]) -> Vec<Trade> {
+    let start = datetime!(2024-1-2 9:30:00 +8);
+    prices
+        .iter()
+        .enumerate()
+        .map(|(idx, (price, volume))| Trade {
+            time: start + Duration::seconds(idx as i64),
+            price: (*price).into(),
+            volume: *volume,
+            update_fields: UpdateFields::all(),
+        })
+        .collect()
+}
+
+/// Returns the open time, the open and the close price of the confirmed bars
+fn confirmed_bars(bar_type: BarType, trades: &[Trade]) -> Vec<(OffsetDateTime, i64, i64, i64)> {
+    let mut builder = BarBuilder::new(bar_type, 1);
+    let mut res = vec![];
+    for trade in trades {
+        if let UpdateAction::AppendNew {
+            confirmed: Some(bar),
+            ..
+        } = builder.merge_trade(trade)
+        {
+            res.push((
+                bar.time,
+                bar.open.try_into().unwrap(),
+                bar.close.try_into().unwrap(),
+                bar.volume,
+            ));
+        }
+    }
+    res
+}
+
+#[test]
+fn tick_bars() {
+    let trades = trades(&[(10, 1), (11, 1), (12, 1), (13, 1), (14, 1)]);
+    assert_eq!(
+        confirmed_bars(BarType::Tick(2), &trades),
+        vec![(trades[0].time, 10, 11, 2), (trades[2].time, 12, 13, 2)]
+    );
+}
+
+#[test]
+fn volume_and_turnover_bars() {
+    let trades = trades(&[
+        (10, 100),
+        (10, 300),
+        (11, 200),
+        (12, 50),
+        (12, 400),
+        (13, 10),
+    ]);
+    assert_eq!(
+        confirmed_bars(BarType::Volume(300), &trades),
+        vec![(trades[0].time, 10, 10, 400), (trades[2].time, 11, 12, 650)]
+    );
+    assert_eq!(
+        confirmed_bars(BarType::Turnover(2000.into()), &trades),
+        vec![
+            (trades[0].time, 10, 10, 400),
+            (trades[2].time, 11, 11, 200),
+            (trades[3].time, 12, 12, 450)
+        ]
+    );
+}
+
+#[test]
+fn range_bars() {
+    let trades = trades(&[(10, 1), (12, 1), (9, 1), (13, 1), (11, 1)]);
+    assert_eq!(
+        confirmed_bars(BarType::Range(2.into()), &trades),
+        vec![(trades[0].time, 10, 12, 2), (trades[2].time, 9, 9, 1)]
+    );
+}
+
+#[test]
+fn volume_only_trades() {
+    let mut builder = BarBuilder::new(BarType::Tick(10), 1);
+    let mut trade = trades(&[(10, 100)])[0];
+    trade.update_fields = UpdateFields::VOLUME;
+    assert_eq!(builder.merge_trade(&trade), UpdateAction::None);
+
+    trade.update_fields = UpdateFields::all();
+    builder.merge_trade(&trade);
+    trade.price = 50.into();
+    trade.update_fields = UpdateFields::VOLUME;
+    let UpdateAction::UpdateLast(bar) = builder.merge_trade(&trade) else {
+        panic!("expect update last");
+    };
+    assert_eq!((bar.close, bar.volume), (10.into(), 200));
+}
+
+#[test]
+fn volume_only_trade_at_threshold() {
+    let mut builder = BarBuilder::new(BarType::Volume(100), 1);
+    let mut trade = trades(&[(10, 100)])[0];
+    builder.merge_trade(&trade);
+
+    trade.price = 50.into();
+    trade.volume = 30;
+    trade.update_fields = UpdateFields::VOLUME;
+    let UpdateAction::AppendNew {
+        confirmed: Some(confirmed),
+        new,
+    } = builder.merge_trade(&trade)
+    else {
+        panic!("expect append new");
+    };
+    assert_eq!(confirmed.volume, 100);
+    assert_eq!(
+        (new.open, new.close, new.volume),
+        (10.into(), 10.into(), 30)
+    );
+    assert_eq!(new.turnover, 1500.into());
+}
@@ -0,0 +1,32 @@
+use longport_candlesticks::{
+    markets::{HK, US},
+    testutil::TestCandlestickTime,
+    Period, TRADE_SESSION_NORMAL, TRADE_SESSION_POST,
+};
+use time::macros::time;
+
+#[test]
+fn custom_minutes() {
+    let t = TestCandlestickTime::new(&HK, Period::Minutes(7));
+    t.check_time(TRADE_SESSION_NORMAL, time!(9:29:59), None);
+    t.check_time(TRADE_SESSION_NORMAL, time!(9:30:00), time!(9:30:00));
+    t.check_time(TRADE_SESSION_NORMAL, time!(9:36:59), time!(9:30:00));
+    t.check_time(TRADE_SESSION_NORMAL, time!(9:37:00), time!(9:37:00));
+    // aligned to the start of the afternoon session
+    t.check_time(TRADE_SESSION_NORMAL, time!(13:06:00), time!(13:00:00));
+    t.check_time(TRADE_SESSION_NORMAL, time!(13:07:00), time!(13:07:00));
+}
+
+#[test]
+fn custom_seconds() {
+    let t = TestCandlestickTime::new(&HK, Period::Seconds(90));
+    t.check_time(TRADE_SESSION_NORMAL, time!(9:30:00), time!(9:30:00));
+    t.check_time(TRADE_SESSION_NORMAL, time!(9:31:29), time!(9:30:00));
+    t.check_time(TRADE_SESSION_NORMAL, time!(9:31:30), time!(9:31:30));
+    t.check_time(TRADE_SESSION_NORMAL, time!(11:59:59), time!(11:58:30));
+    t.check_time(TRADE_SESSION_NORMAL, time!(12:00:00), None);
+
+    let t = TestCandlestickTime::new(&US, Period::Seconds(30));
+    t.check_time(TRADE_SESSION_POST, time!(16:00:29), time!(16:00:00));
+    t.check_time(TRADE_SESSION_POST, time!(16:00:30), time!(16:00:30));
+}
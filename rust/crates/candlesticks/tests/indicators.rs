@@ -0,0 +1,153 @@
+use longport_candlesticks::{Candlestick, Indicator, IndicatorCalculator, IndicatorValue};
+use rust_decimal::Decimal;
+use time::{macros::datetime, Duration};
+use time_tz::timezones::db::asia::HONG_KONG;
+
+fn candlestick(idx: i64, close: i64) -> Candlestick {
+    Candlestick {
+        time: datetime!(2024-1-2 9:30:00 +8) + Duration::minutes(idx),
+        open: close.into(),
+        high: (close + 1).into(),
+        low: (close - 1).into(),
+        close: close.into(),
+        volume: 100,
+        turnover: (close * 100).into(),
+    }
+}
+
+fn values(indicator: Indicator, closes: &[i64]) -> Vec<Option<IndicatorValue>> {
+    let mut calculator = IndicatorCalculator::new(indicator, HONG_KONG);
+    closes
+        .iter()
+        .enumerate()
+        .map(|(idx, close)| calculator.update(&candlestick(idx as i64, *close), true))
+        .collect()
+}
+
+fn value(n: i64) -> Option<IndicatorValue> {
+    Some(IndicatorValue::Value(n.into()))
+}
+
+#[test]
+fn sma() {
+    assert_eq!(
+        values(Indicator::Sma(3), &[1, 2, 3, 4, 8]),
+        vec![None, None, value(2), value(3), value(5)]
+    );
+}
+
+#[test]
+fn ema() {
+    // alpha = 2 / (3 + 1)
+    assert_eq!(
+        values(Indicator::Ema(3), &[1, 2, 3, 5, 8]),
+        vec![
+            None,
+            None,
+            value(2),
+            Some(IndicatorValue::Value(Decimal::new(35, 1))),
+            Some(IndicatorValue::Value(Decimal::new(575, 2)))
+        ]
+    );
+}
+
+#[test]
+fn rsi() {
+    let res = values(Indicator::Rsi(2), &[10, 12, 11, 11]);
+    assert_eq!(res[..2], [None, None]);
+    // avg gain = 1, avg loss = 0.5
+    assert_eq!(
+        res[2].map(|value| match value {
+            IndicatorValue::Value(value) => value.round_dp(4),
+            _ => unreachable!(),
+        }),
+        Some(Decimal::new(666667, 4))
+    );
+
+    assert_eq!(
+        values(Indicator::Rsi(2), &[10, 11, 12]).pop().unwrap(),
+        value(100)
+    );
+}
+
+#[test]
+fn bollinger_bands() {
+    assert_eq!(
+        values(
+            Indicator::BollingerBands {
+                period: 2,
+                multiplier: 2.into(),
+            },
+            &[1, 3],
+        ),
+        vec![
+            None,
+            Some(IndicatorValue::Bands {
+                upper: 4.into(),
+                middle: 2.into(),
+                lower: 0.into(),
+            })
+        ]
+    );
+}
+
+#[test]
+fn atr() {
+    // true ranges: 2, 3 (|13 - 10|), 2
+    assert_eq!(
+        values(Indicator::Atr(2), &[10, 12, 12]),
+        vec![
+            None,
+            Some(IndicatorValue::Value(Decimal::new(25, 1))),
+            Some(IndicatorValue::Value(Decimal::new(225, 2)))
+        ]
+    );
+}
+
+#[test]
+fn vwap_resets_each_day() {
+    let mut calculator = IndicatorCalculator::new(Indicator::Vwap, HONG_KONG);
+    assert_eq!(calculator.update(&candlestick(0, 10), true), value(10));
+    assert_eq!(calculator.update(&candlestick(1, 20), true), value(15));
+    assert_eq!(
+        calculator.update(&candlestick(24 * 60, 30), true),
+        value(30)
+    );
+}
+
+#[test]
+fn macd() {
+    let res = values(
+        Indicator::Macd {
+            fast: 1,
+            slow: 2,
+            signal: 1,
+        },
+        &[1, 3, 6],
+    );
+    assert_eq!(res[0], None);
+    assert_eq!(
+        res[1],
+        Some(IndicatorValue::Macd {
+            macd: 1.into(),
+            signal: 1.into(),
+            histogram: 0.into(),
+        })
+    );
+}
+
+#[test]
+fn unconfirmed_updates() {
+    let mut calculator = IndicatorCalculator::new(Indicator::Sma(2), HONG_KONG);
+    assert_eq!(calculator.update(&candlestick(0, 10), false), None);
+    // the last update of a candlestick is committed when the next one opens
+    assert_eq!(calculator.update(&candlestick(0, 20), false), None);
+    assert_eq!(calculator.update(&candlestick(1, 30), false), value(25));
+    assert_eq!(calculator.update(&candlestick(1, 40), false), value(30));
+    assert_eq!(calculator.update(&candlestick(1, 50), true), value(35));
+    assert_eq!(calculator.value().map(|(_, value)| value), value(35));
+
+    // stale candlesticks are ignored
+    assert_eq!(calculator.update(&candlestick(1, 60), false), None);
+    assert_eq!(calculator.update(&candlestick(2, 60), false), value(55));
+}
@@ -0,0 +1,81 @@
+use std::collections::HashSet;
+
+use longport_candlesticks::{
+    markets::HK, Candlestick, Period, Trade, UpdateFields, TRADE_SESSION_NORMAL,
+};
+use rust_decimal::Decimal;
+use time::{macros::datetime, OffsetDateTime};
+
+fn trade(time: OffsetDateTime, price: i64, volume: i64) -> Trade {
+    Trade {
+        time,
+        price: price.into(),
+        volume,
+        update_fields: UpdateFields::all(),
+    }
+}
+
+fn candlestick(time: OffsetDateTime, open: i64, close: i64, volume: i64) -> Candlestick {
+    Candlestick {
+        time,
+        open: open.into(),
+        high: open.max(close).into(),
+        low: open.min(close).into(),
+        close: close.into(),
+        volume,
+        turnover: Decimal::from(close * volume),
+    }
+}
+
+#[test]
+fn resample_trades() {
+    let trades = [
+        trade(datetime!(2024-1-2 9:30:10 +8), 10, 100),
+        trade(datetime!(2024-1-2 9:30:50 +8), 12, 200),
+        trade(datetime!(2024-1-2 9:31:05 +8), 11, 300),
+        // before the session
+        trade(datetime!(2024-1-2 9:20:00 +8), 100, 100),
+    ]
+    .map(|trade| (TRADE_SESSION_NORMAL, trade));
+
+    let res = HK.resample_trades(false, Period::Min_1, trades);
+    assert_eq!(res.len(), 2);
+    assert_eq!(res[0].0, TRADE_SESSION_NORMAL);
+    assert_eq!(res[0].1.time, datetime!(2024-1-2 1:30:00 UTC));
+    assert_eq!(res[0].1.open, 10.into());
+    assert_eq!(res[0].1.high, 12.into());
+    assert_eq!(res[0].1.close, 12.into());
+    assert_eq!(res[0].1.volume, 300);
+    assert_eq!(res[0].1.turnover, 3400.into());
+    assert_eq!(res[1].1.time, datetime!(2024-1-2 1:31:00 UTC));
+    assert_eq!(res[1].1.open, 11.into());
+
+    let res = HK.resample_trades(false, Period::Day, trades);
+    assert_eq!(res.len(), 1);
+    assert_eq!(res[0].1.time, datetime!(2024-1-1 16:00:00 UTC));
+    assert_eq!(res[0].1.volume, 600);
+}
+
+#[test]
+fn resample_candlesticks() {
+    let candlesticks = [
+        candlestick(datetime!(2024-1-2 11:58:00 +8), 10, 11, 100),
+        candlestick(datetime!(2024-1-2 11:59:00 +8), 11, 9, 100),
+        candlestick(datetime!(2024-1-2 13:00:00 +8), 9, 12, 100),
+    ];
+
+    let res = HK.resample_candlesticks(false, Period::Min_60, candlesticks);
+    assert_eq!(res.len(), 2);
+    assert_eq!(res[0].1.time, datetime!(2024-1-2 3:30:00 UTC));
+    assert_eq!(res[0].1.open, 10.into());
+    assert_eq!(res[0].1.high, 11.into());
+    assert_eq!(res[0].1.low, 9.into());
+    assert_eq!(res[0].1.close, 9.into());
+    assert_eq!(res[0].1.volume, 200);
+    assert_eq!(res[1].1.time, datetime!(2024-1-2 5:00:00 UTC));
+
+    // the afternoon session is closed on the half days
+    let half_days = HashSet::from([datetime!(2024-1-2 0:00 +8).date()]);
+    let res = HK.resample_candlesticks(&half_days, Period::Min_60, candlesticks);
+    assert_eq!(res.len(), 1);
+}
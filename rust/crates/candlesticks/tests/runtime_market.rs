@@ -0,0 +1,65 @@
+use longport_candlesticks::{
+    markets::{HK, US},
+    testutil::TestCandlestickTime,
+    Market, Period, TradeSession, TRADE_SESSION_NORMAL, TRADE_SESSION_OVERNIGHT,
+};
+use time::{
+    macros::{datetime, time},
+    Duration,
+};
+use time_tz::timezones::db;
+
+#[test]
+fn runtime_market() {
+    let market = Market::new(db::asia::HONG_KONG, 1).with_trade_sessions(
+        TRADE_SESSION_NORMAL,
+        [TradeSession::new(time!(9:00:00), time!(15:00:00))],
+    );
+    assert_eq!(market.trade_sessions(TRADE_SESSION_OVERNIGHT), &[]);
+
+    let t = TestCandlestickTime::new(&market, Period::Min_1);
+    t.check_time(TRADE_SESSION_NORMAL, time!(8:59:59), None);
+    t.check_time(TRADE_SESSION_NORMAL, time!(9:00:00), time!(9:00:00));
+    t.check_time(TRADE_SESSION_NORMAL, time!(14:59:59), time!(14:59:00));
+    t.check_time(TRADE_SESSION_NORMAL, time!(15:00:00), None);
+}
+
+#[test]
+fn trade_session_ranges() {
+    // the closing auction timeout of the built-in market is kept
+    let market = HK.with_trade_session_ranges(
+        TRADE_SESSION_NORMAL,
+        [
+            (time!(13:00:00), time!(16:30:00)),
+            (time!(9:30:00), time!(12:00:00)),
+        ],
+    );
+    assert_eq!(
+        market.trade_sessions(TRADE_SESSION_NORMAL),
+        &[
+            TradeSession::new(time!(9:30:00), time!(12:00:00)),
+            TradeSession::new(time!(13:00:00), time!(16:30:00)).with_timeout(Duration::minutes(10)),
+        ]
+    );
+    assert_eq!(market.half_trade_sessions, HK.half_trade_sessions);
+
+    let t = TestCandlestickTime::new(&market, Period::Min_1);
+    t.check_time(TRADE_SESSION_NORMAL, time!(16:20:00), time!(16:20:00));
+    t.check_time(TRADE_SESSION_NORMAL, time!(16:35:00), time!(16:30:00));
+
+    // the overnight session wraps to the next day
+    let market =
+        US.with_trade_session_ranges(TRADE_SESSION_OVERNIGHT, [(time!(20:00:00), time!(3:50:00))]);
+    assert_eq!(
+        market.candlestick_trade_session(datetime!(2024-1-2 3:49:00 -5)),
+        Some(TRADE_SESSION_OVERNIGHT)
+    );
+    assert_eq!(
+        market.candlestick_trade_session(datetime!(2024-1-2 3:55:00 -5)),
+        None
+    );
+    assert_eq!(
+        market.candlestick_trade_session(datetime!(2024-1-1 23:59:59 -5)),
+        Some(TRADE_SESSION_OVERNIGHT)
+    );
+}
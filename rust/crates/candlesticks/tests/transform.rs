@@ -0,0 +1,137 @@
+use longport_candlesticks::{
+    Candlestick, CandlestickTransform, CandlestickTransformer, UpdateAction,
+};
+use rust_decimal::Decimal;
+use time::{macros::datetime, Duration};
+
+fn candlestick(idx: i64, open: i64, high: i64, low: i64, close: i64) -> Candlestick {
+    Candlestick {
+        time: datetime!(2024-1-2 9:30:00 +8) + Duration::minutes(idx),
+        open: open.into(),
+        high: high.into(),
+        low: low.into(),
+        close: close.into(),
+        volume: 100,
+        turnover: (close * 100).into(),
+    }
+}
+
+fn prices(candlestick: &Candlestick) -> [Decimal; 4] {
+    [
+        candlestick.open,
+        candlestick.high,
+        candlestick.low,
+        candlestick.close,
+    ]
+}
+
+#[test]
+fn heikin_ashi() {
+    let mut transformer = CandlestickTransformer::new(CandlestickTransform::HeikinAshi);
+    let res = transformer.transform_candlesticks(
+        &[
+            candlestick(0, 10, 14, 8, 12),
+            candlestick(1, 12, 16, 12, 16),
+        ],
+        true,
+    );
+    assert_eq!(
+        res.iter().map(prices).collect::<Vec<_>>(),
+        vec![
+            [11.into(), 14.into(), 8.into(), 11.into()],
+            [11.into(), 16.into(), 11.into(), 14.into()],
+        ]
+    );
+    assert_eq!(res[1].volume, 100);
+}
+
+#[test]
+fn percent_change() {
+    let mut transformer = CandlestickTransformer::new(CandlestickTransform::PercentChange);
+    assert_eq!(
+        transformer.update(&candlestick(0, 10, 10, 10, 10), true),
+        None
+    );
+    let res = transformer
+        .update(&candlestick(1, 10, 12, 9, 11), true)
+        .unwrap();
+    assert_eq!(prices(&res), [0.into(), 20.into(), (-10).into(), 10.into()]);
+}
+
+#[test]
+fn log_return() {
+    let mut transformer = CandlestickTransformer::new(CandlestickTransform::LogReturn);
+    transformer.update(&candlestick(0, 10, 10, 10, 10), true);
+    let res = transformer
+        .update(&candlestick(1, 10, 10, 10, 10), true)
+        .unwrap();
+    assert_eq!(prices(&res), [Decimal::ZERO; 4]);
+}
+
+#[test]
+fn update_last_does_not_commit() {
+    let mut transformer = CandlestickTransformer::new(CandlestickTransform::PercentChange);
+    transformer.update(&candlestick(0, 10, 10, 10, 10), true);
+
+    // the pending candlestick is updated many times
+    let action = transformer.apply(&UpdateAction::UpdateLast(candlestick(1, 10, 10, 10, 11)));
+    let UpdateAction::UpdateLast(res) = action else {
+        panic!("unexpected action: {action:?}");
+    };
+    assert_eq!(res.close, Decimal::from(10));
+    let action = transformer.apply(&UpdateAction::UpdateLast(candlestick(1, 10, 12, 10, 12)));
+    let UpdateAction::UpdateLast(res) = action else {
+        panic!("unexpected action: {action:?}");
+    };
+    assert_eq!(res.close, Decimal::from(20));
+
+    // the confirmed candlestick becomes the base of the new one
+    let action = transformer.apply(&UpdateAction::AppendNew {
+        confirmed: Some(candlestick(1, 10, 12, 10, 12)),
+        new: candlestick(2, 12, 15, 12, 15),
+    });
+    let UpdateAction::AppendNew {
+        confirmed: Some(confirmed),
+        new,
+    } = action
+    else {
+        panic!("unexpected action: {action:?}");
+    };
+    assert_eq!(confirmed.close, Decimal::from(20));
+    assert_eq!(new.close, Decimal::from(25));
+
+    // the older candlesticks are ignored
+    assert_eq!(
+        transformer.apply(&UpdateAction::UpdateLast(candlestick(1, 10, 10, 10, 10))),
+        UpdateAction::None
+    );
+}
+
+#[test]
+fn missed_confirmation() {
+    let mut transformer = CandlestickTransformer::new(CandlestickTransform::PercentChange);
+    transformer.update(&candlestick(0, 10, 10, 10, 10), false);
+    transformer.update(&candlestick(0, 10, 10, 10, 20), false);
+    // the last update of the previous candlestick is committed
+    let res = transformer
+        .update(&candlestick(1, 20, 20, 20, 30), false)
+        .unwrap();
+    assert_eq!(res.close, Decimal::from(50));
+}
+
+#[test]
+fn late_candlestick_keeps_pending() {
+    let mut transformer = CandlestickTransformer::new(CandlestickTransform::PercentChange);
+    transformer.update(&candlestick(0, 10, 10, 10, 10), true);
+    transformer.update(&candlestick(2, 10, 10, 10, 20), false);
+
+    // the late candlestick is committed before the pending one
+    let res = transformer
+        .update(&candlestick(1, 10, 10, 10, 15), false)
+        .unwrap();
+    assert_eq!(res.close, Decimal::from(50));
+    let res = transformer
+        .update(&candlestick(2, 10, 10, 10, 30), true)
+        .unwrap();
+    assert_eq!(res.close, Decimal::from(100));
+}
@@ -1,4 +1,6 @@
-use crate::HttpClientError;
+use std::sync::Arc;
+
+use crate::{CredentialProvider, HttpClientError, MemoryCredentialProvider};
 
 /// Configuration options for Http client
 #[derive(Debug, Clone)]
@@ -9,8 +11,8 @@ pub struct HttpClientConfig {
     pub(crate) app_key: String,
     /// App secret
     pub(crate) app_secret: String,
-    /// Access token
-    pub(crate) access_token: String,
+    /// Access token provider
+    pub(crate) credential_provider: Arc<dyn CredentialProvider>,
 }
 
 impl HttpClientConfig {
@@ -24,7 +26,7 @@ impl HttpClientConfig {
             http_url: None,
             app_key: app_key.into(),
             app_secret: app_secret.into(),
-            access_token: access_token.into(),
+            credential_provider: Arc::new(MemoryCredentialProvider::new(access_token)),
         }
     }
 
@@ -68,4 +70,28 @@ impl HttpClientConfig {
             ..self
         }
     }
+
+    /// Specifies the provider of the access token, the access token passed to
+    /// [`HttpClientConfig::new`] is ignored.
+    #[must_use]
+    pub fn credential_provider(self, provider: Arc<dyn CredentialProvider>) -> Self {
+        Self {
+            credential_provider: provider,
+            ..self
+        }
+    }
+
+    /// Returns the current access token
+    #[inline]
+    pub fn access_token(&self) -> String {
+        self.credential_provider.access_token()
+    }
+
+    /// Replaces the access token of the provider, the new token is used by
+    /// all clients created from this config or its clones
+    #[inline]
+    pub fn set_access_token(&self, access_token: impl Into<String>) {
+        self.credential_provider
+            .set_access_token(access_token.into());
+    }
 }
@@ -0,0 +1,46 @@
+use std::fmt::{self, Debug};
+
+use parking_lot::RwLock;
+
+/// Provides the access token used to sign the requests
+///
+/// The token is read before sending each request, so a token replaced with
+/// [`CredentialProvider::set_access_token`] is picked up by all clients
+/// sharing the provider.
+pub trait CredentialProvider: Debug + Send + Sync {
+    /// Returns the current access token
+    fn access_token(&self) -> String;
+
+    /// Replaces the access token, called after the token has been refreshed
+    fn set_access_token(&self, access_token: String);
+}
+
+/// Credential provider which keeps the access token in memory
+pub struct MemoryCredentialProvider(RwLock<String>);
+
+impl MemoryCredentialProvider {
+    /// Create a new `MemoryCredentialProvider`
+    #[inline]
+    pub fn new(access_token: impl Into<String>) -> Self {
+        Self(RwLock::new(access_token.into()))
+    }
+}
+
+impl Debug for MemoryCredentialProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryCredentialProvider")
+            .finish_non_exhaustive()
+    }
+}
+
+impl CredentialProvider for MemoryCredentialProvider {
+    #[inline]
+    fn access_token(&self) -> String {
+        self.0.read().clone()
+    }
+
+    #[inline]
+    fn set_access_token(&self, access_token: String) {
+        *self.0.write() = access_token;
+    }
+}
@@ -7,6 +7,7 @@
 
 mod client;
 mod config;
+mod credential;
 mod error;
 mod geo;
 mod qs;
@@ -16,8 +17,10 @@ mod timestamp;
 
 pub use client::HttpClient;
 pub use config::HttpClientConfig;
+pub use credential::{CredentialProvider, MemoryCredentialProvider};
 pub use error::{HttpClientError, HttpClientResult, HttpError};
 pub use geo::is_cn;
 pub use qs::QsError;
 pub use request::{FromPayload, Json, RequestBuilder, ToPayload};
 pub use reqwest::Method;
+pub use signature::{signature, SignatureParams};
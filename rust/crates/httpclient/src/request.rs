@@ -242,7 +242,8 @@ where
             .unwrap_or_else(Timestamp::now);
         let app_key_value =
             HeaderValue::from_str(&config.app_key).map_err(|_| HttpClientError::InvalidApiKey)?;
-        let access_token_value = HeaderValue::from_str(&config.access_token)
+        let access_token = config.access_token();
+        let access_token_value = HeaderValue::from_str(&access_token)
             .map_err(|_| HttpClientError::InvalidAccessToken)?;
 
         let url = self.http_url().await;
@@ -274,11 +275,14 @@ where
 
         // signature the request
         let sign = signature(SignatureParams {
-            request: &request,
+            method: request.method().as_str(),
+            path: request.url().path(),
+            query: request.url().query().unwrap_or_default(),
+            body: request.body().and_then(|body| body.as_bytes()),
             app_key: &config.app_key,
-            access_token: Some(&config.access_token),
+            access_token: Some(&access_token),
             app_secret: &config.app_secret,
-            timestamp,
+            timestamp: &timestamp.to_string(),
         });
         request.headers_mut().insert(
             "X-Api-Signature",
@@ -1,48 +1,61 @@
-use reqwest::Request;
 use sha1::{Digest, Sha1};
 
-use crate::timestamp::Timestamp;
-
-pub(crate) struct SignatureParams<'a> {
-    pub(crate) request: &'a Request,
-    pub(crate) app_key: &'a str,
-    pub(crate) access_token: Option<&'a str>,
-    pub(crate) app_secret: &'a str,
-    pub(crate) timestamp: Timestamp,
+/// The parts of a request covered by the signature
+#[derive(Debug, Copy, Clone)]
+pub struct SignatureParams<'a> {
+    /// Request method
+    pub method: &'a str,
+    /// Request path
+    pub path: &'a str,
+    /// Query string
+    pub query: &'a str,
+    /// Request body
+    pub body: Option<&'a [u8]>,
+    /// App key
+    pub app_key: &'a str,
+    /// Access token
+    pub access_token: Option<&'a str>,
+    /// App secret
+    pub app_secret: &'a str,
+    /// Value of the `X-Timestamp` header
+    pub timestamp: &'a str,
 }
 
-pub(crate) fn signature(params: SignatureParams<'_>) -> String {
-    let method = params.request.method().as_str();
+/// Returns the value of the `X-Api-Signature` header of a request
+pub fn signature(params: SignatureParams<'_>) -> String {
+    let SignatureParams {
+        method,
+        path,
+        query,
+        body,
+        app_key,
+        access_token,
+        app_secret,
+        timestamp,
+    } = params;
 
-    let (signed_headers, signed_values) = match params.access_token {
+    let (signed_headers, signed_values) = match access_token {
         Some(access_token) => (
             "authorization;x-api-key;x-timestamp",
             format!(
                 "authorization:{}\nx-api-key:{}\nx-timestamp:{}\n",
-                access_token, params.app_key, params.timestamp
+                access_token, app_key, timestamp
             ),
         ),
         None => (
             "x-api-key;x-timestamp",
-            format!(
-                "x-api-key:{}\nx-timestamp:{}\n",
-                params.app_key, params.timestamp
-            ),
+            format!("x-api-key:{}\nx-timestamp:{}\n", app_key, timestamp),
         ),
     };
 
-    let url = params.request.url();
-    let path = url.path();
-    let query = url.query().unwrap_or_default();
-
     let mut str_to_sign = format!("{method}|{path}|{query}|{signed_values}|{signed_headers}|",);
 
-    if let Some(body) = params.request.body().and_then(|b| b.as_bytes()) {
+    if let Some(body) = body {
         str_to_sign.push_str(&sha1(body));
     }
 
     let str_to_sign = format!("HMAC-SHA256|{}", sha1(str_to_sign.as_bytes()));
-    let signature = hmac_sha256(&str_to_sign, params.app_secret);
+    let signature = hmac_sha256(&str_to_sign, app_secret);
 
     format!("HMAC-SHA256 SignedHeaders={signed_headers}, Signature={signature}")
 }
@@ -0,0 +1,219 @@
+use std::{collections::HashMap, convert::Infallible, sync::Arc};
+
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    body::{Bytes, Incoming},
+    header::HeaderMap,
+    server::conn::http1,
+    service::service_fn,
+    Method, Request, Response, StatusCode,
+};
+use hyper_util::rt::TokioIo;
+use longport_httpcli::SignatureParams;
+use parking_lot::Mutex;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::json;
+use tokio::net::TcpListener;
+
+use crate::state::State;
+
+type Handler = Arc<dyn Fn(&HttpRequest) -> HttpResponse + Send + Sync>;
+
+/// A HTTP request received by the mock server
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    /// Request method
+    pub method: Method,
+    /// Request path
+    pub path: String,
+    /// Query string
+    pub query: String,
+    /// Request headers
+    pub headers: HeaderMap,
+    /// Request body
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    /// Deserialize the request body as JSON
+    pub fn json<T: DeserializeOwned>(&self) -> serde_json::Result<T> {
+        serde_json::from_slice(&self.body)
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|value| value.to_str().ok())
+    }
+}
+
+/// A scripted HTTP response
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    status: StatusCode,
+    body: serde_json::Value,
+}
+
+impl HttpResponse {
+    /// Create a successful response, `data` is wrapped in the OpenAPI envelope
+    pub fn ok(data: impl Serialize) -> Self {
+        Self {
+            status: StatusCode::OK,
+            body: json!({
+                "code": 0,
+                "message": "success",
+                "data": serde_json::to_value(data).expect("serialize response data"),
+            }),
+        }
+    }
+
+    /// Create an OpenAPI error response
+    pub fn error(code: i32, message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::OK,
+            body: json!({
+                "code": code,
+                "message": message.into(),
+            }),
+        }
+    }
+
+    /// Create a response with an empty body and the specified status code
+    pub fn status(status: StatusCode) -> Self {
+        Self {
+            status,
+            body: serde_json::Value::Null,
+        }
+    }
+
+    fn into_response(self) -> Response<Full<Bytes>> {
+        let body = if self.body.is_null() {
+            Bytes::new()
+        } else {
+            Bytes::from(self.body.to_string())
+        };
+        Response::builder()
+            .status(self.status)
+            .header("Content-Type", "application/json; charset=utf-8")
+            .header("X-Trace-Id", "mock")
+            .body(Full::new(body))
+            .expect("valid response")
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct HttpHandlers {
+    handlers: Mutex<HashMap<(Method, String), Handler>>,
+    requests: Mutex<Vec<HttpRequest>>,
+}
+
+impl HttpHandlers {
+    pub(crate) fn insert(&self, method: Method, path: String, handler: Handler) {
+        self.handlers.lock().insert((method, path), handler);
+    }
+
+    pub(crate) fn requests(&self) -> Vec<HttpRequest> {
+        self.requests.lock().clone()
+    }
+
+    fn handle(&self, state: &State, req: &HttpRequest) -> HttpResponse {
+        self.requests.lock().push(req.clone());
+
+        if !verify_signature(state, req) {
+            return HttpResponse {
+                status: StatusCode::UNAUTHORIZED,
+                ..HttpResponse::error(401, "invalid signature")
+            };
+        }
+
+        let handler = self
+            .handlers
+            .lock()
+            .get(&(req.method.clone(), req.path.clone()))
+            .cloned();
+        match handler {
+            Some(handler) => handler(req),
+            None if req.method == Method::GET
+                && matches!(req.path.as_str(), "/v1/socket/token" | "/v2/socket/token") =>
+            {
+                HttpResponse::ok(json!({ "otp": state.create_otp() }))
+            }
+            None => HttpResponse {
+                status: StatusCode::NOT_FOUND,
+                ..HttpResponse::error(404, format!("no handler for {} {}", req.method, req.path))
+            },
+        }
+    }
+}
+
+fn verify_signature(state: &State, req: &HttpRequest) -> bool {
+    let (Some(app_key), Some(access_token), Some(timestamp), Some(signature)) = (
+        req.header("X-Api-Key"),
+        req.header("Authorization"),
+        req.header("X-Timestamp"),
+        req.header("X-Api-Signature"),
+    ) else {
+        return false;
+    };
+
+    if app_key != state.app_key || access_token != state.access_token {
+        return false;
+    }
+
+    let params = SignatureParams {
+        method: req.method.as_str(),
+        path: &req.path,
+        query: &req.query,
+        body: Some(&req.body),
+        app_key,
+        access_token: Some(access_token),
+        app_secret: &state.app_secret,
+        timestamp,
+    };
+
+    // the client does not sign the body if the request has no body, which
+    // can not be distinguished from an empty body on the wire
+    longport_httpcli::signature(params) == signature
+        || (req.body.is_empty()
+            && longport_httpcli::signature(SignatureParams {
+                body: None,
+                ..params
+            }) == signature)
+}
+
+async fn handle_request(
+    state: Arc<State>,
+    handlers: Arc<HttpHandlers>,
+    req: Request<Incoming>,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+    let (parts, body) = req.into_parts();
+    let body = match body.collect().await {
+        Ok(body) => body.to_bytes().to_vec(),
+        Err(_) => return Ok(HttpResponse::status(StatusCode::BAD_REQUEST).into_response()),
+    };
+    let req = HttpRequest {
+        method: parts.method,
+        path: parts.uri.path().to_string(),
+        query: parts.uri.query().unwrap_or_default().to_string(),
+        headers: parts.headers,
+        body,
+    };
+
+    tracing::info!(method = %req.method, path = %req.path, query = %req.query, "mock http request");
+    Ok(handlers.handle(&state, &req).into_response())
+}
+
+pub(crate) async fn serve(listener: TcpListener, state: Arc<State>, handlers: Arc<HttpHandlers>) {
+    while let Ok((stream, _)) = listener.accept().await {
+        let state = state.clone();
+        let handlers = handlers.clone();
+        tokio::spawn(async move {
+            let service =
+                service_fn(move |req| handle_request(state.clone(), handlers.clone(), req));
+            if let Err(err) = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                tracing::debug!(error = %err, "mock http connection error");
+            }
+        });
+    }
+}
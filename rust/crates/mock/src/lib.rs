@@ -0,0 +1,63 @@
+//! Mock LongPort OpenAPI server
+//!
+//! Serves the signed HTTP JSON API and the quote/trade websocket binary
+//! protocol on local ports, so that `QuoteContext` and `TradeContext` can be
+//! tested end-to-end without live credentials.
+//!
+//! Authentication, session reconnection, heartbeats and subscriptions are
+//! handled by the server itself, everything else can be scripted.
+//!
+//! # Examples
+//!
+//! ```
+//! use longport_mock::{
+//!     proto::quote::{PushQuote, SecurityStaticInfoResponse},
+//!     HttpResponse, Method, MockServer, WsResponse,
+//! };
+//! use serde_json::json;
+//!
+//! # tokio::runtime::Runtime::new().unwrap().block_on(async {
+//! let server = MockServer::start().await.unwrap();
+//!
+//! // script a HTTP response
+//! server.on_http(Method::GET, "/v1/asset/account", |_| {
+//!     HttpResponse::ok(json!({ "list": [] }))
+//! });
+//!
+//! // script a quote command response
+//! server.quote().on_request(10, |_| {
+//!     WsResponse::ok(&SecurityStaticInfoResponse::default())
+//! });
+//!
+//! // inject a push message to all connected quote clients
+//! server.quote().push(
+//!     101,
+//!     &PushQuote {
+//!         symbol: "700.HK".to_string(),
+//!         last_done: "500".to_string(),
+//!         ..Default::default()
+//!     },
+//! );
+//!
+//! // point the `Config` at the server
+//! println!("{}", server.http_url());
+//! println!("{}", server.quote_ws_url());
+//! println!("{}", server.trade_ws_url());
+//! # });
+//! ```
+
+#![forbid(unsafe_code)]
+#![deny(unreachable_pub)]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#![warn(missing_docs)]
+
+mod http;
+mod server;
+mod state;
+mod ws;
+
+pub use http::{HttpRequest, HttpResponse};
+pub use hyper::{Method, StatusCode};
+pub use longport_proto as proto;
+pub use server::MockServer;
+pub use ws::{WsEndpoint, WsRequest, WsResponse};
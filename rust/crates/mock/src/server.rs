@@ -0,0 +1,170 @@
+use std::{io, sync::Arc};
+
+use hyper::Method;
+use tokio::{net::TcpListener, task::JoinHandle};
+
+use crate::{
+    http::{self, HttpHandlers},
+    state::State,
+    ws::EndpointKind,
+    HttpRequest, HttpResponse, WsEndpoint,
+};
+
+/// Mock LongPort OpenAPI server
+///
+/// The server listens on three local ports, one for the HTTP API and one for
+/// each of the quote and trade websocket endpoints. It is shut down when
+/// dropped.
+pub struct MockServer {
+    state: Arc<State>,
+    http_url: String,
+    quote_ws_url: String,
+    trade_ws_url: String,
+    http_handlers: Arc<HttpHandlers>,
+    quote: WsEndpoint,
+    trade: WsEndpoint,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+        self.quote.disconnect();
+        self.trade.disconnect();
+    }
+}
+
+impl MockServer {
+    /// Start a mock server that accepts the credentials `mock-app-key`,
+    /// `mock-app-secret` and `mock-access-token`
+    pub async fn start() -> io::Result<Self> {
+        Self::with_credentials("mock-app-key", "mock-app-secret", "mock-access-token").await
+    }
+
+    /// Start a mock server that accepts the specified credentials
+    pub async fn with_credentials(
+        app_key: impl Into<String>,
+        app_secret: impl Into<String>,
+        access_token: impl Into<String>,
+    ) -> io::Result<Self> {
+        let state = Arc::new(State::new(
+            app_key.into(),
+            app_secret.into(),
+            access_token.into(),
+        ));
+        let http_listener = TcpListener::bind("127.0.0.1:0").await?;
+        let quote_listener = TcpListener::bind("127.0.0.1:0").await?;
+        let trade_listener = TcpListener::bind("127.0.0.1:0").await?;
+
+        let http_url = format!("http://{}", http_listener.local_addr()?);
+        let quote_ws_url = format!("ws://{}", quote_listener.local_addr()?);
+        let trade_ws_url = format!("ws://{}", trade_listener.local_addr()?);
+
+        let http_handlers = Arc::new(HttpHandlers::default());
+        let quote = WsEndpoint::new(EndpointKind::Quote, state.clone());
+        let trade = WsEndpoint::new(EndpointKind::Trade, state.clone());
+
+        let tasks = vec![
+            tokio::spawn(http::serve(
+                http_listener,
+                state.clone(),
+                http_handlers.clone(),
+            )),
+            tokio::spawn(quote.clone().serve(quote_listener)),
+            tokio::spawn(trade.clone().serve(trade_listener)),
+        ];
+
+        tracing::info!(
+            http_url = http_url,
+            quote_ws_url = quote_ws_url,
+            trade_ws_url = trade_ws_url,
+            "mock server started"
+        );
+
+        Ok(Self {
+            state,
+            http_url,
+            quote_ws_url,
+            trade_ws_url,
+            http_handlers,
+            quote,
+            trade,
+            tasks,
+        })
+    }
+
+    /// Returns the app key accepted by the server
+    #[inline]
+    pub fn app_key(&self) -> &str {
+        &self.state.app_key
+    }
+
+    /// Returns the app secret accepted by the server
+    #[inline]
+    pub fn app_secret(&self) -> &str {
+        &self.state.app_secret
+    }
+
+    /// Returns the access token accepted by the server
+    #[inline]
+    pub fn access_token(&self) -> &str {
+        &self.state.access_token
+    }
+
+    /// Returns the url of the HTTP API
+    #[inline]
+    pub fn http_url(&self) -> &str {
+        &self.http_url
+    }
+
+    /// Returns the url of the quote websocket endpoint
+    #[inline]
+    pub fn quote_ws_url(&self) -> &str {
+        &self.quote_ws_url
+    }
+
+    /// Returns the url of the trade websocket endpoint
+    #[inline]
+    pub fn trade_ws_url(&self) -> &str {
+        &self.trade_ws_url
+    }
+
+    /// Sets the handler of the HTTP requests with the specified method and
+    /// path
+    ///
+    /// Requests with an invalid signature are rejected before calling the
+    /// handler. `GET /v1/socket/token` and `GET /v2/socket/token` are handled
+    /// by the server unless overridden.
+    pub fn on_http<F>(&self, method: Method, path: impl Into<String>, handler: F)
+    where
+        F: Fn(&HttpRequest) -> HttpResponse + Send + Sync + 'static,
+    {
+        self.http_handlers
+            .insert(method, path.into(), Arc::new(handler));
+    }
+
+    /// Returns all HTTP requests received by the server
+    pub fn http_requests(&self) -> Vec<HttpRequest> {
+        self.http_handlers.requests()
+    }
+
+    /// Returns the quote websocket endpoint
+    #[inline]
+    pub fn quote(&self) -> &WsEndpoint {
+        &self.quote
+    }
+
+    /// Returns the trade websocket endpoint
+    #[inline]
+    pub fn trade(&self) -> &WsEndpoint {
+        &self.trade
+    }
+
+    /// Invalidates all sessions, the clients have to authenticate with a new
+    /// OTP the next time they reconnect
+    pub fn expire_sessions(&self) {
+        self.state.expire_sessions();
+    }
+}
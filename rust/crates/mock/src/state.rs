@@ -0,0 +1,71 @@
+use std::{
+    collections::HashSet,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use parking_lot::Mutex;
+
+const SESSION_TTL: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// The state shared by the HTTP server and the websocket endpoints
+pub(crate) struct State {
+    pub(crate) app_key: String,
+    pub(crate) app_secret: String,
+    pub(crate) access_token: String,
+    next_id: AtomicU64,
+    otps: Mutex<HashSet<String>>,
+    sessions: Mutex<HashSet<String>>,
+}
+
+impl State {
+    pub(crate) fn new(app_key: String, app_secret: String, access_token: String) -> Self {
+        Self {
+            app_key,
+            app_secret,
+            access_token,
+            next_id: AtomicU64::new(1),
+            otps: Default::default(),
+            sessions: Default::default(),
+        }
+    }
+
+    fn next_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Issues a new one time password
+    pub(crate) fn create_otp(&self) -> String {
+        let otp = format!("mock-otp-{}", self.next_id());
+        self.otps.lock().insert(otp.clone());
+        otp
+    }
+
+    /// Consumes the one time password and creates a new session
+    pub(crate) fn auth(&self, otp: &str) -> Option<String> {
+        if !self.otps.lock().remove(otp) {
+            return None;
+        }
+        let session_id = format!("mock-session-{}", self.next_id());
+        self.sessions.lock().insert(session_id.clone());
+        Some(session_id)
+    }
+
+    /// Returns `true` if the session id is valid
+    pub(crate) fn check_session(&self, session_id: &str) -> bool {
+        self.sessions.lock().contains(session_id)
+    }
+
+    pub(crate) fn expire_sessions(&self) {
+        self.sessions.lock().clear();
+    }
+}
+
+/// Returns the expiration time of a new session in milliseconds since the
+/// unix epoch
+pub(crate) fn session_expires() -> i64 {
+    (SystemTime::now() + SESSION_TTL)
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
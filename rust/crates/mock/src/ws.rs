@@ -0,0 +1,430 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use futures_util::{SinkExt, StreamExt};
+use longport_proto::{control, quote, trade};
+use longport_wscli::Packet;
+use parking_lot::Mutex;
+use prost::Message as _;
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+};
+use tokio_tungstenite::tungstenite::{
+    handshake::server::{ErrorResponse, Request, Response},
+    http::StatusCode,
+    protocol::{frame::coding::CloseCode, CloseFrame},
+    Message,
+};
+
+use crate::state::{session_expires, State};
+
+const STATUS_ERROR: u8 = 3;
+
+type Handler = Arc<dyn Fn(&[u8]) -> WsResponse + Send + Sync>;
+
+/// A websocket request received by the mock server
+#[derive(Debug, Clone)]
+pub struct WsRequest {
+    /// Command code
+    pub command_code: u8,
+    /// Request body
+    pub body: Vec<u8>,
+}
+
+/// A scripted websocket response
+#[derive(Debug, Clone)]
+pub enum WsResponse {
+    /// Success response
+    Ok(Vec<u8>),
+    /// Error response
+    Error {
+        /// Status code, must not be `0`
+        status: u8,
+        /// Error code
+        code: u64,
+        /// Error message
+        message: String,
+    },
+}
+
+impl WsResponse {
+    /// Create a successful response
+    pub fn ok(message: &impl prost::Message) -> Self {
+        Self::Ok(message.encode_to_vec())
+    }
+
+    /// Create an error response
+    pub fn error(code: u64, message: impl Into<String>) -> Self {
+        Self::Error {
+            status: STATUS_ERROR,
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub(crate) enum EndpointKind {
+    Quote,
+    Trade,
+}
+
+pub(crate) struct EndpointInner {
+    kind: EndpointKind,
+    state: Arc<State>,
+    handlers: Mutex<HashMap<u8, Handler>>,
+    requests: Mutex<Vec<WsRequest>>,
+    subscriptions: Mutex<BTreeMap<String, BTreeSet<i32>>>,
+    next_connection_id: AtomicU64,
+    connections: Mutex<HashMap<u64, mpsc::UnboundedSender<Message>>>,
+}
+
+/// A websocket endpoint of the mock server
+///
+/// Authentication (`2`), reconnection (`3`) and heartbeat (`1`) requests are
+/// handled by the endpoint. The quote endpoint additionally handles the user
+/// quote profile (`4`), subscription (`5`, `6`, `7`) and trading days (`9`)
+/// requests, and the trade endpoint handles the subscribe (`16`) and
+/// unsubscribe (`17`) requests. Every command can be overridden with
+/// [`WsEndpoint::on_request`].
+#[derive(Clone)]
+pub struct WsEndpoint(Arc<EndpointInner>);
+
+impl WsEndpoint {
+    pub(crate) fn new(kind: EndpointKind, state: Arc<State>) -> Self {
+        Self(Arc::new(EndpointInner {
+            kind,
+            state,
+            handlers: Default::default(),
+            requests: Default::default(),
+            subscriptions: Default::default(),
+            next_connection_id: AtomicU64::new(0),
+            connections: Default::default(),
+        }))
+    }
+
+    /// Sets the handler of the requests with the specified command code
+    pub fn on_request<F>(&self, command_code: u8, handler: F)
+    where
+        F: Fn(&[u8]) -> WsResponse + Send + Sync + 'static,
+    {
+        self.0
+            .handlers
+            .lock()
+            .insert(command_code, Arc::new(handler));
+    }
+
+    /// Returns all requests received by this endpoint
+    pub fn requests(&self) -> Vec<WsRequest> {
+        self.0.requests.lock().clone()
+    }
+
+    /// Returns the current subscriptions
+    ///
+    /// For the quote endpoint, the keys are the symbols and the values are the
+    /// subscribed [`SubType`](longport_proto::quote::SubType)s. For the trade
+    /// endpoint, the keys are the topics and the values are empty.
+    pub fn subscriptions(&self) -> BTreeMap<String, BTreeSet<i32>> {
+        self.0.subscriptions.lock().clone()
+    }
+
+    /// Returns the number of connected clients
+    pub fn connections(&self) -> usize {
+        self.0.connections.lock().len()
+    }
+
+    /// Sends a push message to all connected clients
+    pub fn push(&self, command_code: u8, message: &impl prost::Message) {
+        self.push_raw(command_code, message.encode_to_vec());
+    }
+
+    /// Sends a push message with a raw body to all connected clients
+    pub fn push_raw(&self, command_code: u8, body: Vec<u8>) {
+        let data = Packet::Push {
+            command_code,
+            body,
+            signature: None,
+        }
+        .encode();
+        self.0
+            .connections
+            .lock()
+            .retain(|_, tx| tx.send(Message::Binary(data.clone().into())).is_ok());
+    }
+
+    /// Closes all connections
+    ///
+    /// The sessions remain valid, so the clients can resume them after
+    /// reconnecting.
+    pub fn disconnect(&self) {
+        let frame = CloseFrame {
+            code: CloseCode::Away,
+            reason: "disconnected by the mock server".into(),
+        };
+        for (_, tx) in self.0.connections.lock().drain() {
+            let _ = tx.send(Message::Close(Some(frame.clone())));
+        }
+    }
+
+    pub(crate) async fn serve(self, listener: TcpListener) {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(self.clone().handle_connection(stream));
+        }
+    }
+
+    async fn handle_connection(self, stream: TcpStream) {
+        let conn = match tokio_tungstenite::accept_hdr_async(stream, check_handshake).await {
+            Ok(conn) => conn,
+            Err(err) => {
+                tracing::debug!(error = %err, "mock websocket handshake failed");
+                return;
+            }
+        };
+        let (mut sink, mut stream) = conn.split();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let id = self.0.next_connection_id.fetch_add(1, Ordering::Relaxed);
+        self.0.connections.lock().insert(id, tx);
+
+        let mut authenticated = false;
+        loop {
+            tokio::select! {
+                item = stream.next() => {
+                    let data = match item {
+                        Some(Ok(Message::Binary(data))) => data,
+                        Some(Ok(Message::Ping(_) | Message::Pong(_))) => continue,
+                        _ => break,
+                    };
+                    let Ok(Packet::Request { command_code, request_id, body, .. }) = Packet::decode(&data) else {
+                        break;
+                    };
+                    let resp = self.handle_request(&mut authenticated, command_code, body);
+                    let (status, body) = match resp {
+                        WsResponse::Ok(body) => (0, body),
+                        WsResponse::Error { status, code, message } => {
+                            (status, longport_proto::Error { code, msg: message }.encode_to_vec())
+                        }
+                    };
+                    let packet = Packet::Response {
+                        command_code,
+                        request_id,
+                        status,
+                        body,
+                        signature: None,
+                    };
+                    if sink.send(Message::Binary(packet.encode().into())).await.is_err() {
+                        break;
+                    }
+                }
+                item = rx.recv() => {
+                    let Some(msg) = item else {
+                        break;
+                    };
+                    let is_close = msg.is_close();
+                    if sink.send(msg).await.is_err() || is_close {
+                        break;
+                    }
+                }
+            }
+        }
+
+        self.0.connections.lock().remove(&id);
+    }
+
+    fn handle_request(
+        &self,
+        authenticated: &mut bool,
+        command_code: u8,
+        body: Vec<u8>,
+    ) -> WsResponse {
+        tracing::info!(command_code = command_code, "mock websocket request");
+        self.0.requests.lock().push(WsRequest {
+            command_code,
+            body: body.clone(),
+        });
+
+        let is_control = matches!(
+            control::Command::try_from(command_code as i32),
+            Ok(control::Command::CmdHeartbeat
+                | control::Command::CmdAuth
+                | control::Command::CmdReconnect)
+        );
+        if !*authenticated && !is_control {
+            return WsResponse::error(401, "unauthenticated");
+        }
+
+        let handler = self.0.handlers.lock().get(&command_code).cloned();
+        let resp = match handler {
+            Some(handler) => handler(&body),
+            None => self
+                .handle_builtin(command_code, &body)
+                .unwrap_or_else(|err| WsResponse::error(400, err.to_string())),
+        };
+
+        if is_control && command_code != control::Command::CmdHeartbeat as u8 {
+            *authenticated |= matches!(resp, WsResponse::Ok(_));
+        }
+        resp
+    }
+
+    fn handle_builtin(
+        &self,
+        command_code: u8,
+        body: &[u8],
+    ) -> Result<WsResponse, prost::DecodeError> {
+        let state = &self.0.state;
+
+        if let Ok(command) = control::Command::try_from(command_code as i32) {
+            match command {
+                control::Command::CmdHeartbeat => return Ok(WsResponse::Ok(vec![])),
+                control::Command::CmdAuth => {
+                    let req = control::AuthRequest::decode(body)?;
+                    return Ok(match state.auth(&req.token) {
+                        Some(session_id) => WsResponse::ok(&control::AuthResponse {
+                            session_id,
+                            expires: session_expires(),
+                            ..Default::default()
+                        }),
+                        None => WsResponse::error(401, "invalid otp"),
+                    });
+                }
+                control::Command::CmdReconnect => {
+                    let req = control::ReconnectRequest::decode(body)?;
+                    return Ok(if state.check_session(&req.session_id) {
+                        WsResponse::ok(&control::ReconnectResponse {
+                            session_id: req.session_id,
+                            expires: session_expires(),
+                            ..Default::default()
+                        })
+                    } else {
+                        WsResponse::error(401, "invalid session")
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        match self.0.kind {
+            EndpointKind::Quote => self.handle_quote_builtin(command_code, body),
+            EndpointKind::Trade => self.handle_trade_builtin(command_code, body),
+        }
+    }
+
+    fn handle_quote_builtin(
+        &self,
+        command_code: u8,
+        body: &[u8],
+    ) -> Result<WsResponse, prost::DecodeError> {
+        let mut subscriptions = self.0.subscriptions.lock();
+
+        Ok(match quote::Command::try_from(command_code as i32) {
+            Ok(quote::Command::QueryUserQuoteProfile) => {
+                WsResponse::ok(&quote::UserQuoteProfileResponse {
+                    member_id: 1,
+                    quote_level: "mock".to_string(),
+                    ..Default::default()
+                })
+            }
+            Ok(quote::Command::QueryMarketTradePeriod) => {
+                WsResponse::ok(&quote::MarketTradePeriodResponse::default())
+            }
+            Ok(quote::Command::QueryMarketTradeDay) => {
+                WsResponse::ok(&quote::MarketTradeDayResponse::default())
+            }
+            Ok(quote::Command::Subscription) => WsResponse::ok(&quote::SubscriptionResponse {
+                sub_list: subscriptions
+                    .iter()
+                    .map(|(symbol, sub_types)| quote::SubTypeList {
+                        symbol: symbol.clone(),
+                        sub_type: sub_types.iter().copied().collect(),
+                    })
+                    .collect(),
+            }),
+            Ok(quote::Command::Subscribe) => {
+                let req = quote::SubscribeRequest::decode(body)?;
+                for symbol in req.symbol {
+                    subscriptions
+                        .entry(symbol)
+                        .or_default()
+                        .extend(req.sub_type.iter().copied());
+                }
+                WsResponse::Ok(vec![])
+            }
+            Ok(quote::Command::Unsubscribe) => {
+                let req = quote::UnsubscribeRequest::decode(body)?;
+                if req.unsub_all {
+                    subscriptions.clear();
+                }
+                for symbol in req.symbol {
+                    if let Some(sub_types) = subscriptions.get_mut(&symbol) {
+                        sub_types.retain(|sub_type| !req.sub_type.contains(sub_type));
+                        if sub_types.is_empty() {
+                            subscriptions.remove(&symbol);
+                        }
+                    }
+                }
+                WsResponse::ok(&quote::UnsubscribeResponse {})
+            }
+            _ => unknown_command(command_code),
+        })
+    }
+
+    fn handle_trade_builtin(
+        &self,
+        command_code: u8,
+        body: &[u8],
+    ) -> Result<WsResponse, prost::DecodeError> {
+        let mut subscriptions = self.0.subscriptions.lock();
+
+        Ok(match trade::Command::try_from(command_code as i32) {
+            Ok(trade::Command::CmdSub) => {
+                let req = trade::Sub::decode(body)?;
+                for topic in &req.topics {
+                    subscriptions.entry(topic.clone()).or_default();
+                }
+                WsResponse::ok(&trade::SubResponse {
+                    success: req.topics,
+                    fail: vec![],
+                    current: subscriptions.keys().cloned().collect(),
+                })
+            }
+            Ok(trade::Command::CmdUnsub) => {
+                let req = trade::Unsub::decode(body)?;
+                for topic in &req.topics {
+                    subscriptions.remove(topic);
+                }
+                WsResponse::ok(&trade::UnsubResponse {
+                    current: subscriptions.keys().cloned().collect(),
+                })
+            }
+            _ => unknown_command(command_code),
+        })
+    }
+}
+
+fn unknown_command(command_code: u8) -> WsResponse {
+    WsResponse::error(404, format!("no handler for command {command_code}"))
+}
+
+#[allow(clippy::result_large_err)]
+fn check_handshake(req: &Request, resp: Response) -> Result<Response, ErrorResponse> {
+    let query = req.uri().query().unwrap_or_default();
+    let has_param = |name: &str| {
+        query
+            .split('&')
+            .any(|pair| pair.split_once('=').is_some_and(|(key, _)| key == name))
+    };
+
+    if ["version", "codec", "platform"].into_iter().all(has_param) {
+        Ok(resp)
+    } else {
+        let mut resp = ErrorResponse::new(Some("missing protocol parameters".to_string()));
+        *resp.status_mut() = StatusCode::BAD_REQUEST;
+        Err(resp)
+    }
+}
@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     str::FromStr,
     sync::Arc,
     time::{Duration, Instant, SystemTime, UNIX_EPOCH},
@@ -28,6 +28,9 @@ use crate::{
 };
 
 const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The capacity of the event channel passed to [`WsClient::open`]
+pub const EVENT_CHANNEL_CAPACITY: usize = 1024;
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
 const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(120);
 const AUTH_TIMEOUT: Duration = Duration::from_secs(5);
@@ -99,14 +102,15 @@ struct Context<'a> {
     sink: SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>,
     stream: SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
     command_rx: &'a mut mpsc::UnboundedReceiver<Command>,
-    event_sender: &'a mut mpsc::UnboundedSender<WsEvent>,
+    event_sender: &'a mut mpsc::Sender<WsEvent>,
+    pending_events: VecDeque<WsEvent>,
 }
 
 impl<'a> Context<'a> {
     fn new(
         conn: WebSocketStream<MaybeTlsStream<TcpStream>>,
         command_rx: &'a mut mpsc::UnboundedReceiver<Command>,
-        event_sender: &'a mut mpsc::UnboundedSender<WsEvent>,
+        event_sender: &'a mut mpsc::Sender<WsEvent>,
     ) -> Self {
         let (sink, stream) = conn.split();
         Context {
@@ -116,6 +120,7 @@ impl<'a> Context<'a> {
             stream,
             command_rx,
             event_sender,
+            pending_events: VecDeque::new(),
         }
     }
 
@@ -125,8 +130,21 @@ impl<'a> Context<'a> {
         self.request_id
     }
 
-    fn send_event(&mut self, event: WsEvent) {
-        let _ = self.event_sender.send(event);
+    async fn send_event(&mut self, event: WsEvent) {
+        for event in self.pending_events.drain(..) {
+            let _ = self.event_sender.send(event).await;
+        }
+        let _ = self.event_sender.send(event).await;
+    }
+
+    /// Returns `true` if the connection should not be read until the pending
+    /// pushes are sent
+    ///
+    /// The connection is still read while there are inflight requests, so the
+    /// receiver of the events can not block their responses.
+    #[inline]
+    fn is_paused(&self) -> bool {
+        !self.pending_events.is_empty() && self.inflight_requests.is_empty()
     }
 
     async fn process_loop(&mut self) -> WsClientResult<()> {
@@ -134,8 +152,17 @@ impl<'a> Context<'a> {
         let mut checkout_timeout = tokio::time::interval(Duration::from_secs(1));
 
         loop {
+            let paused = self.is_paused();
+            let event_sender = self.event_sender.clone();
+
             tokio::select! {
-                item = self.stream.next() => {
+                permit = event_sender.reserve(), if !self.pending_events.is_empty() => {
+                    match permit {
+                        Ok(permit) => permit.send(self.pending_events.pop_front().unwrap()),
+                        Err(_) => self.pending_events.clear(),
+                    }
+                }
+                item = self.stream.next(), if !paused => {
                     match item.transpose()? {
                         Some(msg) => {
                             if msg.is_ping() {
@@ -154,7 +181,10 @@ impl<'a> Context<'a> {
                     }
                 }
                 _ = checkout_timeout.tick() => {
-                    if (Instant::now() - ping_time) > HEARTBEAT_TIMEOUT {
+                    if paused {
+                        // the pings are not read meanwhile
+                        ping_time = Instant::now();
+                    } else if (Instant::now() - ping_time) > HEARTBEAT_TIMEOUT {
                         tracing::info!("heartbeat timeout");
                         return Err(WsClientError::ConnectionClosed { reason: None });
                     }
@@ -220,7 +250,8 @@ impl<'a> Context<'a> {
                 Packet::Push {
                     command_code, body, ..
                 } => {
-                    let _ = self.event_sender.send(WsEvent::Push { command_code, body });
+                    self.pending_events
+                        .push_back(WsEvent::Push { command_code, body });
                 }
                 _ => return Err(WsClientError::UnexpectedResponse),
             },
@@ -264,12 +295,15 @@ pub struct WsClient {
 
 impl WsClient {
     /// Connect to `url` and returns a `WsClient` object
+    ///
+    /// The connection is not read while `event_sender` is full, so a slow
+    /// receiver of the events applies backpressure to the server.
     pub async fn open(
         request: impl IntoClientRequest,
         version: ProtocolVersion,
         codec: CodecType,
         platform: Platform,
-        event_sender: mpsc::UnboundedSender<WsEvent>,
+        event_sender: mpsc::Sender<WsEvent>,
         rate_limit: Vec<(u8, RateLimit)>,
     ) -> WsClientResult<Self> {
         let (command_tx, command_rx) = mpsc::unbounded_channel();
@@ -435,7 +469,7 @@ async fn do_connect(
 async fn client_loop(
     conn: WebSocketStream<MaybeTlsStream<TcpStream>>,
     mut command_tx: mpsc::UnboundedReceiver<Command>,
-    mut event_sender: mpsc::UnboundedSender<WsEvent>,
+    mut event_sender: mpsc::Sender<WsEvent>,
 ) {
     let mut ctx = Context::new(conn, &mut command_tx, &mut event_sender);
 
@@ -443,7 +477,7 @@ async fn client_loop(
     match res {
         Ok(()) => return,
         Err(err) => {
-            ctx.send_event(WsEvent::Error(err));
+            ctx.send_event(WsEvent::Error(err)).await;
         }
     };
 
@@ -9,10 +9,13 @@ const PACKAGE_TYPE_REQUEST: u8 = 1;
 const PACKAGE_TYPE_RESPONSE: u8 = 2;
 const PACKAGE_TYPE_PUSH: u8 = 3;
 
-#[derive(Debug)]
-pub(crate) struct Signature {
-    pub(crate) nonce: [u8; 8],
-    pub(crate) signature: [u8; 16],
+/// Packet signature
+#[derive(Debug, Clone)]
+pub struct PacketSignature {
+    /// Nonce
+    pub nonce: [u8; 8],
+    /// Signature
+    pub signature: [u8; 16],
 }
 
 #[derive(Debug)]
@@ -44,32 +47,49 @@ impl PacketHeader {
     }
 }
 
-#[derive(Debug)]
-#[allow(dead_code)]
-pub(crate) enum Packet {
+/// A packet of the LongPort websocket binary protocol
+#[derive(Debug, Clone)]
+pub enum Packet {
+    /// Request packet
     Request {
+        /// Command code
         command_code: u8,
+        /// Request id
         request_id: u32,
+        /// Timeout in milliseconds
         timeout_millis: u16,
+        /// Body
         body: Vec<u8>,
-        signature: Option<Signature>,
+        /// Signature
+        signature: Option<PacketSignature>,
     },
+    /// Response packet
     Response {
+        /// Command code
         command_code: u8,
+        /// Request id
         request_id: u32,
+        /// Status code, `0` means success
         status: u8,
+        /// Body
         body: Vec<u8>,
-        signature: Option<Signature>,
+        /// Signature
+        signature: Option<PacketSignature>,
     },
+    /// Push packet
     Push {
+        /// Command code
         command_code: u8,
+        /// Body
         body: Vec<u8>,
-        signature: Option<Signature>,
+        /// Signature
+        signature: Option<PacketSignature>,
     },
 }
 
 impl Packet {
-    pub(crate) fn encode(&self) -> Vec<u8> {
+    /// Encode this packet to bytes
+    pub fn encode(&self) -> Vec<u8> {
         match self {
             Packet::Request {
                 command_code,
@@ -91,20 +111,56 @@ impl Packet {
                 let _ = data.write_u24::<BE>(body.len() as u32);
 
                 data.extend(body);
+                write_signature(&mut data, signature.as_ref());
+                data
+            }
+            Packet::Response {
+                command_code,
+                request_id,
+                status,
+                body,
+                signature,
+            } => {
+                let header = PacketHeader {
+                    ty: PACKAGE_TYPE_RESPONSE,
+                    verify: signature.is_some(),
+                    gzip: false,
+                }
+                .encode();
+                let mut data = vec![header, *command_code];
+
+                let _ = data.write_u32::<BE>(*request_id);
+                let _ = data.write_u8(*status);
+                let _ = data.write_u24::<BE>(body.len() as u32);
 
-                if let Some(signature) = signature {
-                    data.extend_from_slice(&signature.nonce);
-                    data.extend_from_slice(&signature.signature);
+                data.extend(body);
+                write_signature(&mut data, signature.as_ref());
+                data
+            }
+            Packet::Push {
+                command_code,
+                body,
+                signature,
+            } => {
+                let header = PacketHeader {
+                    ty: PACKAGE_TYPE_PUSH,
+                    verify: signature.is_some(),
+                    gzip: false,
                 }
+                .encode();
+                let mut data = vec![header, *command_code];
+
+                let _ = data.write_u24::<BE>(body.len() as u32);
 
+                data.extend(body);
+                write_signature(&mut data, signature.as_ref());
                 data
             }
-            Packet::Response { .. } => unimplemented!(),
-            Packet::Push { .. } => unimplemented!(),
         }
     }
 
-    pub(crate) fn decode(data: &[u8]) -> WsClientResult<Packet> {
+    /// Decode a packet from bytes
+    pub fn decode(data: &[u8]) -> WsClientResult<Packet> {
         if data.is_empty() {
             return Err(WsClientError::UnexpectedResponse);
         }
@@ -176,12 +232,19 @@ impl Packet {
     }
 }
 
-fn parse_signature(rdr: &mut impl Read) -> std::io::Result<Signature> {
+fn write_signature(data: &mut Vec<u8>, signature: Option<&PacketSignature>) {
+    if let Some(signature) = signature {
+        data.extend_from_slice(&signature.nonce);
+        data.extend_from_slice(&signature.signature);
+    }
+}
+
+fn parse_signature(rdr: &mut impl Read) -> std::io::Result<PacketSignature> {
     let mut nonce = [0; 8];
     let mut signature = [0; 16];
     rdr.read_exact(&mut nonce)?;
     rdr.read_exact(&mut signature)?;
-    Ok(Signature { nonce, signature })
+    Ok(PacketSignature { nonce, signature })
 }
 
 fn parse_request(header: &PacketHeader, data: &[u8]) -> std::io::Result<Packet> {
@@ -10,6 +10,9 @@ mod codec;
 mod error;
 mod event;
 
-pub use client::{CodecType, Platform, ProtocolVersion, RateLimit, WsClient, WsSession};
+pub use client::{
+    CodecType, Platform, ProtocolVersion, RateLimit, WsClient, WsSession, EVENT_CHANNEL_CAPACITY,
+};
+pub use codec::{Packet, PacketSignature};
 pub use error::{WsClientError, WsClientResult, WsCloseReason, WsResponseErrorDetail};
 pub use event::WsEvent;
@@ -0,0 +1,74 @@
+use std::{sync::Weak, time::Duration};
+
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+
+use crate::Config;
+
+/// Refresh the access token this long before it expires
+const REFRESH_BEFORE: time::Duration = time::Duration::days(1);
+
+/// Validity of the refreshed access tokens
+const NEW_TOKEN_VALIDITY: time::Duration = time::Duration::days(90);
+
+/// Delay before retrying a failed refresh
+const RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Access token refresh event
+///
+/// Emitted by the background task started with
+/// [`Config::spawn_access_token_refresher`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessTokenEvent {
+    /// The access token has been refreshed and replaced
+    Refreshed {
+        /// The expiration time of the new access token
+        expired_at: OffsetDateTime,
+    },
+    /// Failed to refresh the access token, the refresh will be retried
+    RefreshFailed {
+        /// Reason
+        reason: String,
+    },
+}
+
+pub(crate) async fn refresh_loop(
+    config: Weak<Config>,
+    mut expired_at: OffsetDateTime,
+    event_tx: mpsc::UnboundedSender<AccessTokenEvent>,
+) {
+    loop {
+        let delay = expired_at - REFRESH_BEFORE - OffsetDateTime::now_utc();
+        if delay.is_positive() {
+            tokio::time::sleep(delay.unsigned_abs()).await;
+        }
+
+        let Some(config) = config.upgrade() else {
+            return;
+        };
+        let new_expired_at = OffsetDateTime::now_utc() + NEW_TOKEN_VALIDITY;
+        let event = match config.refresh_access_token(Some(new_expired_at)).await {
+            Ok(token) => {
+                config.set_access_token(token);
+                tracing::info!(expired_at = %new_expired_at, "access token refreshed");
+                expired_at = new_expired_at;
+                AccessTokenEvent::Refreshed {
+                    expired_at: new_expired_at,
+                }
+            }
+            Err(err) => {
+                tracing::error!(error = %err, "failed to refresh access token");
+                AccessTokenEvent::RefreshFailed {
+                    reason: err.to_string(),
+                }
+            }
+        };
+        drop(config);
+
+        let failed = matches!(event, AccessTokenEvent::RefreshFailed { .. });
+        let _ = event_tx.send(event);
+        if failed {
+            tokio::time::sleep(RETRY_DELAY).await;
+        }
+    }
+}
@@ -1,20 +1,21 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use time::{Date, PrimitiveDateTime};
 
 use crate::{
     blocking::runtime::BlockingRuntime,
     quote::{
-        AdjustType, CalcIndex, Candlestick, CapitalDistributionResponse, CapitalFlowLine,
-        FilterWarrantExpiryDate, FilterWarrantInOutBoundsType, IntradayLine, IssuerInfo,
-        MarketTradingDays, MarketTradingSession, OptionQuote, ParticipantInfo, Period, PushEvent,
-        QuotePackageDetail, RealtimeQuote, RequestCreateWatchlistGroup,
+        AdjustType, AdjustmentFactors, BarType, CalcIndex, Candlestick,
+        CapitalDistributionResponse, CapitalFlowLine, CustomPeriod, FilterWarrantExpiryDate,
+        FilterWarrantInOutBoundsType, Indicator, IndicatorValue, IntradayLine, IssuerInfo,
+        MarketTradingDays, MarketTradingSession, OptionQuote, OrderBook, ParticipantInfo, Period,
+        PushEvent, QuotePackageDetail, RealtimeQuote, RequestCreateWatchlistGroup,
         RequestUpdateWatchlistGroup, Security, SecurityBrokers, SecurityCalcIndex, SecurityDepth,
         SecurityListCategory, SecurityQuote, SecurityStaticInfo, SortOrderType, StrikePriceInfo,
-        SubFlags, Subscription, Trade, TradeSessions, WarrantInfo, WarrantQuote, WarrantSortBy,
-        WarrantStatus, WarrantType, WatchlistGroup,
+        SubFlags, Subscription, Trade, TradeSessions, TradingCalendar, WarrantInfo, WarrantQuote,
+        WarrantSortBy, WarrantStatus, WarrantType, WatchlistGroup,
     },
-    Config, Market, QuoteContext, Result,
+    Config, ConnectionEvent, Market, QuoteContext, Result,
 };
 
 /// Quote context
@@ -32,6 +33,15 @@ impl QuoteContextSync {
         Ok(Self { rt })
     }
 
+    /// Set the callback for the connection state events
+    pub fn set_on_connection_event<F>(&self, callback: F) -> Result<()>
+    where
+        F: FnMut(ConnectionEvent) + Send + 'static,
+    {
+        self.rt
+            .forward_events(|ctx| ctx.connection_events(), callback)
+    }
+
     /// Returns the member ID
     pub fn member_id(&self) -> Result<i64> {
         self.rt.call(|ctx| async move { Ok(ctx.member_id()) })
@@ -167,6 +177,105 @@ impl QuoteContextSync {
             .call(move |ctx| async move { ctx.unsubscribe_candlesticks(symbol, period).await })
     }
 
+    /// Subscribe security candlesticks of a custom period
+    pub fn subscribe_custom_candlesticks<T>(
+        &self,
+        symbol: T,
+        period: CustomPeriod,
+        trade_sessions: TradeSessions,
+    ) -> Result<Vec<Candlestick>>
+    where
+        T: AsRef<str> + Send + 'static,
+    {
+        self.rt.call(move |ctx| async move {
+            ctx.subscribe_custom_candlesticks(symbol, period, trade_sessions)
+                .await
+        })
+    }
+
+    /// Unsubscribe security candlesticks of a custom period
+    pub fn unsubscribe_custom_candlesticks<T>(&self, symbol: T, period: CustomPeriod) -> Result<()>
+    where
+        T: AsRef<str> + Send + 'static,
+    {
+        self.rt.call(
+            move |ctx| async move { ctx.unsubscribe_custom_candlesticks(symbol, period).await },
+        )
+    }
+
+    /// Subscribe the non-time-based bars of the security
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::{sync::Arc, thread::sleep, time::Duration};
+    ///
+    /// use longport::{
+    ///     blocking::QuoteContextSync,
+    ///     quote::{BarType, PushEvent, TradeSessions},
+    ///     Config,
+    /// };
+    ///
+    /// fn event_handler(event: PushEvent) {
+    ///     println!("{:?}", event);
+    /// }
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let config = Arc::new(Config::from_env()?);
+    /// let ctx = QuoteContextSync::try_new(config, event_handler)?;
+    ///
+    /// ctx.subscribe_bars("AAPL.US", BarType::Tick(100), TradeSessions::Normal)?;
+    /// sleep(Duration::from_secs(30));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn subscribe_bars<T>(
+        &self,
+        symbol: T,
+        bar_type: BarType,
+        trade_sessions: TradeSessions,
+    ) -> Result<()>
+    where
+        T: AsRef<str> + Send + 'static,
+    {
+        self.rt.call(move |ctx| async move {
+            ctx.subscribe_bars(symbol, bar_type, trade_sessions).await
+        })
+    }
+
+    /// Unsubscribe the non-time-based bars of the security
+    pub fn unsubscribe_bars<T>(&self, symbol: T, bar_type: BarType) -> Result<()>
+    where
+        T: AsRef<str> + Send + 'static,
+    {
+        self.rt
+            .call(move |ctx| async move { ctx.unsubscribe_bars(symbol, bar_type).await })
+    }
+
+    /// Attach a technical indicator to the subscribed candlesticks, returns
+    /// the latest value
+    pub fn attach_indicator<T>(
+        &self,
+        symbol: T,
+        period: Period,
+        indicator: Indicator,
+    ) -> Result<Option<IndicatorValue>>
+    where
+        T: AsRef<str> + Send + 'static,
+    {
+        self.rt
+            .call(move |ctx| async move { ctx.attach_indicator(symbol, period, indicator).await })
+    }
+
+    /// Detach a technical indicator from the candlesticks
+    pub fn detach_indicator<T>(&self, symbol: T, period: Period, indicator: Indicator) -> Result<()>
+    where
+        T: AsRef<str> + Send + 'static,
+    {
+        self.rt
+            .call(move |ctx| async move { ctx.detach_indicator(symbol, period, indicator).await })
+    }
+
     /// Get subscription information
     ///
     /// # Examples
@@ -516,6 +625,50 @@ impl QuoteContextSync {
         })
     }
 
+    /// Get the history candlesticks of the securities in a date range, with
+    /// at most `concurrency` securities at a time
+    #[allow(clippy::too_many_arguments)]
+    pub fn bulk_history_candlesticks<I, T>(
+        &self,
+        symbols: I,
+        period: Period,
+        adjust_type: AdjustType,
+        start: Date,
+        end: Date,
+        trade_sessions: TradeSessions,
+        concurrency: usize,
+    ) -> Result<HashMap<String, Result<Vec<Candlestick>>>>
+    where
+        I: IntoIterator<Item = T> + Send + 'static,
+        I::IntoIter: Send + 'static,
+        T: Into<String> + Send + 'static,
+    {
+        self.rt.call(move |ctx| async move {
+            Ok(ctx
+                .bulk_history_candlesticks(
+                    symbols,
+                    period,
+                    adjust_type,
+                    start,
+                    end,
+                    trade_sessions,
+                    concurrency,
+                )
+                .await)
+        })
+    }
+
+    /// Get the adjustment factors of the security in a date range
+    pub fn adjustment_factors(
+        &self,
+        symbol: impl Into<String> + Send + 'static,
+        start: Date,
+        end: Date,
+    ) -> Result<AdjustmentFactors> {
+        self.rt
+            .call(move |ctx| async move { ctx.adjustment_factors(symbol, start, end).await })
+    }
+
     /// Get option chain expiry date list
     ///
     /// # Examples
@@ -682,6 +835,31 @@ impl QuoteContextSync {
             .call(move |ctx| async move { ctx.trading_days(market, begin, end).await })
     }
 
+    /// Get the trading calendar
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// use longport::{blocking::QuoteContextSync, Config, Market};
+    /// use time::OffsetDateTime;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let config = Arc::new(Config::from_env()?);
+    /// let ctx = QuoteContextSync::try_new(config, |_| ())?;
+    ///
+    /// let calendar = ctx.trading_calendar()?;
+    /// let today = OffsetDateTime::now_utc().date();
+    /// println!("{:?}", calendar.next_trading_day(Market::HK, today));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn trading_calendar(&self) -> Result<TradingCalendar> {
+        self.rt
+            .call(move |ctx| async move { ctx.trading_calendar().await })
+    }
+
     /// Get capital flow intraday
     ///
     /// # Examples
@@ -919,6 +1097,39 @@ impl QuoteContextSync {
             .call(move |ctx| async move { ctx.realtime_depth(symbol).await })
     }
 
+    /// Get real-time order book
+    ///
+    /// Get the local order book of the subscribed symbols, which is updated
+    /// incrementally with the depth pushes, it always returns the data in the
+    /// local storage.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::{sync::Arc, thread::sleep, time::Duration};
+    ///
+    /// use longport::{blocking::QuoteContextSync, quote::SubFlags, Config};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let config = Arc::new(Config::from_env()?);
+    /// let ctx = QuoteContextSync::try_new(config, |_| ())?;
+    ///
+    /// ctx.subscribe(["700.HK"], SubFlags::DEPTH, true)?;
+    /// sleep(Duration::from_secs(5));
+    ///
+    /// let book = ctx.realtime_order_book("700.HK")?;
+    /// println!("spread: {:?}", book.spread());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn realtime_order_book(
+        &self,
+        symbol: impl Into<String> + Send + 'static,
+    ) -> Result<OrderBook> {
+        self.rt
+            .call(move |ctx| async move { ctx.realtime_order_book(symbol).await })
+    }
+
     /// Get real-time trades
     ///
     /// Get real-time trades of the subscribed symbols, it always returns the
@@ -1021,4 +1232,39 @@ impl QuoteContextSync {
         self.rt
             .call(move |ctx| async move { ctx.realtime_candlesticks(symbol, period, count).await })
     }
+
+    /// Get real-time candlesticks of a custom period
+    pub fn realtime_custom_candlesticks(
+        &self,
+        symbol: impl Into<String> + Send + 'static,
+        period: CustomPeriod,
+        count: usize,
+    ) -> Result<Vec<Candlestick>> {
+        self.rt.call(move |ctx| async move {
+            ctx.realtime_custom_candlesticks(symbol, period, count)
+                .await
+        })
+    }
+
+    /// Get real-time bars
+    pub fn realtime_bars(
+        &self,
+        symbol: impl Into<String> + Send + 'static,
+        bar_type: BarType,
+        count: usize,
+    ) -> Result<Vec<Candlestick>> {
+        self.rt
+            .call(move |ctx| async move { ctx.realtime_bars(symbol, bar_type, count).await })
+    }
+
+    /// Get the latest value of a technical indicator
+    pub fn indicator(
+        &self,
+        symbol: impl Into<String> + Send + 'static,
+        period: Period,
+        indicator: Indicator,
+    ) -> Result<Option<IndicatorValue>> {
+        self.rt
+            .call(move |ctx| async move { ctx.indicator(symbol, period, indicator).await })
+    }
 }
@@ -109,4 +109,34 @@ where
             .map_err(|_| BlockingError::Closed)?;
         reply_rx.recv().map_err(|_| BlockingError::Closed)?
     }
+
+    /// Forwards the events of the receiver created by `subscribe` to
+    /// `callback`, which is called in a dedicated thread.
+    pub(crate) fn forward_events<T, S, C>(&self, subscribe: S, mut callback: C) -> Result<()>
+    where
+        S: FnOnce(&Ctx) -> mpsc::UnboundedReceiver<T> + Send + 'static,
+        C: FnMut(T) + Send + 'static,
+        T: Send + 'static,
+    {
+        let (tx, rx) = std::sync::mpsc::channel::<T>();
+        thread::Builder::new()
+            .spawn(move || {
+                while let Ok(event) = rx.recv() {
+                    callback(event);
+                }
+            })
+            .expect("spawn thread");
+
+        self.call(move |ctx| async move {
+            let mut event_rx = subscribe(&ctx);
+            tokio::spawn(async move {
+                while let Some(event) = event_rx.recv().await {
+                    if tx.send(event).is_err() {
+                        break;
+                    }
+                }
+            });
+            Ok(())
+        })
+    }
 }
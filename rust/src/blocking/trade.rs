@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use crate::{
     blocking::runtime::BlockingRuntime,
@@ -7,10 +7,10 @@ use crate::{
         EstimateMaxPurchaseQuantityResponse, Execution, FundPositionsResponse, GetCashFlowOptions,
         GetFundPositionsOptions, GetHistoryExecutionsOptions, GetHistoryOrdersOptions,
         GetStockPositionsOptions, GetTodayExecutionsOptions, GetTodayOrdersOptions, MarginRatio,
-        Order, OrderDetail, PushEvent, ReplaceOrderOptions, StockPositionsResponse,
-        SubmitOrderOptions, SubmitOrderResponse, TopicType, TradeContext,
+        Order, OrderDetail, OrderStatus, PushEvent, ReplaceOrderOptions, RiskManager,
+        StockPositionsResponse, SubmitOrderOptions, SubmitOrderResponse, TopicType, TradeContext,
     },
-    Config, Result,
+    Config, ConnectionEvent, Result,
 };
 
 /// Trade context
@@ -28,6 +28,30 @@ impl TradeContextSync {
         Ok(Self { rt })
     }
 
+    /// Set the callback for the connection state events
+    pub fn set_on_connection_event<F>(&self, callback: F) -> Result<()>
+    where
+        F: FnMut(ConnectionEvent) + Send + 'static,
+    {
+        self.rt
+            .forward_events(|ctx| ctx.connection_events(), callback)
+    }
+
+    /// Set the risk manager which checks the orders before they are submitted
+    /// or replaced, `None` disables the checks
+    pub fn set_risk_manager(&self, risk_manager: Option<RiskManager>) -> Result<()> {
+        self.rt.call(move |ctx| async move {
+            ctx.set_risk_manager(risk_manager);
+            Ok(())
+        })
+    }
+
+    /// Returns the risk manager
+    pub fn risk_manager(&self) -> Result<Option<RiskManager>> {
+        self.rt
+            .call(move |ctx| async move { Ok(ctx.risk_manager()) })
+    }
+
     /// Subscribe topics
     pub fn subscribe<I>(&self, topics: I) -> Result<()>
     where
@@ -177,6 +201,47 @@ impl TradeContextSync {
             .call(move |ctx| async move { ctx.today_orders(options).await })
     }
 
+    /// Get the cached open orders of today
+    ///
+    /// Subscribe to [`TopicType::Private`] to enable the cache, see
+    /// [`TradeContext::open_orders`](crate::trade::TradeContext::open_orders).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// use longport::{blocking::TradeContextSync, trade::TopicType, Config};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let config = Arc::new(Config::from_env()?);
+    /// let ctx = TradeContextSync::try_new(config, |_| ())?;
+    ///
+    /// ctx.subscribe([TopicType::Private])?;
+    /// let resp = ctx.open_orders()?;
+    /// println!("{:?}", resp);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn open_orders(&self) -> Result<Vec<Order>> {
+        self.rt
+            .call(move |ctx| async move { ctx.open_orders().await })
+    }
+
+    /// Get the cached orders of today of the security
+    pub fn symbol_orders(&self, symbol: impl Into<String>) -> Result<Vec<Order>> {
+        let symbol = symbol.into();
+        self.rt
+            .call(move |ctx| async move { ctx.symbol_orders(symbol).await })
+    }
+
+    /// Get a cached order of today
+    pub fn order(&self, order_id: impl Into<String>) -> Result<Option<Order>> {
+        let order_id = order_id.into();
+        self.rt
+            .call(move |ctx| async move { ctx.order(order_id).await })
+    }
+
     /// Replace order
     ///
     /// # Examples
@@ -238,6 +303,19 @@ impl TradeContextSync {
             .call(move |ctx| async move { ctx.submit_order(options).await })
     }
 
+    /// Submit order, and retry after the failures which leave it unknown
+    /// whether the order is submitted
+    ///
+    /// See [`TradeContext::submit_order_with_retry`] for details.
+    pub fn submit_order_with_retry(
+        &self,
+        options: SubmitOrderOptions,
+        max_retries: usize,
+    ) -> Result<SubmitOrderResponse> {
+        self.rt
+            .call(move |ctx| async move { ctx.submit_order_with_retry(options, max_retries).await })
+    }
+
     /// Cancel order
     ///
     /// # Examples
@@ -412,6 +490,35 @@ impl TradeContextSync {
             .call(move |ctx| async move { ctx.order_detail(order_id).await })
     }
 
+    /// Wait until the status of the order satisfies the predicate or the
+    /// timeout elapses, returns the latest order detail
+    ///
+    /// See [`TradeContext::wait_for_order`] for details.
+    pub fn wait_for_order<F>(
+        &self,
+        order_id: impl Into<String>,
+        predicate: F,
+        timeout: Duration,
+    ) -> Result<OrderDetail>
+    where
+        F: Fn(OrderStatus) -> bool + Send + 'static,
+    {
+        let order_id = order_id.into();
+        self.rt
+            .call(move |ctx| async move { ctx.wait_for_order(order_id, predicate, timeout).await })
+    }
+
+    /// Submit order, and wait until it is filled, rejected, canceled, expired
+    /// or the timeout elapses, returns the latest order detail
+    pub fn submit_and_wait(
+        &self,
+        options: SubmitOrderOptions,
+        timeout: Duration,
+    ) -> Result<OrderDetail> {
+        self.rt
+            .call(move |ctx| async move { ctx.submit_and_wait(options, timeout).await })
+    }
+
     /// Estimating the maximum purchase quantity for Hong Kong and US stocks,
     /// warrants, and options
     pub fn estimate_max_purchase_quantity(
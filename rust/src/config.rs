@@ -7,21 +7,26 @@ use std::{
 
 use http::Method;
 pub(crate) use http::{header, HeaderValue, Request};
-use longport_httpcli::{is_cn, HttpClient, HttpClientConfig, Json};
+use longport_httpcli::{is_cn, CredentialProvider, HttpClient, HttpClientConfig, Json};
 use num_enum::IntoPrimitive;
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
+use tokio::sync::mpsc;
 use tokio_tungstenite::tungstenite::client::IntoClientRequest;
 use tracing::{subscriber::NoSubscriber, Level, Subscriber};
 use tracing_appender::rolling::{RollingFileAppender, Rotation};
 use tracing_subscriber::{filter::Targets, layer::SubscriberExt};
 
-use crate::error::Result;
+use crate::{
+    access_token::refresh_loop, error::Result, quote::OverflowPolicy, AccessTokenEvent,
+    ReconnectPolicy,
+};
 
 const DEFAULT_QUOTE_WS_URL: &str = "wss://openapi-quote.longportapp.com/v2";
 const DEFAULT_TRADE_WS_URL: &str = "wss://openapi-trade.longportapp.com/v2";
 const DEFAULT_QUOTE_WS_URL_CN: &str = "wss://openapi-quote.longportapp.cn/v2";
 const DEFAULT_TRADE_WS_URL_CN: &str = "wss://openapi-trade.longportapp.cn/v2";
+const DEFAULT_PUSH_STREAM_CAPACITY: usize = 1024;
 
 /// Language identifier
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, IntoPrimitive)]
@@ -74,6 +79,12 @@ pub struct Config {
     pub(crate) enable_print_quote_packages: bool,
     pub(crate) language: Option<Language>,
     pub(crate) log_path: Option<PathBuf>,
+    pub(crate) reconnect_policy: ReconnectPolicy,
+    pub(crate) enable_push_resync: bool,
+    pub(crate) push_record_path: Option<PathBuf>,
+    pub(crate) push_stream_capacity: usize,
+    pub(crate) push_stream_overflow_policy: OverflowPolicy,
+    pub(crate) candlestick_cache_dir: Option<PathBuf>,
 }
 
 impl Config {
@@ -92,6 +103,12 @@ impl Config {
             push_candlestick_mode: None,
             enable_print_quote_packages: true,
             log_path: None,
+            reconnect_policy: ReconnectPolicy::default(),
+            enable_push_resync: false,
+            push_record_path: None,
+            push_stream_capacity: DEFAULT_PUSH_STREAM_CAPACITY,
+            push_stream_overflow_policy: OverflowPolicy::default(),
+            candlestick_cache_dir: None,
         }
     }
 
@@ -118,6 +135,17 @@ impl Config {
     ///   `true` or `false` (Default: `true`)
     /// - `LONGPORT_LOG_PATH` - Set the path of the log files (Default: `no
     ///   logs`)
+    /// - `LONGPORT_ENABLE_PUSH_RESYNC` - Re-fetch the realtime data when a gap
+    ///   in the push sequence is detected, `true` or `false` (Default:
+    ///   `false`)
+    /// - `LONGPORT_PUSH_RECORD_PATH` - Record the raw quote push messages to
+    ///   the file (Default: `no recording`)
+    /// - `LONGPORT_PUSH_STREAM_CAPACITY` - Buffer size of the push streams
+    ///   (Default: `1024`)
+    /// - `LONGPORT_PUSH_STREAM_OVERFLOW_POLICY` - `drop_oldest`,
+    ///   `conflate_latest` or `block` (Default: `drop_oldest`)
+    /// - `LONGPORT_CANDLESTICK_CACHE_DIR` - Cache the history candlesticks in
+    ///   the directory (Default: `no cache`)
     pub fn from_env() -> Result<Self> {
         let _ = dotenv::dotenv();
 
@@ -138,6 +166,26 @@ impl Config {
             .unwrap_or("true")
             == "true";
         let log_path = std::env::var("LONGPORT_LOG_PATH").ok().map(PathBuf::from);
+        let enable_push_resync = std::env::var("LONGPORT_ENABLE_PUSH_RESYNC")
+            .map(|value| value == "true")
+            .unwrap_or_default();
+        let push_record_path = std::env::var("LONGPORT_PUSH_RECORD_PATH")
+            .ok()
+            .map(PathBuf::from);
+        let push_stream_capacity = std::env::var("LONGPORT_PUSH_STREAM_CAPACITY")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_PUSH_STREAM_CAPACITY);
+        let push_stream_overflow_policy = std::env::var("LONGPORT_PUSH_STREAM_OVERFLOW_POLICY")
+            .map(|value| match value.as_str() {
+                "conflate_latest" => OverflowPolicy::ConflateLatest,
+                "block" => OverflowPolicy::Block,
+                _ => OverflowPolicy::DropOldest,
+            })
+            .unwrap_or_default();
+        let candlestick_cache_dir = std::env::var("LONGPORT_CANDLESTICK_CACHE_DIR")
+            .ok()
+            .map(PathBuf::from);
 
         Ok(Config {
             http_cli_config,
@@ -148,6 +196,12 @@ impl Config {
             push_candlestick_mode,
             enable_print_quote_packages,
             log_path,
+            reconnect_policy: ReconnectPolicy::default(),
+            enable_push_resync,
+            push_record_path,
+            push_stream_capacity,
+            push_stream_overflow_policy,
+            candlestick_cache_dir,
         })
     }
 
@@ -218,6 +272,30 @@ impl Config {
         }
     }
 
+    /// Specifies the reconnect policy used by the quote and trade contexts
+    ///
+    /// Default: retry every `2` seconds forever
+    pub fn reconnect_policy(self, policy: ReconnectPolicy) -> Self {
+        Self {
+            reconnect_policy: policy,
+            ..self
+        }
+    }
+
+    /// Re-fetch the quote, depth and brokers snapshots when a gap in the push
+    /// sequence is detected
+    ///
+    /// The failed requests are retried with an exponential backoff until the
+    /// securities are unsubscribed.
+    ///
+    /// Default: `false`
+    pub fn enable_push_resync(self) -> Self {
+        Self {
+            enable_push_resync: true,
+            ..self
+        }
+    }
+
     /// Disable printing the opened quote packages when connected to the server.
     pub fn dont_print_quote_packages(self) -> Self {
         Self {
@@ -290,6 +368,9 @@ impl Config {
 
     /// Gets a new `access_token`
     ///
+    /// The token used by the contexts is not changed, pass the new token to
+    /// [`Config::set_access_token`] to replace it.
+    ///
     /// `expired_at` - The expiration time of the access token, defaults to `90`
     /// days.
     ///
@@ -324,6 +405,64 @@ impl Config {
         Ok(new_token)
     }
 
+    /// Specifies the provider of the access token
+    ///
+    /// The access token is read from the provider for every HTTP request and
+    /// every time a context authenticates with the websocket server, so a
+    /// token rotated by the provider is picked up by the running contexts.
+    ///
+    /// Default: the access token passed to [`Config::new`]
+    #[must_use]
+    pub fn credential_provider(mut self, provider: Arc<dyn CredentialProvider>) -> Self {
+        self.http_cli_config = self.http_cli_config.credential_provider(provider);
+        self
+    }
+
+    /// Replaces the access token
+    ///
+    /// The new token is used by the contexts created from this `Config` or
+    /// its clones, including the running ones.
+    pub fn set_access_token(&self, access_token: impl Into<String>) {
+        self.http_cli_config.set_access_token(access_token);
+    }
+
+    /// Starts a background task which refreshes the access token one day
+    /// before it expires, and returns a receiver of the refresh events
+    ///
+    /// `expired_at` - The expiration time of the current access token, the
+    /// refreshed tokens are valid for `90` days.
+    ///
+    /// Failed refreshes are retried every minute. The task stops when the
+    /// `Config` is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// use longport::{Config, TradeContext};
+    /// use time::macros::datetime;
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let config = Arc::new(Config::from_env()?);
+    /// let mut events = config.spawn_access_token_refresher(datetime!(2025-06-01 0:00 UTC));
+    /// let (ctx, _) = TradeContext::try_new(config).await?;
+    ///
+    /// while let Some(event) = events.recv().await {
+    ///     println!("{:?}", event);
+    /// }
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// # });
+    /// ```
+    pub fn spawn_access_token_refresher(
+        self: &Arc<Self>,
+        expired_at: OffsetDateTime,
+    ) -> mpsc::UnboundedReceiver<AccessTokenEvent> {
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        tokio::spawn(refresh_loop(Arc::downgrade(self), expired_at, event_tx));
+        event_rx
+    }
+
     /// Gets a new `access_token`, and also replaces the `access_token` in
     /// `Config`.
     ///
@@ -352,6 +491,62 @@ impl Config {
         self
     }
 
+    /// Record the raw quote push messages to the file at `path`, the file
+    /// can be replayed with [`PushReplayer`](crate::quote::PushReplayer)
+    ///
+    /// New messages are appended if the file already exists.
+    ///
+    /// Default: `None`
+    pub fn push_record_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.push_record_path = Some(path.into());
+        self
+    }
+
+    /// Cache the history candlesticks queried with
+    /// [`QuoteContext::history_candlesticks_by_date`](crate::QuoteContext::history_candlesticks_by_date)
+    /// in the directory at `path`, one file per symbol, period, adjust type
+    /// and trade sessions
+    ///
+    /// Only the missing date ranges are fetched from the server, and the
+    /// forward adjusted candlesticks are re-fetched when the prices are
+    /// shifted by the corporate actions.
+    ///
+    /// When both `start` and `end` are specified, the cached query returns all
+    /// the candlesticks in the date range, while the uncached query returns
+    /// only the first page of the server response.
+    ///
+    /// NOTE: The cache files are not locked between processes, do not share
+    /// the directory with another running process.
+    ///
+    /// Default: `None`
+    pub fn candlestick_cache_dir(self, path: impl Into<PathBuf>) -> Self {
+        Self {
+            candlestick_cache_dir: Some(path.into()),
+            ..self
+        }
+    }
+
+    /// Specifies the buffer size of the push streams created with the
+    /// `*_stream` methods of [`QuoteContext`](crate::QuoteContext)
+    ///
+    /// Default: `1024`
+    pub fn push_stream_capacity(self, capacity: usize) -> Self {
+        Self {
+            push_stream_capacity: capacity,
+            ..self
+        }
+    }
+
+    /// Specifies what happens when a push stream is full
+    ///
+    /// Default: `OverflowPolicy::DropOldest`
+    pub fn push_stream_overflow_policy(self, policy: OverflowPolicy) -> Self {
+        Self {
+            push_stream_overflow_policy: policy,
+            ..self
+        }
+    }
+
     pub(crate) fn create_log_subscriber(
         &self,
         path: impl AsRef<Path>,
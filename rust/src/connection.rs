@@ -0,0 +1,88 @@
+use std::sync::{Arc, Mutex};
+
+use longport_wscli::WsClientError;
+use tokio::sync::mpsc;
+
+use crate::Error;
+
+/// Connection state event
+///
+/// Emitted by [`QuoteContext`](crate::QuoteContext) and
+/// [`TradeContext`](crate::TradeContext) whenever the underlying websocket
+/// connection changes state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    /// Start connecting to the server
+    Connecting {
+        /// Server url
+        url: String,
+    },
+    /// The websocket connection has been established
+    Connected {
+        /// Server url
+        url: String,
+    },
+    /// A new session was created by authenticating with a new OTP
+    Authenticated,
+    /// The previous session was resumed
+    Resumed,
+    /// The connection was lost, or a connection attempt failed
+    Disconnected {
+        /// Reason
+        reason: String,
+    },
+    /// Failed to restore the subscriptions after reconnecting
+    ResubscribeFailed {
+        /// Reason
+        reason: String,
+    },
+    /// The [`ReconnectPolicy`](crate::ReconnectPolicy) gave up reconnecting,
+    /// and the context has been closed
+    GaveUp {
+        /// Number of failed attempts
+        attempts: usize,
+        /// The last error
+        reason: String,
+    },
+}
+
+#[derive(Default)]
+struct Inner {
+    senders: Vec<mpsc::UnboundedSender<ConnectionEvent>>,
+    gave_up: Option<(usize, String)>,
+}
+
+/// Fans out connection events to all registered receivers
+#[derive(Clone, Default)]
+pub(crate) struct ConnectionEventSenders(Arc<Mutex<Inner>>);
+
+impl ConnectionEventSenders {
+    pub(crate) fn subscribe(&self) -> mpsc::UnboundedReceiver<ConnectionEvent> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.0.lock().unwrap().senders.push(tx);
+        rx
+    }
+
+    pub(crate) fn send(&self, event: ConnectionEvent) {
+        tracing::info!(event = ?event, "connection event");
+        let mut inner = self.0.lock().unwrap();
+        if let ConnectionEvent::GaveUp { attempts, reason } = &event {
+            inner.gave_up = Some((*attempts, reason.clone()));
+        }
+        inner.senders.retain(|tx| tx.send(event.clone()).is_ok());
+    }
+
+    /// Returns the error of the requests to a closed context
+    ///
+    /// [`Error::ConnectionGaveUp`] is returned if the context is closed
+    /// because the reconnect policy gave up.
+    pub(crate) fn closed_error(&self) -> Error {
+        match &self.0.lock().unwrap().gave_up {
+            Some((attempts, reason)) => Error::ConnectionGaveUp {
+                attempts: *attempts,
+                reason: reason.clone(),
+            },
+            None => WsClientError::ClientClosed.into(),
+        }
+    }
+}
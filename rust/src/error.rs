@@ -1,9 +1,12 @@
 use std::fmt::Display;
 
 use longport_httpcli::HttpClientError;
+use longport_proto::quote::Period;
 use longport_wscli::WsClientError;
 use time::OffsetDateTime;
 
+use crate::trade::RiskViolation;
+
 /// LongPort OpenAPI SDK error type
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -55,6 +58,15 @@ pub enum Error {
         time: OffsetDateTime,
     },
 
+    /// Candlesticks not subscribed
+    #[error("candlesticks not subscribed: {symbol}, period={period:?}")]
+    CandlesticksNotSubscribed {
+        /// Security symbol
+        symbol: String,
+        /// Period type
+        period: Period,
+    },
+
     /// HTTP client error
     #[error(transparent)]
     HttpClient(#[from] HttpClientError),
@@ -63,6 +75,46 @@ pub enum Error {
     #[error(transparent)]
     WsClient(#[from] WsClientError),
 
+    /// The context is closed because the reconnect policy gave up
+    #[error("gave up reconnecting after {attempts} attempts: {reason}")]
+    ConnectionGaveUp {
+        /// Number of failed attempts
+        attempts: usize,
+        /// The last error
+        reason: String,
+    },
+
+    /// IO error
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// Rejected by the pre-trade risk checks
+    #[error("risk check failed: {symbol}: {violation}")]
+    RiskCheckFailed {
+        /// Security symbol
+        symbol: String,
+        /// Reason of the rejection
+        violation: RiskViolation,
+    },
+
+    /// The remark of an order with the client order id appended is too long
+    #[error("remark too long: {remark}")]
+    RemarkTooLong {
+        /// Remark
+        remark: String,
+    },
+
+    /// Paper trading error
+    #[error("paper trading: {0}")]
+    PaperTrading(String),
+
+    /// The method is not supported by the implementation
+    #[error("unsupported method: {method}")]
+    Unsupported {
+        /// Method name
+        method: &'static str,
+    },
+
     /// Blocking error
     #[cfg(feature = "blocking")]
     #[error(transparent)]
@@ -114,10 +166,17 @@ impl Error {
             | Error::InvalidSecuritySymbol { .. }
             | Error::UnknownMarket { .. }
             | Error::UnknownTradeSession { .. }
+            | Error::CandlesticksNotSubscribed { .. }
             | Error::ParseField { .. }
             | Error::UnknownCommand(_)
             | Error::HttpClient(_)
-            | Error::WsClient(_) => SimpleError::Other(self.to_string()),
+            | Error::WsClient(_)
+            | Error::ConnectionGaveUp { .. }
+            | Error::Io(_)
+            | Error::RiskCheckFailed { .. }
+            | Error::RemarkTooLong { .. }
+            | Error::PaperTrading(_)
+            | Error::Unsupported { .. } => SimpleError::Other(self.to_string()),
             #[cfg(feature = "blocking")]
             Error::Blocking(_) => SimpleError::Other(self.to_string()),
         }
@@ -7,8 +7,11 @@
 #[macro_use]
 mod macros;
 
+mod access_token;
 mod config;
+mod connection;
 mod error;
+mod reconnect;
 mod serde_utils;
 mod types;
 
@@ -18,11 +21,16 @@ pub mod blocking;
 pub mod quote;
 pub mod trade;
 
+pub use access_token::AccessTokenEvent;
 pub use config::{Config, Language, PushCandlestickMode};
+pub use connection::ConnectionEvent;
 pub use error::{Error, Result, SimpleError};
+pub use longport_candlesticks as candlesticks;
 pub use longport_httpcli as httpclient;
+pub use longport_httpcli::{CredentialProvider, MemoryCredentialProvider};
 pub use longport_wscli as wsclient;
-pub use quote::QuoteContext;
+pub use quote::{QuoteApi, QuoteContext};
+pub use reconnect::ReconnectPolicy;
 pub use rust_decimal::Decimal;
-pub use trade::TradeContext;
+pub use trade::{TradeApi, TradeContext};
 pub use types::Market;
@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+
+use rust_decimal::Decimal;
+use time::Date;
+use time_tz::{OffsetDateTimeExt, Tz};
+
+use crate::{
+    quote::{
+        store::Markets, AdjustType, Candlestick, CandlestickPaginator, Period, QuoteApi,
+        SecurityBoard, TradeSessions,
+    },
+    types::parse_market_from_symbol,
+    Error, Result,
+};
+
+/// Corporate action which shifts the prices
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AdjustmentEvent {
+    /// Ex-date
+    pub ex_date: Date,
+    /// The forward adjusted prices before the ex-date are the original prices
+    /// multiplied by the ratio
+    pub ratio: Decimal,
+}
+
+/// Adjustment factors of a security
+///
+/// Applies the forward or backward adjustment to the candlesticks, for example
+/// the candlesticks stored locally or merged from the realtime pushes, so that
+/// they stay consistent with the history candlesticks requested with
+/// [`AdjustType::ForwardAdjust`](crate::quote::AdjustType::ForwardAdjust). Only
+/// the prices are adjusted, the volumes and the turnovers are kept.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use longport::{
+///     quote::{Period, QuoteContext, TradeSessions},
+///     Config,
+/// };
+/// use time::macros::date;
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let config = Arc::new(Config::from_env()?);
+/// let (ctx, _) = QuoteContext::try_new(config).await?;
+///
+/// let factors = ctx
+///     .adjustment_factors("AAPL.US", date!(2020 - 01 - 01), date!(2024 - 12 - 31))
+///     .await?;
+/// let candlesticks = ctx
+///     .subscribe_candlesticks("AAPL.US", Period::Day, TradeSessions::Normal)
+///     .await?;
+/// println!("{:?}", factors.backward_adjust(&candlesticks));
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct AdjustmentFactors {
+    timezone: &'static Tz,
+    events: Vec<AdjustmentEvent>,
+}
+
+impl AdjustmentFactors {
+    /// Create the adjustment factors of the security from the corporate
+    /// actions, returns `None` if the market of the symbol is unknown
+    pub fn new(symbol: &str, events: impl IntoIterator<Item = AdjustmentEvent>) -> Option<Self> {
+        let timezone = parse_market_from_symbol(symbol).and_then(|market| {
+            Markets::default()
+                .get(market, SecurityBoard::Unknown)
+                .map(|market| market.timezone)
+        })?;
+        let mut events = events.into_iter().collect::<Vec<_>>();
+        events.sort_by_key(|event| event.ex_date);
+        Some(Self { timezone, events })
+    }
+
+    /// Derives the adjustment factors of the security by comparing the
+    /// [`AdjustType::NoAdjust`](crate::quote::AdjustType::NoAdjust) and the
+    /// [`AdjustType::ForwardAdjust`](crate::quote::AdjustType::ForwardAdjust)
+    /// candlesticks, returns `None` if the market of the symbol is unknown
+    ///
+    /// The candlesticks are matched by their timestamps, a corporate action is
+    /// detected when the ratio of the prices changes by more than the
+    /// precision of the forward adjusted prices. If the prices of the last
+    /// candlestick are still adjusted, the corporate actions after the
+    /// candlesticks are recorded as one event on the next day.
+    pub fn derive(
+        symbol: &str,
+        no_adjust: &[Candlestick],
+        forward_adjust: &[Candlestick],
+    ) -> Option<Self> {
+        let mut factors = Self::new(symbol, [])?;
+        let forward_adjust = forward_adjust
+            .iter()
+            .map(|candlestick| (candlestick.timestamp, candlestick))
+            .collect::<HashMap<_, _>>();
+        let mut ratios = no_adjust
+            .iter()
+            .filter(|candlestick| !candlestick.close.is_zero())
+            .filter_map(|candlestick| {
+                let adjusted = forward_adjust.get(&candlestick.timestamp)?;
+                let ratio = adjusted.close / candlestick.close;
+                // the forward adjusted prices are rounded
+                let precision = Decimal::new(1, adjusted.close.scale()) / candlestick.close;
+                Some((candlestick.timestamp, ratio, precision))
+            })
+            .collect::<Vec<_>>();
+        ratios.sort_by_key(|(timestamp, _, _)| *timestamp);
+
+        for pair in ratios.windows(2) {
+            let (_, prev_ratio, prev_precision) = pair[0];
+            let (timestamp, ratio, precision) = pair[1];
+            if (prev_ratio - ratio).abs() > prev_precision.max(precision) {
+                factors.events.push(AdjustmentEvent {
+                    ex_date: timestamp.to_timezone(factors.timezone).date(),
+                    ratio: prev_ratio / ratio,
+                });
+            }
+        }
+
+        if let Some((timestamp, ratio, precision)) = ratios.last() {
+            let date = timestamp.to_timezone(factors.timezone).date();
+            if (*ratio - Decimal::ONE).abs() > *precision {
+                if let Some(ex_date) = date.next_day() {
+                    factors.events.push(AdjustmentEvent {
+                        ex_date,
+                        ratio: *ratio,
+                    });
+                }
+            }
+        }
+
+        Some(factors)
+    }
+
+    /// Fetches the daily candlesticks of the security in `start..=end` and
+    /// derives the adjustment factors from them
+    pub(crate) async fn fetch<A: QuoteApi + Clone>(
+        api: &A,
+        symbol: String,
+        start: Date,
+        end: Date,
+    ) -> Result<Self> {
+        let paginator = |adjust_type| {
+            CandlestickPaginator::new(
+                api.clone(),
+                symbol.clone(),
+                Period::Day,
+                adjust_type,
+                start,
+                end,
+                TradeSessions::Normal,
+            )
+            .collect()
+        };
+        let no_adjust = paginator(AdjustType::NoAdjust).await?;
+        let forward_adjust = paginator(AdjustType::ForwardAdjust).await?;
+        Self::derive(&symbol, &no_adjust, &forward_adjust).ok_or(Error::UnknownMarket { symbol })
+    }
+
+    /// Returns the corporate actions sorted by the ex-dates
+    #[inline]
+    pub fn events(&self) -> &[AdjustmentEvent] {
+        &self.events
+    }
+
+    /// Returns the factor of the forward adjusted prices at the date
+    pub fn forward_factor(&self, date: Date) -> Decimal {
+        self.events
+            .iter()
+            .filter(|event| event.ex_date > date)
+            .map(|event| event.ratio)
+            .product()
+    }
+
+    /// Returns the factor of the backward adjusted prices at the date
+    pub fn backward_factor(&self, date: Date) -> Decimal {
+        self.events
+            .iter()
+            .filter(|event| event.ex_date <= date && !event.ratio.is_zero())
+            .map(|event| Decimal::ONE / event.ratio)
+            .product()
+    }
+
+    fn adjust(
+        &self,
+        candlesticks: &[Candlestick],
+        factor: impl Fn(Date) -> Decimal,
+    ) -> Vec<Candlestick> {
+        candlesticks
+            .iter()
+            .map(|candlestick| {
+                let factor = factor(candlestick.timestamp.to_timezone(self.timezone).date());
+                let adjust = |price: Decimal| (price * factor).round_dp(price.scale().max(3));
+                Candlestick {
+                    close: adjust(candlestick.close),
+                    open: adjust(candlestick.open),
+                    low: adjust(candlestick.low),
+                    high: adjust(candlestick.high),
+                    ..*candlestick
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the forward adjusted candlesticks, the latest prices are kept
+    /// and the prices before the corporate actions are adjusted
+    pub fn forward_adjust(&self, candlesticks: &[Candlestick]) -> Vec<Candlestick> {
+        self.adjust(candlesticks, |date| self.forward_factor(date))
+    }
+
+    /// Returns the backward adjusted candlesticks, the earliest prices are kept
+    /// and the prices after the corporate actions are adjusted
+    pub fn backward_adjust(&self, candlesticks: &[Candlestick]) -> Vec<Candlestick> {
+        self.adjust(candlesticks, |date| self.backward_factor(date))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use longport_proto::quote::TradeSession;
+    use time::macros::{date, datetime};
+
+    use super::*;
+
+    fn candlestick(day: i64, close: Decimal) -> Candlestick {
+        Candlestick {
+            close,
+            open: close,
+            low: close,
+            high: close,
+            volume: 100,
+            turnover: Decimal::ZERO,
+            timestamp: datetime!(2024-01-02 14:30 UTC) + time::Duration::days(day),
+            trade_session: TradeSession::NormalTrade,
+        }
+    }
+
+    #[test]
+    fn test_derive_and_adjust() {
+        // a 2 for 1 split on 2024-01-04
+        let no_adjust = [
+            candlestick(0, Decimal::new(100, 0)),
+            candlestick(1, Decimal::new(102, 0)),
+            candlestick(2, Decimal::new(51, 0)),
+            candlestick(3, Decimal::new(52, 0)),
+        ];
+        let forward_adjust = [
+            candlestick(0, Decimal::new(50000, 3)),
+            candlestick(1, Decimal::new(51000, 3)),
+            candlestick(2, Decimal::new(51000, 3)),
+            candlestick(3, Decimal::new(52000, 3)),
+        ];
+
+        let factors = AdjustmentFactors::derive("AAPL.US", &no_adjust, &forward_adjust).unwrap();
+        assert_eq!(
+            factors.events(),
+            [AdjustmentEvent {
+                ex_date: date!(2024 - 01 - 04),
+                ratio: Decimal::new(5, 1),
+            }]
+        );
+
+        let adjusted = factors.forward_adjust(&no_adjust);
+        assert_eq!(
+            adjusted.iter().map(|c| c.close).collect::<Vec<_>>(),
+            forward_adjust.iter().map(|c| c.close).collect::<Vec<_>>()
+        );
+        let adjusted = factors.backward_adjust(&no_adjust);
+        assert_eq!(
+            adjusted.iter().map(|c| c.close).collect::<Vec<_>>(),
+            [
+                Decimal::new(100, 0),
+                Decimal::new(102, 0),
+                Decimal::new(102, 0),
+                Decimal::new(104, 0)
+            ]
+        );
+
+        // the split is after the last candlestick
+        let factors =
+            AdjustmentFactors::derive("AAPL.US", &no_adjust[..2], &forward_adjust[..2]).unwrap();
+        assert_eq!(
+            factors.events(),
+            [AdjustmentEvent {
+                ex_date: date!(2024 - 01 - 04),
+                ratio: Decimal::new(5, 1),
+            }]
+        );
+        assert_eq!(
+            factors
+                .forward_adjust(&no_adjust[..2])
+                .iter()
+                .map(|c| c.close)
+                .collect::<Vec<_>>(),
+            forward_adjust[..2]
+                .iter()
+                .map(|c| c.close)
+                .collect::<Vec<_>>()
+        );
+    }
+}
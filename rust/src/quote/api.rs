@@ -0,0 +1,1302 @@
+use std::{collections::HashMap, future::Future, sync::Arc};
+
+use futures_util::{future, Stream, StreamExt};
+use time::{Date, Duration, OffsetDateTime, PrimitiveDateTime};
+use tokio::sync::mpsc;
+use tracing::Subscriber;
+
+use crate::{
+    quote::{
+        history,
+        sub_flags::SubFlags,
+        types::{
+            FilterWarrantExpiryDate, FilterWarrantInOutBoundsType, SortOrderType, WarrantSortBy,
+            WarrantStatus,
+        },
+        AdjustType, AdjustmentFactors, BarType, CalcIndex, Candlestick, CandlestickPaginator,
+        CapitalDistributionResponse, CapitalFlowLine, CustomPeriod, Indicator, IndicatorValue,
+        IntradayLine, IssuerInfo, MarketTradingDays, MarketTradingSession, OptionQuote, OrderBook,
+        ParticipantInfo, Period, PushBar, PushBrokers, PushCandlestick, PushCustomCandlestick,
+        PushDepth, PushEvent, PushEventDetail, PushIndicator, PushQuote, PushTrades, QuoteContext,
+        QuotePackageDetail, RealtimeQuote, RequestCreateWatchlistGroup,
+        RequestUpdateWatchlistGroup, Security, SecurityBrokers, SecurityCalcIndex, SecurityDepth,
+        SecurityListCategory, SecurityQuote, SecurityStaticInfo, StrikePriceInfo, Subscription,
+        Trade, TradeSessions, TradingCalendar, WarrantInfo, WarrantQuote, WarrantType,
+        WatchlistGroup,
+    },
+    ConnectionEvent, Market, Result,
+};
+
+/// Quote API
+///
+/// Covers all methods of [`QuoteContext`], so that the code depending on the
+/// quote API can be written against this trait and run with mocks, caches,
+/// rate limiters or simulators instead of the real context.
+///
+/// See the methods of [`QuoteContext`] for details.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use longport::{quote::QuoteApi, Config, QuoteContext};
+///
+/// async fn last_done(api: &impl QuoteApi, symbol: &str) -> longport::Result<String> {
+///     let quotes = api.quote([symbol]).await?;
+///     Ok(quotes[0].last_done.to_string())
+/// }
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let config = Arc::new(Config::from_env()?);
+/// let (ctx, _) = QuoteContext::try_new(config).await?;
+/// println!("{}", last_done(&ctx, "700.HK").await?);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// # });
+/// ```
+pub trait QuoteApi: Send + Sync {
+    /// Returns the log subscriber
+    fn log_subscriber(&self) -> Arc<dyn Subscriber + Send + Sync>;
+
+    /// Returns a receiver of the connection state events
+    fn connection_events(&self) -> mpsc::UnboundedReceiver<ConnectionEvent>;
+
+    /// Returns the member ID
+    fn member_id(&self) -> i64;
+
+    /// Returns the quote level
+    fn quote_level(&self) -> &str;
+
+    /// Returns the quote package details
+    fn quote_package_details(&self) -> &[QuotePackageDetail];
+
+    /// Subscribe
+    fn subscribe<I, T>(
+        &self,
+        symbols: I,
+        sub_types: impl Into<SubFlags>,
+        is_first_push: bool,
+    ) -> impl Future<Output = Result<()>> + Send
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>;
+
+    /// Unsubscribe
+    fn unsubscribe<I, T>(
+        &self,
+        symbols: I,
+        sub_types: impl Into<SubFlags>,
+    ) -> impl Future<Output = Result<()>> + Send
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>;
+
+    /// Subscribe security candlesticks
+    fn subscribe_candlesticks<T>(
+        &self,
+        symbol: T,
+        period: Period,
+        trade_sessions: TradeSessions,
+    ) -> impl Future<Output = Result<Vec<Candlestick>>> + Send
+    where
+        T: AsRef<str>;
+
+    /// Unsubscribe security candlesticks
+    fn unsubscribe_candlesticks<T>(
+        &self,
+        symbol: T,
+        period: Period,
+    ) -> impl Future<Output = Result<()>> + Send
+    where
+        T: AsRef<str>;
+
+    /// Subscribe security candlesticks of a custom period
+    fn subscribe_custom_candlesticks<T>(
+        &self,
+        symbol: T,
+        period: CustomPeriod,
+        trade_sessions: TradeSessions,
+    ) -> impl Future<Output = Result<Vec<Candlestick>>> + Send
+    where
+        T: AsRef<str>;
+
+    /// Unsubscribe security candlesticks of a custom period
+    fn unsubscribe_custom_candlesticks<T>(
+        &self,
+        symbol: T,
+        period: CustomPeriod,
+    ) -> impl Future<Output = Result<()>> + Send
+    where
+        T: AsRef<str>;
+
+    /// Subscribe the non-time-based bars of the security
+    fn subscribe_bars<T>(
+        &self,
+        symbol: T,
+        bar_type: BarType,
+        trade_sessions: TradeSessions,
+    ) -> impl Future<Output = Result<()>> + Send
+    where
+        T: AsRef<str>;
+
+    /// Unsubscribe the non-time-based bars of the security
+    fn unsubscribe_bars<T>(
+        &self,
+        symbol: T,
+        bar_type: BarType,
+    ) -> impl Future<Output = Result<()>> + Send
+    where
+        T: AsRef<str>;
+
+    /// Attach a technical indicator to the subscribed candlesticks
+    fn attach_indicator<T>(
+        &self,
+        symbol: T,
+        period: Period,
+        indicator: Indicator,
+    ) -> impl Future<Output = Result<Option<IndicatorValue>>> + Send
+    where
+        T: AsRef<str>;
+
+    /// Detach a technical indicator from the candlesticks
+    fn detach_indicator<T>(
+        &self,
+        symbol: T,
+        period: Period,
+        indicator: Indicator,
+    ) -> impl Future<Output = Result<()>> + Send
+    where
+        T: AsRef<str>;
+
+    /// Get subscription information
+    fn subscriptions(&self) -> impl Future<Output = Result<Vec<Subscription>>> + Send;
+
+    /// Returns a stream of all push events
+    fn push_stream(&self) -> Result<impl Stream<Item = PushEvent> + Send + Unpin + 'static>;
+
+    /// Returns a stream of the quote pushes of the security
+    ///
+    /// The default implementation filters the events of
+    /// [`QuoteApi::push_stream`].
+    fn quote_stream(
+        &self,
+        symbol: impl Into<String>,
+    ) -> Result<impl Stream<Item = PushQuote> + Send + Unpin + 'static> {
+        let symbol = symbol.into();
+        Ok(filter_events(
+            self.push_stream()?,
+            move |event| match event.detail {
+                PushEventDetail::Quote(quote) if event.symbol == symbol => Some(quote),
+                _ => None,
+            },
+        ))
+    }
+
+    /// Returns a stream of the depth pushes of the security
+    ///
+    /// The default implementation filters the events of
+    /// [`QuoteApi::push_stream`].
+    fn depth_stream(
+        &self,
+        symbol: impl Into<String>,
+    ) -> Result<impl Stream<Item = PushDepth> + Send + Unpin + 'static> {
+        let symbol = symbol.into();
+        Ok(filter_events(
+            self.push_stream()?,
+            move |event| match event.detail {
+                PushEventDetail::Depth(depth) if event.symbol == symbol => Some(depth),
+                _ => None,
+            },
+        ))
+    }
+
+    /// Returns a stream of the brokers pushes of the security
+    ///
+    /// The default implementation filters the events of
+    /// [`QuoteApi::push_stream`].
+    fn brokers_stream(
+        &self,
+        symbol: impl Into<String>,
+    ) -> Result<impl Stream<Item = PushBrokers> + Send + Unpin + 'static> {
+        let symbol = symbol.into();
+        Ok(filter_events(
+            self.push_stream()?,
+            move |event| match event.detail {
+                PushEventDetail::Brokers(brokers) if event.symbol == symbol => Some(brokers),
+                _ => None,
+            },
+        ))
+    }
+
+    /// Returns a stream of the trades pushes of the security
+    ///
+    /// The default implementation filters the events of
+    /// [`QuoteApi::push_stream`].
+    fn trades_stream(
+        &self,
+        symbol: impl Into<String>,
+    ) -> Result<impl Stream<Item = PushTrades> + Send + Unpin + 'static> {
+        let symbol = symbol.into();
+        Ok(filter_events(
+            self.push_stream()?,
+            move |event| match event.detail {
+                PushEventDetail::Trade(trades) if event.symbol == symbol => Some(trades),
+                _ => None,
+            },
+        ))
+    }
+
+    /// Returns a stream of the candlestick pushes of the security
+    ///
+    /// The default implementation filters the events of
+    /// [`QuoteApi::push_stream`].
+    fn candlestick_stream(
+        &self,
+        symbol: impl Into<String>,
+        period: Period,
+    ) -> Result<impl Stream<Item = PushCandlestick> + Send + Unpin + 'static> {
+        let symbol = symbol.into();
+        Ok(filter_events(
+            self.push_stream()?,
+            move |event| match event.detail {
+                PushEventDetail::Candlestick(candlestick)
+                    if event.symbol == symbol && candlestick.period == period =>
+                {
+                    Some(candlestick)
+                }
+                _ => None,
+            },
+        ))
+    }
+
+    /// Returns a stream of the custom period candlestick pushes of the
+    /// security
+    ///
+    /// The default implementation filters the events of
+    /// [`QuoteApi::push_stream`].
+    fn custom_candlestick_stream(
+        &self,
+        symbol: impl Into<String>,
+        period: CustomPeriod,
+    ) -> Result<impl Stream<Item = PushCustomCandlestick> + Send + Unpin + 'static> {
+        let symbol = symbol.into();
+        Ok(filter_events(
+            self.push_stream()?,
+            move |event| match event.detail {
+                PushEventDetail::CustomCandlestick(candlestick)
+                    if event.symbol == symbol && candlestick.period == period =>
+                {
+                    Some(candlestick)
+                }
+                _ => None,
+            },
+        ))
+    }
+
+    /// Returns a stream of the bar pushes of the security
+    ///
+    /// The default implementation filters the events of
+    /// [`QuoteApi::push_stream`].
+    fn bar_stream(
+        &self,
+        symbol: impl Into<String>,
+        bar_type: BarType,
+    ) -> Result<impl Stream<Item = PushBar> + Send + Unpin + 'static> {
+        let symbol = symbol.into();
+        Ok(filter_events(
+            self.push_stream()?,
+            move |event| match event.detail {
+                PushEventDetail::Bar(bar) if event.symbol == symbol && bar.bar_type == bar_type => {
+                    Some(bar)
+                }
+                _ => None,
+            },
+        ))
+    }
+
+    /// Returns a stream of the indicator pushes of the security
+    ///
+    /// The default implementation filters the events of
+    /// [`QuoteApi::push_stream`].
+    fn indicator_stream(
+        &self,
+        symbol: impl Into<String>,
+        period: Period,
+        indicator: Indicator,
+    ) -> Result<impl Stream<Item = PushIndicator> + Send + Unpin + 'static> {
+        let symbol = symbol.into();
+        Ok(filter_events(
+            self.push_stream()?,
+            move |event| match event.detail {
+                PushEventDetail::Indicator(value)
+                    if event.symbol == symbol
+                        && value.period == period
+                        && value.indicator == indicator =>
+                {
+                    Some(value)
+                }
+                _ => None,
+            },
+        ))
+    }
+
+    /// Get basic information of securities
+    fn static_info<I, T>(
+        &self,
+        symbols: I,
+    ) -> impl Future<Output = Result<Vec<SecurityStaticInfo>>> + Send
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>;
+
+    /// Get quote of securities
+    fn quote<I, T>(&self, symbols: I) -> impl Future<Output = Result<Vec<SecurityQuote>>> + Send
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>;
+
+    /// Get quote of option securities
+    fn option_quote<I, T>(
+        &self,
+        symbols: I,
+    ) -> impl Future<Output = Result<Vec<OptionQuote>>> + Send
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>;
+
+    /// Get quote of warrant securities
+    fn warrant_quote<I, T>(
+        &self,
+        symbols: I,
+    ) -> impl Future<Output = Result<Vec<WarrantQuote>>> + Send
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>;
+
+    /// Get security depth
+    fn depth(
+        &self,
+        symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<SecurityDepth>> + Send;
+
+    /// Get security brokers
+    fn brokers(
+        &self,
+        symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<SecurityBrokers>> + Send;
+
+    /// Get participants
+    fn participants(&self) -> impl Future<Output = Result<Vec<ParticipantInfo>>> + Send;
+
+    /// Get security trades
+    fn trades(
+        &self,
+        symbol: impl Into<String>,
+        count: usize,
+    ) -> impl Future<Output = Result<Vec<Trade>>> + Send;
+
+    /// Get security intraday lines
+    fn intraday(
+        &self,
+        symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<Vec<IntradayLine>>> + Send;
+
+    /// Get security candlesticks
+    fn candlesticks(
+        &self,
+        symbol: impl Into<String>,
+        period: Period,
+        count: usize,
+        adjust_type: AdjustType,
+        trade_sessions: TradeSessions,
+    ) -> impl Future<Output = Result<Vec<Candlestick>>> + Send;
+
+    /// Get security history candlesticks by offset
+    #[allow(clippy::too_many_arguments)]
+    fn history_candlesticks_by_offset(
+        &self,
+        symbol: impl Into<String>,
+        period: Period,
+        adjust_type: AdjustType,
+        forward: bool,
+        time: Option<PrimitiveDateTime>,
+        count: usize,
+        trade_sessions: TradeSessions,
+    ) -> impl Future<Output = Result<Vec<Candlestick>>> + Send;
+
+    /// Get security history candlesticks by date
+    fn history_candlesticks_by_date(
+        &self,
+        symbol: impl Into<String>,
+        period: Period,
+        adjust_type: AdjustType,
+        start: Option<Date>,
+        end: Option<Date>,
+        trade_sessions: TradeSessions,
+    ) -> impl Future<Output = Result<Vec<Candlestick>>> + Send;
+
+    /// Returns a paginator of the security history candlesticks in a date
+    /// range
+    ///
+    /// The default implementation requests the pages with
+    /// [`QuoteApi::history_candlesticks_by_offset`].
+    fn history_candlesticks_paginator(
+        &self,
+        symbol: impl Into<String>,
+        period: Period,
+        adjust_type: AdjustType,
+        start: Date,
+        end: Date,
+        trade_sessions: TradeSessions,
+    ) -> CandlestickPaginator<Self>
+    where
+        Self: Clone + Sized,
+    {
+        CandlestickPaginator::new(
+            self.clone(),
+            symbol,
+            period,
+            adjust_type,
+            start,
+            end,
+            trade_sessions,
+        )
+    }
+
+    /// Get the history candlesticks of the securities in a date range
+    ///
+    /// The default implementation walks the date ranges with
+    /// [`QuoteApi::history_candlesticks_paginator`].
+    #[allow(clippy::too_many_arguments)]
+    fn bulk_history_candlesticks<I, T>(
+        &self,
+        symbols: I,
+        period: Period,
+        adjust_type: AdjustType,
+        start: Date,
+        end: Date,
+        trade_sessions: TradeSessions,
+        concurrency: usize,
+    ) -> impl Future<Output = HashMap<String, Result<Vec<Candlestick>>>> + Send
+    where
+        Self: Clone + Sized,
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        history::bulk_history_candlesticks(
+            self,
+            collect_symbols(symbols),
+            period,
+            adjust_type,
+            start,
+            end,
+            trade_sessions,
+            concurrency,
+        )
+    }
+
+    /// Get the adjustment factors of the security in a date range
+    ///
+    /// The default implementation derives the factors from the daily
+    /// candlesticks returned by
+    /// [`QuoteApi::history_candlesticks_paginator`].
+    fn adjustment_factors(
+        &self,
+        symbol: impl Into<String>,
+        start: Date,
+        end: Date,
+    ) -> impl Future<Output = Result<AdjustmentFactors>> + Send
+    where
+        Self: Clone + Sized,
+    {
+        AdjustmentFactors::fetch(self, symbol.into(), start, end)
+    }
+
+    /// Get option chain expiry date list
+    fn option_chain_expiry_date_list(
+        &self,
+        symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<Vec<Date>>> + Send;
+
+    /// Get option chain info by date
+    fn option_chain_info_by_date(
+        &self,
+        symbol: impl Into<String>,
+        expiry_date: Date,
+    ) -> impl Future<Output = Result<Vec<StrikePriceInfo>>> + Send;
+
+    /// Get warrant issuers
+    fn warrant_issuers(&self) -> impl Future<Output = Result<Vec<IssuerInfo>>> + Send;
+
+    /// Query warrant list
+    #[allow(clippy::too_many_arguments)]
+    fn warrant_list(
+        &self,
+        symbol: impl Into<String>,
+        sort_by: WarrantSortBy,
+        sort_order: SortOrderType,
+        warrant_type: Option<&[WarrantType]>,
+        issuer: Option<&[i32]>,
+        expiry_date: Option<&[FilterWarrantExpiryDate]>,
+        price_type: Option<&[FilterWarrantInOutBoundsType]>,
+        status: Option<&[WarrantStatus]>,
+    ) -> impl Future<Output = Result<Vec<WarrantInfo>>> + Send;
+
+    /// Get trading session of the day
+    fn trading_session(&self) -> impl Future<Output = Result<Vec<MarketTradingSession>>> + Send;
+
+    /// Get market trading days
+    fn trading_days(
+        &self,
+        market: Market,
+        begin: Date,
+        end: Date,
+    ) -> impl Future<Output = Result<MarketTradingDays>> + Send;
+
+    /// Get the trading calendar
+    ///
+    /// The default implementation fetches the trading days around today with
+    /// [`QuoteApi::trading_days`].
+    fn trading_calendar(&self) -> impl Future<Output = Result<TradingCalendar>> + Send {
+        async move {
+            let begin = OffsetDateTime::now_utc().date() - Duration::days(5);
+            let end = begin + Duration::days(30);
+            let mut days = Vec::new();
+            for market in [Market::HK, Market::US, Market::SG, Market::CN] {
+                days.push((market, self.trading_days(market, begin, end).await?));
+            }
+            Ok(TradingCalendar::from_trading_days(days))
+        }
+    }
+
+    /// Get capital flow intraday
+    fn capital_flow(
+        &self,
+        symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<Vec<CapitalFlowLine>>> + Send;
+
+    /// Get capital distribution
+    fn capital_distribution(
+        &self,
+        symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<CapitalDistributionResponse>> + Send;
+
+    /// Get calc indexes
+    fn calc_indexes<I, T, J>(
+        &self,
+        symbols: I,
+        indexes: J,
+    ) -> impl Future<Output = Result<Vec<SecurityCalcIndex>>> + Send
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+        J: IntoIterator<Item = CalcIndex>;
+
+    /// Get watchlist
+    fn watchlist(&self) -> impl Future<Output = Result<Vec<WatchlistGroup>>> + Send;
+
+    /// Create watchlist group
+    fn create_watchlist_group(
+        &self,
+        req: RequestCreateWatchlistGroup,
+    ) -> impl Future<Output = Result<i64>> + Send;
+
+    /// Delete watchlist group
+    fn delete_watchlist_group(
+        &self,
+        id: i64,
+        purge: bool,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Update watchlist group
+    fn update_watchlist_group(
+        &self,
+        req: RequestUpdateWatchlistGroup,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Get security list
+    fn security_list(
+        &self,
+        market: Market,
+        category: SecurityListCategory,
+    ) -> impl Future<Output = Result<Vec<Security>>> + Send;
+
+    /// Get real-time quotes
+    fn realtime_quote<I, T>(
+        &self,
+        symbols: I,
+    ) -> impl Future<Output = Result<Vec<RealtimeQuote>>> + Send
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>;
+
+    /// Get real-time depth
+    fn realtime_depth(
+        &self,
+        symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<SecurityDepth>> + Send;
+
+    /// Get real-time order book
+    ///
+    /// The default implementation builds the order book from
+    /// [`QuoteApi::realtime_depth`].
+    fn realtime_order_book(
+        &self,
+        symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<OrderBook>> + Send {
+        let depth = self.realtime_depth(symbol);
+        async move { depth.await.map(OrderBook::from) }
+    }
+
+    /// Get real-time trades
+    fn realtime_trades(
+        &self,
+        symbol: impl Into<String>,
+        count: usize,
+    ) -> impl Future<Output = Result<Vec<Trade>>> + Send;
+
+    /// Get real-time broker queue
+    fn realtime_brokers(
+        &self,
+        symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<SecurityBrokers>> + Send;
+
+    /// Get real-time candlesticks
+    fn realtime_candlesticks(
+        &self,
+        symbol: impl Into<String>,
+        period: Period,
+        count: usize,
+    ) -> impl Future<Output = Result<Vec<Candlestick>>> + Send;
+
+    /// Get real-time candlesticks of a custom period
+    fn realtime_custom_candlesticks(
+        &self,
+        symbol: impl Into<String>,
+        period: CustomPeriod,
+        count: usize,
+    ) -> impl Future<Output = Result<Vec<Candlestick>>> + Send;
+
+    /// Get real-time bars
+    fn realtime_bars(
+        &self,
+        symbol: impl Into<String>,
+        bar_type: BarType,
+        count: usize,
+    ) -> impl Future<Output = Result<Vec<Candlestick>>> + Send;
+
+    /// Get the latest value of a technical indicator
+    fn indicator(
+        &self,
+        symbol: impl Into<String>,
+        period: Period,
+        indicator: Indicator,
+    ) -> impl Future<Output = Result<Option<IndicatorValue>>> + Send;
+}
+
+#[inline]
+fn collect_symbols<I, T>(symbols: I) -> Vec<String>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<String>,
+{
+    symbols.into_iter().map(Into::into).collect()
+}
+
+#[inline]
+fn collect_symbol_refs<I, T>(symbols: I) -> Vec<String>
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<str>,
+{
+    symbols
+        .into_iter()
+        .map(|symbol| symbol.as_ref().to_string())
+        .collect()
+}
+
+#[inline]
+fn filter_events<S, T, F>(stream: S, f: F) -> impl Stream<Item = T> + Send + Unpin + 'static
+where
+    S: Stream<Item = PushEvent> + Send + Unpin + 'static,
+    F: Fn(PushEvent) -> Option<T> + Send + 'static,
+    T: Send + 'static,
+{
+    stream.filter_map(move |event| future::ready(f(event)))
+}
+
+// The arguments are converted to owned values before calling the methods of
+// the context, so that the returned futures are `Send` regardless of the
+// argument types.
+impl QuoteApi for QuoteContext {
+    #[inline]
+    fn log_subscriber(&self) -> Arc<dyn Subscriber + Send + Sync> {
+        QuoteContext::log_subscriber(self)
+    }
+
+    #[inline]
+    fn connection_events(&self) -> mpsc::UnboundedReceiver<ConnectionEvent> {
+        QuoteContext::connection_events(self)
+    }
+
+    #[inline]
+    fn member_id(&self) -> i64 {
+        QuoteContext::member_id(self)
+    }
+
+    #[inline]
+    fn quote_level(&self) -> &str {
+        QuoteContext::quote_level(self)
+    }
+
+    #[inline]
+    fn quote_package_details(&self) -> &[QuotePackageDetail] {
+        QuoteContext::quote_package_details(self)
+    }
+
+    fn subscribe<I, T>(
+        &self,
+        symbols: I,
+        sub_types: impl Into<SubFlags>,
+        is_first_push: bool,
+    ) -> impl Future<Output = Result<()>> + Send
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        QuoteContext::subscribe(
+            self,
+            collect_symbol_refs(symbols),
+            sub_types.into(),
+            is_first_push,
+        )
+    }
+
+    fn unsubscribe<I, T>(
+        &self,
+        symbols: I,
+        sub_types: impl Into<SubFlags>,
+    ) -> impl Future<Output = Result<()>> + Send
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        QuoteContext::unsubscribe(self, collect_symbol_refs(symbols), sub_types.into())
+    }
+
+    fn subscribe_candlesticks<T>(
+        &self,
+        symbol: T,
+        period: Period,
+        trade_sessions: TradeSessions,
+    ) -> impl Future<Output = Result<Vec<Candlestick>>> + Send
+    where
+        T: AsRef<str>,
+    {
+        QuoteContext::subscribe_candlesticks(
+            self,
+            symbol.as_ref().to_string(),
+            period,
+            trade_sessions,
+        )
+    }
+
+    fn unsubscribe_candlesticks<T>(
+        &self,
+        symbol: T,
+        period: Period,
+    ) -> impl Future<Output = Result<()>> + Send
+    where
+        T: AsRef<str>,
+    {
+        QuoteContext::unsubscribe_candlesticks(self, symbol.as_ref().to_string(), period)
+    }
+
+    fn subscribe_custom_candlesticks<T>(
+        &self,
+        symbol: T,
+        period: CustomPeriod,
+        trade_sessions: TradeSessions,
+    ) -> impl Future<Output = Result<Vec<Candlestick>>> + Send
+    where
+        T: AsRef<str>,
+    {
+        QuoteContext::subscribe_custom_candlesticks(
+            self,
+            symbol.as_ref().to_string(),
+            period,
+            trade_sessions,
+        )
+    }
+
+    fn unsubscribe_custom_candlesticks<T>(
+        &self,
+        symbol: T,
+        period: CustomPeriod,
+    ) -> impl Future<Output = Result<()>> + Send
+    where
+        T: AsRef<str>,
+    {
+        QuoteContext::unsubscribe_custom_candlesticks(self, symbol.as_ref().to_string(), period)
+    }
+
+    fn subscribe_bars<T>(
+        &self,
+        symbol: T,
+        bar_type: BarType,
+        trade_sessions: TradeSessions,
+    ) -> impl Future<Output = Result<()>> + Send
+    where
+        T: AsRef<str>,
+    {
+        QuoteContext::subscribe_bars(self, symbol.as_ref().to_string(), bar_type, trade_sessions)
+    }
+
+    fn unsubscribe_bars<T>(
+        &self,
+        symbol: T,
+        bar_type: BarType,
+    ) -> impl Future<Output = Result<()>> + Send
+    where
+        T: AsRef<str>,
+    {
+        QuoteContext::unsubscribe_bars(self, symbol.as_ref().to_string(), bar_type)
+    }
+
+    fn attach_indicator<T>(
+        &self,
+        symbol: T,
+        period: Period,
+        indicator: Indicator,
+    ) -> impl Future<Output = Result<Option<IndicatorValue>>> + Send
+    where
+        T: AsRef<str>,
+    {
+        QuoteContext::attach_indicator(self, symbol.as_ref().to_string(), period, indicator)
+    }
+
+    fn detach_indicator<T>(
+        &self,
+        symbol: T,
+        period: Period,
+        indicator: Indicator,
+    ) -> impl Future<Output = Result<()>> + Send
+    where
+        T: AsRef<str>,
+    {
+        QuoteContext::detach_indicator(self, symbol.as_ref().to_string(), period, indicator)
+    }
+
+    fn subscriptions(&self) -> impl Future<Output = Result<Vec<Subscription>>> + Send {
+        QuoteContext::subscriptions(self)
+    }
+
+    #[inline]
+    fn push_stream(&self) -> Result<impl Stream<Item = PushEvent> + Send + Unpin + 'static> {
+        QuoteContext::push_stream(self)
+    }
+
+    #[inline]
+    fn quote_stream(
+        &self,
+        symbol: impl Into<String>,
+    ) -> Result<impl Stream<Item = PushQuote> + Send + Unpin + 'static> {
+        QuoteContext::quote_stream(self, symbol)
+    }
+
+    #[inline]
+    fn depth_stream(
+        &self,
+        symbol: impl Into<String>,
+    ) -> Result<impl Stream<Item = PushDepth> + Send + Unpin + 'static> {
+        QuoteContext::depth_stream(self, symbol)
+    }
+
+    #[inline]
+    fn brokers_stream(
+        &self,
+        symbol: impl Into<String>,
+    ) -> Result<impl Stream<Item = PushBrokers> + Send + Unpin + 'static> {
+        QuoteContext::brokers_stream(self, symbol)
+    }
+
+    #[inline]
+    fn trades_stream(
+        &self,
+        symbol: impl Into<String>,
+    ) -> Result<impl Stream<Item = PushTrades> + Send + Unpin + 'static> {
+        QuoteContext::trades_stream(self, symbol)
+    }
+
+    #[inline]
+    fn candlestick_stream(
+        &self,
+        symbol: impl Into<String>,
+        period: Period,
+    ) -> Result<impl Stream<Item = PushCandlestick> + Send + Unpin + 'static> {
+        QuoteContext::candlestick_stream(self, symbol, period)
+    }
+
+    #[inline]
+    fn custom_candlestick_stream(
+        &self,
+        symbol: impl Into<String>,
+        period: CustomPeriod,
+    ) -> Result<impl Stream<Item = PushCustomCandlestick> + Send + Unpin + 'static> {
+        QuoteContext::custom_candlestick_stream(self, symbol, period)
+    }
+
+    #[inline]
+    fn bar_stream(
+        &self,
+        symbol: impl Into<String>,
+        bar_type: BarType,
+    ) -> Result<impl Stream<Item = PushBar> + Send + Unpin + 'static> {
+        QuoteContext::bar_stream(self, symbol, bar_type)
+    }
+
+    #[inline]
+    fn indicator_stream(
+        &self,
+        symbol: impl Into<String>,
+        period: Period,
+        indicator: Indicator,
+    ) -> Result<impl Stream<Item = PushIndicator> + Send + Unpin + 'static> {
+        QuoteContext::indicator_stream(self, symbol, period, indicator)
+    }
+
+    fn static_info<I, T>(
+        &self,
+        symbols: I,
+    ) -> impl Future<Output = Result<Vec<SecurityStaticInfo>>> + Send
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        QuoteContext::static_info(self, collect_symbols(symbols))
+    }
+
+    fn quote<I, T>(&self, symbols: I) -> impl Future<Output = Result<Vec<SecurityQuote>>> + Send
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        QuoteContext::quote(self, collect_symbols(symbols))
+    }
+
+    fn option_quote<I, T>(
+        &self,
+        symbols: I,
+    ) -> impl Future<Output = Result<Vec<OptionQuote>>> + Send
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        QuoteContext::option_quote(self, collect_symbols(symbols))
+    }
+
+    fn warrant_quote<I, T>(
+        &self,
+        symbols: I,
+    ) -> impl Future<Output = Result<Vec<WarrantQuote>>> + Send
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        QuoteContext::warrant_quote(self, collect_symbols(symbols))
+    }
+
+    fn depth(
+        &self,
+        symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<SecurityDepth>> + Send {
+        QuoteContext::depth(self, symbol.into())
+    }
+
+    fn brokers(
+        &self,
+        symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<SecurityBrokers>> + Send {
+        QuoteContext::brokers(self, symbol.into())
+    }
+
+    fn participants(&self) -> impl Future<Output = Result<Vec<ParticipantInfo>>> + Send {
+        QuoteContext::participants(self)
+    }
+
+    fn trades(
+        &self,
+        symbol: impl Into<String>,
+        count: usize,
+    ) -> impl Future<Output = Result<Vec<Trade>>> + Send {
+        QuoteContext::trades(self, symbol.into(), count)
+    }
+
+    fn intraday(
+        &self,
+        symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<Vec<IntradayLine>>> + Send {
+        QuoteContext::intraday(self, symbol.into())
+    }
+
+    fn candlesticks(
+        &self,
+        symbol: impl Into<String>,
+        period: Period,
+        count: usize,
+        adjust_type: AdjustType,
+        trade_sessions: TradeSessions,
+    ) -> impl Future<Output = Result<Vec<Candlestick>>> + Send {
+        QuoteContext::candlesticks(
+            self,
+            symbol.into(),
+            period,
+            count,
+            adjust_type,
+            trade_sessions,
+        )
+    }
+
+    fn history_candlesticks_by_offset(
+        &self,
+        symbol: impl Into<String>,
+        period: Period,
+        adjust_type: AdjustType,
+        forward: bool,
+        time: Option<PrimitiveDateTime>,
+        count: usize,
+        trade_sessions: TradeSessions,
+    ) -> impl Future<Output = Result<Vec<Candlestick>>> + Send {
+        QuoteContext::history_candlesticks_by_offset(
+            self,
+            symbol.into(),
+            period,
+            adjust_type,
+            forward,
+            time,
+            count,
+            trade_sessions,
+        )
+    }
+
+    fn history_candlesticks_by_date(
+        &self,
+        symbol: impl Into<String>,
+        period: Period,
+        adjust_type: AdjustType,
+        start: Option<Date>,
+        end: Option<Date>,
+        trade_sessions: TradeSessions,
+    ) -> impl Future<Output = Result<Vec<Candlestick>>> + Send {
+        QuoteContext::history_candlesticks_by_date(
+            self,
+            symbol.into(),
+            period,
+            adjust_type,
+            start,
+            end,
+            trade_sessions,
+        )
+    }
+
+    fn option_chain_expiry_date_list(
+        &self,
+        symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<Vec<Date>>> + Send {
+        QuoteContext::option_chain_expiry_date_list(self, symbol.into())
+    }
+
+    fn option_chain_info_by_date(
+        &self,
+        symbol: impl Into<String>,
+        expiry_date: Date,
+    ) -> impl Future<Output = Result<Vec<StrikePriceInfo>>> + Send {
+        QuoteContext::option_chain_info_by_date(self, symbol.into(), expiry_date)
+    }
+
+    fn warrant_issuers(&self) -> impl Future<Output = Result<Vec<IssuerInfo>>> + Send {
+        QuoteContext::warrant_issuers(self)
+    }
+
+    fn warrant_list(
+        &self,
+        symbol: impl Into<String>,
+        sort_by: WarrantSortBy,
+        sort_order: SortOrderType,
+        warrant_type: Option<&[WarrantType]>,
+        issuer: Option<&[i32]>,
+        expiry_date: Option<&[FilterWarrantExpiryDate]>,
+        price_type: Option<&[FilterWarrantInOutBoundsType]>,
+        status: Option<&[WarrantStatus]>,
+    ) -> impl Future<Output = Result<Vec<WarrantInfo>>> + Send {
+        QuoteContext::warrant_list(
+            self,
+            symbol.into(),
+            sort_by,
+            sort_order,
+            warrant_type,
+            issuer,
+            expiry_date,
+            price_type,
+            status,
+        )
+    }
+
+    fn trading_session(&self) -> impl Future<Output = Result<Vec<MarketTradingSession>>> + Send {
+        QuoteContext::trading_session(self)
+    }
+
+    fn trading_days(
+        &self,
+        market: Market,
+        begin: Date,
+        end: Date,
+    ) -> impl Future<Output = Result<MarketTradingDays>> + Send {
+        QuoteContext::trading_days(self, market, begin, end)
+    }
+
+    fn trading_calendar(&self) -> impl Future<Output = Result<TradingCalendar>> + Send {
+        QuoteContext::trading_calendar(self)
+    }
+
+    fn capital_flow(
+        &self,
+        symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<Vec<CapitalFlowLine>>> + Send {
+        QuoteContext::capital_flow(self, symbol.into())
+    }
+
+    fn capital_distribution(
+        &self,
+        symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<CapitalDistributionResponse>> + Send {
+        QuoteContext::capital_distribution(self, symbol.into())
+    }
+
+    fn calc_indexes<I, T, J>(
+        &self,
+        symbols: I,
+        indexes: J,
+    ) -> impl Future<Output = Result<Vec<SecurityCalcIndex>>> + Send
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+        J: IntoIterator<Item = CalcIndex>,
+    {
+        QuoteContext::calc_indexes(
+            self,
+            collect_symbols(symbols),
+            indexes.into_iter().collect::<Vec<_>>(),
+        )
+    }
+
+    fn watchlist(&self) -> impl Future<Output = Result<Vec<WatchlistGroup>>> + Send {
+        QuoteContext::watchlist(self)
+    }
+
+    fn create_watchlist_group(
+        &self,
+        req: RequestCreateWatchlistGroup,
+    ) -> impl Future<Output = Result<i64>> + Send {
+        QuoteContext::create_watchlist_group(self, req)
+    }
+
+    fn delete_watchlist_group(
+        &self,
+        id: i64,
+        purge: bool,
+    ) -> impl Future<Output = Result<()>> + Send {
+        QuoteContext::delete_watchlist_group(self, id, purge)
+    }
+
+    fn update_watchlist_group(
+        &self,
+        req: RequestUpdateWatchlistGroup,
+    ) -> impl Future<Output = Result<()>> + Send {
+        QuoteContext::update_watchlist_group(self, req)
+    }
+
+    fn security_list(
+        &self,
+        market: Market,
+        category: SecurityListCategory,
+    ) -> impl Future<Output = Result<Vec<Security>>> + Send {
+        QuoteContext::security_list(self, market, category)
+    }
+
+    fn realtime_quote<I, T>(
+        &self,
+        symbols: I,
+    ) -> impl Future<Output = Result<Vec<RealtimeQuote>>> + Send
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        QuoteContext::realtime_quote(self, collect_symbols(symbols))
+    }
+
+    fn realtime_depth(
+        &self,
+        symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<SecurityDepth>> + Send {
+        QuoteContext::realtime_depth(self, symbol.into())
+    }
+
+    fn realtime_order_book(
+        &self,
+        symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<OrderBook>> + Send {
+        QuoteContext::realtime_order_book(self, symbol.into())
+    }
+
+    fn realtime_trades(
+        &self,
+        symbol: impl Into<String>,
+        count: usize,
+    ) -> impl Future<Output = Result<Vec<Trade>>> + Send {
+        QuoteContext::realtime_trades(self, symbol.into(), count)
+    }
+
+    fn realtime_brokers(
+        &self,
+        symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<SecurityBrokers>> + Send {
+        QuoteContext::realtime_brokers(self, symbol.into())
+    }
+
+    fn realtime_candlesticks(
+        &self,
+        symbol: impl Into<String>,
+        period: Period,
+        count: usize,
+    ) -> impl Future<Output = Result<Vec<Candlestick>>> + Send {
+        QuoteContext::realtime_candlesticks(self, symbol.into(), period, count)
+    }
+
+    fn realtime_custom_candlesticks(
+        &self,
+        symbol: impl Into<String>,
+        period: CustomPeriod,
+        count: usize,
+    ) -> impl Future<Output = Result<Vec<Candlestick>>> + Send {
+        QuoteContext::realtime_custom_candlesticks(self, symbol.into(), period, count)
+    }
+
+    fn realtime_bars(
+        &self,
+        symbol: impl Into<String>,
+        bar_type: BarType,
+        count: usize,
+    ) -> impl Future<Output = Result<Vec<Candlestick>>> + Send {
+        QuoteContext::realtime_bars(self, symbol.into(), bar_type, count)
+    }
+
+    fn indicator(
+        &self,
+        symbol: impl Into<String>,
+        period: Period,
+        indicator: Indicator,
+    ) -> impl Future<Output = Result<Option<IndicatorValue>>> + Send {
+        QuoteContext::indicator(self, symbol.into(), period, indicator)
+    }
+}
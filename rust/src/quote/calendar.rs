@@ -0,0 +1,315 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use longport_candlesticks::{
+    TradeSessionType, TRADE_SESSION_NORMAL, TRADE_SESSION_OVERNIGHT, TRADE_SESSION_POST,
+    TRADE_SESSION_PRE,
+};
+use time::{Date, Duration, OffsetDateTime, PrimitiveDateTime, Time};
+use time_tz::{OffsetDateTimeExt, PrimitiveDateTimeExt, Tz};
+
+use crate::{
+    quote::{core::TradingDays, store::Markets, MarketTradingDays, SecurityBoard, TradeSession},
+    types::parse_market_from_symbol,
+    Market,
+};
+
+const TRADE_SESSION_TYPES: [(TradeSessionType, TradeSession); 4] = [
+    (TRADE_SESSION_NORMAL, TradeSession::NormalTrade),
+    (TRADE_SESSION_PRE, TradeSession::PreTrade),
+    (TRADE_SESSION_POST, TradeSession::PostTrade),
+    (TRADE_SESSION_OVERNIGHT, TradeSession::OvernightTrade),
+];
+
+/// Trading calendar
+///
+/// A snapshot of the trading days cached by the quote context and the trade
+/// sessions of the markets. The trading days are refreshed daily and cover
+/// about a month around today, the queries outside of the range return
+/// `false`, `None` or an empty list.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use longport::{quote::QuoteContext, Config, Market};
+/// use time::OffsetDateTime;
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let config = Arc::new(Config::from_env()?);
+/// let (ctx, _) = QuoteContext::try_new(config).await?;
+///
+/// let calendar = ctx.trading_calendar().await?;
+/// let now = OffsetDateTime::now_utc();
+/// println!("{:?}", calendar.next_trading_day(Market::HK, now.date()));
+/// println!("{:?}", calendar.current_session("700.HK", now));
+/// println!("{:?}", calendar.time_until_open("700.HK", now));
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct TradingCalendar {
+    trading_days: HashMap<Market, BTreeSet<Date>>,
+    half_trading_days: HashMap<Market, HashSet<Date>>,
+    markets: Markets,
+}
+
+impl TradingCalendar {
+    pub(super) fn new(days: &TradingDays, markets: Markets) -> Self {
+        let mut trading_days: HashMap<Market, BTreeSet<Date>> = HashMap::new();
+        for (market, days) in days.normal_days.iter().chain(&days.half_days) {
+            trading_days
+                .entry(*market)
+                .or_default()
+                .extend(days.iter().copied());
+        }
+        Self {
+            trading_days,
+            half_trading_days: days.half_days.clone(),
+            markets,
+        }
+    }
+
+    /// Create a trading calendar from the trading days of the markets
+    pub fn from_trading_days(days: impl IntoIterator<Item = (Market, MarketTradingDays)>) -> Self {
+        let mut trading_days = TradingDays::default();
+        for (market, days) in days {
+            trading_days
+                .normal_days
+                .insert(market, days.trading_days.into_iter().collect());
+            trading_days
+                .half_days
+                .insert(market, days.half_trading_days.into_iter().collect());
+        }
+        Self::new(&trading_days, Markets::default())
+    }
+
+    fn days(&self, market: Market) -> Option<&BTreeSet<Date>> {
+        self.trading_days.get(&market)
+    }
+
+    fn market(&self, symbol: &str) -> Option<(Market, &longport_candlesticks::Market)> {
+        let market = parse_market_from_symbol(symbol)?;
+        Some((market, self.markets.get(market, SecurityBoard::Unknown)?))
+    }
+
+    fn trade_sessions<'a>(
+        &self,
+        market: Market,
+        candlestick_market: &'a longport_candlesticks::Market,
+        date: Date,
+        ts: TradeSessionType,
+    ) -> &'a [longport_candlesticks::TradeSession] {
+        if self.is_half_trading_day(market, date) {
+            candlestick_market.half_trade_sessions(ts)
+        } else {
+            candlestick_market.trade_sessions(ts)
+        }
+    }
+
+    /// Returns `true` if the date is a trading day of the market, including
+    /// the half trading days
+    pub fn is_trading_day(&self, market: Market, date: Date) -> bool {
+        self.days(market).is_some_and(|days| days.contains(&date))
+    }
+
+    /// Returns `true` if the date is a half trading day of the market
+    pub fn is_half_trading_day(&self, market: Market, date: Date) -> bool {
+        self.half_trading_days
+            .get(&market)
+            .is_some_and(|days| days.contains(&date))
+    }
+
+    /// Returns the first trading day of the market after the date
+    pub fn next_trading_day(&self, market: Market, date: Date) -> Option<Date> {
+        let next_day = date.next_day()?;
+        self.days(market)?.range(next_day..).next().copied()
+    }
+
+    /// Returns the last trading day of the market before the date
+    pub fn prev_trading_day(&self, market: Market, date: Date) -> Option<Date> {
+        self.days(market)?.range(..date).next_back().copied()
+    }
+
+    /// Returns the trading days of the market in `start..=end`
+    pub fn trading_days_between(&self, market: Market, start: Date, end: Date) -> Vec<Date> {
+        match self.days(market) {
+            Some(days) if start <= end => days.range(start..=end).copied().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the trade session of the security at the time, returns `None`
+    /// if the market is closed
+    ///
+    /// The sessions are looked up on the date in the timezone of the market,
+    /// so the overnight session is only reported on the trading days.
+    pub fn current_session(&self, symbol: &str, time: OffsetDateTime) -> Option<TradeSession> {
+        let (market, candlestick_market) = self.market(symbol)?;
+        let local = time.to_timezone(candlestick_market.timezone);
+        if !self.is_trading_day(market, local.date()) {
+            return None;
+        }
+
+        TRADE_SESSION_TYPES
+            .into_iter()
+            .find(|(ts, _)| {
+                self.trade_sessions(market, candlestick_market, local.date(), *ts)
+                    .iter()
+                    .any(|trade_session| {
+                        local.time() >= trade_session.start
+                            && (local.time() < trade_session.end
+                                || (trade_session.inclusive && local.time() <= trade_session.end))
+                    })
+            })
+            .map(|(_, trade_session)| trade_session)
+    }
+
+    /// Returns the duration until the next normal session of the security
+    /// opens, returns zero if a normal session is open
+    pub fn time_until_open(&self, symbol: &str, time: OffsetDateTime) -> Option<Duration> {
+        let (market, candlestick_market) = self.market(symbol)?;
+        let timezone = candlestick_market.timezone;
+        let date = time.to_timezone(timezone).date();
+
+        for day in self.days(market)?.range(date..) {
+            for trade_session in
+                self.trade_sessions(market, candlestick_market, *day, TRADE_SESSION_NORMAL)
+            {
+                let start = to_offset_date_time(*day, trade_session.start, timezone)?;
+                let end = to_offset_date_time(*day, trade_session.end, timezone)?;
+                if time < start {
+                    return Some(start - time);
+                } else if time < end {
+                    return Some(Duration::ZERO);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Returns the duration until the current normal session of the security
+    /// closes, returns `None` if no normal session is open
+    pub fn time_until_close(&self, symbol: &str, time: OffsetDateTime) -> Option<Duration> {
+        let (market, candlestick_market) = self.market(symbol)?;
+        let timezone = candlestick_market.timezone;
+        let date = time.to_timezone(timezone).date();
+        if !self.is_trading_day(market, date) {
+            return None;
+        }
+
+        self.trade_sessions(market, candlestick_market, date, TRADE_SESSION_NORMAL)
+            .iter()
+            .find_map(|trade_session| {
+                let start = to_offset_date_time(date, trade_session.start, timezone)?;
+                let end = to_offset_date_time(date, trade_session.end, timezone)?;
+                (time >= start && time < end).then(|| end - time)
+            })
+    }
+}
+
+fn to_offset_date_time(date: Date, time: Time, timezone: &'static Tz) -> Option<OffsetDateTime> {
+    PrimitiveDateTime::new(date, time)
+        .assume_timezone(timezone)
+        .take_first()
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::{date, datetime};
+
+    use super::*;
+
+    fn calendar() -> TradingCalendar {
+        let mut days = TradingDays::default();
+        days.normal_days.insert(
+            Market::HK,
+            [
+                date!(2024 - 12 - 23),
+                date!(2024 - 12 - 27),
+                date!(2024 - 12 - 30),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        days.half_days.insert(
+            Market::HK,
+            [date!(2024 - 12 - 24), date!(2024 - 12 - 31)]
+                .into_iter()
+                .collect(),
+        );
+        TradingCalendar::new(&days, Markets::default())
+    }
+
+    #[test]
+    fn test_trading_days() {
+        let calendar = calendar();
+
+        assert!(calendar.is_trading_day(Market::HK, date!(2024 - 12 - 24)));
+        assert!(calendar.is_half_trading_day(Market::HK, date!(2024 - 12 - 24)));
+        assert!(!calendar.is_trading_day(Market::HK, date!(2024 - 12 - 25)));
+        assert!(!calendar.is_trading_day(Market::US, date!(2024 - 12 - 23)));
+
+        assert_eq!(
+            calendar.next_trading_day(Market::HK, date!(2024 - 12 - 24)),
+            Some(date!(2024 - 12 - 27))
+        );
+        assert_eq!(
+            calendar.prev_trading_day(Market::HK, date!(2024 - 12 - 27)),
+            Some(date!(2024 - 12 - 24))
+        );
+        assert_eq!(
+            calendar.next_trading_day(Market::HK, date!(2024 - 12 - 31)),
+            None
+        );
+        assert_eq!(
+            calendar.trading_days_between(Market::HK, date!(2024 - 12 - 24), date!(2024 - 12 - 30)),
+            vec![
+                date!(2024 - 12 - 24),
+                date!(2024 - 12 - 27),
+                date!(2024 - 12 - 30)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sessions() {
+        let calendar = calendar();
+
+        assert_eq!(
+            calendar.current_session("700.HK", datetime!(2024-12-23 10:00 +8)),
+            Some(TradeSession::NormalTrade)
+        );
+        assert_eq!(
+            calendar.current_session("700.HK", datetime!(2024-12-23 12:30 +8)),
+            None
+        );
+        // half trading day
+        assert_eq!(
+            calendar.current_session("700.HK", datetime!(2024-12-24 13:30 +8)),
+            None
+        );
+
+        assert_eq!(
+            calendar.time_until_open("700.HK", datetime!(2024-12-23 12:30 +8)),
+            Some(Duration::minutes(30))
+        );
+        assert_eq!(
+            calendar.time_until_open("700.HK", datetime!(2024-12-23 10:00 +8)),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(
+            calendar.time_until_open("700.HK", datetime!(2024-12-24 13:00 +8)),
+            Some(Duration::days(2) + Duration::hours(20) + Duration::minutes(30))
+        );
+        assert_eq!(
+            calendar.time_until_close("700.HK", datetime!(2024-12-23 15:00 +8)),
+            Some(Duration::hours(1))
+        );
+        assert_eq!(
+            calendar.time_until_close("700.HK", datetime!(2024-12-23 12:30 +8)),
+            None
+        );
+    }
+}
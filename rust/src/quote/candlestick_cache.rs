@@ -0,0 +1,327 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+use time::{Date, OffsetDateTime};
+use time_tz::{OffsetDateTimeExt, Tz};
+
+use crate::{
+    quote::{
+        history::CandlestickPaginator, store::Markets, AdjustType, Candlestick, Period,
+        QuoteContext, SecurityBoard, TradeSessions,
+    },
+    types::parse_market_from_symbol,
+    Error, Result,
+};
+
+/// The history candlesticks of a symbol, period, adjust type and trade
+/// sessions stored in a file
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheFile {
+    /// The sorted and merged date ranges which have been fetched
+    ranges: Vec<(Date, Date)>,
+    /// The date when the forward adjusted prices were validated
+    validated_at: Option<Date>,
+    /// The candlesticks sorted by time
+    candlesticks: Vec<Candlestick>,
+}
+
+impl CacheFile {
+    fn load(path: &Path) -> Self {
+        match fs::read(path) {
+            Ok(data) => serde_json::from_slice(&data).unwrap_or_else(|err| {
+                tracing::warn!(path = %path.display(), error = %err, "invalid candlestick cache file");
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    fn save(&self, path: &Path) -> io::Result<()> {
+        // write to a temporary file first and rename it over the cache file, so
+        // that the cache is never left half written
+        let tmp_path = path.with_extension(format!(
+            "{}.{:08x}.tmp",
+            std::process::id(),
+            rand::random::<u32>()
+        ));
+        let res = fs::write(&tmp_path, serde_json::to_vec(self)?)
+            .and_then(|_| fs::rename(&tmp_path, path));
+        if res.is_err() {
+            let _ = fs::remove_file(&tmp_path);
+        }
+        res
+    }
+
+    /// Returns the date ranges in `start..=end` which have not been fetched
+    fn missing_ranges(&self, start: Date, end: Date) -> Vec<(Date, Date)> {
+        let mut res = Vec::new();
+        let mut from = start;
+
+        for (range_start, range_end) in &self.ranges {
+            if *range_end < from {
+                continue;
+            }
+            if *range_start > end {
+                break;
+            }
+            if *range_start > from {
+                res.push((from, range_start.previous_day().unwrap()));
+            }
+            match range_end.next_day() {
+                Some(next_day) => from = next_day,
+                None => return res,
+            }
+        }
+
+        if from <= end {
+            res.push((from, end));
+        }
+        res
+    }
+
+    fn insert(&mut self, range: (Date, Date), candlesticks: Vec<Candlestick>) {
+        let mut merged: HashMap<_, _> = self
+            .candlesticks
+            .drain(..)
+            .chain(candlesticks)
+            .map(|candlestick| (candlestick.timestamp, candlestick))
+            .collect();
+        self.candlesticks = merged.drain().map(|(_, candlestick)| candlestick).collect();
+        self.candlesticks
+            .sort_by_key(|candlestick| candlestick.timestamp);
+
+        self.ranges.push(range);
+        self.ranges.sort();
+        let mut ranges: Vec<(Date, Date)> = Vec::with_capacity(self.ranges.len());
+        for (start, end) in self.ranges.drain(..) {
+            match ranges.last_mut() {
+                Some(last) if last.1.next_day().is_none_or(|next_day| start <= next_day) => {
+                    last.1 = last.1.max(end);
+                }
+                _ => ranges.push((start, end)),
+            }
+        }
+        self.ranges = ranges;
+    }
+}
+
+/// On-disk cache of the history candlesticks
+///
+/// Only the days before today in the timezone of the market are cached, the
+/// candlesticks of today are always fetched from the server.
+///
+/// The updates of a cache file are serialized within the process only, the
+/// cache directory must not be shared by multiple processes at the same time.
+pub(crate) struct CandlestickCache {
+    dir: PathBuf,
+    locks: Mutex<HashMap<PathBuf, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl CandlestickCache {
+    pub(crate) fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            locks: Default::default(),
+        }
+    }
+
+    /// Returns the lock of the cache file at `path`, so the queries of
+    /// different files do not wait for each other
+    fn lock(&self, path: &Path) -> Arc<tokio::sync::Mutex<()>> {
+        self.locks
+            .lock()
+            .unwrap()
+            .entry(path.to_path_buf())
+            .or_default()
+            .clone()
+    }
+
+    fn path(
+        &self,
+        symbol: &str,
+        period: Period,
+        adjust_type: AdjustType,
+        trade_sessions: TradeSessions,
+    ) -> PathBuf {
+        self.dir.join(format!(
+            "{}_{:?}_{:?}_{:?}.json",
+            symbol, period, adjust_type, trade_sessions
+        ))
+    }
+
+    /// Returns `true` if the forward adjusted prices of the last cached day
+    /// are still the same on the server
+    async fn validate_forward_adjust(
+        &self,
+        ctx: &QuoteContext,
+        symbol: &str,
+        period: Period,
+        trade_sessions: TradeSessions,
+        timezone: &'static Tz,
+        file: &CacheFile,
+    ) -> Result<bool> {
+        let Some(last) = file.candlesticks.last() else {
+            return Ok(true);
+        };
+        let date = last.timestamp.to_timezone(timezone).date();
+        let candlesticks = CandlestickPaginator::new(
+            ctx.clone(),
+            symbol.to_string(),
+            period,
+            AdjustType::ForwardAdjust,
+            date,
+            date,
+            trade_sessions,
+        )
+        .next_page()
+        .await?
+        .unwrap_or_default();
+        let cached: HashMap<_, _> = file
+            .candlesticks
+            .iter()
+            .rev()
+            .take_while(|candlestick| candlestick.timestamp.to_timezone(timezone).date() == date)
+            .map(|candlestick| (candlestick.timestamp, candlestick))
+            .collect();
+
+        Ok(candlesticks.iter().all(|candlestick| {
+            cached.get(&candlestick.timestamp).is_none_or(|cached| {
+                cached.open == candlestick.open
+                    && cached.close == candlestick.close
+                    && cached.high == candlestick.high
+                    && cached.low == candlestick.low
+            })
+        }))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn history_candlesticks_by_date(
+        &self,
+        ctx: &QuoteContext,
+        symbol: String,
+        period: Period,
+        adjust_type: AdjustType,
+        start: Date,
+        end: Date,
+        trade_sessions: TradeSessions,
+    ) -> Result<Vec<Candlestick>> {
+        let Some(timezone) = parse_market_from_symbol(&symbol).and_then(|market| {
+            Markets::default()
+                .get(market, SecurityBoard::Unknown)
+                .map(|market| market.timezone)
+        }) else {
+            return Err(Error::UnknownMarket { symbol });
+        };
+        let today = OffsetDateTime::now_utc().to_timezone(timezone).date();
+        let paginator = |start, end| {
+            CandlestickPaginator::new(
+                ctx.clone(),
+                symbol.clone(),
+                period,
+                adjust_type,
+                start,
+                end,
+                trade_sessions,
+            )
+        };
+
+        let path = self.path(&symbol, period, adjust_type, trade_sessions);
+        let lock = self.lock(&path);
+        let _guard = lock.lock().await;
+        let mut file = CacheFile::load(&path);
+        let mut changed = false;
+
+        // the forward adjusted prices of the whole history are shifted by the
+        // corporate actions
+        if adjust_type == AdjustType::ForwardAdjust && file.validated_at != Some(today) {
+            if !self
+                .validate_forward_adjust(ctx, &symbol, period, trade_sessions, timezone, &file)
+                .await?
+            {
+                tracing::info!(symbol = symbol, period = ?period, "forward adjusted candlestick cache invalidated");
+                file = CacheFile::default();
+            }
+            file.validated_at = Some(today);
+            changed = true;
+        }
+
+        let mut candlesticks = Vec::new();
+        let mut fetch_error = None;
+
+        if let Some(cache_end) = today.previous_day().map(|day| day.min(end)) {
+            for (missing_start, missing_end) in file.missing_ranges(start, cache_end) {
+                tracing::info!(symbol = symbol, period = ?period, start = %missing_start, end = %missing_end, "fetch missing history candlesticks");
+                match paginator(missing_start, missing_end).collect().await {
+                    Ok(candlesticks) => {
+                        file.insert((missing_start, missing_end), candlesticks);
+                        changed = true;
+                    }
+                    Err(err) => {
+                        // keep the ranges fetched before the error
+                        fetch_error = Some(err);
+                        break;
+                    }
+                }
+            }
+
+            candlesticks.extend(file.candlesticks.iter().copied().filter(|candlestick| {
+                let date = candlestick.timestamp.to_timezone(timezone).date();
+                date >= start && date <= cache_end
+            }));
+        }
+
+        if changed {
+            if let Err(err) = fs::create_dir_all(&self.dir).and_then(|_| file.save(&path)) {
+                tracing::warn!(path = %path.display(), error = %err, "failed to save candlestick cache");
+            }
+        }
+
+        if let Some(err) = fetch_error {
+            return Err(err);
+        }
+
+        if end >= today {
+            candlesticks.extend(paginator(start.max(today), end).collect().await?);
+        }
+
+        Ok(candlesticks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::date;
+
+    use super::*;
+
+    #[test]
+    fn test_missing_ranges() {
+        let mut file = CacheFile::default();
+        assert_eq!(
+            file.missing_ranges(date!(2024 - 01 - 01), date!(2024 - 01 - 31)),
+            vec![(date!(2024 - 01 - 01), date!(2024 - 01 - 31))]
+        );
+
+        file.insert((date!(2024 - 01 - 10), date!(2024 - 01 - 15)), vec![]);
+        file.insert((date!(2024 - 01 - 16), date!(2024 - 01 - 20)), vec![]);
+        assert_eq!(
+            file.ranges,
+            vec![(date!(2024 - 01 - 10), date!(2024 - 01 - 20))]
+        );
+        assert_eq!(
+            file.missing_ranges(date!(2024 - 01 - 01), date!(2024 - 01 - 31)),
+            vec![
+                (date!(2024 - 01 - 01), date!(2024 - 01 - 09)),
+                (date!(2024 - 01 - 21), date!(2024 - 01 - 31))
+            ]
+        );
+        assert!(file
+            .missing_ranges(date!(2024 - 01 - 12), date!(2024 - 01 - 18))
+            .is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use std::{sync::Arc, time::Duration};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use longport_httpcli::{HttpClient, Json, Method};
 use longport_proto::quote;
@@ -9,25 +9,32 @@ use tokio::sync::{mpsc, oneshot};
 use tracing::{dispatcher, instrument::WithSubscriber, Subscriber};
 
 use crate::{
+    connection::ConnectionEventSenders,
     quote::{
         cache::{Cache, CacheWithKey},
+        candlestick_cache::CandlestickCache,
         cmd_code,
         core::{Command, Core},
+        history::{self, CandlestickPaginator},
+        stream::{OverflowPolicy, PushStream},
         sub_flags::SubFlags,
         types::{
             FilterWarrantExpiryDate, FilterWarrantInOutBoundsType, SecuritiesUpdateMode,
             SortOrderType, WarrantSortBy, WarrantStatus,
         },
         utils::{format_date, parse_date},
-        AdjustType, CalcIndex, Candlestick, CapitalDistributionResponse, CapitalFlowLine,
-        IntradayLine, IssuerInfo, MarketTradingDays, MarketTradingSession, OptionQuote,
-        ParticipantInfo, Period, PushEvent, QuotePackageDetail, RealtimeQuote,
-        RequestCreateWatchlistGroup, RequestUpdateWatchlistGroup, Security, SecurityBrokers,
-        SecurityCalcIndex, SecurityDepth, SecurityListCategory, SecurityQuote, SecurityStaticInfo,
-        StrikePriceInfo, Subscription, Trade, TradeSessions, WarrantInfo, WarrantQuote,
-        WarrantType, WatchlistGroup,
+        AdjustType, AdjustmentFactors, BarType, CalcIndex, Candlestick,
+        CapitalDistributionResponse, CapitalFlowLine, CustomPeriod, Indicator, IndicatorValue,
+        IntradayLine, IssuerInfo, MarketTradingDays, MarketTradingSession, OptionQuote, OrderBook,
+        ParticipantInfo, Period, PushBar, PushBrokers, PushCandlestick, PushCustomCandlestick,
+        PushDepth, PushEvent, PushEventDetail, PushIndicator, PushQuote, PushTrades,
+        QuotePackageDetail, RealtimeQuote, RequestCreateWatchlistGroup,
+        RequestUpdateWatchlistGroup, Security, SecurityBrokers, SecurityCalcIndex, SecurityDepth,
+        SecurityListCategory, SecurityQuote, SecurityStaticInfo, StrikePriceInfo, Subscription,
+        Trade, TradeSessions, TradingCalendar, WarrantInfo, WarrantQuote, WarrantType,
+        WatchlistGroup,
     },
-    serde_utils, Config, Error, Language, Market, Result,
+    serde_utils, Config, ConnectionEvent, Error, Language, Market, Result,
 };
 
 const RETRY_COUNT: usize = 3;
@@ -49,7 +56,11 @@ struct InnerQuoteContext {
     member_id: i64,
     quote_level: String,
     quote_package_details: Vec<QuotePackageDetail>,
+    connection_event_tx: ConnectionEventSenders,
     log_subscriber: Arc<dyn Subscriber + Send + Sync>,
+    push_stream_capacity: usize,
+    push_stream_overflow_policy: OverflowPolicy,
+    candlestick_cache: Option<CandlestickCache>,
 }
 
 impl Drop for InnerQuoteContext {
@@ -66,6 +77,11 @@ pub struct QuoteContext(Arc<InnerQuoteContext>);
 
 impl QuoteContext {
     /// Create a `QuoteContext`
+    ///
+    /// The returned receiver is unbounded, it receives all push events
+    /// alongside the push streams created with [`QuoteContext::push_stream`]
+    /// or the other `*_stream` methods. Drop it if it is not consumed, so the
+    /// events are not buffered without bound.
     pub async fn try_new(
         config: Arc<Config>,
     ) -> Result<(Self, mpsc::UnboundedReceiver<PushEvent>)> {
@@ -83,9 +99,16 @@ impl QuoteContext {
 
         let language = config.language.unwrap_or_default();
         let http_cli = config.create_http_client();
+        let push_stream_capacity = config.push_stream_capacity;
+        let push_stream_overflow_policy = config.push_stream_overflow_policy;
+        let candlestick_cache = config
+            .candlestick_cache_dir
+            .clone()
+            .map(CandlestickCache::new);
         let (command_tx, command_rx) = mpsc::unbounded_channel();
         let (push_tx, push_rx) = mpsc::unbounded_channel();
-        let core = Core::try_new(config, command_rx, push_tx)
+        let connection_event_tx = ConnectionEventSenders::default();
+        let core = Core::try_new(config, command_rx, push_tx, connection_event_tx.clone())
             .with_subscriber(log_subscriber.clone())
             .await?;
         let member_id = core.member_id();
@@ -114,7 +137,11 @@ impl QuoteContext {
                 member_id,
                 quote_level,
                 quote_package_details,
+                connection_event_tx,
                 log_subscriber,
+                push_stream_capacity,
+                push_stream_overflow_policy,
+                candlestick_cache,
             })),
             push_rx,
         ))
@@ -126,6 +153,33 @@ impl QuoteContext {
         self.0.log_subscriber.clone()
     }
 
+    /// Returns a receiver of the connection state events
+    ///
+    /// Every call creates a new receiver, and only the events that occur
+    /// after the call are delivered to it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// use longport::{QuoteContext, Config};
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let config = Arc::new(Config::from_env()?);
+    /// let (ctx, _) = QuoteContext::try_new(config).await?;
+    ///
+    /// let mut events = ctx.connection_events();
+    /// while let Some(event) = events.recv().await {
+    ///     println!("{:?}", event);
+    /// }
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// # });
+    /// ```
+    pub fn connection_events(&self) -> mpsc::UnboundedReceiver<ConnectionEvent> {
+        self.0.connection_event_tx.subscribe()
+    }
+
     /// Returns the member ID
     #[inline]
     pub fn member_id(&self) -> i64 {
@@ -155,8 +209,10 @@ impl QuoteContext {
                     body: body.clone(),
                     reply_tx,
                 })
-                .map_err(|_| WsClientError::ClientClosed)?;
-            let res = reply_rx.await.map_err(|_| WsClientError::ClientClosed)?;
+                .map_err(|_| self.0.connection_event_tx.closed_error())?;
+            let res = reply_rx
+                .await
+                .map_err(|_| self.0.connection_event_tx.closed_error())?;
 
             match res {
                 Ok(resp) => return Ok(resp),
@@ -235,8 +291,10 @@ impl QuoteContext {
                 is_first_push,
                 reply_tx,
             })
-            .map_err(|_| WsClientError::ClientClosed)?;
-        reply_rx.await.map_err(|_| WsClientError::ClientClosed)?
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+        reply_rx
+            .await
+            .map_err(|_| self.0.connection_event_tx.closed_error())?
     }
 
     /// Unsubscribe
@@ -279,8 +337,10 @@ impl QuoteContext {
                 sub_types: sub_types.into(),
                 reply_tx,
             })
-            .map_err(|_| WsClientError::ClientClosed)?;
-        reply_rx.await.map_err(|_| WsClientError::ClientClosed)?
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+        reply_rx
+            .await
+            .map_err(|_| self.0.connection_event_tx.closed_error())?
     }
 
     /// Subscribe security candlesticks
@@ -325,8 +385,10 @@ impl QuoteContext {
                 trade_sessions,
                 reply_tx,
             })
-            .map_err(|_| WsClientError::ClientClosed)?;
-        reply_rx.await.map_err(|_| WsClientError::ClientClosed)?
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+        reply_rx
+            .await
+            .map_err(|_| self.0.connection_event_tx.closed_error())?
     }
 
     /// Unsubscribe security candlesticks
@@ -342,8 +404,238 @@ impl QuoteContext {
                 period,
                 reply_tx,
             })
-            .map_err(|_| WsClientError::ClientClosed)?;
-        reply_rx.await.map_err(|_| WsClientError::ClientClosed)?
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+        reply_rx
+            .await
+            .map_err(|_| self.0.connection_event_tx.closed_error())?
+    }
+
+    /// Subscribe security candlesticks of a custom period
+    ///
+    /// The candlesticks are aggregated locally from the trades, and the
+    /// candlesticks of the whole minutes are seeded from the `1` minute
+    /// history candlesticks. Returns the candlesticks built from the history.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// use longport::{
+    ///     quote::{CustomPeriod, QuoteContext, TradeSessions},
+    ///     Config,
+    /// };
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let config = Arc::new(Config::from_env()?);
+    /// let (ctx, mut receiver) = QuoteContext::try_new(config).await?;
+    ///
+    /// ctx.subscribe_custom_candlesticks("AAPL.US", CustomPeriod::Minutes(7), TradeSessions::Normal)
+    ///     .await?;
+    /// while let Some(msg) = receiver.recv().await {
+    ///     println!("{:?}", msg);
+    /// }
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// # });
+    /// ```
+    pub async fn subscribe_custom_candlesticks<T>(
+        &self,
+        symbol: T,
+        period: CustomPeriod,
+        trade_sessions: TradeSessions,
+    ) -> Result<Vec<Candlestick>>
+    where
+        T: AsRef<str>,
+    {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.0
+            .command_tx
+            .send(Command::SubscribeCustomCandlesticks {
+                symbol: normalize_symbol(symbol.as_ref()).into(),
+                period,
+                trade_sessions,
+                reply_tx,
+            })
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+        reply_rx
+            .await
+            .map_err(|_| self.0.connection_event_tx.closed_error())?
+    }
+
+    /// Unsubscribe security candlesticks of a custom period
+    pub async fn unsubscribe_custom_candlesticks<T>(
+        &self,
+        symbol: T,
+        period: CustomPeriod,
+    ) -> Result<()>
+    where
+        T: AsRef<str>,
+    {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.0
+            .command_tx
+            .send(Command::UnsubscribeCustomCandlesticks {
+                symbol: normalize_symbol(symbol.as_ref()).into(),
+                period,
+                reply_tx,
+            })
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+        reply_rx
+            .await
+            .map_err(|_| self.0.connection_event_tx.closed_error())?
+    }
+
+    /// Subscribe the non-time-based bars of the security
+    ///
+    /// The bars are built from the trades pushed after the subscription and
+    /// pushed as [`PushEventDetail::Bar`], following
+    /// [`Config::push_candlestick_mode`](crate::Config::push_candlestick_mode).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// use longport::{
+    ///     quote::{BarType, QuoteContext, TradeSessions},
+    ///     Config,
+    /// };
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let config = Arc::new(Config::from_env()?);
+    /// let (ctx, mut receiver) = QuoteContext::try_new(config).await?;
+    ///
+    /// ctx.subscribe_bars("AAPL.US", BarType::Volume(10000), TradeSessions::Normal)
+    ///     .await?;
+    /// while let Some(msg) = receiver.recv().await {
+    ///     println!("{:?}", msg);
+    /// }
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// # });
+    /// ```
+    pub async fn subscribe_bars<T>(
+        &self,
+        symbol: T,
+        bar_type: BarType,
+        trade_sessions: TradeSessions,
+    ) -> Result<()>
+    where
+        T: AsRef<str>,
+    {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.0
+            .command_tx
+            .send(Command::SubscribeBars {
+                symbol: normalize_symbol(symbol.as_ref()).into(),
+                bar_type,
+                trade_sessions,
+                reply_tx,
+            })
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+        reply_rx
+            .await
+            .map_err(|_| self.0.connection_event_tx.closed_error())?
+    }
+
+    /// Unsubscribe the non-time-based bars of the security
+    pub async fn unsubscribe_bars<T>(&self, symbol: T, bar_type: BarType) -> Result<()>
+    where
+        T: AsRef<str>,
+    {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.0
+            .command_tx
+            .send(Command::UnsubscribeBars {
+                symbol: normalize_symbol(symbol.as_ref()).into(),
+                bar_type,
+                reply_tx,
+            })
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+        reply_rx
+            .await
+            .map_err(|_| self.0.connection_event_tx.closed_error())?
+    }
+
+    /// Attach a technical indicator to the candlesticks subscribed with
+    /// [`QuoteContext::subscribe_candlesticks`], returns the latest value
+    ///
+    /// The indicator is calculated from the candlesticks in the local storage
+    /// and then updated on every candlestick push, the values are pushed as
+    /// [`PushEventDetail::Indicator`] in the
+    /// [`PushCandlestickMode`](crate::PushCandlestickMode) of the candlesticks.
+    /// The indicator is detached when the candlesticks are unsubscribed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// use longport::{
+    ///     quote::{Indicator, Period, QuoteContext, TradeSessions},
+    ///     Config,
+    /// };
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let config = Arc::new(Config::from_env()?);
+    /// let (ctx, mut receiver) = QuoteContext::try_new(config).await?;
+    ///
+    /// ctx.subscribe_candlesticks("AAPL.US", Period::OneMinute, TradeSessions::Normal)
+    ///     .await?;
+    /// ctx.attach_indicator("AAPL.US", Period::OneMinute, Indicator::Rsi(14))
+    ///     .await?;
+    /// while let Some(msg) = receiver.recv().await {
+    ///     println!("{:?}", msg);
+    /// }
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// # });
+    /// ```
+    pub async fn attach_indicator<T>(
+        &self,
+        symbol: T,
+        period: Period,
+        indicator: Indicator,
+    ) -> Result<Option<IndicatorValue>>
+    where
+        T: AsRef<str>,
+    {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.0
+            .command_tx
+            .send(Command::AttachIndicator {
+                symbol: normalize_symbol(symbol.as_ref()).into(),
+                period,
+                indicator,
+                reply_tx,
+            })
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+        reply_rx
+            .await
+            .map_err(|_| self.0.connection_event_tx.closed_error())?
+    }
+
+    /// Detach a technical indicator from the candlesticks
+    pub async fn detach_indicator<T>(
+        &self,
+        symbol: T,
+        period: Period,
+        indicator: Indicator,
+    ) -> Result<()>
+    where
+        T: AsRef<str>,
+    {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.0
+            .command_tx
+            .send(Command::DetachIndicator {
+                symbol: normalize_symbol(symbol.as_ref()).into(),
+                period,
+                indicator,
+                reply_tx,
+            })
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+        reply_rx
+            .await
+            .map_err(|_| self.0.connection_event_tx.closed_error())
     }
 
     /// Get subscription information
@@ -374,8 +666,272 @@ impl QuoteContext {
         self.0
             .command_tx
             .send(Command::Subscriptions { reply_tx })
-            .map_err(|_| WsClientError::ClientClosed)?;
-        Ok(reply_rx.await.map_err(|_| WsClientError::ClientClosed)?)
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+        reply_rx
+            .await
+            .map_err(|_| self.0.connection_event_tx.closed_error())
+    }
+
+    fn create_push_stream<T>(
+        &self,
+        filter: impl Fn(&PushEvent) -> bool + Send + Sync + 'static,
+        extract: fn(PushEvent) -> Option<T>,
+    ) -> Result<PushStream<T>> {
+        let (stream, sender) = PushStream::new(
+            self.0.push_stream_capacity,
+            self.0.push_stream_overflow_policy,
+            filter,
+            extract,
+        );
+        self.0
+            .command_tx
+            .send(Command::AddPushStream { sender })
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+        Ok(stream)
+    }
+
+    /// Returns a stream of all push events
+    ///
+    /// Unlike the receiver returned by [`QuoteContext::try_new`], the stream
+    /// is bounded, see [`PushStream`]. The receiver keeps receiving the events
+    /// after the push streams are created.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// use longport::{
+    ///     quote::{QuoteContext, SubFlags},
+    ///     Config,
+    /// };
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let config = Arc::new(Config::from_env()?);
+    /// let (ctx, _) = QuoteContext::try_new(config).await?;
+    ///
+    /// let mut stream = ctx.push_stream()?;
+    /// ctx.subscribe(["700.HK", "AAPL.US"], SubFlags::QUOTE, true)
+    ///     .await?;
+    /// while let Some(event) = stream.recv().await {
+    ///     println!("{:?}", event);
+    /// }
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// # });
+    /// ```
+    pub fn push_stream(&self) -> Result<PushStream<PushEvent>> {
+        self.create_push_stream(|_| true, Some)
+    }
+
+    /// Returns a stream of the quote pushes of the security
+    ///
+    /// The security must be subscribed with [`SubFlags::QUOTE`] to receive
+    /// the pushes.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// use longport::{
+    ///     quote::{QuoteContext, SubFlags},
+    ///     Config,
+    /// };
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let config = Arc::new(Config::from_env()?);
+    /// let (ctx, _) = QuoteContext::try_new(config).await?;
+    ///
+    /// let mut stream = ctx.quote_stream("700.HK")?;
+    /// ctx.subscribe(["700.HK"], SubFlags::QUOTE, true).await?;
+    /// while let Some(quote) = stream.recv().await {
+    ///     println!("{:?}", quote);
+    /// }
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// # });
+    /// ```
+    pub fn quote_stream(&self, symbol: impl Into<String>) -> Result<PushStream<PushQuote>> {
+        let symbol = symbol.into();
+        self.create_push_stream(
+            move |event| {
+                event.symbol == symbol && matches!(event.detail, PushEventDetail::Quote(_))
+            },
+            |event| match event.detail {
+                PushEventDetail::Quote(quote) => Some(quote),
+                _ => None,
+            },
+        )
+    }
+
+    /// Returns a stream of the depth pushes of the security
+    ///
+    /// The security must be subscribed with [`SubFlags::DEPTH`] to receive
+    /// the pushes.
+    pub fn depth_stream(&self, symbol: impl Into<String>) -> Result<PushStream<PushDepth>> {
+        let symbol = symbol.into();
+        self.create_push_stream(
+            move |event| {
+                event.symbol == symbol && matches!(event.detail, PushEventDetail::Depth(_))
+            },
+            |event| match event.detail {
+                PushEventDetail::Depth(depth) => Some(depth),
+                _ => None,
+            },
+        )
+    }
+
+    /// Returns a stream of the brokers pushes of the security
+    ///
+    /// The security must be subscribed with [`SubFlags::BROKER`] to receive
+    /// the pushes.
+    pub fn brokers_stream(&self, symbol: impl Into<String>) -> Result<PushStream<PushBrokers>> {
+        let symbol = symbol.into();
+        self.create_push_stream(
+            move |event| {
+                event.symbol == symbol && matches!(event.detail, PushEventDetail::Brokers(_))
+            },
+            |event| match event.detail {
+                PushEventDetail::Brokers(brokers) => Some(brokers),
+                _ => None,
+            },
+        )
+    }
+
+    /// Returns a stream of the trades pushes of the security
+    ///
+    /// The security must be subscribed with [`SubFlags::TRADE`] to receive
+    /// the pushes.
+    pub fn trades_stream(&self, symbol: impl Into<String>) -> Result<PushStream<PushTrades>> {
+        let symbol = symbol.into();
+        self.create_push_stream(
+            move |event| {
+                event.symbol == symbol && matches!(event.detail, PushEventDetail::Trade(_))
+            },
+            |event| match event.detail {
+                PushEventDetail::Trade(trades) => Some(trades),
+                _ => None,
+            },
+        )
+    }
+
+    /// Returns a stream of the candlestick pushes of the security
+    ///
+    /// The candlesticks must be subscribed with
+    /// [`QuoteContext::subscribe_candlesticks`] to receive the pushes.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// use longport::{
+    ///     quote::{Period, QuoteContext, TradeSessions},
+    ///     Config,
+    /// };
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let config = Arc::new(Config::from_env()?);
+    /// let (ctx, _) = QuoteContext::try_new(config).await?;
+    ///
+    /// let mut stream = ctx.candlestick_stream("AAPL.US", Period::OneMinute)?;
+    /// ctx.subscribe_candlesticks("AAPL.US", Period::OneMinute, TradeSessions::Normal)
+    ///     .await?;
+    /// while let Some(candlestick) = stream.recv().await {
+    ///     println!("{:?}", candlestick);
+    /// }
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// # });
+    /// ```
+    pub fn candlestick_stream(
+        &self,
+        symbol: impl Into<String>,
+        period: Period,
+    ) -> Result<PushStream<PushCandlestick>> {
+        let symbol = symbol.into();
+        self.create_push_stream(
+            move |event| match &event.detail {
+                PushEventDetail::Candlestick(candlestick) => {
+                    event.symbol == symbol && candlestick.period == period
+                }
+                _ => false,
+            },
+            |event| match event.detail {
+                PushEventDetail::Candlestick(candlestick) => Some(candlestick),
+                _ => None,
+            },
+        )
+    }
+
+    /// Returns a stream of the custom period candlestick pushes of the
+    /// security
+    ///
+    /// The candlesticks must be subscribed with
+    /// [`QuoteContext::subscribe_custom_candlesticks`] to receive the pushes.
+    pub fn custom_candlestick_stream(
+        &self,
+        symbol: impl Into<String>,
+        period: CustomPeriod,
+    ) -> Result<PushStream<PushCustomCandlestick>> {
+        let symbol = symbol.into();
+        self.create_push_stream(
+            move |event| match &event.detail {
+                PushEventDetail::CustomCandlestick(candlestick) => {
+                    event.symbol == symbol && candlestick.period == period
+                }
+                _ => false,
+            },
+            |event| match event.detail {
+                PushEventDetail::CustomCandlestick(candlestick) => Some(candlestick),
+                _ => None,
+            },
+        )
+    }
+
+    /// Returns a stream of the bar pushes of the security
+    ///
+    /// The bars must be subscribed with [`QuoteContext::subscribe_bars`] to
+    /// receive the pushes.
+    pub fn bar_stream(
+        &self,
+        symbol: impl Into<String>,
+        bar_type: BarType,
+    ) -> Result<PushStream<PushBar>> {
+        let symbol = symbol.into();
+        self.create_push_stream(
+            move |event| match &event.detail {
+                PushEventDetail::Bar(bar) => event.symbol == symbol && bar.bar_type == bar_type,
+                _ => false,
+            },
+            |event| match event.detail {
+                PushEventDetail::Bar(bar) => Some(bar),
+                _ => None,
+            },
+        )
+    }
+
+    /// Returns a stream of the indicator pushes of the security
+    ///
+    /// The indicator must be attached with [`QuoteContext::attach_indicator`]
+    /// to receive the pushes.
+    pub fn indicator_stream(
+        &self,
+        symbol: impl Into<String>,
+        period: Period,
+        indicator: Indicator,
+    ) -> Result<PushStream<PushIndicator>> {
+        let symbol = symbol.into();
+        self.create_push_stream(
+            move |event| match &event.detail {
+                PushEventDetail::Indicator(value) => {
+                    event.symbol == symbol && value.period == period && value.indicator == indicator
+                }
+                _ => false,
+            },
+            |event| match event.detail {
+                PushEventDetail::Indicator(value) => Some(value),
+                _ => None,
+            },
+        )
     }
 
     /// Get basic information of securities
@@ -838,6 +1394,10 @@ impl QuoteContext {
     }
 
     /// Get security history candlesticks by date
+    ///
+    /// If [`Config::candlestick_cache_dir`] is set and both `start` and `end`
+    /// are specified, all the candlesticks in the date range are returned, and
+    /// only the date ranges missing in the cache are fetched from the server.
     pub async fn history_candlesticks_by_date(
         &self,
         symbol: impl Into<String>,
@@ -847,6 +1407,20 @@ impl QuoteContext {
         end: Option<Date>,
         trade_sessions: TradeSessions,
     ) -> Result<Vec<Candlestick>> {
+        if let (Some(cache), Some(start), Some(end)) = (&self.0.candlestick_cache, start, end) {
+            return cache
+                .history_candlesticks_by_date(
+                    self,
+                    symbol.into(),
+                    period,
+                    adjust_type,
+                    start,
+                    end,
+                    trade_sessions,
+                )
+                .await;
+        }
+
         let resp: quote::SecurityCandlestickResponse = self
             .request(
                 cmd_code::GET_SECURITY_HISTORY_CANDLESTICKS,
@@ -890,6 +1464,108 @@ impl QuoteContext {
         Ok(candlesticks)
     }
 
+    /// Returns a paginator of the security history candlesticks in a date
+    /// range, see [`CandlestickPaginator`]
+    pub fn history_candlesticks_paginator(
+        &self,
+        symbol: impl Into<String>,
+        period: Period,
+        adjust_type: AdjustType,
+        start: Date,
+        end: Date,
+        trade_sessions: TradeSessions,
+    ) -> CandlestickPaginator {
+        CandlestickPaginator::new(
+            self.clone(),
+            symbol.into(),
+            period,
+            adjust_type,
+            start,
+            end,
+            trade_sessions,
+        )
+    }
+
+    /// Get the history candlesticks of the securities in a date range
+    ///
+    /// Walks the date range of each security with a [`CandlestickPaginator`],
+    /// with at most `concurrency` securities at a time, and returns the result
+    /// of each security.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// use longport::{
+    ///     quote::{AdjustType, Period, QuoteContext, TradeSessions},
+    ///     Config,
+    /// };
+    /// use time::macros::date;
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let config = Arc::new(Config::from_env()?);
+    /// let (ctx, _) = QuoteContext::try_new(config).await?;
+    ///
+    /// let resp = ctx
+    ///     .bulk_history_candlesticks(
+    ///         ["700.HK", "AAPL.US"],
+    ///         Period::Day,
+    ///         AdjustType::ForwardAdjust,
+    ///         date!(2020 - 01 - 01),
+    ///         date!(2024 - 12 - 31),
+    ///         TradeSessions::Normal,
+    ///         4,
+    ///     )
+    ///     .await;
+    /// for (symbol, candlesticks) in resp {
+    ///     println!("{} {:?}", symbol, candlesticks.map(|c| c.len()));
+    /// }
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// # });
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub async fn bulk_history_candlesticks<I, T>(
+        &self,
+        symbols: I,
+        period: Period,
+        adjust_type: AdjustType,
+        start: Date,
+        end: Date,
+        trade_sessions: TradeSessions,
+        concurrency: usize,
+    ) -> HashMap<String, Result<Vec<Candlestick>>>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        history::bulk_history_candlesticks(
+            self,
+            symbols.into_iter().map(Into::into).collect(),
+            period,
+            adjust_type,
+            start,
+            end,
+            trade_sessions,
+            concurrency,
+        )
+        .await
+    }
+
+    /// Get the adjustment factors of the security in a date range
+    ///
+    /// The factors are derived by comparing the
+    /// [`AdjustType::NoAdjust`] and the [`AdjustType::ForwardAdjust`] daily
+    /// candlesticks, see [`AdjustmentFactors::derive`].
+    pub async fn adjustment_factors(
+        &self,
+        symbol: impl Into<String>,
+        start: Date,
+        end: Date,
+    ) -> Result<AdjustmentFactors> {
+        AdjustmentFactors::fetch(self, symbol.into(), start, end).await
+    }
+
     /// Get option chain expiry date list
     ///
     /// Reference: <https://open.longportapp.com/en/docs/quote/pull/optionchain-date>
@@ -1164,6 +1840,40 @@ impl QuoteContext {
         })
     }
 
+    /// Get the trading calendar
+    ///
+    /// The calendar is built from the trading days and the trade sessions
+    /// cached by the quote context, see [`TradingCalendar`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// use longport::{quote::QuoteContext, Config, Market};
+    /// use time::OffsetDateTime;
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let config = Arc::new(Config::from_env()?);
+    /// let (ctx, _) = QuoteContext::try_new(config).await?;
+    ///
+    /// let calendar = ctx.trading_calendar().await?;
+    /// let today = OffsetDateTime::now_utc().date();
+    /// println!("{:?}", calendar.is_trading_day(Market::HK, today));
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// # });
+    /// ```
+    pub async fn trading_calendar(&self) -> Result<TradingCalendar> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.0
+            .command_tx
+            .send(Command::GetTradingCalendar { reply_tx })
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+        reply_rx
+            .await
+            .map_err(|_| self.0.connection_event_tx.closed_error())
+    }
+
     /// Get capital flow intraday
     ///
     /// Reference: <https://open.longportapp.com/en/docs/quote/pull/capital-flow-intraday>
@@ -1512,8 +2222,10 @@ impl QuoteContext {
                 symbols: symbols.into_iter().map(Into::into).collect(),
                 reply_tx,
             })
-            .map_err(|_| WsClientError::ClientClosed)?;
-        Ok(reply_rx.await.map_err(|_| WsClientError::ClientClosed)?)
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+        reply_rx
+            .await
+            .map_err(|_| self.0.connection_event_tx.closed_error())
     }
 
     /// Get real-time depth
@@ -1552,8 +2264,54 @@ impl QuoteContext {
                 symbol: symbol.into(),
                 reply_tx,
             })
-            .map_err(|_| WsClientError::ClientClosed)?;
-        Ok(reply_rx.await.map_err(|_| WsClientError::ClientClosed)?)
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+        reply_rx
+            .await
+            .map_err(|_| self.0.connection_event_tx.closed_error())
+    }
+
+    /// Get real-time order book
+    ///
+    /// Get the local order book of the subscribed symbols, which is updated
+    /// incrementally with the depth pushes, it always returns the data in the
+    /// local storage.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::{sync::Arc, time::Duration};
+    ///
+    /// use longport::{
+    ///     quote::{QuoteContext, SubFlags},
+    ///     Config,
+    /// };
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let config = Arc::new(Config::from_env()?);
+    /// let (ctx, _) = QuoteContext::try_new(config).await?;
+    ///
+    /// ctx.subscribe(["700.HK"], SubFlags::DEPTH, true).await?;
+    /// tokio::time::sleep(Duration::from_secs(5)).await;
+    ///
+    /// let book = ctx.realtime_order_book("700.HK").await?;
+    /// println!("spread: {:?}", book.spread());
+    /// println!("imbalance: {:?}", book.imbalance(5));
+    /// println!("vwap to buy 1000: {:?}", book.vwap_to_buy(1000));
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// # });
+    /// ```
+    pub async fn realtime_order_book(&self, symbol: impl Into<String>) -> Result<OrderBook> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.0
+            .command_tx
+            .send(Command::GetRealtimeOrderBook {
+                symbol: symbol.into(),
+                reply_tx,
+            })
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+        reply_rx
+            .await
+            .map_err(|_| self.0.connection_event_tx.closed_error())
     }
 
     /// Get real-time trades
@@ -1597,8 +2355,10 @@ impl QuoteContext {
                 count,
                 reply_tx,
             })
-            .map_err(|_| WsClientError::ClientClosed)?;
-        Ok(reply_rx.await.map_err(|_| WsClientError::ClientClosed)?)
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+        reply_rx
+            .await
+            .map_err(|_| self.0.connection_event_tx.closed_error())
     }
 
     /// Get real-time broker queue
@@ -1638,8 +2398,10 @@ impl QuoteContext {
                 symbol: symbol.into(),
                 reply_tx,
             })
-            .map_err(|_| WsClientError::ClientClosed)?;
-        Ok(reply_rx.await.map_err(|_| WsClientError::ClientClosed)?)
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+        reply_rx
+            .await
+            .map_err(|_| self.0.connection_event_tx.closed_error())
     }
 
     /// Get real-time candlesticks
@@ -1687,8 +2449,88 @@ impl QuoteContext {
                 count,
                 reply_tx,
             })
-            .map_err(|_| WsClientError::ClientClosed)?;
-        Ok(reply_rx.await.map_err(|_| WsClientError::ClientClosed)?)
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+        reply_rx
+            .await
+            .map_err(|_| self.0.connection_event_tx.closed_error())
+    }
+
+    /// Get real-time candlesticks of a custom period
+    ///
+    /// Get real-time candlesticks of the symbols subscribed with
+    /// [`QuoteContext::subscribe_custom_candlesticks`], it always returns the
+    /// data in the local storage.
+    pub async fn realtime_custom_candlesticks(
+        &self,
+        symbol: impl Into<String>,
+        period: CustomPeriod,
+        count: usize,
+    ) -> Result<Vec<Candlestick>> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.0
+            .command_tx
+            .send(Command::GetRealtimeCustomCandlesticks {
+                symbol: symbol.into(),
+                period,
+                count,
+                reply_tx,
+            })
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+        reply_rx
+            .await
+            .map_err(|_| self.0.connection_event_tx.closed_error())
+    }
+
+    /// Get real-time bars
+    ///
+    /// Get real-time bars of the symbols subscribed with
+    /// [`QuoteContext::subscribe_bars`], it always returns the data in the
+    /// local storage.
+    pub async fn realtime_bars(
+        &self,
+        symbol: impl Into<String>,
+        bar_type: BarType,
+        count: usize,
+    ) -> Result<Vec<Candlestick>> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.0
+            .command_tx
+            .send(Command::GetRealtimeBars {
+                symbol: symbol.into(),
+                bar_type,
+                count,
+                reply_tx,
+            })
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+        reply_rx
+            .await
+            .map_err(|_| self.0.connection_event_tx.closed_error())
+    }
+
+    /// Get the latest value of a technical indicator
+    ///
+    /// Get the latest value of the indicator attached with
+    /// [`QuoteContext::attach_indicator`], returns `None` if the indicator is
+    /// not attached or there are not enough candlesticks to calculate it.
+    pub async fn indicator(
+        &self,
+        symbol: impl Into<String>,
+        period: Period,
+        indicator: Indicator,
+    ) -> Result<Option<IndicatorValue>> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.0
+            .command_tx
+            .send(Command::GetIndicator {
+                symbol: symbol.into(),
+                period,
+                indicator,
+                reply_tx,
+            })
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+        reply_rx
+            .await
+            .map_err(|_| self.0.connection_event_tx.closed_error())
     }
 }
 
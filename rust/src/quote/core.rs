@@ -5,7 +5,9 @@ use std::{
 
 use comfy_table::Table;
 use itertools::Itertools;
-use longport_candlesticks::{TradeSessionType, UpdateAction};
+use longport_candlesticks::{
+    BarType, Indicator, IndicatorCalculator, IndicatorValue, TradeSessionType, UpdateAction,
+};
 use longport_httpcli::HttpClient;
 use longport_proto::quote::{
     self, AdjustType, MarketTradeDayRequest, MarketTradeDayResponse, MultiSecurityRequest, Period,
@@ -14,7 +16,9 @@ use longport_proto::quote::{
 };
 use longport_wscli::{
     CodecType, Platform, ProtocolVersion, RateLimit, WsClient, WsClientError, WsEvent, WsSession,
+    EVENT_CHANNEL_CAPACITY,
 };
+use prost::Message;
 use time::{Date, OffsetDateTime};
 use time_tz::OffsetDateTimeExt;
 use tokio::{
@@ -24,20 +28,28 @@ use tokio::{
 
 use crate::{
     config::PushCandlestickMode,
+    connection::{ConnectionEvent, ConnectionEventSenders},
     quote::{
         cmd_code,
-        store::{get_market, Candlesticks, Store, TailCandlestick},
+        record::PushRecorder,
+        store::{convert_period, Bars, Candlesticks, Store, TailCandlestick},
+        stream::{PushSender, PushStreamSender},
         sub_flags::SubFlags,
         types::QuotePackageDetail,
-        utils::{convert_trade_session, format_date, parse_date},
-        Candlestick, PushCandlestick, PushEvent, PushEventDetail, PushQuote, PushTrades,
-        RealtimeQuote, SecurityBoard, SecurityBrokers, SecurityDepth, Subscription, Trade,
-        TradeSessions,
+        utils::{convert_trade_session, convert_trade_session_type, format_date, parse_date},
+        Candlestick, CustomPeriod, MarketTradingSession, OrderBook, PushBar, PushBrokers,
+        PushCandlestick, PushCustomCandlestick, PushDepth, PushEvent, PushEventDetail,
+        PushIndicator, PushQuote, PushResynced, PushTrades, RealtimeQuote, SecurityBoard,
+        SecurityBrokers, SecurityDepth, SecurityQuote, Subscription, Trade, TradeSessions,
+        TradingCalendar,
     },
+    types::parse_market_from_symbol,
     Config, Error, Market, Result,
 };
 
-const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+const RESYNC_INTERVAL: Duration = Duration::from_secs(1);
+/// Maximum delay before retrying the failed resync requests
+const RESYNC_MAX_DELAY: Duration = Duration::from_secs(32);
 
 pub(crate) enum Command {
     Request {
@@ -67,6 +79,40 @@ pub(crate) enum Command {
         period: Period,
         reply_tx: oneshot::Sender<Result<()>>,
     },
+    SubscribeCustomCandlesticks {
+        symbol: String,
+        period: CustomPeriod,
+        trade_sessions: TradeSessions,
+        reply_tx: oneshot::Sender<Result<Vec<Candlestick>>>,
+    },
+    UnsubscribeCustomCandlesticks {
+        symbol: String,
+        period: CustomPeriod,
+        reply_tx: oneshot::Sender<Result<()>>,
+    },
+    SubscribeBars {
+        symbol: String,
+        bar_type: BarType,
+        trade_sessions: TradeSessions,
+        reply_tx: oneshot::Sender<Result<()>>,
+    },
+    UnsubscribeBars {
+        symbol: String,
+        bar_type: BarType,
+        reply_tx: oneshot::Sender<Result<()>>,
+    },
+    AttachIndicator {
+        symbol: String,
+        period: Period,
+        indicator: Indicator,
+        reply_tx: oneshot::Sender<Result<Option<IndicatorValue>>>,
+    },
+    DetachIndicator {
+        symbol: String,
+        period: Period,
+        indicator: Indicator,
+        reply_tx: oneshot::Sender<()>,
+    },
     Subscriptions {
         reply_tx: oneshot::Sender<Vec<Subscription>>,
     },
@@ -78,6 +124,13 @@ pub(crate) enum Command {
         symbol: String,
         reply_tx: oneshot::Sender<SecurityDepth>,
     },
+    GetRealtimeOrderBook {
+        symbol: String,
+        reply_tx: oneshot::Sender<OrderBook>,
+    },
+    AddPushStream {
+        sender: PushStreamSender,
+    },
     GetRealtimeTrade {
         symbol: String,
         count: usize,
@@ -93,12 +146,33 @@ pub(crate) enum Command {
         count: usize,
         reply_tx: oneshot::Sender<Vec<Candlestick>>,
     },
+    GetRealtimeCustomCandlesticks {
+        symbol: String,
+        period: CustomPeriod,
+        count: usize,
+        reply_tx: oneshot::Sender<Vec<Candlestick>>,
+    },
+    GetIndicator {
+        symbol: String,
+        period: Period,
+        indicator: Indicator,
+        reply_tx: oneshot::Sender<Option<IndicatorValue>>,
+    },
+    GetRealtimeBars {
+        symbol: String,
+        bar_type: BarType,
+        count: usize,
+        reply_tx: oneshot::Sender<Vec<Candlestick>>,
+    },
+    GetTradingCalendar {
+        reply_tx: oneshot::Sender<TradingCalendar>,
+    },
 }
 
 #[derive(Debug, Default)]
-struct TradingDays {
-    normal_days: HashMap<Market, HashSet<Date>>,
-    half_days: HashMap<Market, HashSet<Date>>,
+pub(super) struct TradingDays {
+    pub(super) normal_days: HashMap<Market, HashSet<Date>>,
+    pub(super) half_days: HashMap<Market, HashSet<Date>>,
 }
 
 impl TradingDays {
@@ -132,9 +206,10 @@ pub(crate) struct Core {
     config: Arc<Config>,
     rate_limit: Vec<(u8, RateLimit)>,
     command_rx: mpsc::UnboundedReceiver<Command>,
-    push_tx: mpsc::UnboundedSender<PushEvent>,
-    event_tx: mpsc::UnboundedSender<WsEvent>,
-    event_rx: mpsc::UnboundedReceiver<WsEvent>,
+    push_tx: PushSender,
+    connection_event_tx: ConnectionEventSenders,
+    event_tx: mpsc::Sender<WsEvent>,
+    event_rx: mpsc::Receiver<WsEvent>,
     http_cli: HttpClient,
     ws_cli: WsClient,
     session: Option<WsSession>,
@@ -146,6 +221,13 @@ pub(crate) struct Core {
     quote_level: String,
     quote_package_details: Vec<QuotePackageDetail>,
     push_candlestick_mode: PushCandlestickMode,
+    enable_push_resync: bool,
+    pending_resync: HashMap<String, SubFlags>,
+    /// The number of the consecutive resyncs with failed requests
+    resync_failures: u32,
+    /// The failed resync requests are retried after the time
+    resync_retry_at: Option<Instant>,
+    push_recorder: Option<PushRecorder>,
 }
 
 impl Core {
@@ -153,11 +235,12 @@ impl Core {
         config: Arc<Config>,
         command_rx: mpsc::UnboundedReceiver<Command>,
         push_tx: mpsc::UnboundedSender<PushEvent>,
+        connection_event_tx: ConnectionEventSenders,
     ) -> Result<Self> {
         let http_cli = config.create_http_client();
         let otp = http_cli.get_otp_v2().await?;
 
-        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        let (event_tx, event_rx) = mpsc::channel(EVENT_CHANNEL_CAPACITY);
 
         tracing::info!("connecting to quote server");
         let (url, res) = config.create_quote_ws_request().await;
@@ -235,7 +318,20 @@ impl Core {
         ws_cli.set_rate_limit(rate_limit.clone());
 
         let current_trade_days = fetch_trading_days(&ws_cli).await?;
+        let mut store = Store::default();
+        match fetch_trading_sessions(&ws_cli).await {
+            Ok(trading_sessions) => store.markets.update(&trading_sessions),
+            Err(err) => {
+                tracing::warn!(error = %err, "failed to fetch trading sessions");
+            }
+        }
         let push_candlestick_mode = config.push_candlestick_mode.unwrap_or_default();
+        let enable_push_resync = config.enable_push_resync;
+        let push_recorder = config
+            .push_record_path
+            .as_deref()
+            .map(PushRecorder::create)
+            .transpose()?;
 
         let mut table = Table::new();
         for market_packages in quote_package_details_by_market {
@@ -268,7 +364,8 @@ impl Core {
             config,
             rate_limit,
             command_rx,
-            push_tx,
+            push_tx: PushSender::new(push_tx),
+            connection_event_tx,
             event_tx,
             event_rx,
             http_cli,
@@ -277,11 +374,16 @@ impl Core {
             close: false,
             subscriptions: HashMap::new(),
             trading_days: current_trade_days,
-            store: Store::default(),
+            store,
             member_id,
             quote_level,
             quote_package_details,
             push_candlestick_mode,
+            enable_push_resync,
+            pending_resync: HashMap::new(),
+            resync_failures: 0,
+            resync_retry_at: None,
+            push_recorder,
         })
     }
 
@@ -302,18 +404,40 @@ impl Core {
 
     pub(crate) async fn run(mut self) {
         while !self.close {
-            match self.main_loop().await {
+            let mut last_error = match self.main_loop().await {
                 Ok(()) => return,
-                Err(err) => tracing::error!(error = %err, "quote disconnected"),
-            }
+                Err(err) => {
+                    tracing::error!(error = %err, "quote disconnected");
+                    err.to_string()
+                }
+            };
+            self.connection_event_tx
+                .send(ConnectionEvent::Disconnected {
+                    reason: last_error.clone(),
+                });
 
+            let mut backoff = self.config.reconnect_policy.backoff();
             loop {
                 // reconnect
-                tokio::time::sleep(RECONNECT_DELAY).await;
+                let Some(delay) = backoff.next_delay() else {
+                    tracing::error!(
+                        attempts = backoff.attempts(),
+                        "give up reconnecting to quote server"
+                    );
+                    self.connection_event_tx.send(ConnectionEvent::GaveUp {
+                        attempts: backoff.attempts(),
+                        reason: last_error,
+                    });
+                    return;
+                };
+                tokio::time::sleep(delay).await;
 
                 tracing::info!("connecting to quote server");
                 let (url, res) = self.config.create_quote_ws_request().await;
                 let request = res.expect("BUG: failed to create quote ws request");
+                self.connection_event_tx.send(ConnectionEvent::Connecting {
+                    url: url.to_string(),
+                });
 
                 match WsClient::open(
                     request,
@@ -328,11 +452,19 @@ impl Core {
                     Ok(ws_cli) => self.ws_cli = ws_cli,
                     Err(err) => {
                         tracing::error!(error = %err, "failed to connect quote server");
+                        last_error = err.to_string();
+                        self.connection_event_tx
+                            .send(ConnectionEvent::Disconnected {
+                                reason: last_error.clone(),
+                            });
                         continue;
                     }
                 }
 
                 tracing::info!(url = url, "quote server connected");
+                self.connection_event_tx.send(ConnectionEvent::Connected {
+                    url: url.to_string(),
+                });
 
                 // request new session
                 match &self.session {
@@ -342,10 +474,18 @@ impl Core {
                             .request_reconnect(&session.session_id, self.config.create_metadata())
                             .await
                         {
-                            Ok(new_session) => self.session = Some(new_session),
+                            Ok(new_session) => {
+                                self.session = Some(new_session);
+                                self.connection_event_tx.send(ConnectionEvent::Resumed);
+                            }
                             Err(err) => {
                                 self.session = None; // invalid session
                                 tracing::error!(error = %err, "failed to request session id");
+                                last_error = err.to_string();
+                                self.connection_event_tx
+                                    .send(ConnectionEvent::Disconnected {
+                                        reason: last_error.clone(),
+                                    });
                                 continue;
                             }
                         }
@@ -355,6 +495,11 @@ impl Core {
                             Ok(otp) => otp,
                             Err(err) => {
                                 tracing::error!(error = %err, "failed to request otp");
+                                last_error = err.to_string();
+                                self.connection_event_tx
+                                    .send(ConnectionEvent::Disconnected {
+                                        reason: last_error.clone(),
+                                    });
                                 continue;
                             }
                         };
@@ -364,9 +509,18 @@ impl Core {
                             .request_auth(otp, self.config.create_metadata())
                             .await
                         {
-                            Ok(new_session) => self.session = Some(new_session),
+                            Ok(new_session) => {
+                                self.session = Some(new_session);
+                                self.connection_event_tx
+                                    .send(ConnectionEvent::Authenticated);
+                            }
                             Err(err) => {
                                 tracing::error!(error = %err, "failed to request session id");
+                                last_error = err.to_string();
+                                self.connection_event_tx
+                                    .send(ConnectionEvent::Disconnected {
+                                        reason: last_error.clone(),
+                                    });
                                 continue;
                             }
                         }
@@ -375,9 +529,17 @@ impl Core {
 
                 // handle reconnect
                 match self.resubscribe().await {
-                    Ok(()) => break,
+                    Ok(()) => {
+                        self.store.mark_reconnected();
+                        break;
+                    }
                     Err(err) => {
                         tracing::error!(error = %err, "failed to subscribe topics");
+                        last_error = err.to_string();
+                        self.connection_event_tx
+                            .send(ConnectionEvent::ResubscribeFailed {
+                                reason: last_error.clone(),
+                            });
                         continue;
                     }
                 }
@@ -390,10 +552,11 @@ impl Core {
             Instant::now() + Duration::from_secs(60 * 60 * 24),
             Duration::from_secs(60 * 60 * 24),
         );
+        let mut resync_interval = tokio::time::interval(RESYNC_INTERVAL);
 
         loop {
             tokio::select! {
-                item = self.event_rx.recv() => {
+                item = recv_ws_event(&self.push_tx, &mut self.event_rx) => {
                     match item {
                         Some(event) => self.handle_ws_event(event).await?,
                         None => unreachable!(),
@@ -412,6 +575,16 @@ impl Core {
                     if let Ok(days) = fetch_trading_days(&self.ws_cli).await {
                         self.trading_days = days;
                     }
+                    if let Ok(trading_sessions) = fetch_trading_sessions(&self.ws_cli).await {
+                        self.store.markets.update(&trading_sessions);
+                    }
+                }
+                _ = resync_interval.tick() => {
+                    if !self.pending_resync.is_empty()
+                        && self.resync_retry_at.is_none_or(|at| Instant::now() >= at)
+                    {
+                        self.handle_resync().await;
+                    }
                 }
             }
         }
@@ -464,6 +637,68 @@ impl Core {
                 let _ = reply_tx.send(self.handle_unsubscribe_candlesticks(symbol, period).await);
                 Ok(())
             }
+            Command::SubscribeCustomCandlesticks {
+                symbol,
+                period,
+                trade_sessions,
+                reply_tx,
+            } => {
+                let _ = reply_tx.send(
+                    self.handle_subscribe_custom_candlesticks(symbol, period, trade_sessions)
+                        .await,
+                );
+                Ok(())
+            }
+            Command::UnsubscribeCustomCandlesticks {
+                symbol,
+                period,
+                reply_tx,
+            } => {
+                let _ = reply_tx.send(
+                    self.handle_unsubscribe_custom_candlesticks(symbol, period)
+                        .await,
+                );
+                Ok(())
+            }
+            Command::SubscribeBars {
+                symbol,
+                bar_type,
+                trade_sessions,
+                reply_tx,
+            } => {
+                let _ = reply_tx.send(
+                    self.handle_subscribe_bars(symbol, bar_type, trade_sessions)
+                        .await,
+                );
+                Ok(())
+            }
+            Command::UnsubscribeBars {
+                symbol,
+                bar_type,
+                reply_tx,
+            } => {
+                let _ = reply_tx.send(self.handle_unsubscribe_bars(symbol, bar_type).await);
+                Ok(())
+            }
+            Command::AttachIndicator {
+                symbol,
+                period,
+                indicator,
+                reply_tx,
+            } => {
+                let _ = reply_tx.send(self.handle_attach_indicator(symbol, period, indicator));
+                Ok(())
+            }
+            Command::DetachIndicator {
+                symbol,
+                period,
+                indicator,
+                reply_tx,
+            } => {
+                self.handle_detach_indicator(symbol, period, indicator);
+                let _ = reply_tx.send(());
+                Ok(())
+            }
             Command::Subscriptions { reply_tx } => {
                 let res = self.handle_subscriptions().await;
                 let _ = reply_tx.send(res);
@@ -477,6 +712,14 @@ impl Core {
                 let _ = reply_tx.send(self.handle_get_realtime_depth(symbol));
                 Ok(())
             }
+            Command::GetRealtimeOrderBook { symbol, reply_tx } => {
+                let _ = reply_tx.send(self.handle_get_realtime_order_book(symbol));
+                Ok(())
+            }
+            Command::AddPushStream { sender } => {
+                self.push_tx.add_stream(sender);
+                Ok(())
+            }
             Command::GetRealtimeTrade {
                 symbol,
                 count,
@@ -498,6 +741,41 @@ impl Core {
                 let _ = reply_tx.send(self.handle_get_realtime_candlesticks(symbol, period, count));
                 Ok(())
             }
+            Command::GetRealtimeCustomCandlesticks {
+                symbol,
+                period,
+                count,
+                reply_tx,
+            } => {
+                let _ = reply_tx
+                    .send(self.handle_get_realtime_custom_candlesticks(symbol, period, count));
+                Ok(())
+            }
+            Command::GetIndicator {
+                symbol,
+                period,
+                indicator,
+                reply_tx,
+            } => {
+                let _ = reply_tx.send(self.handle_get_indicator(symbol, period, indicator));
+                Ok(())
+            }
+            Command::GetRealtimeBars {
+                symbol,
+                bar_type,
+                count,
+                reply_tx,
+            } => {
+                let _ = reply_tx.send(self.handle_get_realtime_bars(symbol, bar_type, count));
+                Ok(())
+            }
+            Command::GetTradingCalendar { reply_tx } => {
+                let _ = reply_tx.send(TradingCalendar::new(
+                    &self.trading_days,
+                    self.store.markets.clone(),
+                ));
+                Ok(())
+            }
         }
     }
 
@@ -552,15 +830,13 @@ impl Core {
         for symbol in &symbols {
             let mut st = sub_types;
 
-            if let Some(candlesticks) = self
-                .store
-                .securities
-                .get(symbol)
-                .map(|data| &data.candlesticks)
-            {
-                if !candlesticks.is_empty() {
+            if let Some(data) = self.store.securities.get(symbol) {
+                if !data.candlesticks.is_empty() {
                     st.remove(SubFlags::QUOTE | SubFlags::TRADE);
                 }
+                if data.requires_trades() {
+                    st.remove(SubFlags::TRADE);
+                }
             }
 
             if !st.is_empty() {
@@ -646,7 +922,7 @@ impl Core {
         tracing::info!(symbol = symbol, board = ?security_data.board, "got the symbol board");
 
         let Some(market) = parse_market_from_symbol(&symbol)
-            .and_then(|market| get_market(market, security_data.board))
+            .and_then(|market| self.store.markets.get(market, security_data.board))
         else {
             return Err(Error::UnknownMarket { symbol });
         };
@@ -704,6 +980,7 @@ impl Core {
                 trade_sessions,
                 candlesticks: candlesticks.clone(),
                 tails,
+                indicators: Default::default(),
             });
 
         // subscribe
@@ -737,13 +1014,8 @@ impl Core {
         symbol: String,
         period: Period,
     ) -> Result<()> {
-        if let Some(periods) = self
-            .store
-            .securities
-            .get_mut(&symbol)
-            .map(|data| &mut data.candlesticks)
-        {
-            periods.remove(&period);
+        if let Some(data) = self.store.securities.get_mut(&symbol) {
+            data.candlesticks.remove(&period);
 
             let sub_flags = self
                 .subscriptions
@@ -751,7 +1023,16 @@ impl Core {
                 .copied()
                 .unwrap_or_else(SubFlags::empty);
 
-            if periods.is_empty() && !sub_flags.intersects(SubFlags::QUOTE | SubFlags::TRADE) {
+            if data.candlesticks.is_empty()
+                && !sub_flags.intersects(SubFlags::QUOTE | SubFlags::TRADE)
+            {
+                // the trades may still be required by the custom candlesticks and the bars
+                let sub_type = if !data.requires_trades() {
+                    SubFlags::QUOTE | SubFlags::TRADE
+                } else {
+                    SubFlags::QUOTE
+                };
+
                 tracing::info!(symbol = symbol, "unsubscribe quote for candlesticks");
                 self.ws_cli
                     .request::<_, ()>(
@@ -759,7 +1040,7 @@ impl Core {
                         None,
                         UnsubscribeRequest {
                             symbol: vec![symbol],
-                            sub_type: (SubFlags::QUOTE | SubFlags::TRADE).into(),
+                            sub_type: sub_type.into(),
                             unsub_all: false,
                         },
                     )
@@ -770,153 +1051,367 @@ impl Core {
         Ok(())
     }
 
-    async fn handle_subscriptions(&mut self) -> Vec<Subscription> {
-        self.subscriptions
-            .iter()
-            .map(|(symbol, sub_flags)| Subscription {
-                symbol: symbol.clone(),
-                sub_types: *sub_flags,
-                candlesticks: self
-                    .store
-                    .securities
-                    .get(symbol)
-                    .map(|data| &data.candlesticks)
-                    .map(|periods| periods.keys().copied().collect())
-                    .unwrap_or_default(),
-            })
-            .collect()
+    /// Returns `true` if the trades of the symbol are pushed
+    fn is_trade_subscribed(&self, symbol: &str) -> bool {
+        self.store
+            .securities
+            .get(symbol)
+            .map(|data| !data.candlesticks.is_empty() || data.requires_trades())
+            .unwrap_or_default()
+            || self
+                .subscriptions
+                .get(symbol)
+                .copied()
+                .unwrap_or_else(SubFlags::empty)
+                .contains(SubFlags::TRADE)
     }
 
-    async fn handle_ws_event(&mut self, event: WsEvent) -> Result<()> {
-        match event {
-            WsEvent::Error(err) => Err(err.into()),
-            WsEvent::Push { command_code, body } => self.handle_push(command_code, body),
+    async fn subscribe_trades(&self, symbol: &str) -> Result<()> {
+        tracing::info!(symbol = symbol, "subscribe trades");
+        self.ws_cli
+            .request::<_, ()>(
+                cmd_code::SUBSCRIBE,
+                None,
+                SubscribeRequest {
+                    symbol: vec![symbol.to_string()],
+                    sub_type: SubFlags::TRADE.into(),
+                    is_first_push: false,
+                },
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Unsubscribes the trades if they are no longer required
+    async fn release_trades(&mut self, symbol: String) -> Result<()> {
+        if self.is_trade_subscribed(&symbol) {
+            return Ok(());
         }
+
+        tracing::info!(symbol = symbol, "unsubscribe trades");
+        self.ws_cli
+            .request::<_, ()>(
+                cmd_code::UNSUBSCRIBE,
+                None,
+                UnsubscribeRequest {
+                    symbol: vec![symbol],
+                    sub_type: SubFlags::TRADE.into(),
+                    unsub_all: false,
+                },
+            )
+            .await?;
+        Ok(())
     }
 
-    async fn resubscribe(&mut self) -> Result<()> {
-        let mut subscriptions: HashMap<SubFlags, HashSet<String>> = HashMap::new();
+    async fn handle_subscribe_custom_candlesticks(
+        &mut self,
+        symbol: String,
+        period: CustomPeriod,
+        trade_sessions: TradeSessions,
+    ) -> Result<Vec<Candlestick>> {
+        tracing::info!(symbol = symbol, period = ?period, "subscribe custom candlesticks");
 
-        for (symbol, flags) in &self.subscriptions {
-            subscriptions
-                .entry(*flags)
-                .or_default()
-                .insert(symbol.clone());
+        if let Some(candlesticks) = self
+            .store
+            .securities
+            .get(&symbol)
+            .and_then(|data| data.custom_candlesticks.get(&period))
+            .filter(|candlesticks| candlesticks.trade_sessions == trade_sessions)
+        {
+            return Ok(candlesticks.candlesticks.clone());
         }
 
-        for (symbol, data) in &self.store.securities {
-            if !data.candlesticks.is_empty() {
-                subscriptions
-                    .entry(SubFlags::QUOTE | SubFlags::TRADE)
-                    .or_default()
-                    .insert(symbol.clone());
-            }
-        }
+        let board = self
+            .store
+            .securities
+            .get(&symbol)
+            .map(|data| data.board)
+            .unwrap_or_default();
+        let Some((market_type, market)) = parse_market_from_symbol(&symbol)
+            .and_then(|market| Some((market, self.store.markets.get(market, board)?.clone())))
+        else {
+            return Err(Error::UnknownMarket { symbol });
+        };
 
-        tracing::info!(subscriptions = ?subscriptions, "resubscribe");
+        let mut candlesticks = Candlesticks {
+            trade_sessions,
+            candlesticks: vec![],
+            tails: HashMap::new(),
+            indicators: HashMap::new(),
+        };
 
-        for (flags, symbols) in subscriptions {
-            self.ws_cli
-                .request::<_, ()>(
-                    cmd_code::SUBSCRIBE,
+        // the candlesticks of the whole minutes are built from the 1 minute history
+        if period.is_whole_minutes() {
+            tracing::info!(symbol = symbol, period = ?period, "pull 1 minute history candlesticks");
+            let resp: SecurityCandlestickResponse = self
+                .ws_cli
+                .request(
+                    cmd_code::GET_SECURITY_CANDLESTICKS,
                     None,
-                    SubscribeRequest {
-                        symbol: symbols.into_iter().collect(),
-                        sub_type: flags.into(),
-                        is_first_push: false,
+                    SecurityCandlestickRequest {
+                        symbol: symbol.clone(),
+                        period: Period::OneMinute.into(),
+                        count: 1000,
+                        adjust_type: AdjustType::NoAdjust.into(),
+                        trade_session: trade_sessions as i32,
                     },
                 )
                 .await?;
-        }
-        Ok(())
-    }
-
-    fn merge_candlesticks_by_quote(&mut self, symbol: &str, push_quote: &PushQuote) {
-        if push_quote.trade_session != TradeSession::NormalTrade {
-            return;
+            let history = resp
+                .candlesticks
+                .into_iter()
+                .map(|candlestick| Candlestick::try_from(candlestick).map(Into::into))
+                .collect::<Result<Vec<longport_candlesticks::Candlestick>>>()?;
+
+            for (ts, candlestick) in market.resample_candlesticks(
+                self.trading_days.half_days(market_type),
+                period.into(),
+                history,
+            ) {
+                let candlestick: Candlestick = (candlestick, convert_trade_session_type(ts)).into();
+                candlesticks.tails.insert(
+                    ts,
+                    TailCandlestick {
+                        index: candlesticks.candlesticks.len(),
+                        candlestick,
+                    },
+                );
+                candlesticks.candlesticks.push(candlestick);
+            }
         }
 
-        let Some(market_type) = parse_market_from_symbol(symbol) else {
-            return;
-        };
-        let Some(security_data) = self.store.securities.get_mut(symbol) else {
-            return;
-        };
-        let half_days = self.trading_days.half_days(market_type);
+        tracing::info!(symbol = symbol, period = ?period, count = candlesticks.candlesticks.len(), "custom candlesticks loaded");
 
-        if let Some(candlesticks) = security_data.candlesticks.get_mut(&Period::Day) {
-            let ts = convert_trade_session(push_quote.trade_session);
-            let action = candlesticks.merge_quote(
-                ts,
-                market_type,
-                half_days,
-                security_data.board,
-                Period::Day,
-                push_quote,
-            );
-            update_and_push_candlestick(
-                candlesticks,
-                ts,
-                push_quote.trade_session,
-                symbol,
-                Period::Day,
-                action,
-                self.push_candlestick_mode,
-                &mut self.push_tx,
-            );
+        let res = candlesticks.candlesticks.clone();
+        let trade_subscribed = self.is_trade_subscribed(&symbol);
+        self.store
+            .securities
+            .entry(symbol.clone())
+            .or_default()
+            .custom_candlesticks
+            .insert(period, candlesticks);
+
+        if !trade_subscribed {
+            if let Err(err) = self.subscribe_trades(&symbol).await {
+                if let Some(data) = self.store.securities.get_mut(&symbol) {
+                    data.custom_candlesticks.remove(&period);
+                }
+                return Err(err);
+            }
         }
+
+        Ok(res)
     }
 
-    fn merge_candlesticks_by_trades(&mut self, symbol: &str, push_trades: &PushTrades) {
-        let Some(market_type) = parse_market_from_symbol(symbol) else {
-            return;
-        };
-        let Some(security_data) = self.store.securities.get_mut(symbol) else {
-            return;
+    async fn handle_unsubscribe_custom_candlesticks(
+        &mut self,
+        symbol: String,
+        period: CustomPeriod,
+    ) -> Result<()> {
+        let removed = self
+            .store
+            .securities
+            .get_mut(&symbol)
+            .and_then(|data| data.custom_candlesticks.remove(&period))
+            .is_some();
+        if !removed {
+            return Ok(());
+        }
+        self.release_trades(symbol).await
+    }
+
+    async fn handle_subscribe_bars(
+        &mut self,
+        symbol: String,
+        bar_type: BarType,
+        trade_sessions: TradeSessions,
+    ) -> Result<()> {
+        tracing::info!(symbol = symbol, bar_type = ?bar_type, "subscribe bars");
+
+        let trade_subscribed = self.is_trade_subscribed(&symbol);
+        let security_data = self.store.securities.entry(symbol.clone()).or_default();
+        if let Some(bars) = security_data.bars.get_mut(&bar_type) {
+            bars.trade_sessions = trade_sessions;
+            return Ok(());
+        }
+
+        let Some(market) = parse_market_from_symbol(&symbol)
+            .and_then(|market| self.store.markets.get(market, security_data.board))
+        else {
+            return Err(Error::UnknownMarket { symbol });
         };
-        let half_days = self.trading_days.half_days(market_type);
 
-        for trade in &push_trades.trades {
-            let ts = convert_trade_session(trade.trade_session);
+        security_data.bars.insert(
+            bar_type,
+            Bars::new(bar_type, market.lot_size, trade_sessions),
+        );
 
-            for (period, candlesticks) in &mut security_data.candlesticks {
-                if *period >= Period::Day && !ts.is_normal() {
-                    continue;
+        if !trade_subscribed {
+            if let Err(err) = self.subscribe_trades(&symbol).await {
+                if let Some(data) = self.store.securities.get_mut(&symbol) {
+                    data.bars.remove(&bar_type);
                 }
+                return Err(err);
+            }
+        }
 
-                let action = candlesticks.merge_trade(
-                    ts,
-                    market_type,
-                    half_days,
-                    security_data.board,
-                    *period,
-                    trade,
-                );
-                update_and_push_candlestick(
-                    candlesticks,
-                    ts,
-                    trade.trade_session,
-                    symbol,
-                    *period,
-                    action,
-                    self.push_candlestick_mode,
-                    &mut self.push_tx,
-                );
+        Ok(())
+    }
+
+    async fn handle_unsubscribe_bars(&mut self, symbol: String, bar_type: BarType) -> Result<()> {
+        let removed = self
+            .store
+            .securities
+            .get_mut(&symbol)
+            .and_then(|data| data.bars.remove(&bar_type))
+            .is_some();
+        if !removed {
+            return Ok(());
+        }
+        self.release_trades(symbol).await
+    }
+
+    fn handle_attach_indicator(
+        &mut self,
+        symbol: String,
+        period: Period,
+        indicator: Indicator,
+    ) -> Result<Option<IndicatorValue>> {
+        tracing::info!(symbol = symbol, period = ?period, indicator = ?indicator, "attach indicator");
+
+        let Some(security_data) = self.store.securities.get_mut(&symbol) else {
+            return Err(Error::CandlesticksNotSubscribed { symbol, period });
+        };
+        let Some(market) = parse_market_from_symbol(&symbol)
+            .and_then(|market| self.store.markets.get(market, security_data.board))
+        else {
+            return Err(Error::UnknownMarket { symbol });
+        };
+        let Some(candlesticks) = security_data.candlesticks.get_mut(&period) else {
+            return Err(Error::CandlesticksNotSubscribed { symbol, period });
+        };
+
+        let calculator = candlesticks.indicators.entry(indicator).or_insert_with(|| {
+            let mut calculator = IndicatorCalculator::new(indicator, market.timezone);
+            let mut history = candlesticks
+                .candlesticks
+                .iter()
+                .filter(|candlestick| {
+                    candlesticks
+                        .trade_sessions
+                        .contains(candlestick.trade_session)
+                })
+                .peekable();
+            while let Some(candlestick) = history.next() {
+                // the last candlestick is still being updated
+                calculator.update(&(*candlestick).into(), history.peek().is_some());
+            }
+            calculator
+        });
+        Ok(calculator.value().map(|(_, value)| value))
+    }
+
+    fn handle_detach_indicator(&mut self, symbol: String, period: Period, indicator: Indicator) {
+        if let Some(candlesticks) = self
+            .store
+            .securities
+            .get_mut(&symbol)
+            .and_then(|data| data.candlesticks.get_mut(&period))
+        {
+            candlesticks.indicators.remove(&indicator);
+        }
+    }
+
+    async fn handle_subscriptions(&mut self) -> Vec<Subscription> {
+        self.subscriptions
+            .iter()
+            .map(|(symbol, sub_flags)| Subscription {
+                symbol: symbol.clone(),
+                sub_types: *sub_flags,
+                candlesticks: self
+                    .store
+                    .securities
+                    .get(symbol)
+                    .map(|data| &data.candlesticks)
+                    .map(|periods| periods.keys().copied().collect())
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    async fn handle_ws_event(&mut self, event: WsEvent) -> Result<()> {
+        match event {
+            WsEvent::Error(err) => Err(err.into()),
+            WsEvent::Push { command_code, body } => self.handle_push(command_code, body),
+        }
+    }
+
+    async fn resubscribe(&mut self) -> Result<()> {
+        let mut subscriptions: HashMap<SubFlags, HashSet<String>> = HashMap::new();
+
+        for (symbol, flags) in &self.subscriptions {
+            subscriptions
+                .entry(*flags)
+                .or_default()
+                .insert(symbol.clone());
+        }
+
+        for (symbol, data) in &self.store.securities {
+            if !data.candlesticks.is_empty() {
+                subscriptions
+                    .entry(SubFlags::QUOTE | SubFlags::TRADE)
+                    .or_default()
+                    .insert(symbol.clone());
+            } else if data.requires_trades() {
+                subscriptions
+                    .entry(SubFlags::TRADE)
+                    .or_default()
+                    .insert(symbol.clone());
             }
         }
+
+        tracing::info!(subscriptions = ?subscriptions, "resubscribe");
+
+        for (flags, symbols) in subscriptions {
+            self.ws_cli
+                .request::<_, ()>(
+                    cmd_code::SUBSCRIBE,
+                    None,
+                    SubscribeRequest {
+                        symbol: symbols.into_iter().collect(),
+                        sub_type: flags.into(),
+                        is_first_push: false,
+                    },
+                )
+                .await?;
+        }
+        Ok(())
     }
 
     fn handle_push(&mut self, command_code: u8, body: Vec<u8>) -> Result<()> {
+        if let Some(recorder) = &self.push_recorder {
+            recorder.record(command_code, &body);
+        }
+
         match PushEvent::parse(command_code, &body) {
             Ok((mut event, tag)) => {
                 tracing::info!(event = ?event, tag = ?tag, "push event");
 
+                self.check_sequence(&event);
+
                 if tag != Some(PushQuoteTag::Eod) {
                     self.store.handle_push(&mut event);
                 }
 
                 if let PushEventDetail::Quote(push_quote) = &event.detail {
-                    self.merge_candlesticks_by_quote(&event.symbol, push_quote);
+                    merge_candlesticks_by_quote(
+                        &mut self.store,
+                        &self.trading_days,
+                        self.push_candlestick_mode,
+                        &mut self.push_tx,
+                        &event.symbol,
+                        push_quote,
+                    );
 
                     if !self
                         .subscriptions
@@ -927,7 +1422,21 @@ impl Core {
                         return Ok(());
                     }
                 } else if let PushEventDetail::Trade(trades) = &event.detail {
-                    self.merge_candlesticks_by_trades(&event.symbol, trades);
+                    merge_candlesticks_by_trades(
+                        &mut self.store,
+                        &self.trading_days,
+                        self.push_candlestick_mode,
+                        &mut self.push_tx,
+                        &event.symbol,
+                        trades,
+                    );
+                    merge_bars_by_trades(
+                        &mut self.store,
+                        self.push_candlestick_mode,
+                        &mut self.push_tx,
+                        &event.symbol,
+                        trades,
+                    );
 
                     if !self
                         .subscriptions
@@ -943,7 +1452,7 @@ impl Core {
                     return Ok(());
                 }
 
-                let _ = self.push_tx.send(event);
+                self.push_tx.send(event);
             }
             Err(err) => {
                 tracing::error!(error = %err, "failed to parse push message");
@@ -952,6 +1461,238 @@ impl Core {
         Ok(())
     }
 
+    fn check_sequence(&mut self, event: &PushEvent) {
+        let Some(gap) = self.store.check_sequence(event) else {
+            return;
+        };
+        if !self
+            .subscriptions
+            .get(&event.symbol)
+            .map(|sub_flags| sub_flags.contains(gap.sub_type))
+            .unwrap_or_default()
+        {
+            return;
+        }
+
+        tracing::warn!(symbol = event.symbol, gap = ?gap, "push sequence gap");
+
+        if self.enable_push_resync
+            && gap
+                .sub_type
+                .intersects(SubFlags::QUOTE | SubFlags::DEPTH | SubFlags::BROKER)
+        {
+            *self
+                .pending_resync
+                .entry(event.symbol.clone())
+                .or_insert_with(SubFlags::empty) |= gap.sub_type;
+        }
+
+        self.push_tx.send(PushEvent {
+            sequence: 0,
+            symbol: event.symbol.clone(),
+            detail: PushEventDetail::SequenceGap(gap),
+        });
+    }
+
+    async fn handle_resync(&mut self) {
+        let pending = std::mem::take(&mut self.pending_resync);
+        let mut resynced: HashMap<String, SubFlags> = HashMap::new();
+        let mut failed: HashMap<String, SubFlags> = HashMap::new();
+
+        tracing::info!(symbols = ?pending, "resync realtime data");
+
+        let quote_symbols = pending
+            .iter()
+            .filter(|(_, sub_types)| sub_types.contains(SubFlags::QUOTE))
+            .map(|(symbol, _)| symbol.clone())
+            .collect::<Vec<_>>();
+        if !quote_symbols.is_empty() {
+            let res = self
+                .ws_cli
+                .request::<_, quote::SecurityQuoteResponse>(
+                    cmd_code::GET_REALTIME_QUOTE,
+                    None,
+                    MultiSecurityRequest {
+                        symbol: quote_symbols.clone(),
+                    },
+                )
+                .await
+                .map_err(Error::from)
+                .and_then(|resp| {
+                    resp.secu_quote
+                        .into_iter()
+                        .map(SecurityQuote::try_from)
+                        .collect::<Result<Vec<_>>>()
+                });
+
+            match res {
+                Ok(quotes) => {
+                    for quote in quotes {
+                        let prev_quote = self
+                            .store
+                            .securities
+                            .get(&quote.symbol)
+                            .map(|data| data.quote.clone())
+                            .unwrap_or_default();
+                        let mut event = PushEvent {
+                            sequence: 0,
+                            symbol: quote.symbol,
+                            detail: PushEventDetail::Quote(PushQuote {
+                                last_done: quote.last_done,
+                                open: quote.open,
+                                high: quote.high,
+                                low: quote.low,
+                                timestamp: quote.timestamp,
+                                volume: quote.volume,
+                                turnover: quote.turnover,
+                                trade_status: quote.trade_status,
+                                trade_session: prev_quote.trade_session,
+                                current_volume: 0,
+                                current_turnover: Default::default(),
+                            }),
+                        };
+                        self.store.handle_push(&mut event);
+                        *resynced
+                            .entry(event.symbol.clone())
+                            .or_insert_with(SubFlags::empty) |= SubFlags::QUOTE;
+                        self.push_tx.send(event);
+                    }
+                }
+                Err(err) => {
+                    tracing::error!(error = %err, "failed to resync quotes");
+                    for symbol in quote_symbols {
+                        *failed.entry(symbol).or_insert_with(SubFlags::empty) |= SubFlags::QUOTE;
+                    }
+                }
+            }
+        }
+
+        for (symbol, sub_types) in &pending {
+            if sub_types.contains(SubFlags::DEPTH) {
+                let res = self
+                    .ws_cli
+                    .request::<_, quote::SecurityDepthResponse>(
+                        cmd_code::GET_SECURITY_DEPTH,
+                        None,
+                        quote::SecurityRequest {
+                            symbol: symbol.clone(),
+                        },
+                    )
+                    .await
+                    .map_err(Error::from)
+                    .and_then(|resp| {
+                        Ok(PushDepth {
+                            asks: resp
+                                .ask
+                                .into_iter()
+                                .map(TryInto::try_into)
+                                .collect::<Result<Vec<_>>>()?,
+                            bids: resp
+                                .bid
+                                .into_iter()
+                                .map(TryInto::try_into)
+                                .collect::<Result<Vec<_>>>()?,
+                        })
+                    });
+
+                match res {
+                    Ok(depth) => {
+                        let data = self.store.securities.entry(symbol.clone()).or_default();
+                        data.order_book.reset(&depth);
+                        *resynced
+                            .entry(symbol.clone())
+                            .or_insert_with(SubFlags::empty) |= SubFlags::DEPTH;
+                        self.push_tx.send(PushEvent {
+                            sequence: 0,
+                            symbol: symbol.clone(),
+                            detail: PushEventDetail::Depth(depth),
+                        });
+                    }
+                    Err(err) => {
+                        tracing::error!(symbol = symbol, error = %err, "failed to resync depth");
+                        *failed.entry(symbol.clone()).or_insert_with(SubFlags::empty) |=
+                            SubFlags::DEPTH;
+                    }
+                }
+            }
+
+            if sub_types.contains(SubFlags::BROKER) {
+                let res = self
+                    .ws_cli
+                    .request::<_, quote::SecurityBrokersResponse>(
+                        cmd_code::GET_SECURITY_BROKERS,
+                        None,
+                        quote::SecurityRequest {
+                            symbol: symbol.clone(),
+                        },
+                    )
+                    .await;
+
+                match res {
+                    Ok(resp) => {
+                        let brokers = PushBrokers {
+                            ask_brokers: resp.ask_brokers.into_iter().map(Into::into).collect(),
+                            bid_brokers: resp.bid_brokers.into_iter().map(Into::into).collect(),
+                        };
+                        let data = self.store.securities.entry(symbol.clone()).or_default();
+                        data.ask_brokers.clone_from(&brokers.ask_brokers);
+                        data.bid_brokers.clone_from(&brokers.bid_brokers);
+                        *resynced
+                            .entry(symbol.clone())
+                            .or_insert_with(SubFlags::empty) |= SubFlags::BROKER;
+                        self.push_tx.send(PushEvent {
+                            sequence: 0,
+                            symbol: symbol.clone(),
+                            detail: PushEventDetail::Brokers(brokers),
+                        });
+                    }
+                    Err(err) => {
+                        tracing::error!(symbol = symbol, error = %err, "failed to resync brokers");
+                        *failed.entry(symbol.clone()).or_insert_with(SubFlags::empty) |=
+                            SubFlags::BROKER;
+                    }
+                }
+            }
+        }
+
+        for (symbol, sub_types) in resynced {
+            tracing::info!(symbol = symbol, sub_types = ?sub_types, "resynced");
+            self.push_tx.send(PushEvent {
+                sequence: 0,
+                symbol,
+                detail: PushEventDetail::Resynced(PushResynced { sub_types }),
+            });
+        }
+
+        if failed.is_empty() {
+            self.resync_failures = 0;
+            self.resync_retry_at = None;
+            return;
+        }
+
+        // the failed requests are retried with an exponential backoff, unless
+        // the securities are unsubscribed
+        self.resync_failures += 1;
+        let delay = RESYNC_INTERVAL
+            .saturating_mul(1 << self.resync_failures.min(5))
+            .min(RESYNC_MAX_DELAY);
+        self.resync_retry_at = Some(Instant::now() + delay);
+        for (symbol, sub_types) in failed {
+            let sub_types = sub_types
+                & self
+                    .subscriptions
+                    .get(&symbol)
+                    .copied()
+                    .unwrap_or_else(SubFlags::empty);
+            if !sub_types.is_empty() {
+                *self
+                    .pending_resync
+                    .entry(symbol)
+                    .or_insert_with(SubFlags::empty) |= sub_types;
+            }
+        }
+    }
+
     fn handle_get_realtime_quote(&self, symbols: Vec<String>) -> Vec<RealtimeQuote> {
         let mut result = Vec::new();
 
@@ -975,12 +1716,19 @@ impl Core {
     }
 
     fn handle_get_realtime_depth(&self, symbol: String) -> SecurityDepth {
-        let mut result = SecurityDepth::default();
-        if let Some(data) = self.store.securities.get(&symbol) {
-            result.asks.clone_from(&data.asks);
-            result.bids.clone_from(&data.bids);
-        }
-        result
+        self.store
+            .securities
+            .get(&symbol)
+            .map(|data| data.order_book.to_security_depth())
+            .unwrap_or_default()
+    }
+
+    fn handle_get_realtime_order_book(&self, symbol: String) -> OrderBook {
+        self.store
+            .securities
+            .get(&symbol)
+            .map(|data| data.order_book.clone())
+            .unwrap_or_default()
     }
 
     fn handle_get_realtime_trades(&self, symbol: String, count: usize) -> Vec<Trade> {
@@ -1027,6 +1775,63 @@ impl Core {
             })
             .unwrap_or_default()
     }
+
+    fn handle_get_realtime_custom_candlesticks(
+        &self,
+        symbol: String,
+        period: CustomPeriod,
+        count: usize,
+    ) -> Vec<Candlestick> {
+        self.store
+            .securities
+            .get(&symbol)
+            .and_then(|data| data.custom_candlesticks.get(&period))
+            .map(|candlesticks| {
+                let candlesticks = &candlesticks.candlesticks;
+                candlesticks[candlesticks.len().saturating_sub(count)..].to_vec()
+            })
+            .unwrap_or_default()
+    }
+
+    fn handle_get_indicator(
+        &self,
+        symbol: String,
+        period: Period,
+        indicator: Indicator,
+    ) -> Option<IndicatorValue> {
+        self.store
+            .securities
+            .get(&symbol)
+            .and_then(|data| data.candlesticks.get(&period))
+            .and_then(|candlesticks| candlesticks.indicators.get(&indicator))
+            .and_then(|calculator| calculator.value())
+            .map(|(_, value)| value)
+    }
+
+    fn handle_get_realtime_bars(
+        &self,
+        symbol: String,
+        bar_type: BarType,
+        count: usize,
+    ) -> Vec<Candlestick> {
+        self.store
+            .securities
+            .get(&symbol)
+            .and_then(|data| data.bars.get(&bar_type))
+            .map(|bars| bars.bars[bars.bars.len().saturating_sub(count)..].to_vec())
+            .unwrap_or_default()
+    }
+}
+
+async fn fetch_trading_sessions(cli: &WsClient) -> Result<Vec<MarketTradingSession>> {
+    let resp = cli
+        .request_raw(cmd_code::GET_TRADING_SESSION, None, vec![])
+        .await?;
+    quote::MarketTradePeriodResponse::decode(&*resp)?
+        .market_trade_session
+        .into_iter()
+        .map(TryInto::try_into)
+        .collect()
 }
 
 async fn fetch_trading_days(cli: &WsClient) -> Result<TradingDays> {
@@ -1071,28 +1876,245 @@ async fn fetch_trading_days(cli: &WsClient) -> Result<TradingDays> {
     Ok(days)
 }
 
+/// Receives the next websocket event after the blocking push streams have
+/// room for the pushes
+async fn recv_ws_event(
+    push_tx: &PushSender,
+    event_rx: &mut mpsc::Receiver<WsEvent>,
+) -> Option<WsEvent> {
+    push_tx.ready().await;
+    event_rx.recv().await
+}
+
+pub(super) fn merge_candlesticks_by_quote(
+    store: &mut Store,
+    trading_days: &TradingDays,
+    push_candlestick_mode: PushCandlestickMode,
+    push_tx: &mut PushSender,
+    symbol: &str,
+    push_quote: &PushQuote,
+) {
+    if push_quote.trade_session != TradeSession::NormalTrade {
+        return;
+    }
+
+    let Some(market_type) = parse_market_from_symbol(symbol) else {
+        return;
+    };
+    let Some(security_data) = store.securities.get_mut(symbol) else {
+        return;
+    };
+    let Some(market) = store.markets.get(market_type, security_data.board) else {
+        return;
+    };
+    let half_days = trading_days.half_days(market_type);
+
+    if let Some(candlesticks) = security_data.candlesticks.get_mut(&Period::Day) {
+        let ts = convert_trade_session(push_quote.trade_session);
+        let action = candlesticks.merge_quote(ts, market, half_days, Period::Day, push_quote);
+        let updated_candlesticks = update_and_push_candlestick(
+            candlesticks,
+            ts,
+            push_quote.trade_session,
+            symbol,
+            action,
+            push_candlestick_mode,
+            push_tx,
+            |candlestick, is_confirmed| {
+                PushEventDetail::Candlestick(PushCandlestick {
+                    period: Period::Day,
+                    candlestick,
+                    is_confirmed,
+                })
+            },
+        );
+        update_and_push_indicators(
+            candlesticks,
+            Period::Day,
+            symbol,
+            &updated_candlesticks,
+            push_candlestick_mode,
+            push_tx,
+        );
+    }
+}
+
+pub(super) fn merge_candlesticks_by_trades(
+    store: &mut Store,
+    trading_days: &TradingDays,
+    push_candlestick_mode: PushCandlestickMode,
+    push_tx: &mut PushSender,
+    symbol: &str,
+    push_trades: &PushTrades,
+) {
+    let Some(market_type) = parse_market_from_symbol(symbol) else {
+        return;
+    };
+    let Some(security_data) = store.securities.get_mut(symbol) else {
+        return;
+    };
+    let Some(market) = store.markets.get(market_type, security_data.board) else {
+        return;
+    };
+    let half_days = trading_days.half_days(market_type);
+
+    for trade in &push_trades.trades {
+        let ts = convert_trade_session(trade.trade_session);
+
+        for (period, candlesticks) in &mut security_data.candlesticks {
+            if *period >= Period::Day && !ts.is_normal() {
+                continue;
+            }
+
+            let action = candlesticks.merge_trade(
+                ts,
+                market,
+                market_type,
+                half_days,
+                security_data.board,
+                convert_period(*period),
+                trade,
+            );
+            let updated_candlesticks = update_and_push_candlestick(
+                candlesticks,
+                ts,
+                trade.trade_session,
+                symbol,
+                action,
+                push_candlestick_mode,
+                push_tx,
+                |candlestick, is_confirmed| {
+                    PushEventDetail::Candlestick(PushCandlestick {
+                        period: *period,
+                        candlestick,
+                        is_confirmed,
+                    })
+                },
+            );
+            update_and_push_indicators(
+                candlesticks,
+                *period,
+                symbol,
+                &updated_candlesticks,
+                push_candlestick_mode,
+                push_tx,
+            );
+        }
+
+        for (period, candlesticks) in &mut security_data.custom_candlesticks {
+            let action = candlesticks.merge_trade(
+                ts,
+                market,
+                market_type,
+                half_days,
+                security_data.board,
+                (*period).into(),
+                trade,
+            );
+            update_and_push_candlestick(
+                candlesticks,
+                ts,
+                trade.trade_session,
+                symbol,
+                action,
+                push_candlestick_mode,
+                push_tx,
+                |candlestick, is_confirmed| {
+                    PushEventDetail::CustomCandlestick(PushCustomCandlestick {
+                        period: *period,
+                        candlestick,
+                        is_confirmed,
+                    })
+                },
+            );
+        }
+    }
+}
+
+pub(super) fn merge_bars_by_trades(
+    store: &mut Store,
+    push_candlestick_mode: PushCandlestickMode,
+    push_tx: &mut PushSender,
+    symbol: &str,
+    push_trades: &PushTrades,
+) {
+    let Some(market_type) = parse_market_from_symbol(symbol) else {
+        return;
+    };
+    let Some(security_data) = store.securities.get_mut(symbol) else {
+        return;
+    };
+
+    for trade in &push_trades.trades {
+        let ts = convert_trade_session(trade.trade_session);
+
+        for (bar_type, bars) in &mut security_data.bars {
+            if !bars.trade_sessions.contains(trade.trade_session) {
+                continue;
+            }
+
+            let mut push_bars = Vec::new();
+            match bars.merge_trade(ts, market_type, security_data.board, trade) {
+                UpdateAction::UpdateLast(_) => {
+                    if push_candlestick_mode == PushCandlestickMode::Realtime {
+                        push_bars.extend(bars.bars.last().map(|bar| (*bar, false)));
+                    }
+                }
+                UpdateAction::AppendNew { confirmed, .. } => {
+                    if confirmed.is_some() {
+                        push_bars.extend(bars.bars.iter().rev().nth(1).map(|bar| (*bar, true)));
+                    }
+                    if push_candlestick_mode == PushCandlestickMode::Realtime {
+                        push_bars.extend(bars.bars.last().map(|bar| (*bar, false)));
+                    }
+                }
+                UpdateAction::None => {}
+            }
+
+            for (candlestick, is_confirmed) in push_bars {
+                tracing::info!(
+                    symbol = symbol,
+                    bar_type = ?bar_type,
+                    is_confirmed = is_confirmed,
+                    candlestick = ?candlestick,
+                    "push bar"
+                );
+                push_tx.send(PushEvent {
+                    sequence: 0,
+                    symbol: symbol.to_string(),
+                    detail: PushEventDetail::Bar(PushBar {
+                        bar_type: *bar_type,
+                        candlestick,
+                        is_confirmed,
+                    }),
+                });
+            }
+        }
+    }
+}
+
+/// Updates the candlesticks, pushes the candlesticks updated in the
+/// subscribed trade sessions and returns them, including the unconfirmed
+/// updates which are not pushed in [`PushCandlestickMode::Confirmed`]
 #[allow(clippy::too_many_arguments)]
 fn update_and_push_candlestick(
     candlesticks: &mut Candlesticks,
     ts: TradeSessionType,
     ts1: TradeSession,
     symbol: &str,
-    period: Period,
     action: UpdateAction,
     push_candlestick_mode: PushCandlestickMode,
-    tx: &mut mpsc::UnboundedSender<PushEvent>,
-) {
-    let mut push_candlesticks = Vec::new();
+    tx: &mut PushSender,
+    push_detail: impl Fn(Candlestick, bool) -> PushEventDetail,
+) -> Vec<(Candlestick, bool)> {
+    let mut updated_candlesticks = Vec::new();
 
     match action {
         UpdateAction::UpdateLast(candlestick) => {
             let tail = candlesticks.tails.get_mut(&ts).unwrap();
             candlesticks.candlesticks[tail.index] = (candlestick, ts1).into();
             tail.candlestick = (candlestick, ts1).into();
-
-            if push_candlestick_mode == PushCandlestickMode::Realtime {
-                push_candlesticks.push(((candlestick, ts1).into(), false));
-            }
+            updated_candlesticks.push(((candlestick, ts1).into(), false));
         }
         UpdateAction::AppendNew { confirmed, new } => {
             let index = if let Some(tail) = candlesticks.tails.get_mut(&ts) {
@@ -1122,57 +2144,73 @@ fn update_and_push_candlestick(
 
             candlesticks.check_and_remove();
 
-            match push_candlestick_mode {
-                PushCandlestickMode::Realtime => {
-                    if let Some(confirmed) = confirmed {
-                        push_candlesticks.push(((confirmed, ts1).into(), true));
-                    }
-                    push_candlesticks.push(((new, ts1).into(), false));
-                }
-                PushCandlestickMode::Confirmed => {
-                    if let Some(confirmed) = confirmed {
-                        push_candlesticks.push(((confirmed, ts1).into(), true));
-                    }
-                }
+            if let Some(confirmed) = confirmed {
+                updated_candlesticks.push(((confirmed, ts1).into(), true));
             }
+            updated_candlesticks.push(((new, ts1).into(), false));
         }
         UpdateAction::None => {}
     };
 
-    for (candlestick, is_confirmed) in push_candlesticks {
-        if candlesticks.trade_sessions.contains(ts1) {
-            tracing::info!(
-                symbol = symbol,
-                period = ?period,
-                is_confirmed = is_confirmed,
-                candlestick = ?candlestick,
-                trade_session = ?ts,
-                "push candlestick"
-            );
-            let _ = tx.send(PushEvent {
+    if !candlesticks.trade_sessions.contains(ts1) {
+        return Vec::new();
+    }
+
+    for (candlestick, is_confirmed) in &updated_candlesticks {
+        if push_candlestick_mode == PushCandlestickMode::Confirmed && !is_confirmed {
+            continue;
+        }
+
+        let detail = push_detail(*candlestick, *is_confirmed);
+        tracing::info!(
+            symbol = symbol,
+            detail = ?detail,
+            trade_session = ?ts,
+            "push candlestick"
+        );
+        tx.send(PushEvent {
+            sequence: 0,
+            symbol: symbol.to_string(),
+            detail,
+        });
+    }
+
+    updated_candlesticks
+}
+
+/// Updates the indicators attached to the candlesticks and pushes the values
+fn update_and_push_indicators(
+    candlesticks: &mut Candlesticks,
+    period: Period,
+    symbol: &str,
+    updated_candlesticks: &[(Candlestick, bool)],
+    push_candlestick_mode: PushCandlestickMode,
+    tx: &mut PushSender,
+) {
+    for (candlestick, is_confirmed) in updated_candlesticks {
+        for (indicator, calculator) in &mut candlesticks.indicators {
+            let Some(value) = calculator.update(&(*candlestick).into(), *is_confirmed) else {
+                continue;
+            };
+            if push_candlestick_mode == PushCandlestickMode::Confirmed && !is_confirmed {
+                continue;
+            }
+
+            tx.send(PushEvent {
                 sequence: 0,
                 symbol: symbol.to_string(),
-                detail: PushEventDetail::Candlestick(PushCandlestick {
+                detail: PushEventDetail::Indicator(PushIndicator {
                     period,
-                    candlestick,
-                    is_confirmed,
+                    indicator: *indicator,
+                    timestamp: candlestick.timestamp,
+                    value,
+                    is_confirmed: *is_confirmed,
                 }),
             });
         }
     }
 }
 
-fn parse_market_from_symbol(symbol: &str) -> Option<Market> {
-    let market = symbol.rfind('.').map(|idx| &symbol[idx + 1..])?;
-    Some(match market {
-        "US" => Market::US,
-        "HK" => Market::HK,
-        "SG" => Market::SG,
-        "SH" | "SZ" => Market::CN,
-        _ => return None,
-    })
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+
+use futures_util::{stream, Stream, StreamExt};
+use time::{Date, OffsetDateTime, PrimitiveDateTime, Time};
+use time_tz::{OffsetDateTimeExt, Tz};
+
+use crate::{
+    quote::{
+        store::Markets, AdjustType, Candlestick, Period, QuoteApi, QuoteContext, SecurityBoard,
+        TradeSessions,
+    },
+    types::parse_market_from_symbol,
+    Error, Result,
+};
+
+/// The maximum number of candlesticks returned by a history request
+const MAX_PAGE_SIZE: usize = 1000;
+
+/// Historical candlestick paginator
+///
+/// Walks a date range with
+/// [`QuoteApi::history_candlesticks_by_offset`] page by page, the requests
+/// made with a [`QuoteContext`] are throttled by its rate limits. The
+/// candlesticks overlapping the previous pages are removed by their
+/// timestamps, and the paginator can be resumed from the
+/// [`checkpoint`](CandlestickPaginator::checkpoint) saved by a previous run.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use longport::{
+///     quote::{AdjustType, Period, QuoteContext, TradeSessions},
+///     Config,
+/// };
+/// use time::macros::date;
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let config = Arc::new(Config::from_env()?);
+/// let (ctx, _) = QuoteContext::try_new(config).await?;
+///
+/// let mut paginator = ctx.history_candlesticks_paginator(
+///     "700.HK",
+///     Period::OneMinute,
+///     AdjustType::NoAdjust,
+///     date!(2023 - 01 - 01),
+///     date!(2023 - 12 - 31),
+///     TradeSessions::Normal,
+/// );
+/// while let Some(candlesticks) = paginator.next_page().await? {
+///     println!("{:?} {:?}", candlesticks.len(), paginator.checkpoint());
+/// }
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// # });
+/// ```
+#[derive(Clone)]
+pub struct CandlestickPaginator<A = QuoteContext> {
+    api: A,
+    symbol: String,
+    period: Period,
+    adjust_type: AdjustType,
+    start: Date,
+    end: Date,
+    trade_sessions: TradeSessions,
+    page_size: usize,
+    checkpoint: Option<OffsetDateTime>,
+    finished: bool,
+}
+
+impl<A: QuoteApi> CandlestickPaginator<A> {
+    /// Create a paginator of the security history candlesticks in
+    /// `start..=end` requested with the quote API
+    pub fn new(
+        api: A,
+        symbol: impl Into<String>,
+        period: Period,
+        adjust_type: AdjustType,
+        start: Date,
+        end: Date,
+        trade_sessions: TradeSessions,
+    ) -> Self {
+        Self {
+            api,
+            symbol: symbol.into(),
+            period,
+            adjust_type,
+            start,
+            end,
+            trade_sessions,
+            page_size: MAX_PAGE_SIZE,
+            checkpoint: None,
+            finished: false,
+        }
+    }
+
+    /// Sets the number of candlesticks requested per page, up to `1000`
+    /// (Default: `1000`)
+    #[must_use]
+    pub fn page_size(self, page_size: usize) -> Self {
+        Self {
+            page_size: page_size.clamp(1, MAX_PAGE_SIZE),
+            ..self
+        }
+    }
+
+    /// Resumes from a [`checkpoint`](CandlestickPaginator::checkpoint), the
+    /// candlesticks at or before the checkpoint are skipped
+    #[must_use]
+    pub fn resume_from(self, checkpoint: OffsetDateTime) -> Self {
+        Self {
+            checkpoint: Some(checkpoint),
+            ..self
+        }
+    }
+
+    /// Returns the timestamp of the last candlestick returned
+    #[inline]
+    pub fn checkpoint(&self) -> Option<OffsetDateTime> {
+        self.checkpoint
+    }
+
+    /// Returns `true` if all the candlesticks in the date range have been
+    /// returned
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    fn timezone(&self) -> Result<&'static Tz> {
+        parse_market_from_symbol(&self.symbol)
+            .and_then(|market| {
+                Markets::default()
+                    .get(market, SecurityBoard::Unknown)
+                    .map(|market| market.timezone)
+            })
+            .ok_or_else(|| Error::UnknownMarket {
+                symbol: self.symbol.clone(),
+            })
+    }
+
+    /// Fetches the next page, returns `None` if all the candlesticks in the
+    /// date range have been returned
+    pub async fn next_page(&mut self) -> Result<Option<Vec<Candlestick>>> {
+        let timezone = self.timezone()?;
+
+        while !self.finished {
+            let cursor = match self.checkpoint {
+                Some(checkpoint) => {
+                    let time = checkpoint.to_timezone(timezone);
+                    PrimitiveDateTime::new(time.date(), time.time())
+                }
+                None => PrimitiveDateTime::new(self.start, Time::MIDNIGHT),
+            };
+            if cursor.date() > self.end {
+                self.finished = true;
+                break;
+            }
+
+            let resp = self
+                .api
+                .history_candlesticks_by_offset(
+                    self.symbol.clone(),
+                    self.period,
+                    self.adjust_type,
+                    true,
+                    Some(cursor),
+                    self.page_size,
+                    self.trade_sessions,
+                )
+                .await?;
+            let mut finished = resp.len() < self.page_size;
+            let mut candlesticks = Vec::with_capacity(resp.len());
+
+            for candlestick in resp {
+                let date = candlestick.timestamp.to_timezone(timezone).date();
+                if date > self.end {
+                    finished = true;
+                    break;
+                }
+                if date >= self.start
+                    && self
+                        .checkpoint
+                        .is_none_or(|checkpoint| candlestick.timestamp > checkpoint)
+                {
+                    self.checkpoint = Some(candlestick.timestamp);
+                    candlesticks.push(candlestick);
+                }
+            }
+
+            // the server returned the overlapped candlesticks only
+            self.finished = finished || candlesticks.is_empty();
+            if !candlesticks.is_empty() {
+                return Ok(Some(candlesticks));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Fetches all the remaining pages
+    pub async fn collect(mut self) -> Result<Vec<Candlestick>> {
+        let mut candlesticks = Vec::new();
+        while let Some(page) = self.next_page().await? {
+            candlesticks.extend(page);
+        }
+        Ok(candlesticks)
+    }
+
+    /// Converts to a stream of the pages
+    pub fn into_stream(self) -> impl Stream<Item = Result<Vec<Candlestick>>> + Send
+    where
+        A: 'static,
+    {
+        stream::try_unfold(self, |mut paginator| async move {
+            Ok(paginator.next_page().await?.map(|page| (page, paginator)))
+        })
+    }
+}
+
+/// Fetches the candlesticks of the symbols in a date range with at most
+/// `concurrency` symbols at a time
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn bulk_history_candlesticks<A: QuoteApi + Clone>(
+    api: &A,
+    symbols: Vec<String>,
+    period: Period,
+    adjust_type: AdjustType,
+    start: Date,
+    end: Date,
+    trade_sessions: TradeSessions,
+    concurrency: usize,
+) -> HashMap<String, Result<Vec<Candlestick>>> {
+    stream::iter(symbols)
+        .map(|symbol| {
+            let paginator = CandlestickPaginator::new(
+                api.clone(),
+                symbol.clone(),
+                period,
+                adjust_type,
+                start,
+                end,
+                trade_sessions,
+            );
+            async move { (symbol, paginator.collect().await) }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await
+}
@@ -1,29 +1,51 @@
 //! Quote related types
 
+mod adjust;
+mod api;
 mod cache;
+mod calendar;
+mod candlestick_cache;
 mod cmd_code;
 mod context;
 mod core;
+mod history;
+mod order_book;
 mod push_types;
+mod record;
+mod resample;
 mod store;
+mod stream;
 mod sub_flags;
+mod transform;
 mod types;
 mod utils;
 
+pub use adjust::{AdjustmentEvent, AdjustmentFactors};
+pub use api::QuoteApi;
+pub use calendar::TradingCalendar;
 pub use context::QuoteContext;
+pub use history::CandlestickPaginator;
+pub use longport_candlesticks::{BarType, CandlestickTransform, Indicator, IndicatorValue};
 pub use longport_proto::quote::{AdjustType, Period, TradeSession, TradeStatus};
+pub use order_book::{OrderBook, OrderBookLevel};
 pub use push_types::{
-    PushBrokers, PushCandlestick, PushDepth, PushEvent, PushEventDetail, PushQuote, PushTrades,
+    PushBar, PushBrokers, PushCandlestick, PushCustomCandlestick, PushDepth, PushEvent,
+    PushEventDetail, PushIndicator, PushQuote, PushResynced, PushSequenceGap, PushTrades,
 };
+pub use record::{PushRecord, PushRecordReader, PushReplayer, ReplaySpeed};
+pub use resample::Resampler;
+pub use stream::{OverflowPolicy, PushStream};
 pub use sub_flags::SubFlags;
+pub use transform::CandlestickTransformer;
 pub use types::{
     Brokers, CalcIndex, Candlestick, CapitalDistribution, CapitalDistributionResponse,
-    CapitalFlowLine, Depth, DerivativeType, FilterWarrantExpiryDate, FilterWarrantInOutBoundsType,
-    IntradayLine, IssuerInfo, MarketTradingDays, MarketTradingSession, OptionDirection,
-    OptionQuote, OptionType, ParticipantInfo, PrePostQuote, QuotePackageDetail, RealtimeQuote,
-    RequestCreateWatchlistGroup, RequestUpdateWatchlistGroup, SecuritiesUpdateMode, Security,
-    SecurityBoard, SecurityBrokers, SecurityCalcIndex, SecurityDepth, SecurityListCategory,
-    SecurityQuote, SecurityStaticInfo, SortOrderType, StrikePriceInfo, Subscription, Trade,
-    TradeDirection, TradeSessions, TradingSessionInfo, WarrantInfo, WarrantQuote, WarrantSortBy,
-    WarrantStatus, WarrantType, WatchlistGroup, WatchlistSecurity,
+    CapitalFlowLine, CustomPeriod, Depth, DerivativeType, FilterWarrantExpiryDate,
+    FilterWarrantInOutBoundsType, IntradayLine, IssuerInfo, MarketTradingDays,
+    MarketTradingSession, OptionDirection, OptionQuote, OptionType, ParticipantInfo, PrePostQuote,
+    QuotePackageDetail, RealtimeQuote, RequestCreateWatchlistGroup, RequestUpdateWatchlistGroup,
+    SecuritiesUpdateMode, Security, SecurityBoard, SecurityBrokers, SecurityCalcIndex,
+    SecurityDepth, SecurityListCategory, SecurityQuote, SecurityStaticInfo, SortOrderType,
+    StrikePriceInfo, Subscription, Trade, TradeDirection, TradeSessions, TradingSessionInfo,
+    WarrantInfo, WarrantQuote, WarrantSortBy, WarrantStatus, WarrantType, WatchlistGroup,
+    WatchlistSecurity,
 };
@@ -0,0 +1,207 @@
+use rust_decimal::Decimal;
+
+use crate::quote::{store::replace, Depth, PushDepth, SecurityDepth};
+
+/// Price level of an order book
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct OrderBookLevel {
+    /// Price
+    pub price: Decimal,
+    /// Volume
+    pub volume: i64,
+    /// Number of orders
+    pub order_num: i64,
+}
+
+/// Local order book of a security
+///
+/// Maintained from the [`PushDepth`] events of the subscribed securities,
+/// see [`QuoteContext::realtime_order_book`](crate::QuoteContext::realtime_order_book).
+///
+/// The levels are ordered from the best price, the empty levels are skipped.
+#[derive(Debug, Clone, Default)]
+pub struct OrderBook {
+    asks: Vec<Depth>,
+    bids: Vec<Depth>,
+}
+
+fn levels(depths: &[Depth]) -> impl Iterator<Item = OrderBookLevel> + '_ {
+    depths.iter().filter_map(|depth| {
+        let price = depth.price.filter(|price| !price.is_zero())?;
+        (depth.volume > 0).then_some(OrderBookLevel {
+            price,
+            volume: depth.volume,
+            order_num: depth.order_num,
+        })
+    })
+}
+
+fn vwap(levels: impl Iterator<Item = OrderBookLevel>, quantity: i64) -> Option<Decimal> {
+    if quantity <= 0 {
+        return None;
+    }
+
+    let mut remaining = quantity;
+    let mut amount = Decimal::ZERO;
+    for level in levels {
+        let volume = level.volume.min(remaining);
+        amount += level.price * Decimal::from(volume);
+        remaining -= volume;
+        if remaining == 0 {
+            return Some(amount / Decimal::from(quantity));
+        }
+    }
+    None
+}
+
+impl From<SecurityDepth> for OrderBook {
+    fn from(depth: SecurityDepth) -> Self {
+        let mut order_book = Self {
+            asks: depth.asks,
+            bids: depth.bids,
+        };
+        order_book.asks.sort_by_key(|depth| depth.position);
+        order_book.bids.sort_by_key(|depth| depth.position);
+        order_book
+    }
+}
+
+impl OrderBook {
+    /// Merges the changed levels of a depth push
+    pub(crate) fn merge(&mut self, depth: &PushDepth) {
+        replace(&mut self.asks, depth.asks.clone(), |v| v.position);
+        replace(&mut self.bids, depth.bids.clone(), |v| v.position);
+    }
+
+    /// Replaces all levels with a depth snapshot
+    pub(crate) fn reset(&mut self, depth: &PushDepth) {
+        self.asks.clone_from(&depth.asks);
+        self.bids.clone_from(&depth.bids);
+    }
+
+    pub(crate) fn to_security_depth(&self) -> SecurityDepth {
+        SecurityDepth {
+            asks: self.asks.clone(),
+            bids: self.bids.clone(),
+        }
+    }
+
+    /// Returns the ask levels, from the lowest price
+    pub fn asks(&self) -> impl Iterator<Item = OrderBookLevel> + '_ {
+        levels(&self.asks)
+    }
+
+    /// Returns the bid levels, from the highest price
+    pub fn bids(&self) -> impl Iterator<Item = OrderBookLevel> + '_ {
+        levels(&self.bids)
+    }
+
+    /// Returns `true` if there are no ask and bid levels
+    pub fn is_empty(&self) -> bool {
+        self.best_ask().is_none() && self.best_bid().is_none()
+    }
+
+    /// Returns the best ask level
+    #[inline]
+    pub fn best_ask(&self) -> Option<OrderBookLevel> {
+        self.asks().next()
+    }
+
+    /// Returns the best bid level
+    #[inline]
+    pub fn best_bid(&self) -> Option<OrderBookLevel> {
+        self.bids().next()
+    }
+
+    /// Returns the difference between the best ask price and the best bid
+    /// price
+    pub fn spread(&self) -> Option<Decimal> {
+        Some(self.best_ask()?.price - self.best_bid()?.price)
+    }
+
+    /// Returns the average of the best ask price and the best bid price
+    pub fn mid_price(&self) -> Option<Decimal> {
+        Some((self.best_ask()?.price + self.best_bid()?.price) / Decimal::TWO)
+    }
+
+    /// Returns the total volume of the best `levels` ask levels
+    pub fn ask_volume(&self, levels: usize) -> i64 {
+        self.asks().take(levels).map(|level| level.volume).sum()
+    }
+
+    /// Returns the total volume of the best `levels` bid levels
+    pub fn bid_volume(&self, levels: usize) -> i64 {
+        self.bids().take(levels).map(|level| level.volume).sum()
+    }
+
+    /// Returns the volume imbalance of the best `levels` levels
+    ///
+    /// `(bid_volume - ask_volume) / (bid_volume + ask_volume)`, from `-1`
+    /// (asks only) to `1` (bids only).
+    pub fn imbalance(&self, levels: usize) -> Option<Decimal> {
+        let bid_volume = self.bid_volume(levels);
+        let ask_volume = self.ask_volume(levels);
+        let total = bid_volume + ask_volume;
+        (total > 0).then(|| Decimal::from(bid_volume - ask_volume) / Decimal::from(total))
+    }
+
+    /// Returns the volume weighted average price to buy `quantity` from the
+    /// ask levels, or `None` if the ask levels are not deep enough
+    pub fn vwap_to_buy(&self, quantity: i64) -> Option<Decimal> {
+        vwap(self.asks(), quantity)
+    }
+
+    /// Returns the volume weighted average price to sell `quantity` to the
+    /// bid levels, or `None` if the bid levels are not deep enough
+    pub fn vwap_to_sell(&self, quantity: i64) -> Option<Decimal> {
+        vwap(self.bids(), quantity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn depth(position: i32, price: i64, volume: i64) -> Depth {
+        Depth {
+            position,
+            price: Some(price.into()),
+            volume,
+            order_num: 1,
+        }
+    }
+
+    #[test]
+    fn test_order_book() {
+        let mut book = OrderBook::default();
+        assert!(book.is_empty());
+        assert_eq!(book.spread(), None);
+
+        book.reset(&PushDepth {
+            asks: vec![depth(1, 101, 100), depth(2, 102, 200), depth(3, 103, 300)],
+            bids: vec![depth(1, 99, 400), depth(2, 98, 500)],
+        });
+        assert_eq!(book.spread(), Some(2.into()));
+        assert_eq!(book.mid_price(), Some(100.into()));
+        assert_eq!(book.ask_volume(2), 300);
+        assert_eq!(book.bid_volume(10), 900);
+        assert_eq!(book.imbalance(2), Some(Decimal::new(5, 1)));
+        assert_eq!(book.vwap_to_buy(200), Some(Decimal::new(1015, 1)));
+        assert_eq!(book.vwap_to_buy(1000), None);
+        assert_eq!(book.vwap_to_sell(400), Some(99.into()));
+
+        // the best ask level is emptied
+        book.merge(&PushDepth {
+            asks: vec![Depth {
+                position: 1,
+                price: None,
+                volume: 0,
+                order_num: 0,
+            }],
+            bids: vec![],
+        });
+        assert_eq!(book.best_ask().map(|level| level.price), Some(102.into()));
+        assert_eq!(book.spread(), Some(3.into()));
+        assert_eq!(book.to_security_depth().asks.len(), 3);
+    }
+}
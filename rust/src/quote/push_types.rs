@@ -1,10 +1,11 @@
+use longport_candlesticks::{BarType, Indicator, IndicatorValue};
 use longport_proto::quote::{self, Period, PushQuoteTag, TradeSession, TradeStatus};
 use prost::Message;
 use rust_decimal::Decimal;
 use time::OffsetDateTime;
 
 use crate::{
-    quote::{cmd_code, Brokers, Candlestick, Depth, Trade},
+    quote::{cmd_code, Brokers, Candlestick, CustomPeriod, Depth, SubFlags, Trade},
     Error, Result,
 };
 
@@ -54,7 +55,7 @@ impl Default for PushQuote {
 }
 
 /// Depth message
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PushDepth {
     /// Ask depth
     pub asks: Vec<Depth>,
@@ -63,7 +64,7 @@ pub struct PushDepth {
 }
 
 /// Brokers message
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PushBrokers {
     /// Ask brokers
     pub ask_brokers: Vec<Brokers>,
@@ -72,7 +73,7 @@ pub struct PushBrokers {
 }
 
 /// Trades message
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PushTrades {
     /// Trades data
     pub trades: Vec<Trade>,
@@ -89,8 +90,82 @@ pub struct PushCandlestick {
     pub is_confirmed: bool,
 }
 
+/// Custom period candlestick updated message
+///
+/// Pushed for the candlesticks subscribed with
+/// [`QuoteContext::subscribe_custom_candlesticks`](crate::QuoteContext::subscribe_custom_candlesticks).
+#[derive(Debug, Copy, Clone)]
+pub struct PushCustomCandlestick {
+    /// Custom period
+    pub period: CustomPeriod,
+    /// Candlestick
+    pub candlestick: Candlestick,
+    /// Is confirmed
+    pub is_confirmed: bool,
+}
+
+/// Bar updated message
+///
+/// Pushed for the bars subscribed with
+/// [`QuoteContext::subscribe_bars`](crate::QuoteContext::subscribe_bars).
+#[derive(Debug, Copy, Clone)]
+pub struct PushBar {
+    /// Bar type
+    pub bar_type: BarType,
+    /// Bar
+    pub candlestick: Candlestick,
+    /// Is confirmed
+    pub is_confirmed: bool,
+}
+
+/// Indicator updated message
+///
+/// Pushed for the indicators attached with
+/// [`QuoteContext::attach_indicator`](crate::QuoteContext::attach_indicator).
+#[derive(Debug, Copy, Clone)]
+pub struct PushIndicator {
+    /// Period type
+    pub period: Period,
+    /// Indicator
+    pub indicator: Indicator,
+    /// Time of the candlestick
+    pub timestamp: OffsetDateTime,
+    /// Value
+    pub value: IndicatorValue,
+    /// Is confirmed
+    pub is_confirmed: bool,
+}
+
+/// Sequence gap message
+///
+/// Sent when the sequence of the pushes of a subscription type is not
+/// continuous, which means that some pushes may have been missed (for example
+/// during a reconnection).
+#[derive(Debug, Copy, Clone)]
+pub struct PushSequenceGap {
+    /// Subscription type
+    pub sub_type: SubFlags,
+    /// The last sequence received before the gap
+    pub last_sequence: i64,
+    /// The sequence which revealed the gap
+    pub sequence: i64,
+}
+
+/// Resynced message
+///
+/// Sent after the realtime data has been re-fetched from the server because
+/// of a sequence gap, the fresh snapshots are pushed as [`PushQuote`],
+/// [`PushDepth`] and [`PushBrokers`] before this message.
+///
+/// Requires [`Config::enable_push_resync`](crate::Config::enable_push_resync).
+#[derive(Debug, Copy, Clone)]
+pub struct PushResynced {
+    /// Subscription types which have been resynced
+    pub sub_types: SubFlags,
+}
+
 /// Push event detail
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum PushEventDetail {
     /// Quote
     Quote(PushQuote),
@@ -102,13 +177,42 @@ pub enum PushEventDetail {
     Trade(PushTrades),
     /// Candlestick
     Candlestick(PushCandlestick),
+    /// Custom period candlestick
+    CustomCandlestick(PushCustomCandlestick),
+    /// Bar
+    Bar(PushBar),
+    /// Indicator
+    Indicator(PushIndicator),
+    /// Sequence gap
+    SequenceGap(PushSequenceGap),
+    /// Resynced
+    Resynced(PushResynced),
+}
+
+impl PushEventDetail {
+    /// Returns the subscription type of the pushes which are sequenced by the
+    /// server
+    pub(crate) fn sequenced_sub_type(&self) -> Option<SubFlags> {
+        match self {
+            PushEventDetail::Quote(_) => Some(SubFlags::QUOTE),
+            PushEventDetail::Depth(_) => Some(SubFlags::DEPTH),
+            PushEventDetail::Brokers(_) => Some(SubFlags::BROKER),
+            PushEventDetail::Trade(_) => Some(SubFlags::TRADE),
+            PushEventDetail::Candlestick(_)
+            | PushEventDetail::CustomCandlestick(_)
+            | PushEventDetail::Bar(_)
+            | PushEventDetail::Indicator(_)
+            | PushEventDetail::SequenceGap(_)
+            | PushEventDetail::Resynced(_) => None,
+        }
+    }
 }
 
 /// Push event
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PushEvent {
-    #[allow(dead_code)]
-    pub(crate) sequence: i64,
+    /// Push sequence, `0` for the events generated by the SDK
+    pub sequence: i64,
     /// Security code
     pub symbol: String,
     /// Event detail
@@ -0,0 +1,503 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::Path,
+    sync::mpsc::{self as std_mpsc, TryRecvError},
+    time::{Duration, Instant},
+};
+
+use longport_candlesticks::BarType;
+use longport_proto::quote::{Period, PushQuoteTag};
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+
+use crate::{
+    config::PushCandlestickMode,
+    quote::{
+        core::{
+            merge_bars_by_trades, merge_candlesticks_by_quote, merge_candlesticks_by_trades,
+            TradingDays,
+        },
+        store::{Bars, Candlesticks, Store},
+        stream::PushSender,
+        CustomPeriod, PushEvent, PushEventDetail, SecurityBoard, TradeSessions,
+    },
+    types::parse_market_from_symbol,
+    Result,
+};
+
+/// File magic followed by the format version
+const MAGIC: &[u8; 8] = b"LPPUSH\x00\x01";
+
+/// A raw quote push message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PushRecord {
+    /// Command code
+    pub command_code: u8,
+    /// Protobuf encoded body
+    pub body: Vec<u8>,
+    /// The time the message was received
+    pub received_at: OffsetDateTime,
+}
+
+impl PushRecord {
+    fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        let micros = (self.received_at.unix_timestamp_nanos() / 1000) as i64;
+        writer.write_all(&micros.to_be_bytes())?;
+        writer.write_all(&[self.command_code])?;
+        writer.write_all(&(self.body.len() as u32).to_be_bytes())?;
+        writer.write_all(&self.body)
+    }
+
+    fn read_from(reader: &mut impl Read) -> io::Result<Option<Self>> {
+        let mut header = [0; 13];
+        match reader.read_exact(&mut header) {
+            Ok(()) => {}
+            // the last record may be truncated if the recording process was killed
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err),
+        }
+
+        let micros = i64::from_be_bytes(header[..8].try_into().unwrap());
+        let command_code = header[8];
+        let len = u32::from_be_bytes(header[9..].try_into().unwrap());
+        let mut body = vec![0; len as usize];
+        match reader.read_exact(&mut body) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err),
+        }
+
+        let received_at = OffsetDateTime::from_unix_timestamp_nanos(micros as i128 * 1000)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        Ok(Some(PushRecord {
+            command_code,
+            body,
+            received_at,
+        }))
+    }
+}
+
+/// Appends the raw push messages to a file in a background thread
+pub(crate) struct PushRecorder {
+    tx: std_mpsc::Sender<PushRecord>,
+}
+
+impl PushRecorder {
+    pub(crate) fn create(path: &Path) -> io::Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .create(true)
+            .append(true)
+            .open(path)?;
+        if file.metadata()?.len() == 0 {
+            file.write_all(MAGIC)?;
+        } else {
+            read_magic(&mut file)?;
+            file.seek(SeekFrom::End(0))?;
+        }
+
+        let (tx, rx) = std_mpsc::channel::<PushRecord>();
+        let mut writer = BufWriter::new(file);
+
+        std::thread::Builder::new()
+            .name("push-recorder".to_string())
+            .spawn(move || {
+                let res = (|| loop {
+                    let record = match rx.try_recv() {
+                        Ok(record) => record,
+                        Err(TryRecvError::Empty) => {
+                            // flush when there are no pending records
+                            writer.flush()?;
+                            match rx.recv() {
+                                Ok(record) => record,
+                                Err(_) => return writer.flush(),
+                            }
+                        }
+                        Err(TryRecvError::Disconnected) => return writer.flush(),
+                    };
+                    record.write_to(&mut writer)?;
+                })();
+                if let Err(err) = res {
+                    tracing::error!(error = %err, "failed to record push messages");
+                }
+            })?;
+
+        Ok(Self { tx })
+    }
+
+    pub(crate) fn record(&self, command_code: u8, body: &[u8]) {
+        let _ = self.tx.send(PushRecord {
+            command_code,
+            body: body.to_vec(),
+            received_at: OffsetDateTime::now_utc(),
+        });
+    }
+}
+
+fn read_magic(reader: &mut impl Read) -> io::Result<()> {
+    let mut magic = [0; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not a push record file",
+        ));
+    }
+    Ok(())
+}
+
+/// Reads the push messages recorded with
+/// [`Config::push_record_path`](crate::Config::push_record_path)
+///
+/// # Examples
+///
+/// ```no_run
+/// use longport::quote::PushRecordReader;
+///
+/// for record in PushRecordReader::open("pushes.bin")? {
+///     let record = record?;
+///     println!("{} {}", record.received_at, record.command_code);
+/// }
+/// # Ok::<_, longport::Error>(())
+/// ```
+pub struct PushRecordReader {
+    reader: BufReader<File>,
+}
+
+impl PushRecordReader {
+    /// Open a recorded file
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        read_magic(&mut reader)?;
+        Ok(Self { reader })
+    }
+}
+
+impl Iterator for PushRecordReader {
+    type Item = Result<PushRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        PushRecord::read_from(&mut self.reader)
+            .map_err(Into::into)
+            .transpose()
+    }
+}
+
+/// Replay speed
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ReplaySpeed {
+    /// Keep the original intervals between the messages
+    RealTime,
+    /// Divide the original intervals by the factor
+    Accelerated(f64),
+    /// Replay as fast as possible
+    Unlimited,
+}
+
+/// Replays the push messages recorded with
+/// [`Config::push_record_path`](crate::Config::push_record_path)
+///
+/// The messages are processed in the same way as
+/// [`QuoteContext`](crate::QuoteContext) does, including merging the
+/// candlesticks for the subscribed periods. The replay has no access to the
+/// trading calendar, so half trading days are treated as normal trading days.
+///
+/// # Examples
+///
+/// ```no_run
+/// use longport::quote::{Period, PushReplayer, ReplaySpeed, TradeSessions};
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let mut receiver = PushReplayer::open("pushes.bin")?
+///     .speed(ReplaySpeed::Accelerated(60.0))
+///     .subscribe_candlesticks("700.HK", Period::OneMinute, TradeSessions::Normal)
+///     .start();
+/// while let Some(event) = receiver.recv().await {
+///     println!("{:?}", event);
+/// }
+/// # Ok::<_, longport::Error>(())
+/// # });
+/// ```
+pub struct PushReplayer {
+    reader: PushRecordReader,
+    speed: ReplaySpeed,
+    push_candlestick_mode: PushCandlestickMode,
+    candlesticks: Vec<(String, Period, TradeSessions)>,
+    custom_candlesticks: Vec<(String, CustomPeriod, TradeSessions)>,
+    bars: Vec<(String, BarType, TradeSessions)>,
+}
+
+impl PushReplayer {
+    /// Open a recorded file
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self {
+            reader: PushRecordReader::open(path)?,
+            speed: ReplaySpeed::RealTime,
+            push_candlestick_mode: PushCandlestickMode::default(),
+            candlesticks: Vec::new(),
+            custom_candlesticks: Vec::new(),
+            bars: Vec::new(),
+        })
+    }
+
+    /// Specifies the replay speed
+    ///
+    /// Default: `ReplaySpeed::RealTime`
+    #[must_use]
+    pub fn speed(self, speed: ReplaySpeed) -> Self {
+        Self { speed, ..self }
+    }
+
+    /// Specifies the push candlestick mode
+    ///
+    /// Default: `PushCandlestickMode::Realtime`
+    #[must_use]
+    pub fn push_candlestick_mode(self, mode: PushCandlestickMode) -> Self {
+        Self {
+            push_candlestick_mode: mode,
+            ..self
+        }
+    }
+
+    /// Merge the replayed quotes and trades into the candlesticks of the
+    /// `symbol` and `period`, starting with no history candlesticks
+    #[must_use]
+    pub fn subscribe_candlesticks(
+        mut self,
+        symbol: impl Into<String>,
+        period: Period,
+        trade_sessions: TradeSessions,
+    ) -> Self {
+        self.candlesticks
+            .push((symbol.into(), period, trade_sessions));
+        self
+    }
+
+    /// Merge the replayed trades into the candlesticks of the `symbol` and
+    /// custom `period`, starting with no history candlesticks
+    #[must_use]
+    pub fn subscribe_custom_candlesticks(
+        mut self,
+        symbol: impl Into<String>,
+        period: CustomPeriod,
+        trade_sessions: TradeSessions,
+    ) -> Self {
+        self.custom_candlesticks
+            .push((symbol.into(), period, trade_sessions));
+        self
+    }
+
+    /// Merge the replayed trades into the bars of the `symbol` and
+    /// `bar_type`
+    #[must_use]
+    pub fn subscribe_bars(
+        mut self,
+        symbol: impl Into<String>,
+        bar_type: BarType,
+        trade_sessions: TradeSessions,
+    ) -> Self {
+        self.bars.push((symbol.into(), bar_type, trade_sessions));
+        self
+    }
+
+    /// Start replaying in a background thread, and returns the receiver of
+    /// the push events
+    pub fn start(self) -> mpsc::UnboundedReceiver<PushEvent> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        std::thread::spawn(move || self.run(tx));
+        rx
+    }
+
+    fn run(self, tx: mpsc::UnboundedSender<PushEvent>) {
+        let mut tx = PushSender::new(tx);
+        let mut store = Store::default();
+        let trading_days = TradingDays::default();
+
+        for (symbol, period, trade_sessions) in self.candlesticks {
+            store
+                .securities
+                .entry(symbol)
+                .or_default()
+                .candlesticks
+                .insert(
+                    period,
+                    Candlesticks {
+                        trade_sessions,
+                        candlesticks: Vec::new(),
+                        tails: Default::default(),
+                        indicators: Default::default(),
+                    },
+                );
+        }
+
+        for (symbol, period, trade_sessions) in self.custom_candlesticks {
+            store
+                .securities
+                .entry(symbol)
+                .or_default()
+                .custom_candlesticks
+                .insert(
+                    period,
+                    Candlesticks {
+                        trade_sessions,
+                        candlesticks: Vec::new(),
+                        tails: Default::default(),
+                        indicators: Default::default(),
+                    },
+                );
+        }
+
+        for (symbol, bar_type, trade_sessions) in self.bars {
+            let lot_size = parse_market_from_symbol(&symbol)
+                .and_then(|market| store.markets.get(market, SecurityBoard::Unknown))
+                .map(|market| market.lot_size)
+                .unwrap_or(1);
+            store
+                .securities
+                .entry(symbol)
+                .or_default()
+                .bars
+                .insert(bar_type, Bars::new(bar_type, lot_size, trade_sessions));
+        }
+
+        let mut start: Option<(Instant, OffsetDateTime)> = None;
+
+        for record in self.reader {
+            let record = match record {
+                Ok(record) => record,
+                Err(err) => {
+                    tracing::error!(error = %err, "failed to read push record");
+                    return;
+                }
+            };
+
+            let (start_instant, start_time) =
+                *start.get_or_insert_with(|| (Instant::now(), record.received_at));
+            let offset = Duration::try_from(record.received_at - start_time).unwrap_or_default();
+            let offset = match self.speed {
+                ReplaySpeed::RealTime => Some(offset),
+                ReplaySpeed::Accelerated(factor) if factor > 0.0 => Some(offset.div_f64(factor)),
+                ReplaySpeed::Accelerated(_) | ReplaySpeed::Unlimited => None,
+            };
+            if let Some(offset) = offset {
+                let deadline = start_instant + offset;
+                let now = Instant::now();
+                if deadline > now {
+                    std::thread::sleep(deadline - now);
+                }
+            }
+
+            let (mut event, tag) = match PushEvent::parse(record.command_code, &record.body) {
+                Ok(res) => res,
+                Err(err) => {
+                    tracing::error!(error = %err, "failed to parse push message");
+                    continue;
+                }
+            };
+
+            if let Some(gap) = store.check_sequence(&event) {
+                tx.send(PushEvent {
+                    sequence: 0,
+                    symbol: event.symbol.clone(),
+                    detail: PushEventDetail::SequenceGap(gap),
+                });
+            }
+
+            if tag != Some(PushQuoteTag::Eod) {
+                store.handle_push(&mut event);
+            }
+
+            match &event.detail {
+                PushEventDetail::Quote(push_quote) => merge_candlesticks_by_quote(
+                    &mut store,
+                    &trading_days,
+                    self.push_candlestick_mode,
+                    &mut tx,
+                    &event.symbol,
+                    push_quote,
+                ),
+                PushEventDetail::Trade(trades) => {
+                    merge_candlesticks_by_trades(
+                        &mut store,
+                        &trading_days,
+                        self.push_candlestick_mode,
+                        &mut tx,
+                        &event.symbol,
+                        trades,
+                    );
+                    merge_bars_by_trades(
+                        &mut store,
+                        self.push_candlestick_mode,
+                        &mut tx,
+                        &event.symbol,
+                        trades,
+                    );
+                }
+                _ => {}
+            }
+
+            if tag != Some(PushQuoteTag::Eod) {
+                tx.send(event);
+            }
+            if tx.is_closed() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prost::Message;
+
+    use super::*;
+
+    #[test]
+    fn test_record_and_replay() {
+        let path = std::env::temp_dir().join(format!("longport-push-{}.bin", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let recorder = PushRecorder::create(&path).unwrap();
+        for (sequence, last_done) in [(1, "1.5"), (2, "1.6")] {
+            let body = longport_proto::quote::PushQuote {
+                symbol: "700.HK".to_string(),
+                sequence,
+                last_done: last_done.to_string(),
+                ..Default::default()
+            }
+            .encode_to_vec();
+            recorder.record(101, &body);
+        }
+        drop(recorder);
+
+        // wait for the background thread
+        let mut records = Vec::new();
+        for _ in 0..100 {
+            records = PushRecordReader::open(&path)
+                .unwrap()
+                .collect::<Result<Vec<_>>>()
+                .unwrap();
+            if records.len() == 2 {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(records.len(), 2);
+        assert!(records.iter().all(|record| record.command_code == 101));
+
+        let mut rx = PushReplayer::open(&path)
+            .unwrap()
+            .speed(ReplaySpeed::Unlimited)
+            .start();
+        let mut last_done = Vec::new();
+        while let Some(event) = rx.blocking_recv() {
+            if let PushEventDetail::Quote(quote) = event.detail {
+                last_done.push(quote.last_done.to_string());
+            }
+        }
+        assert_eq!(last_done, vec!["1.5", "1.6"]);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::HashSet;
+
+use time::Date;
+
+use crate::{
+    quote::{
+        store::{convert_period, trade_update_fields, Markets},
+        utils::{convert_trade_session, convert_trade_session_type},
+        Candlestick, Period, SecurityBoard, Trade,
+    },
+    types::parse_market_from_symbol,
+    Market,
+};
+
+/// Offline candlestick resampler
+///
+/// Resamples the trades or the candlesticks of a shorter period to the
+/// candlesticks of any period, with the same trade sessions, half trading days
+/// and trade type rules used to merge the candlesticks of the realtime pushes,
+/// so that the back-filled history matches the pushed candlesticks.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use longport::{
+///     quote::{Period, QuoteContext, Resampler, SecurityBoard, TradeSessions},
+///     Config,
+/// };
+/// use time::macros::date;
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let config = Arc::new(Config::from_env()?);
+/// let (ctx, _) = QuoteContext::try_new(config).await?;
+///
+/// let candlesticks = ctx
+///     .history_candlesticks_by_date(
+///         "700.HK",
+///         Period::OneMinute,
+///         longport::quote::AdjustType::NoAdjust,
+///         Some(date!(2024 - 01 - 02)),
+///         Some(date!(2024 - 01 - 02)),
+///         TradeSessions::Normal,
+///     )
+///     .await?;
+/// let resampler = Resampler::new("700.HK", SecurityBoard::HKEquity).unwrap();
+/// let resp = resampler.resample_candlesticks(&candlesticks, Period::FifteenMinute);
+/// println!("{:?}", resp);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct Resampler {
+    market_type: Market,
+    board: SecurityBoard,
+    market: longport_candlesticks::Market,
+    half_days: HashSet<Date>,
+}
+
+impl Resampler {
+    /// Create a `Resampler` for the security with the built-in trade sessions
+    /// of its market, returns `None` if the market of the symbol is unknown
+    pub fn new(symbol: &str, board: SecurityBoard) -> Option<Self> {
+        let market_type = parse_market_from_symbol(symbol)?;
+        let market = Markets::default().get(market_type, board)?.clone();
+        Some(Self {
+            market_type,
+            board,
+            market,
+            half_days: HashSet::new(),
+        })
+    }
+
+    /// Replaces the market definition, for example with
+    /// [`MarketTradingSession::candlestick_market`](crate::quote::MarketTradingSession::candlestick_market)
+    #[must_use]
+    pub fn market(self, market: longport_candlesticks::Market) -> Self {
+        Self { market, ..self }
+    }
+
+    /// Specifies the half trading days, for example
+    /// [`MarketTradingDays::half_trading_days`](crate::quote::MarketTradingDays::half_trading_days)
+    #[must_use]
+    pub fn half_trading_days(self, days: impl IntoIterator<Item = Date>) -> Self {
+        Self {
+            half_days: days.into_iter().collect(),
+            ..self
+        }
+    }
+
+    /// Resamples the trades to the candlesticks of the period
+    ///
+    /// The trades must be sorted by time.
+    pub fn resample_trades(&self, trades: &[Trade], period: Period) -> Vec<Candlestick> {
+        if period == Period::UnknownPeriod {
+            return vec![];
+        }
+
+        let trades = trades.iter().map(|trade| {
+            let ts = convert_trade_session(trade.trade_session);
+            (
+                ts,
+                longport_candlesticks::Trade {
+                    time: trade.timestamp,
+                    price: trade.price,
+                    volume: trade.volume,
+                    update_fields: trade_update_fields(
+                        ts,
+                        self.market_type,
+                        self.board,
+                        &trade.trade_type,
+                    ),
+                },
+            )
+        });
+        self.market
+            .resample_trades(&self.half_days, convert_period(period), trades)
+            .into_iter()
+            .map(|(ts, candlestick)| (candlestick, convert_trade_session_type(ts)).into())
+            .collect()
+    }
+
+    /// Resamples the candlesticks of a shorter period, such as the `1` minute
+    /// history candlesticks, to the candlesticks of the period
+    ///
+    /// The candlesticks must be sorted by time.
+    pub fn resample_candlesticks(
+        &self,
+        candlesticks: &[Candlestick],
+        period: Period,
+    ) -> Vec<Candlestick> {
+        if period == Period::UnknownPeriod {
+            return vec![];
+        }
+
+        self.market
+            .resample_candlesticks(
+                &self.half_days,
+                convert_period(period),
+                candlesticks.iter().map(|candlestick| (*candlestick).into()),
+            )
+            .into_iter()
+            .map(|(ts, candlestick)| (candlestick, convert_trade_session_type(ts)).into())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+    use time::macros::datetime;
+
+    use super::*;
+    use crate::quote::{TradeDirection, TradeSession};
+
+    fn trade(timestamp: time::OffsetDateTime, price: i64, trade_type: &str) -> Trade {
+        Trade {
+            price: price.into(),
+            volume: 100,
+            timestamp,
+            trade_type: trade_type.to_string(),
+            direction: TradeDirection::Neutral,
+            trade_session: TradeSession::NormalTrade,
+        }
+    }
+
+    #[test]
+    fn test_resample_trades() {
+        let resampler = Resampler::new("700.HK", SecurityBoard::HKEquity).unwrap();
+        let trades = [
+            trade(datetime!(2024-1-2 9:30:10 +8), 10, ""),
+            // only the volume of the odd-lot trades is merged
+            trade(datetime!(2024-1-2 9:30:20 +8), 50, "D"),
+            trade(datetime!(2024-1-2 9:35:00 +8), 11, ""),
+        ];
+
+        let res = resampler.resample_trades(&trades, Period::FiveMinute);
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[0].timestamp, datetime!(2024-1-2 1:30:00 UTC));
+        assert_eq!(res[0].high, Decimal::from(10));
+        assert_eq!(res[0].volume, 200);
+        assert_eq!(res[0].trade_session, TradeSession::NormalTrade);
+
+        let res = resampler.resample_candlesticks(&res, Period::Day);
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].open, Decimal::from(10));
+        assert_eq!(res[0].close, Decimal::from(11));
+        assert_eq!(res[0].volume, 300);
+    }
+}
@@ -1,15 +1,17 @@
 use std::collections::HashMap;
 
 use longport_candlesticks::{
-    Days, TradeSessionType, UpdateAction, UpdateFields, TRADE_SESSION_POST, TRADE_SESSION_PRE,
+    BarBuilder, BarType, Days, Indicator, IndicatorCalculator, TradeSessionType, UpdateAction,
+    UpdateFields, TRADE_SESSION_POST, TRADE_SESSION_PRE,
 };
 use longport_proto::quote::Period;
 
 use crate::{
     quote::{
         push_types::{PushEventDetail, PushQuote},
-        Brokers, Candlestick, Depth, PushBrokers, PushDepth, PushEvent, PushTrades, SecurityBoard,
-        Trade, TradeSessions,
+        Brokers, Candlestick, CustomPeriod, MarketTradingSession, OrderBook, PushBrokers,
+        PushDepth, PushEvent, PushSequenceGap, PushTrades, SecurityBoard, SubFlags, Trade,
+        TradeSessions,
     },
     Market,
 };
@@ -48,6 +50,7 @@ pub(crate) struct Candlesticks {
     pub(crate) trade_sessions: TradeSessions,
     pub(crate) candlesticks: Vec<Candlestick>,
     pub(crate) tails: HashMap<TradeSessionType, TailCandlestick>,
+    pub(crate) indicators: HashMap<Indicator, IndicatorCalculator>,
 }
 
 impl Candlesticks {
@@ -73,63 +76,21 @@ impl Candlesticks {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn merge_trade<H>(
         &mut self,
         ts: TradeSessionType,
+        market: &longport_candlesticks::Market,
         market_type: Market,
         half_days: H,
         board: SecurityBoard,
-        period: Period,
+        period: longport_candlesticks::Period,
         trade: &Trade,
     ) -> UpdateAction
     where
         H: Days,
     {
-        let Some(market) = get_market(market_type, board) else {
-            return UpdateAction::None;
-        };
-        let period = convert_period(period);
-
-        let trade_type = trade.trade_type.as_str();
-        let update_fields = match market_type {
-            Market::Unknown => unreachable!(),
-            Market::HK => match trade_type {
-                "" => UpdateFields::all(),
-                "D" => UpdateFields::VOLUME,
-                "M" => UpdateFields::VOLUME,
-                "P" => UpdateFields::VOLUME,
-                "U" => UpdateFields::all(),
-                "X" => UpdateFields::VOLUME,
-                "Y" => UpdateFields::VOLUME,
-                _ => UpdateFields::empty(),
-            },
-            Market::US => match trade_type {
-                "" => UpdateFields::all(),
-                "A" => UpdateFields::all(),
-                "B" => UpdateFields::all(),
-                "C" => UpdateFields::VOLUME,
-                "D" => UpdateFields::all(),
-                "E" => UpdateFields::all(),
-                "F" => UpdateFields::all(),
-                "G" => UpdateFields::VOLUME,
-                "H" => UpdateFields::VOLUME,
-                "I" if board == SecurityBoard::USOption || board == SecurityBoard::USOptionS => {
-                    UpdateFields::all()
-                }
-                "I" if ts == TRADE_SESSION_PRE || ts == TRADE_SESSION_POST => UpdateFields::all(),
-                "I" => UpdateFields::VOLUME,
-                "K" => UpdateFields::all(),
-                "M" => UpdateFields::empty(),
-                "P" => UpdateFields::empty(),
-                "S" => UpdateFields::all(),
-                "V" => UpdateFields::VOLUME,
-                "W" => UpdateFields::VOLUME,
-                "X" => UpdateFields::all(),
-                "1" => UpdateFields::all(),
-                _ => UpdateFields::empty(),
-            },
-            Market::CN | Market::SG => UpdateFields::all(),
-        };
+        let update_fields = trade_update_fields(ts, market_type, board, trade.trade_type.as_str());
 
         market.merge_trade(
             ts,
@@ -148,9 +109,8 @@ impl Candlesticks {
     pub(crate) fn merge_quote<H>(
         &mut self,
         ts: TradeSessionType,
-        market_type: Market,
+        market: &longport_candlesticks::Market,
         half_days: H,
-        board: SecurityBoard,
         period: Period,
         push_quote: &PushQuote,
     ) -> UpdateAction
@@ -159,9 +119,6 @@ impl Candlesticks {
     {
         debug_assert!(period == Period::Day);
 
-        let Some(market) = get_market(market_type, board) else {
-            return UpdateAction::None;
-        };
         let period = convert_period(period);
 
         market.merge_quote(
@@ -205,12 +162,67 @@ impl Candlesticks {
     }
 }
 
+#[derive(Debug)]
+pub(crate) struct Bars {
+    pub(crate) trade_sessions: TradeSessions,
+    pub(crate) builder: BarBuilder,
+    pub(crate) bars: Vec<Candlestick>,
+}
+
+impl Bars {
+    pub(crate) fn new(bar_type: BarType, lot_size: i64, trade_sessions: TradeSessions) -> Self {
+        Self {
+            trade_sessions,
+            builder: BarBuilder::new(bar_type, lot_size),
+            bars: Vec::new(),
+        }
+    }
+
+    pub(crate) fn merge_trade(
+        &mut self,
+        ts: TradeSessionType,
+        market_type: Market,
+        board: SecurityBoard,
+        trade: &Trade,
+    ) -> UpdateAction {
+        let update_fields = trade_update_fields(ts, market_type, board, trade.trade_type.as_str());
+        let action = self.builder.merge_trade(&longport_candlesticks::Trade {
+            time: trade.timestamp,
+            price: trade.price,
+            volume: trade.volume,
+            update_fields,
+        });
+
+        match action {
+            UpdateAction::UpdateLast(candlestick) => {
+                if let Some(last) = self.bars.last_mut() {
+                    *last = (candlestick, last.trade_session).into();
+                }
+            }
+            UpdateAction::AppendNew { new, .. } => {
+                self.bars.push((new, trade.trade_session).into());
+                if self.bars.len() > MAX_CANDLESTICKS * 2 {
+                    self.bars.drain(..self.bars.len() - MAX_CANDLESTICKS);
+                }
+            }
+            UpdateAction::None => {}
+        }
+
+        action
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+pub(crate) struct Sequence {
+    pub(crate) last: i64,
+    pub(crate) reconnected: bool,
+}
+
 #[derive(Debug, Default)]
 pub(crate) struct SecuritiesData {
     pub(crate) quote: PushQuote,
 
-    pub(crate) asks: Vec<Depth>,
-    pub(crate) bids: Vec<Depth>,
+    pub(crate) order_book: OrderBook,
 
     pub(crate) ask_brokers: Vec<Brokers>,
     pub(crate) bid_brokers: Vec<Brokers>,
@@ -219,11 +231,67 @@ pub(crate) struct SecuritiesData {
 
     pub(crate) board: SecurityBoard,
     pub(crate) candlesticks: HashMap<Period, Candlesticks>,
+    pub(crate) custom_candlesticks: HashMap<CustomPeriod, Candlesticks>,
+    pub(crate) bars: HashMap<BarType, Bars>,
+
+    pub(crate) sequences: HashMap<SubFlags, Sequence>,
+}
+
+impl SecuritiesData {
+    /// Returns `true` if the trades are required by the custom period
+    /// candlesticks or the bars
+    #[inline]
+    pub(crate) fn requires_trades(&self) -> bool {
+        !self.custom_candlesticks.is_empty() || !self.bars.is_empty()
+    }
+}
+
+/// Market definitions used to merge the candlesticks, the built-in
+/// definitions are replaced with the trading sessions returned by the server
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Markets(HashMap<Market, longport_candlesticks::Market>);
+
+impl Markets {
+    pub(crate) fn get(
+        &self,
+        market: Market,
+        board: SecurityBoard,
+    ) -> Option<&longport_candlesticks::Market> {
+        use longport_candlesticks::markets::*;
+
+        if market == Market::US && board == SecurityBoard::USOptionS {
+            return Some(&US_OPTION);
+        }
+        if let Some(market) = self.0.get(&market) {
+            return Some(market);
+        }
+        Some(match market {
+            Market::US => &US,
+            Market::HK => &HK,
+            Market::SG => &SG,
+            Market::CN => &CN,
+            Market::Unknown => return None,
+        })
+    }
+
+    pub(crate) fn update(&mut self, trading_sessions: &[MarketTradingSession]) {
+        for trading_session in trading_sessions {
+            if let Some(market) = trading_session.candlestick_market() {
+                tracing::info!(
+                    market = %trading_session.market,
+                    trade_sessions = ?market.trade_sessions,
+                    "update market trade sessions"
+                );
+                self.0.insert(trading_session.market, market);
+            }
+        }
+    }
 }
 
 #[derive(Debug, Default)]
 pub(crate) struct Store {
     pub(crate) securities: HashMap<String, SecuritiesData>,
+    pub(crate) markets: Markets,
 }
 
 impl Store {
@@ -235,7 +303,50 @@ impl Store {
             PushEventDetail::Depth(depth) => merge_depth(data, depth),
             PushEventDetail::Brokers(brokers) => merge_brokers(data, brokers),
             PushEventDetail::Trade(trade) => merge_trades(data, trade),
-            PushEventDetail::Candlestick(_) => unreachable!(),
+            PushEventDetail::Candlestick(_)
+            | PushEventDetail::CustomCandlestick(_)
+            | PushEventDetail::Bar(_)
+            | PushEventDetail::Indicator(_)
+            | PushEventDetail::SequenceGap(_)
+            | PushEventDetail::Resynced(_) => unreachable!(),
+        }
+    }
+
+    /// Updates the last sequence of the push event, returns a
+    /// [`PushSequenceGap`] if the sequence is not continuous
+    pub(crate) fn check_sequence(&mut self, event: &PushEvent) -> Option<PushSequenceGap> {
+        let sub_type = event.detail.sequenced_sub_type()?;
+        if event.sequence == 0 {
+            return None;
+        }
+
+        let data = self.securities.entry(event.symbol.clone()).or_default();
+        let prev = data.sequences.insert(
+            sub_type,
+            Sequence {
+                last: event.sequence,
+                reconnected: false,
+            },
+        )?;
+
+        let is_gap = if prev.reconnected {
+            event.sequence != prev.last + 1
+        } else {
+            event.sequence > prev.last + 1
+        };
+        is_gap.then_some(PushSequenceGap {
+            sub_type,
+            last_sequence: prev.last,
+            sequence: event.sequence,
+        })
+    }
+
+    /// Marks all sequences as received before a reconnection
+    pub(crate) fn mark_reconnected(&mut self) {
+        for data in self.securities.values_mut() {
+            for sequence in data.sequences.values_mut() {
+                sequence.reconnected = true;
+            }
         }
     }
 }
@@ -260,8 +371,7 @@ fn merge_quote(data: &mut SecuritiesData, quote: &mut PushQuote) {
 }
 
 fn merge_depth(data: &mut SecuritiesData, depth: &PushDepth) {
-    replace(&mut data.asks, depth.asks.clone(), |v| v.position);
-    replace(&mut data.bids, depth.bids.clone(), |v| v.position);
+    data.order_book.merge(depth);
 }
 
 fn merge_brokers(data: &mut SecuritiesData, brokers: &PushBrokers) {
@@ -280,7 +390,9 @@ fn merge_trades(data: &mut SecuritiesData, trades: &PushTrades) {
     }
 }
 
-fn replace<T, B, F>(elements: &mut Vec<T>, others: Vec<T>, f: F)
+/// Inserts the elements into the sorted list, replacing the ones with the
+/// same key
+pub(super) fn replace<T, B, F>(elements: &mut Vec<T>, others: Vec<T>, f: F)
 where
     F: Fn(&T) -> B,
     B: Ord,
@@ -293,23 +405,55 @@ where
     }
 }
 
-pub(crate) fn get_market(
-    market: Market,
+/// Returns the fields of the candlestick updated by the trade
+pub(crate) fn trade_update_fields(
+    ts: TradeSessionType,
+    market_type: Market,
     board: SecurityBoard,
-) -> Option<&'static longport_candlesticks::Market> {
-    use longport_candlesticks::markets::*;
-
-    Some(match market {
-        Market::US if board == SecurityBoard::USOptionS => &US_OPTION,
-        Market::US => &US,
-        Market::HK => &HK,
-        Market::SG => &SG,
-        Market::CN => &CN,
-        Market::Unknown => return None,
-    })
+    trade_type: &str,
+) -> UpdateFields {
+    match market_type {
+        Market::Unknown => unreachable!(),
+        Market::HK => match trade_type {
+            "" => UpdateFields::all(),
+            "D" => UpdateFields::VOLUME,
+            "M" => UpdateFields::VOLUME,
+            "P" => UpdateFields::VOLUME,
+            "U" => UpdateFields::all(),
+            "X" => UpdateFields::VOLUME,
+            "Y" => UpdateFields::VOLUME,
+            _ => UpdateFields::empty(),
+        },
+        Market::US => match trade_type {
+            "" => UpdateFields::all(),
+            "A" => UpdateFields::all(),
+            "B" => UpdateFields::all(),
+            "C" => UpdateFields::VOLUME,
+            "D" => UpdateFields::all(),
+            "E" => UpdateFields::all(),
+            "F" => UpdateFields::all(),
+            "G" => UpdateFields::VOLUME,
+            "H" => UpdateFields::VOLUME,
+            "I" if board == SecurityBoard::USOption || board == SecurityBoard::USOptionS => {
+                UpdateFields::all()
+            }
+            "I" if ts == TRADE_SESSION_PRE || ts == TRADE_SESSION_POST => UpdateFields::all(),
+            "I" => UpdateFields::VOLUME,
+            "K" => UpdateFields::all(),
+            "M" => UpdateFields::empty(),
+            "P" => UpdateFields::empty(),
+            "S" => UpdateFields::all(),
+            "V" => UpdateFields::VOLUME,
+            "W" => UpdateFields::VOLUME,
+            "X" => UpdateFields::all(),
+            "1" => UpdateFields::all(),
+            _ => UpdateFields::empty(),
+        },
+        Market::CN | Market::SG => UpdateFields::all(),
+    }
 }
 
-fn convert_period(period: Period) -> longport_candlesticks::Period {
+pub(crate) fn convert_period(period: Period) -> longport_candlesticks::Period {
     use longport_candlesticks::Period::*;
 
     match period {
@@ -334,3 +478,37 @@ fn convert_period(period: Period) -> longport_candlesticks::Period {
         Period::Year => Year,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trades_event(sequence: i64) -> PushEvent {
+        PushEvent {
+            sequence,
+            symbol: "700.HK".to_string(),
+            detail: PushEventDetail::Trade(PushTrades { trades: vec![] }),
+        }
+    }
+
+    #[test]
+    fn test_check_sequence() {
+        let mut store = Store::default();
+
+        assert!(store.check_sequence(&trades_event(1)).is_none());
+        assert!(store.check_sequence(&trades_event(2)).is_none());
+
+        let gap = store.check_sequence(&trades_event(5)).unwrap();
+        assert_eq!(gap.sub_type, SubFlags::TRADE);
+        assert_eq!(gap.last_sequence, 2);
+        assert_eq!(gap.sequence, 5);
+
+        // the sequence may be reset after reconnecting
+        store.mark_reconnected();
+        assert!(store.check_sequence(&trades_event(1)).is_some());
+        assert!(store.check_sequence(&trades_event(2)).is_none());
+
+        store.mark_reconnected();
+        assert!(store.check_sequence(&trades_event(3)).is_none());
+    }
+}
@@ -0,0 +1,447 @@
+use std::{
+    collections::VecDeque,
+    fmt::{self, Debug},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+};
+
+use futures_util::{task::AtomicWaker, Stream, StreamExt};
+use tokio::sync::{mpsc, Notify};
+
+use crate::quote::{PushEvent, PushEventDetail};
+
+/// Overflow policy of the push streams
+///
+/// Decides what happens when a push stream is full because its consumer
+/// does not keep up with the pushes.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Drop the oldest buffered event
+    #[default]
+    DropOldest,
+    /// Buffer only the latest snapshot of each security and kind (the quote,
+    /// the depth and the brokers of `700.HK`, or the unconfirmed updates of
+    /// its 1-minute candlestick), and drop the oldest buffered event when the
+    /// buffer is full
+    ///
+    /// The trades and the confirmed candlesticks, bars and indicator values
+    /// are never conflated.
+    ConflateLatest,
+    /// Stop reading the pushes from the connection until the consumer catches
+    /// up, the responses of the requests are also delayed meanwhile
+    Block,
+}
+
+struct Shared {
+    queue: Mutex<VecDeque<PushEvent>>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    sender_closed: AtomicBool,
+    receiver_closed: AtomicBool,
+    waker: AtomicWaker,
+    space: Notify,
+}
+
+impl Shared {
+    fn is_full(&self) -> bool {
+        !self.receiver_closed.load(Ordering::Acquire)
+            && self.queue.lock().unwrap().len() >= self.capacity
+    }
+}
+
+/// The sending half of a push stream, owned by the quote core
+pub(crate) struct PushStreamSender {
+    shared: Arc<Shared>,
+    filter: Box<dyn Fn(&PushEvent) -> bool + Send + Sync>,
+}
+
+impl Drop for PushStreamSender {
+    fn drop(&mut self) {
+        self.shared.sender_closed.store(true, Ordering::Release);
+        self.shared.waker.wake();
+    }
+}
+
+impl PushStreamSender {
+    fn send(&self, event: &PushEvent) {
+        if !(self.filter)(event) {
+            return;
+        }
+
+        let mut queue = self.shared.queue.lock().unwrap();
+        if self.shared.policy == OverflowPolicy::ConflateLatest {
+            if let Some(buffered) = queue
+                .iter_mut()
+                .find(|buffered| is_same_kind(buffered, event))
+            {
+                *buffered = event.clone();
+                drop(queue);
+                self.shared.waker.wake();
+                return;
+            }
+        }
+        if queue.len() >= self.shared.capacity {
+            match self.shared.policy {
+                OverflowPolicy::DropOldest | OverflowPolicy::ConflateLatest => {
+                    queue.pop_front();
+                }
+                // the core waits for the consumer before reading more pushes
+                OverflowPolicy::Block => {}
+            }
+        }
+        queue.push_back(event.clone());
+        drop(queue);
+        self.shared.waker.wake();
+    }
+}
+
+/// Returns `true` if the event `b` supersedes the buffered event `a`
+///
+/// Only the snapshots and the unconfirmed updates of the same candlestick are
+/// superseded, the trades and the confirmed candlesticks are never lost.
+fn is_same_kind(a: &PushEvent, b: &PushEvent) -> bool {
+    if a.symbol != b.symbol {
+        return false;
+    }
+    match (&a.detail, &b.detail) {
+        (PushEventDetail::Quote(_), PushEventDetail::Quote(_))
+        | (PushEventDetail::Depth(_), PushEventDetail::Depth(_))
+        | (PushEventDetail::Brokers(_), PushEventDetail::Brokers(_)) => true,
+        (PushEventDetail::Candlestick(a), PushEventDetail::Candlestick(b)) => {
+            !a.is_confirmed
+                && a.period == b.period
+                && a.candlestick.timestamp == b.candlestick.timestamp
+        }
+        (PushEventDetail::CustomCandlestick(a), PushEventDetail::CustomCandlestick(b)) => {
+            !a.is_confirmed
+                && a.period == b.period
+                && a.candlestick.timestamp == b.candlestick.timestamp
+        }
+        (PushEventDetail::Bar(a), PushEventDetail::Bar(b)) => {
+            !a.is_confirmed
+                && a.bar_type == b.bar_type
+                && a.candlestick.timestamp == b.candlestick.timestamp
+        }
+        (PushEventDetail::Indicator(a), PushEventDetail::Indicator(b)) => {
+            !a.is_confirmed
+                && a.period == b.period
+                && a.indicator == b.indicator
+                && a.timestamp == b.timestamp
+        }
+        _ => false,
+    }
+}
+
+/// Dispatches the push events to the receiver returned by
+/// `QuoteContext::try_new` and to the push streams
+pub(crate) struct PushSender {
+    tx: mpsc::UnboundedSender<PushEvent>,
+    streams: Vec<PushStreamSender>,
+}
+
+impl PushSender {
+    pub(crate) fn new(tx: mpsc::UnboundedSender<PushEvent>) -> Self {
+        Self {
+            tx,
+            streams: Vec::new(),
+        }
+    }
+
+    pub(crate) fn add_stream(&mut self, stream: PushStreamSender) {
+        self.streams.push(stream);
+    }
+
+    pub(crate) fn send(&mut self, event: PushEvent) {
+        self.streams
+            .retain(|stream| !stream.shared.receiver_closed.load(Ordering::Acquire));
+        for stream in &self.streams {
+            stream.send(&event);
+        }
+        let _ = self.tx.send(event);
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.tx.is_closed() && self.streams.is_empty()
+    }
+
+    /// Waits until none of the push streams with [`OverflowPolicy::Block`] is
+    /// full
+    pub(crate) async fn ready(&self) {
+        for stream in &self.streams {
+            if stream.shared.policy != OverflowPolicy::Block {
+                continue;
+            }
+            loop {
+                let notified = stream.shared.space.notified();
+                if !stream.shared.is_full() {
+                    break;
+                }
+                notified.await;
+            }
+        }
+    }
+}
+
+/// A bounded stream of push events
+///
+/// Created with the `*_stream` methods of
+/// [`QuoteContext`](crate::QuoteContext), each stream has its own buffer so
+/// the events can be consumed by multiple consumers. The buffer size and the
+/// behavior when it is full are specified with
+/// [`Config::push_stream_capacity`](crate::Config::push_stream_capacity) and
+/// [`Config::push_stream_overflow_policy`](crate::Config::push_stream_overflow_policy).
+///
+/// The stream ends when the quote context is closed.
+pub struct PushStream<T> {
+    shared: Arc<Shared>,
+    extract: fn(PushEvent) -> Option<T>,
+}
+
+impl<T> Debug for PushStream<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PushStream")
+            .field("capacity", &self.shared.capacity)
+            .field("policy", &self.shared.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Drop for PushStream<T> {
+    fn drop(&mut self) {
+        self.shared.receiver_closed.store(true, Ordering::Release);
+        self.shared.space.notify_one();
+    }
+}
+
+impl<T> PushStream<T> {
+    pub(crate) fn new(
+        capacity: usize,
+        policy: OverflowPolicy,
+        filter: impl Fn(&PushEvent) -> bool + Send + Sync + 'static,
+        extract: fn(PushEvent) -> Option<T>,
+    ) -> (Self, PushStreamSender) {
+        let shared = Arc::new(Shared {
+            queue: Mutex::new(VecDeque::new()),
+            capacity: capacity.max(1),
+            policy,
+            sender_closed: AtomicBool::new(false),
+            receiver_closed: AtomicBool::new(false),
+            waker: AtomicWaker::new(),
+            space: Notify::new(),
+        });
+        (
+            Self {
+                shared: shared.clone(),
+                extract,
+            },
+            PushStreamSender {
+                shared,
+                filter: Box::new(filter),
+            },
+        )
+    }
+
+    /// Receives the next event, returns `None` if the quote context is
+    /// closed
+    #[inline]
+    pub async fn recv(&mut self) -> Option<T> {
+        self.next().await
+    }
+}
+
+impl<T> Stream for PushStream<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.shared.waker.register(cx.waker());
+        loop {
+            let event = self.shared.queue.lock().unwrap().pop_front();
+            match event {
+                Some(event) => {
+                    self.shared.space.notify_one();
+                    if let Some(item) = (self.extract)(event) {
+                        return Poll::Ready(Some(item));
+                    }
+                }
+                None if self.shared.sender_closed.load(Ordering::Acquire) => {
+                    return Poll::Ready(None)
+                }
+                None => return Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+    use time::OffsetDateTime;
+
+    use super::*;
+    use crate::quote::{
+        Candlestick, Period, PushCandlestick, PushDepth, PushQuote, PushTrades, TradeSession,
+    };
+
+    fn quote_event(symbol: &str, volume: i64) -> PushEvent {
+        PushEvent {
+            sequence: 0,
+            symbol: symbol.to_string(),
+            detail: PushEventDetail::Quote(PushQuote {
+                volume,
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn quote_stream(sender: &mut PushSender, policy: OverflowPolicy) -> PushStream<PushQuote> {
+        let (stream, stream_sender) = PushStream::new(
+            2,
+            policy,
+            |event| event.symbol == "700.HK",
+            |event| match event.detail {
+                PushEventDetail::Quote(quote) => Some(quote),
+                _ => None,
+            },
+        );
+        sender.add_stream(stream_sender);
+        stream
+    }
+
+    #[tokio::test]
+    async fn test_push_stream() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut sender = PushSender::new(tx);
+        let drop_oldest = quote_stream(&mut sender, OverflowPolicy::DropOldest);
+        let conflate = quote_stream(&mut sender, OverflowPolicy::ConflateLatest);
+
+        for volume in 1..=3 {
+            sender.send(quote_event("700.HK", volume));
+            sender.send(quote_event("AAPL.US", volume));
+        }
+        drop(sender);
+
+        // the receiver still receives all events
+        let mut count = 0;
+        while rx.recv().await.is_some() {
+            count += 1;
+        }
+        assert_eq!(count, 6);
+
+        let volumes = |stream: PushStream<PushQuote>| async move {
+            stream.map(|quote| quote.volume).collect::<Vec<_>>().await
+        };
+        assert_eq!(volumes(drop_oldest).await, vec![2, 3]);
+        assert_eq!(volumes(conflate).await, vec![3]);
+    }
+
+    #[tokio::test]
+    async fn test_push_stream_conflate() {
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut sender = PushSender::new(tx);
+        let (stream, stream_sender) =
+            PushStream::new(3, OverflowPolicy::ConflateLatest, |_| true, Some);
+        sender.add_stream(stream_sender);
+
+        let depth = PushEvent {
+            sequence: 0,
+            symbol: "700.HK".to_string(),
+            detail: PushEventDetail::Depth(PushDepth {
+                asks: Vec::new(),
+                bids: Vec::new(),
+            }),
+        };
+        sender.send(quote_event("700.HK", 1));
+        sender.send(depth);
+        sender.send(quote_event("700.HK", 2));
+        sender.send(quote_event("AAPL.US", 3));
+        sender.send(quote_event("AAPL.US", 4));
+        sender.send(quote_event("9988.HK", 5));
+        drop(sender);
+
+        let events = stream
+            .map(|event| match event.detail {
+                PushEventDetail::Quote(quote) => format!("{} {}", event.symbol, quote.volume),
+                _ => format!("{} depth", event.symbol),
+            })
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(events, vec!["700.HK depth", "AAPL.US 4", "9988.HK 5"]);
+    }
+
+    #[tokio::test]
+    async fn test_push_stream_conflate_candlesticks() {
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut sender = PushSender::new(tx);
+        let (stream, stream_sender) =
+            PushStream::new(10, OverflowPolicy::ConflateLatest, |_| true, Some);
+        sender.add_stream(stream_sender);
+
+        let candlestick = |minute: i64, volume: i64, is_confirmed: bool| PushEvent {
+            sequence: 0,
+            symbol: "700.HK".to_string(),
+            detail: PushEventDetail::Candlestick(PushCandlestick {
+                period: Period::OneMinute,
+                candlestick: Candlestick {
+                    close: Decimal::ZERO,
+                    open: Decimal::ZERO,
+                    low: Decimal::ZERO,
+                    high: Decimal::ZERO,
+                    volume,
+                    turnover: Decimal::ZERO,
+                    timestamp: OffsetDateTime::UNIX_EPOCH + time::Duration::minutes(minute),
+                    trade_session: TradeSession::NormalTrade,
+                },
+                is_confirmed,
+            }),
+        };
+        let trades = PushEvent {
+            sequence: 0,
+            symbol: "700.HK".to_string(),
+            detail: PushEventDetail::Trade(PushTrades { trades: Vec::new() }),
+        };
+        sender.send(candlestick(0, 1, false));
+        sender.send(candlestick(0, 2, false));
+        sender.send(candlestick(0, 3, true));
+        sender.send(candlestick(1, 4, false));
+        sender.send(candlestick(1, 5, true));
+        sender.send(trades.clone());
+        sender.send(trades);
+        drop(sender);
+
+        let events = stream
+            .map(|event| match event.detail {
+                PushEventDetail::Candlestick(candlestick) => {
+                    candlestick.candlestick.volume.to_string()
+                }
+                _ => "trades".to_string(),
+            })
+            .collect::<Vec<_>>()
+            .await;
+        // the confirmed candlesticks and the trades are kept
+        assert_eq!(events, vec!["3", "5", "trades", "trades"]);
+    }
+
+    #[tokio::test]
+    async fn test_push_stream_block() {
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let mut sender = PushSender::new(tx);
+        let mut stream = quote_stream(&mut sender, OverflowPolicy::Block);
+
+        sender.send(quote_event("700.HK", 1));
+        sender.send(quote_event("700.HK", 2));
+        assert!(tokio::time::timeout(Default::default(), sender.ready())
+            .await
+            .is_err());
+
+        assert_eq!(stream.recv().await.map(|quote| quote.volume), Some(1));
+        sender.ready().await;
+
+        drop(stream);
+        sender.send(quote_event("700.HK", 3));
+        assert!(sender.streams.is_empty());
+    }
+}
@@ -0,0 +1,105 @@
+use futures_util::{future, Stream, StreamExt};
+use longport_candlesticks::CandlestickTransform;
+
+use crate::quote::{Candlestick, PushCandlestick};
+
+/// Transforms the realtime candlesticks
+///
+/// Emits the Heikin-Ashi, percent change or log return candlesticks of the
+/// candlesticks returned by [`QuoteContext::realtime_candlesticks`] and the
+/// pushes of [`QuoteContext::candlestick_stream`]. The unconfirmed pushes
+/// update the last transformed candlestick without changing the state, and
+/// the state is advanced when the candlestick is confirmed or a later
+/// candlestick is pushed.
+///
+/// [`QuoteContext::realtime_candlesticks`]: crate::quote::QuoteContext::realtime_candlesticks
+/// [`QuoteContext::candlestick_stream`]: crate::quote::QuoteContext::candlestick_stream
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use futures_util::StreamExt;
+/// use longport::{
+///     quote::{CandlestickTransform, CandlestickTransformer, Period, QuoteContext, TradeSessions},
+///     Config,
+/// };
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let config = Arc::new(Config::from_env()?);
+/// let (ctx, _) = QuoteContext::try_new(config).await?;
+///
+/// let stream = ctx.candlestick_stream("700.HK", Period::OneMinute)?;
+/// ctx.subscribe_candlesticks("700.HK", Period::OneMinute, TradeSessions::Normal)
+///     .await?;
+///
+/// let mut transformer = CandlestickTransformer::new(CandlestickTransform::HeikinAshi);
+/// let candlesticks = ctx
+///     .realtime_candlesticks("700.HK", Period::OneMinute, 100)
+///     .await?;
+/// println!("{:?}", transformer.transform_candlesticks(&candlesticks));
+///
+/// let mut stream = transformer.transform_stream(stream);
+/// while let Some(push) = stream.next().await {
+///     println!("{:?}", push);
+/// }
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct CandlestickTransformer(longport_candlesticks::CandlestickTransformer);
+
+impl CandlestickTransformer {
+    /// Create a new `CandlestickTransformer`
+    #[inline]
+    pub fn new(transform: CandlestickTransform) -> Self {
+        Self(longport_candlesticks::CandlestickTransformer::new(
+            transform,
+        ))
+    }
+
+    /// Returns the transform
+    #[inline]
+    pub fn transform(&self) -> CandlestickTransform {
+        self.0.transform()
+    }
+
+    fn update(&mut self, candlestick: &Candlestick, is_confirmed: bool) -> Option<Candlestick> {
+        self.0
+            .update(&(*candlestick).into(), is_confirmed)
+            .map(|transformed| (transformed, candlestick.trade_session).into())
+    }
+
+    /// Transforms the candlesticks sorted by time, such as the candlesticks
+    /// returned by
+    /// [`QuoteContext::realtime_candlesticks`](crate::quote::QuoteContext::realtime_candlesticks)
+    ///
+    /// The last candlestick is not confirmed, so it can be updated by the
+    /// following pushes.
+    pub fn transform_candlesticks(&mut self, candlesticks: &[Candlestick]) -> Vec<Candlestick> {
+        candlesticks
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, candlestick)| self.update(candlestick, idx + 1 < candlesticks.len()))
+            .collect()
+    }
+
+    /// Transforms a candlestick push, returns `None` if the push is ignored or
+    /// there are not enough candlesticks to transform it
+    pub fn transform_push(&mut self, push: &PushCandlestick) -> Option<PushCandlestick> {
+        Some(PushCandlestick {
+            candlestick: self.update(&push.candlestick, push.is_confirmed)?,
+            ..*push
+        })
+    }
+
+    /// Converts a stream of the candlestick pushes to a stream of the
+    /// transformed pushes
+    pub fn transform_stream<S>(mut self, stream: S) -> impl Stream<Item = PushCandlestick>
+    where
+        S: Stream<Item = PushCandlestick>,
+    {
+        stream.filter_map(move |push| future::ready(self.transform_push(&push)))
+    }
+}
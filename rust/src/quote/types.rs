@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use longport_proto::quote::{self, Period, TradeSession, TradeStatus};
 use num_enum::{FromPrimitive, IntoPrimitive, TryFromPrimitive};
 use rust_decimal::Decimal;
@@ -6,7 +8,10 @@ use strum_macros::{Display, EnumString};
 use time::{Date, OffsetDateTime, Time};
 
 use crate::{
-    quote::{utils::parse_date, SubFlags},
+    quote::{
+        utils::{convert_trade_session, parse_date},
+        SubFlags,
+    },
     serde_utils, Error, Market, Result,
 };
 
@@ -1137,6 +1142,55 @@ impl TryFrom<quote::MarketTradePeriod> for MarketTradingSession {
     }
 }
 
+impl MarketTradingSession {
+    /// Returns the market definition used to merge the candlesticks, with the
+    /// trade sessions replaced by these trading sessions
+    ///
+    /// The half day sessions and the closing auction timeouts are taken from
+    /// the built-in definition of the market, returns `None` if the market is
+    /// unknown.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// use longport::{quote::QuoteContext, Config};
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let config = Arc::new(Config::from_env()?);
+    /// let (ctx, _) = QuoteContext::try_new(config).await?;
+    ///
+    /// for trading_session in ctx.trading_session().await? {
+    ///     println!("{:?}", trading_session.candlestick_market());
+    /// }
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// # });
+    /// ```
+    pub fn candlestick_market(&self) -> Option<longport_candlesticks::Market> {
+        use longport_candlesticks::markets::*;
+
+        let mut market = match self.market {
+            Market::US => US,
+            Market::HK => HK,
+            Market::CN => CN,
+            Market::SG => SG,
+            Market::Unknown => return None,
+        };
+        let mut ranges: HashMap<_, Vec<_>> = HashMap::new();
+        for info in &self.trade_sessions {
+            ranges
+                .entry(convert_trade_session(info.trade_session))
+                .or_default()
+                .push((info.begin_time, info.end_time));
+        }
+        for (ts, ranges) in ranges {
+            market = market.with_trade_session_ranges(ts, ranges);
+        }
+        Some(market)
+    }
+}
+
 /// Market trading days
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketTradingDays {
@@ -1748,6 +1802,40 @@ impl TryFrom<quote::user_quote_level_detail::PackageDetail> for QuotePackageDeta
     }
 }
 
+/// Custom candlestick period
+///
+/// The candlesticks of the custom periods are aggregated locally, and aligned
+/// to the start of each trade session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CustomPeriod {
+    /// `N` minutes
+    Minutes(u16),
+    /// `N` seconds
+    Seconds(u32),
+}
+
+impl CustomPeriod {
+    /// Returns `true` if the candlesticks can be built from the `1` minute
+    /// candlesticks
+    #[inline]
+    pub(crate) fn is_whole_minutes(&self) -> bool {
+        match self {
+            CustomPeriod::Minutes(_) => true,
+            CustomPeriod::Seconds(seconds) => seconds % 60 == 0,
+        }
+    }
+}
+
+impl From<CustomPeriod> for longport_candlesticks::Period {
+    #[inline]
+    fn from(period: CustomPeriod) -> Self {
+        match period {
+            CustomPeriod::Minutes(minutes) => longport_candlesticks::Period::Minutes(minutes),
+            CustomPeriod::Seconds(seconds) => longport_candlesticks::Period::Seconds(seconds),
+        }
+    }
+}
+
 /// Trade sessions
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(i32)]
@@ -25,3 +25,14 @@ pub(crate) fn convert_trade_session(ts: longport_proto::quote::TradeSession) ->
         longport_proto::quote::TradeSession::OvernightTrade => TRADE_SESSION_OVERNIGHT,
     }
 }
+
+pub(crate) fn convert_trade_session_type(
+    ts: TradeSessionType,
+) -> longport_proto::quote::TradeSession {
+    match ts {
+        TRADE_SESSION_PRE => longport_proto::quote::TradeSession::PreTrade,
+        TRADE_SESSION_POST => longport_proto::quote::TradeSession::PostTrade,
+        TRADE_SESSION_OVERNIGHT => longport_proto::quote::TradeSession::OvernightTrade,
+        _ => longport_proto::quote::TradeSession::NormalTrade,
+    }
+}
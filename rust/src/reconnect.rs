@@ -0,0 +1,259 @@
+use std::time::Duration;
+
+use rand::Rng;
+use tokio::time::Instant;
+
+/// Reconnect policy
+///
+/// Controls how the quote and trade contexts reconnect to the server after
+/// the connection has been lost.
+///
+/// The default policy retries every `2` seconds forever.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use longport::{Config, ReconnectPolicy};
+///
+/// let policy = ReconnectPolicy::exponential(Duration::from_secs(1), Duration::from_secs(60))
+///     .jitter(0.2)
+///     .max_elapsed_time(Duration::from_secs(60 * 30));
+/// let config = Config::new("app_key", "app_secret", "access_token").reconnect_policy(policy);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectPolicy {
+    enabled: bool,
+    initial_delay: Duration,
+    multiplier: f64,
+    max_delay: Duration,
+    jitter: f64,
+    max_attempts: Option<usize>,
+    max_elapsed_time: Option<Duration>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self::fixed(Duration::from_secs(2))
+    }
+}
+
+impl ReconnectPolicy {
+    /// Create a policy that retries with the same `delay` every time
+    pub fn fixed(delay: Duration) -> Self {
+        Self {
+            enabled: true,
+            initial_delay: delay,
+            multiplier: 1.0,
+            max_delay: delay,
+            jitter: 0.0,
+            max_attempts: None,
+            max_elapsed_time: None,
+        }
+    }
+
+    /// Create a policy that doubles the delay after every failed attempt,
+    /// starting with `initial_delay` and capped at `max_delay`
+    pub fn exponential(initial_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            multiplier: 2.0,
+            max_delay,
+            ..Self::fixed(initial_delay)
+        }
+    }
+
+    /// Create a policy that never reconnects
+    ///
+    /// The context is closed as soon as the connection is lost.
+    pub fn never() -> Self {
+        Self {
+            enabled: false,
+            ..Self::default()
+        }
+    }
+
+    /// Specifies the delay before the first attempt
+    #[must_use]
+    pub fn initial_delay(self, delay: Duration) -> Self {
+        Self {
+            initial_delay: delay,
+            ..self
+        }
+    }
+
+    /// Specifies the factor by which the delay grows after every failed
+    /// attempt
+    ///
+    /// Values less than `1.0` are treated as `1.0`.
+    #[must_use]
+    pub fn multiplier(self, multiplier: f64) -> Self {
+        Self {
+            multiplier: multiplier.max(1.0),
+            ..self
+        }
+    }
+
+    /// Specifies the upper bound of the delay
+    #[must_use]
+    pub fn max_delay(self, delay: Duration) -> Self {
+        Self {
+            max_delay: delay,
+            ..self
+        }
+    }
+
+    /// Specifies the jitter factor in range `[0, 1]`
+    ///
+    /// Every delay is randomized within `delay * (1 ± jitter)` so that many
+    /// clients do not reconnect at the same instant.
+    #[must_use]
+    pub fn jitter(self, jitter: f64) -> Self {
+        Self {
+            jitter: jitter.clamp(0.0, 1.0),
+            ..self
+        }
+    }
+
+    /// Gives up after `attempts` consecutive failed attempts
+    #[must_use]
+    pub fn max_attempts(self, attempts: usize) -> Self {
+        Self {
+            max_attempts: Some(attempts),
+            ..self
+        }
+    }
+
+    /// Gives up when the connection could not be restored within `elapsed`
+    #[must_use]
+    pub fn max_elapsed_time(self, elapsed: Duration) -> Self {
+        Self {
+            max_elapsed_time: Some(elapsed),
+            ..self
+        }
+    }
+
+    pub(crate) fn backoff(&self) -> Backoff {
+        Backoff {
+            policy: self.clone(),
+            attempts: 0,
+            start: Instant::now(),
+        }
+    }
+}
+
+/// The state of a reconnection sequence
+pub(crate) struct Backoff {
+    policy: ReconnectPolicy,
+    attempts: usize,
+    start: Instant,
+}
+
+impl Backoff {
+    /// Returns the delay before the next attempt, or `None` if the policy
+    /// gives up
+    pub(crate) fn next_delay(&mut self) -> Option<Duration> {
+        let policy = &self.policy;
+
+        if !policy.enabled {
+            return None;
+        }
+        if matches!(policy.max_attempts, Some(max_attempts) if self.attempts >= max_attempts) {
+            return None;
+        }
+
+        let max_delay = policy.max_delay.max(policy.initial_delay);
+        let delay = Duration::try_from_secs_f64(
+            (policy.initial_delay.as_secs_f64()
+                * policy.multiplier.powi(self.attempts.min(64) as i32))
+            .min(max_delay.as_secs_f64()),
+        )
+        .unwrap_or(max_delay);
+        let delay = if policy.jitter > 0.0 {
+            let factor = rand::thread_rng().gen_range(1.0 - policy.jitter..=1.0 + policy.jitter);
+            Duration::try_from_secs_f64(delay.as_secs_f64() * factor).unwrap_or(Duration::MAX)
+        } else {
+            delay
+        };
+
+        if let Some(max_elapsed_time) = policy.max_elapsed_time {
+            if self
+                .start
+                .elapsed()
+                .checked_add(delay)
+                .is_none_or(|elapsed| elapsed > max_elapsed_time)
+            {
+                return None;
+            }
+        }
+
+        self.attempts += 1;
+        Some(delay)
+    }
+
+    /// Returns the number of attempts made so far
+    #[inline]
+    pub(crate) fn attempts(&self) -> usize {
+        self.attempts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed() {
+        let mut backoff = ReconnectPolicy::default().backoff();
+        for _ in 0..10 {
+            assert_eq!(backoff.next_delay(), Some(Duration::from_secs(2)));
+        }
+    }
+
+    #[test]
+    fn test_exponential() {
+        let mut backoff =
+            ReconnectPolicy::exponential(Duration::from_secs(1), Duration::from_secs(10))
+                .max_attempts(6)
+                .backoff();
+        let delays = std::iter::from_fn(|| backoff.next_delay())
+            .map(|delay| delay.as_secs())
+            .collect::<Vec<_>>();
+        assert_eq!(delays, vec![1, 2, 4, 8, 10, 10]);
+        assert_eq!(backoff.attempts(), 6);
+    }
+
+    #[test]
+    fn test_jitter() {
+        let mut backoff = ReconnectPolicy::fixed(Duration::from_secs(10))
+            .jitter(0.5)
+            .backoff();
+        for _ in 0..100 {
+            let delay = backoff.next_delay().unwrap();
+            assert!(delay >= Duration::from_secs(5) && delay <= Duration::from_secs(15));
+        }
+    }
+
+    #[test]
+    fn test_huge_delay() {
+        let mut backoff = ReconnectPolicy::exponential(Duration::from_secs(1), Duration::MAX)
+            .jitter(1.0)
+            .max_elapsed_time(Duration::from_secs(3600))
+            .backoff();
+        while backoff.next_delay().is_some() {}
+        assert!(backoff.attempts() < 64);
+
+        let mut backoff = ReconnectPolicy::fixed(Duration::MAX).backoff();
+        assert!(backoff.next_delay().is_some());
+    }
+
+    #[test]
+    fn test_give_up() {
+        assert_eq!(ReconnectPolicy::never().backoff().next_delay(), None);
+
+        let mut backoff = ReconnectPolicy::fixed(Duration::from_secs(2))
+            .max_elapsed_time(Duration::from_secs(1))
+            .backoff();
+        assert_eq!(backoff.next_delay(), None);
+    }
+}
@@ -0,0 +1,415 @@
+use std::{future::Future, sync::Arc, time::Duration};
+
+use tokio::{sync::mpsc, time::Instant};
+use tracing::Subscriber;
+
+use crate::{
+    trade::{
+        context::{poll_order, submit_with_retry},
+        AccountBalance, CashFlow, EstimateMaxPurchaseQuantityOptions,
+        EstimateMaxPurchaseQuantityResponse, Execution, FundPositionsResponse, GetCashFlowOptions,
+        GetFundPositionsOptions, GetHistoryExecutionsOptions, GetHistoryOrdersOptions,
+        GetStockPositionsOptions, GetTodayExecutionsOptions, GetTodayOrdersOptions, MarginRatio,
+        Order, OrderDetail, OrderStatus, ReplaceOrderOptions, RiskManager, StockPositionsResponse,
+        SubmitOrderOptions, SubmitOrderResponse, TopicType, TradeContext,
+    },
+    ConnectionEvent, Result,
+};
+
+/// Trade API
+///
+/// Covers all methods of [`TradeContext`], so that the code depending on the
+/// trade API can be written against this trait and run with mocks, rate
+/// limiters or simulators instead of the real context.
+///
+/// See the methods of [`TradeContext`] for details.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use longport::{trade::TradeApi, Config, TradeContext};
+///
+/// async fn pending_orders(api: &impl TradeApi) -> longport::Result<usize> {
+///     Ok(api.today_orders(None).await?.len())
+/// }
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let config = Arc::new(Config::from_env()?);
+/// let (ctx, _) = TradeContext::try_new(config).await?;
+/// println!("{}", pending_orders(&ctx).await?);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// # });
+/// ```
+pub trait TradeApi: Send + Sync {
+    /// Returns the log subscriber
+    fn log_subscriber(&self) -> Arc<dyn Subscriber + Send + Sync>;
+
+    /// Set the risk manager which checks the orders before they are submitted
+    /// or replaced, `None` disables the checks
+    fn set_risk_manager(&self, risk_manager: Option<RiskManager>);
+
+    /// Returns the risk manager
+    fn risk_manager(&self) -> Option<RiskManager>;
+
+    /// Returns a receiver of the connection state events
+    fn connection_events(&self) -> mpsc::UnboundedReceiver<ConnectionEvent>;
+
+    /// Subscribe
+    fn subscribe<I>(&self, topics: I) -> impl Future<Output = Result<()>> + Send
+    where
+        I: IntoIterator<Item = TopicType>;
+
+    /// Unsubscribe
+    fn unsubscribe<I>(&self, topics: I) -> impl Future<Output = Result<()>> + Send
+    where
+        I: IntoIterator<Item = TopicType>;
+
+    /// Get history executions
+    fn history_executions(
+        &self,
+        options: impl Into<Option<GetHistoryExecutionsOptions>>,
+    ) -> impl Future<Output = Result<Vec<Execution>>> + Send;
+
+    /// Get today executions
+    fn today_executions(
+        &self,
+        options: impl Into<Option<GetTodayExecutionsOptions>>,
+    ) -> impl Future<Output = Result<Vec<Execution>>> + Send;
+
+    /// Get history orders
+    fn history_orders(
+        &self,
+        options: impl Into<Option<GetHistoryOrdersOptions>>,
+    ) -> impl Future<Output = Result<Vec<Order>>> + Send;
+
+    /// Get today orders
+    fn today_orders(
+        &self,
+        options: impl Into<Option<GetTodayOrdersOptions>>,
+    ) -> impl Future<Output = Result<Vec<Order>>> + Send;
+
+    /// Get the cached open orders of today
+    ///
+    /// The default implementation filters the orders returned by
+    /// [`TradeApi::today_orders`].
+    fn open_orders(&self) -> impl Future<Output = Result<Vec<Order>>> + Send {
+        let orders = self.today_orders(None::<GetTodayOrdersOptions>);
+        async move {
+            Ok(orders
+                .await?
+                .into_iter()
+                .filter(|order| order.status != OrderStatus::Unknown && !order.status.is_terminal())
+                .collect())
+        }
+    }
+
+    /// Get the cached orders of today of the security
+    ///
+    /// The default implementation calls [`TradeApi::today_orders`].
+    fn symbol_orders(
+        &self,
+        symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<Vec<Order>>> + Send {
+        self.today_orders(GetTodayOrdersOptions::new().symbol(symbol))
+    }
+
+    /// Get a cached order of today
+    ///
+    /// The default implementation calls [`TradeApi::today_orders`].
+    fn order(
+        &self,
+        order_id: impl Into<String>,
+    ) -> impl Future<Output = Result<Option<Order>>> + Send {
+        let orders = self.today_orders(GetTodayOrdersOptions::new().order_id(order_id.into()));
+        async move { Ok(orders.await?.into_iter().next()) }
+    }
+
+    /// Replace order
+    fn replace_order(
+        &self,
+        options: ReplaceOrderOptions,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Submit order
+    fn submit_order(
+        &self,
+        options: SubmitOrderOptions,
+    ) -> impl Future<Output = Result<SubmitOrderResponse>> + Send;
+
+    /// Submit order, and retry after the ambiguous failures
+    ///
+    /// The default implementation retries with [`TradeApi::submit_order`] and
+    /// reconciles with [`TradeApi::today_orders`].
+    fn submit_order_with_retry(
+        &self,
+        options: SubmitOrderOptions,
+        max_retries: usize,
+    ) -> impl Future<Output = Result<SubmitOrderResponse>> + Send {
+        submit_with_retry(self, options, max_retries)
+    }
+
+    /// Cancel order
+    fn cancel_order(&self, order_id: impl Into<String>) -> impl Future<Output = Result<()>> + Send;
+
+    /// Get account balance
+    fn account_balance(
+        &self,
+        currency: Option<&str>,
+    ) -> impl Future<Output = Result<Vec<AccountBalance>>> + Send;
+
+    /// Get cash flow
+    fn cash_flow(
+        &self,
+        options: GetCashFlowOptions,
+    ) -> impl Future<Output = Result<Vec<CashFlow>>> + Send;
+
+    /// Get fund positions
+    fn fund_positions(
+        &self,
+        opts: impl Into<Option<GetFundPositionsOptions>>,
+    ) -> impl Future<Output = Result<FundPositionsResponse>> + Send;
+
+    /// Get stock positions
+    fn stock_positions(
+        &self,
+        opts: impl Into<Option<GetStockPositionsOptions>>,
+    ) -> impl Future<Output = Result<StockPositionsResponse>> + Send;
+
+    /// Get margin ratio
+    fn margin_ratio(
+        &self,
+        symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<MarginRatio>> + Send;
+
+    /// Get order detail
+    fn order_detail(
+        &self,
+        order_id: impl Into<String>,
+    ) -> impl Future<Output = Result<OrderDetail>> + Send;
+
+    /// Wait until the status of the order satisfies the predicate or the
+    /// timeout elapses
+    ///
+    /// The default implementation polls [`TradeApi::order_detail`].
+    fn wait_for_order<F>(
+        &self,
+        order_id: impl Into<String>,
+        predicate: F,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<OrderDetail>> + Send
+    where
+        F: Fn(OrderStatus) -> bool + Send,
+    {
+        poll_order(self, order_id.into(), predicate, timeout)
+    }
+
+    /// Submit order, and wait until it is terminated or the timeout elapses
+    ///
+    /// The default implementation calls [`TradeApi::submit_order`] and
+    /// [`TradeApi::wait_for_order`].
+    fn submit_and_wait(
+        &self,
+        options: SubmitOrderOptions,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<OrderDetail>> + Send {
+        async move {
+            let deadline = Instant::now() + timeout;
+            let resp = self.submit_order(options).await?;
+            self.wait_for_order(
+                resp.order_id,
+                OrderStatus::is_terminal,
+                deadline.saturating_duration_since(Instant::now()),
+            )
+            .await
+        }
+    }
+
+    /// Estimating the maximum purchase quantity for Hong Kong and US stocks,
+    /// warrants, and options
+    fn estimate_max_purchase_quantity(
+        &self,
+        opts: EstimateMaxPurchaseQuantityOptions,
+    ) -> impl Future<Output = Result<EstimateMaxPurchaseQuantityResponse>> + Send;
+}
+
+// The arguments are converted to owned values before calling the methods of
+// the context, so that the returned futures are `Send` regardless of the
+// argument types.
+impl TradeApi for TradeContext {
+    #[inline]
+    fn log_subscriber(&self) -> Arc<dyn Subscriber + Send + Sync> {
+        TradeContext::log_subscriber(self)
+    }
+
+    #[inline]
+    fn set_risk_manager(&self, risk_manager: Option<RiskManager>) {
+        TradeContext::set_risk_manager(self, risk_manager)
+    }
+
+    #[inline]
+    fn risk_manager(&self) -> Option<RiskManager> {
+        TradeContext::risk_manager(self)
+    }
+
+    #[inline]
+    fn connection_events(&self) -> mpsc::UnboundedReceiver<ConnectionEvent> {
+        TradeContext::connection_events(self)
+    }
+
+    fn subscribe<I>(&self, topics: I) -> impl Future<Output = Result<()>> + Send
+    where
+        I: IntoIterator<Item = TopicType>,
+    {
+        TradeContext::subscribe(self, topics.into_iter().collect::<Vec<_>>())
+    }
+
+    fn unsubscribe<I>(&self, topics: I) -> impl Future<Output = Result<()>> + Send
+    where
+        I: IntoIterator<Item = TopicType>,
+    {
+        TradeContext::unsubscribe(self, topics.into_iter().collect::<Vec<_>>())
+    }
+
+    fn history_executions(
+        &self,
+        options: impl Into<Option<GetHistoryExecutionsOptions>>,
+    ) -> impl Future<Output = Result<Vec<Execution>>> + Send {
+        TradeContext::history_executions(self, options.into())
+    }
+
+    fn today_executions(
+        &self,
+        options: impl Into<Option<GetTodayExecutionsOptions>>,
+    ) -> impl Future<Output = Result<Vec<Execution>>> + Send {
+        TradeContext::today_executions(self, options.into())
+    }
+
+    fn history_orders(
+        &self,
+        options: impl Into<Option<GetHistoryOrdersOptions>>,
+    ) -> impl Future<Output = Result<Vec<Order>>> + Send {
+        TradeContext::history_orders(self, options.into())
+    }
+
+    fn today_orders(
+        &self,
+        options: impl Into<Option<GetTodayOrdersOptions>>,
+    ) -> impl Future<Output = Result<Vec<Order>>> + Send {
+        TradeContext::today_orders(self, options.into())
+    }
+
+    fn open_orders(&self) -> impl Future<Output = Result<Vec<Order>>> + Send {
+        TradeContext::open_orders(self)
+    }
+
+    fn symbol_orders(
+        &self,
+        symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<Vec<Order>>> + Send {
+        TradeContext::symbol_orders(self, symbol.into())
+    }
+
+    fn order(
+        &self,
+        order_id: impl Into<String>,
+    ) -> impl Future<Output = Result<Option<Order>>> + Send {
+        TradeContext::order(self, order_id.into())
+    }
+
+    fn replace_order(
+        &self,
+        options: ReplaceOrderOptions,
+    ) -> impl Future<Output = Result<()>> + Send {
+        TradeContext::replace_order(self, options)
+    }
+
+    fn submit_order(
+        &self,
+        options: SubmitOrderOptions,
+    ) -> impl Future<Output = Result<SubmitOrderResponse>> + Send {
+        TradeContext::submit_order(self, options)
+    }
+
+    fn submit_order_with_retry(
+        &self,
+        options: SubmitOrderOptions,
+        max_retries: usize,
+    ) -> impl Future<Output = Result<SubmitOrderResponse>> + Send {
+        TradeContext::submit_order_with_retry(self, options, max_retries)
+    }
+
+    fn cancel_order(&self, order_id: impl Into<String>) -> impl Future<Output = Result<()>> + Send {
+        TradeContext::cancel_order(self, order_id.into())
+    }
+
+    fn account_balance(
+        &self,
+        currency: Option<&str>,
+    ) -> impl Future<Output = Result<Vec<AccountBalance>>> + Send {
+        TradeContext::account_balance(self, currency)
+    }
+
+    fn cash_flow(
+        &self,
+        options: GetCashFlowOptions,
+    ) -> impl Future<Output = Result<Vec<CashFlow>>> + Send {
+        TradeContext::cash_flow(self, options)
+    }
+
+    fn fund_positions(
+        &self,
+        opts: impl Into<Option<GetFundPositionsOptions>>,
+    ) -> impl Future<Output = Result<FundPositionsResponse>> + Send {
+        TradeContext::fund_positions(self, opts.into())
+    }
+
+    fn stock_positions(
+        &self,
+        opts: impl Into<Option<GetStockPositionsOptions>>,
+    ) -> impl Future<Output = Result<StockPositionsResponse>> + Send {
+        TradeContext::stock_positions(self, opts.into())
+    }
+
+    fn margin_ratio(
+        &self,
+        symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<MarginRatio>> + Send {
+        TradeContext::margin_ratio(self, symbol.into())
+    }
+
+    fn order_detail(
+        &self,
+        order_id: impl Into<String>,
+    ) -> impl Future<Output = Result<OrderDetail>> + Send {
+        TradeContext::order_detail(self, order_id.into())
+    }
+
+    fn wait_for_order<F>(
+        &self,
+        order_id: impl Into<String>,
+        predicate: F,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<OrderDetail>> + Send
+    where
+        F: Fn(OrderStatus) -> bool + Send,
+    {
+        TradeContext::wait_for_order(self, order_id.into(), predicate, timeout)
+    }
+
+    fn submit_and_wait(
+        &self,
+        options: SubmitOrderOptions,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<OrderDetail>> + Send {
+        TradeContext::submit_and_wait(self, options, timeout)
+    }
+
+    fn estimate_max_purchase_quantity(
+        &self,
+        opts: EstimateMaxPurchaseQuantityOptions,
+    ) -> impl Future<Output = Result<EstimateMaxPurchaseQuantityResponse>> + Send {
+        TradeContext::estimate_max_purchase_quantity(self, opts)
+    }
+}
@@ -1,25 +1,145 @@
-use std::sync::Arc;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
-use longport_httpcli::{HttpClient, Json, Method};
-use longport_wscli::WsClientError;
+use longport_httpcli::{HttpClient, HttpClientError, Json, Method};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use tokio::sync::{mpsc, oneshot};
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::Instant,
+};
 use tracing::{dispatcher, instrument::WithSubscriber, Subscriber};
 
 use crate::{
+    connection::ConnectionEventSenders,
+    reconnect::ReconnectPolicy,
     serde_utils,
     trade::{
-        core::{Command, Core},
+        core::{fetch_today_orders, Command, Core},
+        risk::OrderRequest,
         AccountBalance, CashFlow, EstimateMaxPurchaseQuantityOptions, Execution,
         FundPositionsResponse, GetCashFlowOptions, GetFundPositionsOptions,
         GetHistoryExecutionsOptions, GetHistoryOrdersOptions, GetStockPositionsOptions,
         GetTodayExecutionsOptions, GetTodayOrdersOptions, MarginRatio, Order, OrderDetail,
-        PushEvent, ReplaceOrderOptions, StockPositionsResponse, SubmitOrderOptions, TopicType,
+        OrderStatus, PushEvent, ReplaceOrderOptions, RiskManager, RiskViolation,
+        StockPositionsResponse, SubmitOrderOptions, TopicType, TradeApi,
     },
-    Config, Result,
+    Config, ConnectionEvent, Error, Result,
 };
 
+/// Initial delay before retrying a failed order submission
+const SUBMIT_RETRY_INITIAL_DELAY: Duration = Duration::from_secs(1);
+/// Maximum delay before retrying a failed order submission
+const SUBMIT_RETRY_MAX_DELAY: Duration = Duration::from_secs(8);
+/// Interval of polling the order detail if no order changed push arrives
+const ORDER_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Prefix of the remarks carrying the client order ids
+/// Returns `true` if it is unknown whether the request reached the server
+fn is_ambiguous_error(err: &Error) -> bool {
+    match err {
+        Error::HttpClient(HttpClientError::RequestTimeout | HttpClientError::Http(_)) => true,
+        Error::HttpClient(HttpClientError::BadStatus(status)) => {
+            status.is_server_error() || status.as_u16() == 429
+        }
+        _ => false,
+    }
+}
+
+/// Submits the order and retries after the ambiguous failures, see
+/// [`TradeContext::submit_order_with_retry`]
+pub(super) async fn submit_with_retry<A: TradeApi + ?Sized>(
+    api: &A,
+    options: SubmitOrderOptions,
+    max_retries: usize,
+) -> Result<SubmitOrderResponse> {
+    let client_order_id = options
+        .client_order_id
+        .clone()
+        .unwrap_or_else(|| format!("{:016x}", rand::random::<u64>()));
+    let options = options
+        .client_order_id(client_order_id.clone())
+        .append_client_order_id()?;
+    let remark = options.remark.clone().unwrap_or_default();
+    let is_submitted = |order: &Order| {
+        order.remark == remark
+            && order.symbol == options.symbol
+            && order.side == options.side
+            && order.quantity == options.submitted_quantity
+    };
+    let mut backoff =
+        ReconnectPolicy::exponential(SUBMIT_RETRY_INITIAL_DELAY, SUBMIT_RETRY_MAX_DELAY)
+            .max_attempts(max_retries)
+            .backoff();
+
+    let mut last_err = match api.submit_order(options.clone()).await {
+        Err(err) if is_ambiguous_error(&err) => err,
+        res => return res,
+    };
+
+    loop {
+        let Some(delay) = backoff.next_delay() else {
+            return Err(last_err);
+        };
+        tracing::warn!(
+            client_order_id = %client_order_id,
+            error = %last_err,
+            attempts = backoff.attempts(),
+            "failed to submit order, retrying",
+        );
+        tokio::time::sleep(delay).await;
+
+        match api
+            .today_orders(GetTodayOrdersOptions::new().symbol(options.symbol.clone()))
+            .await
+        {
+            Ok(orders) => {
+                if let Some(order) = orders.into_iter().find(is_submitted) {
+                    return Ok(SubmitOrderResponse {
+                        order_id: order.order_id,
+                    });
+                }
+            }
+            Err(err) if is_ambiguous_error(&err) => {
+                // the order must not be submitted again until it is known
+                // to be missing
+                last_err = err;
+                continue;
+            }
+            Err(err) => return Err(err),
+        }
+
+        match api.submit_order(options.clone()).await {
+            Err(err) if is_ambiguous_error(&err) => last_err = err,
+            res => return res,
+        }
+    }
+}
+
+/// Polls the order detail until its status satisfies the predicate or the
+/// timeout elapses, returns the latest order detail
+pub(super) async fn poll_order<A, F>(
+    api: &A,
+    order_id: String,
+    predicate: F,
+    timeout: Duration,
+) -> Result<OrderDetail>
+where
+    A: TradeApi + ?Sized,
+    F: Fn(OrderStatus) -> bool + Send,
+{
+    let deadline = Instant::now() + timeout;
+    loop {
+        let detail = api.order_detail(order_id.clone()).await?;
+        let now = Instant::now();
+        if predicate(detail.status) || now >= deadline {
+            return Ok(detail);
+        }
+        tokio::time::sleep_until((now + ORDER_POLL_INTERVAL).min(deadline)).await;
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct EmptyResponse {}
 
@@ -44,6 +164,8 @@ pub struct EstimateMaxPurchaseQuantityResponse {
 struct InnerTradeContext {
     command_tx: mpsc::UnboundedSender<Command>,
     http_cli: HttpClient,
+    connection_event_tx: ConnectionEventSenders,
+    risk_manager: Mutex<Option<RiskManager>>,
     log_subscriber: Arc<dyn Subscriber + Send + Sync>,
 }
 
@@ -73,7 +195,8 @@ impl TradeContext {
         let http_cli = config.create_http_client();
         let (command_tx, command_rx) = mpsc::unbounded_channel();
         let (push_tx, push_rx) = mpsc::unbounded_channel();
-        let core = Core::try_new(config, command_rx, push_tx)
+        let connection_event_tx = ConnectionEventSenders::default();
+        let core = Core::try_new(config, command_rx, push_tx, connection_event_tx.clone())
             .with_subscriber(log_subscriber.clone())
             .await?;
         tokio::spawn(core.run().with_subscriber(log_subscriber.clone()));
@@ -86,6 +209,8 @@ impl TradeContext {
             TradeContext(Arc::new(InnerTradeContext {
                 http_cli,
                 command_tx,
+                connection_event_tx,
+                risk_manager: Mutex::new(None),
                 log_subscriber,
             })),
             push_rx,
@@ -98,6 +223,44 @@ impl TradeContext {
         self.0.log_subscriber.clone()
     }
 
+    /// Set the risk manager which checks the orders before they are submitted
+    /// or replaced, `None` disables the checks
+    pub fn set_risk_manager(&self, risk_manager: Option<RiskManager>) {
+        *self.0.risk_manager.lock().unwrap() = risk_manager;
+    }
+
+    /// Returns the risk manager
+    pub fn risk_manager(&self) -> Option<RiskManager> {
+        self.0.risk_manager.lock().unwrap().clone()
+    }
+
+    /// Returns a receiver of the connection state events
+    ///
+    /// Every call creates a new receiver, and only the events that occur
+    /// after the call are delivered to it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// use longport::{TradeContext, Config};
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let config = Arc::new(Config::from_env()?);
+    /// let (ctx, _) = TradeContext::try_new(config).await?;
+    ///
+    /// let mut events = ctx.connection_events();
+    /// while let Some(event) = events.recv().await {
+    ///     println!("{:?}", event);
+    /// }
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// # });
+    /// ```
+    pub fn connection_events(&self) -> mpsc::UnboundedReceiver<ConnectionEvent> {
+        self.0.connection_event_tx.subscribe()
+    }
+
     /// Subscribe
     ///
     /// Reference: <https://open.longportapp.com/en/docs/trade/trade-push#subscribe>
@@ -146,8 +309,10 @@ impl TradeContext {
                 topics: topics.into_iter().collect(),
                 reply_tx,
             })
-            .map_err(|_| WsClientError::ClientClosed)?;
-        reply_rx.await.map_err(|_| WsClientError::ClientClosed)?
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+        reply_rx
+            .await
+            .map_err(|_| self.0.connection_event_tx.closed_error())?
     }
 
     /// Unsubscribe
@@ -164,8 +329,10 @@ impl TradeContext {
                 topics: topics.into_iter().collect(),
                 reply_tx,
             })
-            .map_err(|_| WsClientError::ClientClosed)?;
-        reply_rx.await.map_err(|_| WsClientError::ClientClosed)?
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+        reply_rx
+            .await
+            .map_err(|_| self.0.connection_event_tx.closed_error())?
     }
 
     /// Get history executions
@@ -349,28 +516,107 @@ impl TradeContext {
         &self,
         options: impl Into<Option<GetTodayOrdersOptions>>,
     ) -> Result<Vec<Order>> {
-        #[derive(Deserialize)]
-        struct Response {
-            orders: Vec<Order>,
-        }
+        fetch_today_orders(&self.0.http_cli, options.into())
+            .with_subscriber(self.0.log_subscriber.clone())
+            .await
+    }
 
+    /// Get the cached open orders of today
+    ///
+    /// The orders are maintained from the snapshot of today's orders and the
+    /// order changed pushes without HTTP requests, subscribe to
+    /// [`TopicType::Private`] to enable the cache. The snapshot is fetched
+    /// again after reconnecting, and the missed changes are pushed as
+    /// [`PushEvent::OrdersReconciled`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// use longport::{
+    ///     trade::{TopicType, TradeContext},
+    ///     Config,
+    /// };
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let config = Arc::new(Config::from_env()?);
+    /// let (ctx, _) = TradeContext::try_new(config).await?;
+    ///
+    /// ctx.subscribe([TopicType::Private]).await?;
+    /// let resp = ctx.open_orders().await?;
+    /// println!("{:?}", resp);
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// # });
+    /// ```
+    pub async fn open_orders(&self) -> Result<Vec<Order>> {
+        Ok(self.cached_orders(None, true).await?.unwrap_or_default())
+    }
+
+    /// Get the cached orders of today of the security, see
+    /// [`TradeContext::open_orders`]
+    pub async fn symbol_orders(&self, symbol: impl Into<String>) -> Result<Vec<Order>> {
         Ok(self
-            .0
-            .http_cli
-            .request(Method::GET, "/v1/trade/order/today")
-            .query_params(options.into().unwrap_or_default())
-            .response::<Json<Response>>()
-            .send()
-            .with_subscriber(self.0.log_subscriber.clone())
+            .cached_orders(Some(symbol.into()), false)
             .await?
-            .0
-            .orders)
+            .unwrap_or_default())
+    }
+
+    /// Get a cached order of today, see [`TradeContext::open_orders`]
+    pub async fn order(&self, order_id: impl Into<String>) -> Result<Option<Order>> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.0
+            .command_tx
+            .send(Command::GetOrder {
+                order_id: order_id.into(),
+                reply_tx,
+            })
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+        reply_rx
+            .await
+            .map_err(|_| self.0.connection_event_tx.closed_error())
+    }
+
+    /// Returns `None` if the orders are not tracked
+    async fn cached_orders(
+        &self,
+        symbol: Option<String>,
+        open_only: bool,
+    ) -> Result<Option<Vec<Order>>> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.0
+            .command_tx
+            .send(Command::GetOrders {
+                symbol,
+                open_only,
+                reply_tx,
+            })
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+        reply_rx
+            .await
+            .map_err(|_| self.0.connection_event_tx.closed_error())
+    }
+
+    /// Returns the cached orders of today of the security for the risk checks
+    async fn risk_orders(&self, symbol: &str) -> Result<Vec<Order>> {
+        self.cached_orders(Some(symbol.to_string()), false)
+            .await?
+            .ok_or_else(|| Error::RiskCheckFailed {
+                symbol: symbol.to_string(),
+                violation: RiskViolation::OrdersNotTracked,
+            })
     }
 
     /// Replace order
     ///
     /// Reference: <https://open.longportapp.com/en/docs/trade/order/replace>
     ///
+    /// If a risk manager is set, the order is checked before it is replaced
+    /// and [`Error::RiskCheckFailed`](crate::Error::RiskCheckFailed) is
+    /// returned if it is rejected. The checks read the cached orders, so
+    /// [`TopicType::Private`] must be subscribed, and the new quantity is
+    /// reserved until the change of the order is pushed.
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -394,22 +640,68 @@ impl TradeContext {
     /// # });
     /// ```
     pub async fn replace_order(&self, options: ReplaceOrderOptions) -> Result<()> {
-        Ok(self
-            .0
+        let reservation = match self.risk_manager() {
+            Some(risk_manager) => {
+                let (symbol, side, executed_quantity, updated_at) =
+                    match self.order(&options.order_id).await? {
+                        Some(order) => (
+                            order.symbol,
+                            order.side,
+                            order.executed_quantity,
+                            order.updated_at.unwrap_or(order.submitted_at),
+                        ),
+                        None => {
+                            let detail = self.order_detail(&options.order_id).await?;
+                            (
+                                detail.symbol,
+                                detail.side,
+                                detail.executed_quantity,
+                                detail.updated_at.unwrap_or(detail.submitted_at),
+                            )
+                        }
+                    };
+                let orders = self.risk_orders(&symbol).await?;
+                Some(risk_manager.reserve(
+                    &OrderRequest::replace(
+                        Some(&options.order_id),
+                        &symbol,
+                        side,
+                        executed_quantity,
+                        options.quantity,
+                        options.price,
+                    ),
+                    &orders,
+                    Some(updated_at),
+                )?)
+            }
+            None => None,
+        };
+
+        let order_id = options.order_id.clone();
+        self.0
             .http_cli
             .request(Method::PUT, "/v1/trade/order")
             .body(Json(options))
             .response::<Json<EmptyResponse>>()
             .send()
             .with_subscriber(self.0.log_subscriber.clone())
-            .await
-            .map(|_| ())?)
+            .await?;
+        if let Some(reservation) = reservation {
+            reservation.keep(&order_id);
+        }
+        Ok(())
     }
 
     /// Submit order
     ///
     /// Reference: <https://open.longportapp.com/en/docs/trade/order/submit>
     ///
+    /// If a risk manager is set, the order is checked before it is submitted
+    /// and [`Error::RiskCheckFailed`](crate::Error::RiskCheckFailed) is
+    /// returned if it is rejected. The checks read the cached orders, so
+    /// [`TopicType::Private`] must be subscribed, and the quantity of the
+    /// order is reserved until the order is pushed.
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -439,6 +731,15 @@ impl TradeContext {
     /// # });
     /// ```
     pub async fn submit_order(&self, options: SubmitOrderOptions) -> Result<SubmitOrderResponse> {
+        let options = options.append_client_order_id()?;
+        let reservation = match self.risk_manager() {
+            Some(risk_manager) => {
+                let orders = self.risk_orders(&options.symbol).await?;
+                Some(risk_manager.reserve(&OrderRequest::submit(&options), &orders, None)?)
+            }
+            None => None,
+        };
+
         let resp: SubmitOrderResponse = self
             .0
             .http_cli
@@ -449,6 +750,62 @@ impl TradeContext {
             .with_subscriber(self.0.log_subscriber.clone())
             .await?
             .0;
+        if let Some(reservation) = reservation {
+            reservation.keep(&resp.order_id);
+        }
+        _ = self.0.command_tx.send(Command::SubmittedOrder {
+            order_id: resp.order_id.clone(),
+        });
+        Ok(resp)
+    }
+
+    /// Submit order, and retry after the failures which leave it unknown
+    /// whether the order is submitted, such as the request timeouts
+    ///
+    /// The client order id of the order (see
+    /// [`SubmitOrderOptions::client_order_id`]) is appended to the remark, a
+    /// random one is generated if it is not set. Before every retry, the
+    /// order is looked up in today's orders by the remark, the symbol, the
+    /// side and the quantity, and the found order is returned instead of
+    /// being submitted again. Gives up and returns the last error after
+    /// `max_retries` retries.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// use longport::{
+    ///     decimal,
+    ///     trade::{OrderSide, OrderType, SubmitOrderOptions, TimeInForceType, TradeContext},
+    ///     Config,
+    /// };
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let config = Arc::new(Config::from_env()?);
+    /// let (ctx, _) = TradeContext::try_new(config).await?;
+    ///
+    /// let opts = SubmitOrderOptions::new(
+    ///     "700.HK",
+    ///     OrderType::LO,
+    ///     OrderSide::Buy,
+    ///     decimal!(200),
+    ///     TimeInForceType::Day,
+    /// )
+    /// .submitted_price(decimal!(50i32))
+    /// .client_order_id("my-order-1");
+    /// let resp = ctx.submit_order_with_retry(opts, 3).await?;
+    /// println!("{:?}", resp);
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// # });
+    /// ```
+    pub async fn submit_order_with_retry(
+        &self,
+        options: SubmitOrderOptions,
+        max_retries: usize,
+    ) -> Result<SubmitOrderResponse> {
+        let resp = submit_with_retry(self, options, max_retries).await?;
+        // flushes the pushes of the order if it is found by the reconciliation
         _ = self.0.command_tx.send(Command::SubmittedOrder {
             order_id: resp.order_id.clone(),
         });
@@ -738,6 +1095,142 @@ impl TradeContext {
             .0)
     }
 
+    /// Wait until the status of the order satisfies the predicate or the
+    /// timeout elapses, returns the latest order detail
+    ///
+    /// The order is watched with the order changed pushes, subscribe
+    /// [`TopicType::Private`] to receive them. The order detail is polled if
+    /// no push arrives in a while. When the timeout elapses, the latest order
+    /// detail is returned, whose status may not satisfy the predicate.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::{sync::Arc, time::Duration};
+    ///
+    /// use longport::{
+    ///     trade::{OrderStatus, TopicType, TradeContext},
+    ///     Config,
+    /// };
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let config = Arc::new(Config::from_env()?);
+    /// let (ctx, _) = TradeContext::try_new(config).await?;
+    /// ctx.subscribe([TopicType::Private]).await?;
+    ///
+    /// let resp = ctx
+    ///     .wait_for_order(
+    ///         "701276261045858304",
+    ///         OrderStatus::is_terminal,
+    ///         Duration::from_secs(30),
+    ///     )
+    ///     .await?;
+    /// println!("{:?}", resp);
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// # });
+    /// ```
+    pub async fn wait_for_order<F>(
+        &self,
+        order_id: impl Into<String>,
+        predicate: F,
+        timeout: Duration,
+    ) -> Result<OrderDetail>
+    where
+        F: Fn(OrderStatus) -> bool + Send,
+    {
+        let order_id = order_id.into();
+        let deadline = Instant::now() + timeout;
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        self.0
+            .command_tx
+            .send(Command::WatchOrder {
+                order_id: order_id.clone(),
+                tx,
+            })
+            .map_err(|_| self.0.connection_event_tx.closed_error())?;
+
+        // the order may be changed before the watcher is added
+        let mut detail = self.order_detail(order_id.clone()).await?;
+        // `true` if a push is received after the order detail is fetched
+        let mut is_stale = false;
+        loop {
+            let now = Instant::now();
+            if predicate(detail.status) {
+                return Ok(detail);
+            }
+            if now >= deadline {
+                if is_stale {
+                    detail = self.order_detail(order_id.clone()).await?;
+                }
+                return Ok(detail);
+            }
+
+            match tokio::time::timeout_at((now + ORDER_POLL_INTERVAL).min(deadline), rx.recv())
+                .await
+            {
+                Ok(Some(order_changed)) if !predicate(order_changed.status) => {
+                    is_stale = true;
+                    continue;
+                }
+                Ok(Some(_)) | Err(_) => {}
+                Ok(None) => return Err(self.0.connection_event_tx.closed_error()),
+            }
+            detail = self.order_detail(order_id.clone()).await?;
+            is_stale = false;
+        }
+    }
+
+    /// Submit order, and wait until it is filled, rejected, canceled, expired
+    /// or the timeout elapses, returns the latest order detail
+    ///
+    /// See [`TradeContext::wait_for_order`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::{sync::Arc, time::Duration};
+    ///
+    /// use longport::{
+    ///     decimal,
+    ///     trade::{
+    ///         OrderSide, OrderType, SubmitOrderOptions, TimeInForceType, TopicType, TradeContext,
+    ///     },
+    ///     Config,
+    /// };
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let config = Arc::new(Config::from_env()?);
+    /// let (ctx, _) = TradeContext::try_new(config).await?;
+    /// ctx.subscribe([TopicType::Private]).await?;
+    ///
+    /// let opts = SubmitOrderOptions::new(
+    ///     "700.HK",
+    ///     OrderType::LO,
+    ///     OrderSide::Buy,
+    ///     decimal!(200),
+    ///     TimeInForceType::Day,
+    /// )
+    /// .submitted_price(decimal!(50i32));
+    /// let resp = ctx.submit_and_wait(opts, Duration::from_secs(30)).await?;
+    /// println!("{:?}", resp.status);
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// # });
+    /// ```
+    pub async fn submit_and_wait(
+        &self,
+        options: SubmitOrderOptions,
+        timeout: Duration,
+    ) -> Result<OrderDetail> {
+        let deadline = Instant::now() + timeout;
+        let resp = self.submit_order(options).await?;
+        self.wait_for_order(
+            resp.order_id,
+            OrderStatus::is_terminal,
+            deadline.saturating_duration_since(Instant::now()),
+        )
+        .await
+    }
+
     /// Estimating the maximum purchase quantity for Hong Kong and US stocks,
     /// warrants, and options
     ///
@@ -1,25 +1,33 @@
 use std::{
-    collections::{HashSet, VecDeque},
+    collections::{HashMap, HashSet, VecDeque},
     sync::Arc,
     time::Duration,
 };
 
-use longport_httpcli::HttpClient;
+use longport_httpcli::{HttpClient, Json, Method};
 use longport_proto::trade::{Sub, SubResponse, Unsub, UnsubResponse};
 use longport_wscli::{
     CodecType, Platform, ProtocolVersion, WsClient, WsClientError, WsEvent, WsSession,
+    EVENT_CHANNEL_CAPACITY,
 };
+use serde::Deserialize;
 use tokio::{
     sync::{mpsc, oneshot},
     time::Instant,
 };
 
 use crate::{
-    trade::{cmd_code, PushEvent, PushOrderChanged, TopicType},
+    connection::{ConnectionEvent, ConnectionEventSenders},
+    trade::{
+        cmd_code, store::OrderStore, GetTodayOrdersOptions, Order, PushEvent, PushOrderChanged,
+        PushOrdersReconciled, TopicType,
+    },
     Config, Result,
 };
 
-const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+/// The interval to refresh the snapshot of today's orders, so that the orders
+/// of the previous day are removed from the order cache
+const SYNC_ORDERS_INTERVAL: Duration = Duration::from_secs(30 * 60);
 
 pub(crate) enum Command {
     Subscribe {
@@ -33,20 +41,36 @@ pub(crate) enum Command {
     SubmittedOrder {
         order_id: String,
     },
+    GetOrder {
+        order_id: String,
+        reply_tx: oneshot::Sender<Option<Order>>,
+    },
+    GetOrders {
+        symbol: Option<String>,
+        open_only: bool,
+        reply_tx: oneshot::Sender<Option<Vec<Order>>>,
+    },
+    WatchOrder {
+        order_id: String,
+        tx: mpsc::UnboundedSender<PushOrderChanged>,
+    },
 }
 
 pub(crate) struct Core {
     config: Arc<Config>,
     command_rx: mpsc::UnboundedReceiver<Command>,
     push_tx: mpsc::UnboundedSender<PushEvent>,
-    event_tx: mpsc::UnboundedSender<WsEvent>,
-    event_rx: mpsc::UnboundedReceiver<WsEvent>,
+    connection_event_tx: ConnectionEventSenders,
+    event_tx: mpsc::Sender<WsEvent>,
+    event_rx: mpsc::Receiver<WsEvent>,
     http_cli: HttpClient,
     ws_cli: WsClient,
     session: Option<WsSession>,
     close: bool,
     subscriptions: HashSet<String>,
     unknown_orders: VecDeque<(Instant, PushOrderChanged)>,
+    orders: OrderStore,
+    order_watchers: HashMap<String, Vec<mpsc::UnboundedSender<PushOrderChanged>>>,
 }
 
 impl Core {
@@ -54,11 +78,12 @@ impl Core {
         config: Arc<Config>,
         command_rx: mpsc::UnboundedReceiver<Command>,
         push_tx: mpsc::UnboundedSender<PushEvent>,
+        connection_event_tx: ConnectionEventSenders,
     ) -> Result<Self> {
         let http_cli = config.create_http_client();
         let otp = http_cli.get_otp_v2().await?;
 
-        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        let (event_tx, event_rx) = mpsc::channel(EVENT_CHANNEL_CAPACITY);
 
         tracing::info!("connecting to trade server");
         let (url, res) = config.create_trade_ws_request().await;
@@ -81,6 +106,7 @@ impl Core {
             config,
             command_rx,
             push_tx,
+            connection_event_tx,
             event_tx,
             event_rx,
             http_cli,
@@ -89,23 +115,47 @@ impl Core {
             close: false,
             subscriptions: HashSet::new(),
             unknown_orders: VecDeque::new(),
+            orders: OrderStore::default(),
+            order_watchers: HashMap::new(),
         })
     }
 
     pub(crate) async fn run(mut self) {
         while !self.close {
-            match self.main_loop().await {
+            let mut last_error = match self.main_loop().await {
                 Ok(()) => return,
-                Err(err) => tracing::error!(error = %err, "trade disconnected"),
-            }
+                Err(err) => {
+                    tracing::error!(error = %err, "trade disconnected");
+                    err.to_string()
+                }
+            };
+            self.connection_event_tx
+                .send(ConnectionEvent::Disconnected {
+                    reason: last_error.clone(),
+                });
 
+            let mut backoff = self.config.reconnect_policy.backoff();
             loop {
                 // reconnect
-                tokio::time::sleep(RECONNECT_DELAY).await;
+                let Some(delay) = backoff.next_delay() else {
+                    tracing::error!(
+                        attempts = backoff.attempts(),
+                        "give up reconnecting to trade server"
+                    );
+                    self.connection_event_tx.send(ConnectionEvent::GaveUp {
+                        attempts: backoff.attempts(),
+                        reason: last_error,
+                    });
+                    return;
+                };
+                tokio::time::sleep(delay).await;
 
                 tracing::info!("connecting to trade server");
                 let (url, res) = self.config.create_trade_ws_request().await;
                 let request = res.expect("BUG: failed to create trade ws request");
+                self.connection_event_tx.send(ConnectionEvent::Connecting {
+                    url: url.to_string(),
+                });
 
                 match WsClient::open(
                     request,
@@ -120,11 +170,19 @@ impl Core {
                     Ok(ws_cli) => self.ws_cli = ws_cli,
                     Err(err) => {
                         tracing::error!(error = %err, "failed to connect trade server");
+                        last_error = err.to_string();
+                        self.connection_event_tx
+                            .send(ConnectionEvent::Disconnected {
+                                reason: last_error.clone(),
+                            });
                         continue;
                     }
                 }
 
                 tracing::info!(url = url, "trade server connected");
+                self.connection_event_tx.send(ConnectionEvent::Connected {
+                    url: url.to_string(),
+                });
 
                 // request new session
                 match &self.session {
@@ -134,10 +192,18 @@ impl Core {
                             .request_reconnect(&session.session_id, Default::default())
                             .await
                         {
-                            Ok(new_session) => self.session = Some(new_session),
+                            Ok(new_session) => {
+                                self.session = Some(new_session);
+                                self.connection_event_tx.send(ConnectionEvent::Resumed);
+                            }
                             Err(err) => {
                                 self.session = None; // invalid session
                                 tracing::error!(error = %err, "failed to request session id");
+                                last_error = err.to_string();
+                                self.connection_event_tx
+                                    .send(ConnectionEvent::Disconnected {
+                                        reason: last_error.clone(),
+                                    });
                                 continue;
                             }
                         }
@@ -147,14 +213,28 @@ impl Core {
                             Ok(otp) => otp,
                             Err(err) => {
                                 tracing::error!(error = %err, "failed to request otp");
+                                last_error = err.to_string();
+                                self.connection_event_tx
+                                    .send(ConnectionEvent::Disconnected {
+                                        reason: last_error.clone(),
+                                    });
                                 continue;
                             }
                         };
 
                         match self.ws_cli.request_auth(otp, Default::default()).await {
-                            Ok(new_session) => self.session = Some(new_session),
+                            Ok(new_session) => {
+                                self.session = Some(new_session);
+                                self.connection_event_tx
+                                    .send(ConnectionEvent::Authenticated);
+                            }
                             Err(err) => {
                                 tracing::error!(error = %err, "failed to request session id");
+                                last_error = err.to_string();
+                                self.connection_event_tx
+                                    .send(ConnectionEvent::Disconnected {
+                                        reason: last_error.clone(),
+                                    });
                                 continue;
                             }
                         }
@@ -166,6 +246,11 @@ impl Core {
                     Ok(()) => break,
                     Err(err) => {
                         tracing::error!(error = %err, "failed to subscribe topics");
+                        last_error = err.to_string();
+                        self.connection_event_tx
+                            .send(ConnectionEvent::ResubscribeFailed {
+                                reason: last_error.clone(),
+                            });
                         continue;
                     }
                 }
@@ -175,6 +260,8 @@ impl Core {
 
     async fn main_loop(&mut self) -> Result<()> {
         let mut tick = tokio::time::interval(Duration::from_millis(500));
+        let mut sync_orders_tick =
+            tokio::time::interval_at(Instant::now() + SYNC_ORDERS_INTERVAL, SYNC_ORDERS_INTERVAL);
 
         loop {
             tokio::select! {
@@ -194,6 +281,11 @@ impl Core {
                     }
                 }
                 now = tick.tick() => self.handle_tick(now),
+                _ = sync_orders_tick.tick() => {
+                    if self.subscriptions.contains(&TopicType::Private.to_string()) {
+                        self.sync_orders(true).await;
+                    }
+                }
             }
         }
     }
@@ -209,6 +301,15 @@ impl Core {
         match PushEvent::parse(command_code, &body) {
             Ok(Some(event)) => {
                 tracing::info!(event = ?event, "push event");
+                if let PushEvent::OrderChanged(order_changed) = &event {
+                    if !self.orders.apply(order_changed) {
+                        tracing::info!(
+                            order_id = order_changed.order_id,
+                            "outdated order changed push"
+                        );
+                    }
+                    self.notify_order_watchers(order_changed);
+                }
                 let _ = self.push_tx.send(event);
             }
             Ok(None) => {}
@@ -223,11 +324,24 @@ impl Core {
         while let Some((t, _)) = self.unknown_orders.front() {
             if now - *t > Duration::from_secs(1) {
                 let (_, order_changed) = self.unknown_orders.pop_front().unwrap();
+                self.notify_order_watchers(&order_changed);
                 _ = self.push_tx.send(PushEvent::OrderChanged(order_changed));
             } else {
                 break;
             }
         }
+
+        self.order_watchers.retain(|_, watchers| {
+            watchers.retain(|tx| !tx.is_closed());
+            !watchers.is_empty()
+        });
+    }
+
+    /// Sends an order changed push to the watchers of the order
+    fn notify_order_watchers(&mut self, order_changed: &PushOrderChanged) {
+        if let Some(watchers) = self.order_watchers.get_mut(&order_changed.order_id) {
+            watchers.retain(|tx| tx.send(order_changed.clone()).is_ok());
+        }
     }
 
     async fn handle_command(&mut self, command: Command) -> Result<()> {
@@ -252,10 +366,32 @@ impl Core {
                     let Some((_, order_changed)) = self.unknown_orders.remove(idx) else {
                         unreachable!();
                     };
+                    self.notify_order_watchers(&order_changed);
                     let _ = self.push_tx.send(PushEvent::OrderChanged(order_changed));
                 }
                 Ok(())
             }
+            Command::GetOrder { order_id, reply_tx } => {
+                let _ = reply_tx.send(self.orders.order(&order_id));
+                Ok(())
+            }
+            Command::GetOrders {
+                symbol,
+                open_only,
+                reply_tx,
+            } => {
+                // the orders are not tracked without the private topic
+                let orders = self
+                    .subscriptions
+                    .contains(&TopicType::Private.to_string())
+                    .then(|| self.orders.orders(symbol.as_deref(), open_only));
+                let _ = reply_tx.send(orders);
+                Ok(())
+            }
+            Command::WatchOrder { order_id, tx } => {
+                self.order_watchers.entry(order_id).or_default().push(tx);
+                Ok(())
+            }
         }
     }
 
@@ -266,6 +402,9 @@ impl Core {
         tracing::info!(topics = ?req.topics, "subscribing topics");
         let resp: SubResponse = self.ws_cli.request(cmd_code::SUBSCRIBE, None, req).await?;
         self.subscriptions = resp.current.into_iter().collect();
+        if topics.contains(&TopicType::Private) {
+            self.sync_orders(false).await;
+        }
         Ok(())
     }
 
@@ -289,6 +428,59 @@ impl Core {
         };
         let resp: SubResponse = self.ws_cli.request(cmd_code::SUBSCRIBE, None, req).await?;
         self.subscriptions = resp.current.into_iter().collect();
+        if self.subscriptions.contains(&TopicType::Private.to_string()) {
+            self.sync_orders(true).await;
+        }
         Ok(())
     }
+
+    /// Replaces the order store with the snapshot of today's orders, the orders
+    /// changed or removed since the last snapshot are pushed as
+    /// [`PushEvent::OrdersReconciled`] if `reconcile` is `true`
+    async fn sync_orders(&mut self, reconcile: bool) {
+        match fetch_today_orders(&self.http_cli, None).await {
+            Ok(orders) => {
+                let (orders, removed) = self.orders.replace_snapshot(orders);
+                tracing::info!(
+                    changed = orders.len(),
+                    removed = removed.len(),
+                    "today orders synced"
+                );
+                if reconcile && (!orders.is_empty() || !removed.is_empty()) {
+                    let _ = self
+                        .push_tx
+                        .send(PushEvent::OrdersReconciled(PushOrdersReconciled {
+                            orders,
+                            removed,
+                        }));
+                }
+            }
+            Err(err) => {
+                tracing::warn!(error = %err, "failed to fetch today orders");
+            }
+        }
+    }
+}
+
+/// Requests today's orders, shared by [`TradeContext::today_orders`] and the
+/// order cache
+///
+/// [`TradeContext::today_orders`]: crate::trade::TradeContext::today_orders
+pub(super) async fn fetch_today_orders(
+    http_cli: &HttpClient,
+    options: Option<GetTodayOrdersOptions>,
+) -> Result<Vec<Order>> {
+    #[derive(Deserialize)]
+    struct Response {
+        orders: Vec<Order>,
+    }
+
+    Ok(http_cli
+        .request(Method::GET, "/v1/trade/order/today")
+        .query_params(options.unwrap_or_default())
+        .response::<Json<Response>>()
+        .send()
+        .await?
+        .0
+        .orders)
 }
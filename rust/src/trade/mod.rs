@@ -1,19 +1,28 @@
 //! Trade related types
 
+mod api;
 mod cmd_code;
 mod context;
 mod core;
+mod paper;
+mod portfolio;
 mod push_types;
 mod requests;
+mod risk;
+mod store;
 mod types;
 
+pub use api::TradeApi;
 pub use context::{EstimateMaxPurchaseQuantityResponse, SubmitOrderResponse, TradeContext};
-pub use push_types::{PushEvent, PushOrderChanged, TopicType};
+pub use paper::PaperTradeContext;
+pub use portfolio::{CurrencyPnl, PortfolioTracker, PositionPnl};
+pub use push_types::{PushEvent, PushOrderChanged, PushOrdersReconciled, TopicType};
 pub use requests::{
     EstimateMaxPurchaseQuantityOptions, GetCashFlowOptions, GetFundPositionsOptions,
     GetHistoryExecutionsOptions, GetHistoryOrdersOptions, GetStockPositionsOptions,
     GetTodayExecutionsOptions, GetTodayOrdersOptions, ReplaceOrderOptions, SubmitOrderOptions,
 };
+pub use risk::{RiskLimits, RiskManager, RiskViolation};
 pub use types::{
     AccountBalance, BalanceType, CashFlow, CashFlowDirection, CashInfo, ChargeCategoryCode,
     CommissionFreeStatus, DeductionStatus, Execution, FundPosition, FundPositionChannel,
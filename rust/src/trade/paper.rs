@@ -0,0 +1,1392 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    future::Future,
+    sync::{Arc, Mutex},
+};
+
+use rust_decimal::Decimal;
+use time::{Date, OffsetDateTime};
+use time_tz::{OffsetDateTimeExt, Tz};
+use tokio::sync::mpsc;
+use tracing::{subscriber::NoSubscriber, Subscriber};
+
+use crate::{
+    quote::{self, PushEventDetail, TradeSession},
+    trade::{
+        AccountBalance, CashFlow, CashInfo, CommissionFreeStatus, DeductionStatus,
+        EstimateMaxPurchaseQuantityOptions, EstimateMaxPurchaseQuantityResponse, Execution,
+        FundPositionsResponse, GetCashFlowOptions, GetFundPositionsOptions,
+        GetHistoryExecutionsOptions, GetHistoryOrdersOptions, GetStockPositionsOptions,
+        GetTodayExecutionsOptions, GetTodayOrdersOptions, MarginRatio, Order, OrderChargeDetail,
+        OrderDetail, OrderSide, OrderStatus, OrderTag, OrderType, OutsideRTH, PushEvent,
+        PushOrderChanged, ReplaceOrderOptions, RiskManager, StockPosition, StockPositionChannel,
+        StockPositionsResponse, SubmitOrderOptions, SubmitOrderResponse, TimeInForceType,
+        TopicType, TradeApi, TriggerStatus,
+    },
+    types::{market_currency, parse_market_from_symbol},
+    ConnectionEvent, Error, Market, Result,
+};
+
+const ACCOUNT_NO: &str = "paper";
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum TriggerKind {
+    None,
+    Touched,
+    TrailingAmount,
+    TrailingPercent,
+}
+
+/// Returns the trigger kind of the order type and whether it is executed as
+/// a market order once triggered
+fn classify_order_type(order_type: OrderType) -> Option<(TriggerKind, bool)> {
+    Some(match order_type {
+        OrderType::LO | OrderType::ELO | OrderType::ALO | OrderType::SLO | OrderType::ODD => {
+            (TriggerKind::None, false)
+        }
+        OrderType::MO | OrderType::AO => (TriggerKind::None, true),
+        OrderType::LIT => (TriggerKind::Touched, false),
+        OrderType::MIT => (TriggerKind::Touched, true),
+        OrderType::TSLPAMT => (TriggerKind::TrailingAmount, false),
+        OrderType::TSLPPCT => (TriggerKind::TrailingPercent, false),
+        OrderType::TSMAMT => (TriggerKind::TrailingAmount, true),
+        OrderType::TSMPCT => (TriggerKind::TrailingPercent, true),
+        OrderType::Unknown => return None,
+    })
+}
+
+fn market_timezone(market: Market) -> &'static Tz {
+    use longport_candlesticks::markets::*;
+
+    match market {
+        Market::US | Market::Unknown => US.timezone,
+        Market::HK => HK.timezone,
+        Market::CN => CN.timezone,
+        Market::SG => SG.timezone,
+    }
+}
+
+fn session_allowed(outside_rth: Option<OutsideRTH>, session: TradeSession) -> bool {
+    match session {
+        TradeSession::NormalTrade => true,
+        TradeSession::PreTrade | TradeSession::PostTrade => {
+            outside_rth == Some(OutsideRTH::AnyTime)
+        }
+        TradeSession::OvernightTrade => matches!(
+            outside_rth,
+            Some(OutsideRTH::AnyTime | OutsideRTH::Overnight)
+        ),
+    }
+}
+
+struct PaperOrder {
+    order: Order,
+    market: Market,
+    trigger_kind: TriggerKind,
+    is_market: bool,
+    triggered: bool,
+    /// The highest (sell) or lowest (buy) price since submission, used by the
+    /// trailing orders
+    extreme_price: Option<Decimal>,
+    reserved_cash: Decimal,
+}
+
+impl PaperOrder {
+    fn local_date(&self, t: OffsetDateTime) -> Date {
+        t.to_timezone(market_timezone(self.market)).date()
+    }
+
+    fn is_expired(&self, now: OffsetDateTime) -> bool {
+        let today = self.local_date(now);
+        match self.order.time_in_force {
+            TimeInForceType::GoodTilCanceled => false,
+            TimeInForceType::GoodTilDate => self
+                .order
+                .expire_date
+                .map(|expire_date| today > expire_date)
+                .unwrap_or_default(),
+            TimeInForceType::Day | TimeInForceType::Unknown => {
+                today > self.local_date(self.order.submitted_at)
+            }
+        }
+    }
+
+    /// Returns the trailing stop price for the specified extreme price
+    fn trailing_stop_price(&self, extreme_price: Decimal) -> Option<Decimal> {
+        let offset = match self.trigger_kind {
+            TriggerKind::TrailingAmount => self.order.trailing_amount?,
+            TriggerKind::TrailingPercent => {
+                extreme_price * self.order.trailing_percent? / Decimal::ONE_HUNDRED
+            }
+            TriggerKind::None | TriggerKind::Touched => return None,
+        };
+        Some(match self.order.side {
+            OrderSide::Buy => extreme_price + offset,
+            _ => extreme_price - offset,
+        })
+    }
+
+    /// Updates the trigger state with a new price, returns `true` if the order
+    /// has just been triggered
+    fn update_trigger(&mut self, price: Decimal, now: OffsetDateTime) -> bool {
+        if self.triggered {
+            return false;
+        }
+
+        let is_buy = self.order.side == OrderSide::Buy;
+        let triggered = match self.trigger_kind {
+            TriggerKind::None => return false,
+            TriggerKind::Touched => match self.order.trigger_price {
+                Some(trigger_price) if is_buy => price <= trigger_price,
+                Some(trigger_price) => price >= trigger_price,
+                None => false,
+            },
+            TriggerKind::TrailingAmount | TriggerKind::TrailingPercent => {
+                let extreme_price = match self.extreme_price {
+                    Some(extreme_price) if is_buy => extreme_price.min(price),
+                    Some(extreme_price) => extreme_price.max(price),
+                    None => price,
+                };
+                self.extreme_price = Some(extreme_price);
+                self.order.trigger_price = self.trailing_stop_price(extreme_price);
+                match self.order.trigger_price {
+                    Some(stop_price) if is_buy => price >= stop_price,
+                    Some(stop_price) => price <= stop_price,
+                    None => false,
+                }
+            }
+        };
+
+        if triggered {
+            self.triggered = true;
+            self.order.trigger_status = Some(TriggerStatus::Released);
+            self.order.trigger_at = Some(now);
+            self.order.updated_at = Some(now);
+
+            if !self.is_market
+                && matches!(
+                    self.trigger_kind,
+                    TriggerKind::TrailingAmount | TriggerKind::TrailingPercent
+                )
+            {
+                let limit_offset = self.order.limit_offset.unwrap_or_default();
+                self.order.price = self.order.trigger_price.map(|stop_price| match is_buy {
+                    true => stop_price + limit_offset,
+                    false => stop_price - limit_offset,
+                });
+            }
+        }
+
+        triggered
+    }
+
+    /// Returns `true` if the order can be executed at the specified price
+    fn is_marketable(&self, price: Decimal) -> bool {
+        if self.trigger_kind != TriggerKind::None && !self.triggered {
+            return false;
+        }
+        if self.is_market {
+            return true;
+        }
+        match (self.order.side, self.order.price) {
+            (OrderSide::Buy, Some(limit_price)) => price <= limit_price,
+            (_, Some(limit_price)) => price >= limit_price,
+            (_, None) => false,
+        }
+    }
+
+    fn reference_price(&self, last_price: Option<Decimal>) -> Option<Decimal> {
+        match self.is_market {
+            true => last_price.or(self.order.trigger_price),
+            false => self.order.price.or(self.order.trigger_price).or(last_price),
+        }
+    }
+
+    fn to_push_event(&self, last: Option<(Decimal, Decimal)>) -> PushEvent {
+        let order = &self.order;
+        PushEvent::OrderChanged(PushOrderChanged {
+            side: order.side,
+            stock_name: order.stock_name.clone(),
+            submitted_quantity: order.quantity,
+            symbol: order.symbol.clone(),
+            order_type: order.order_type,
+            submitted_price: order.price.unwrap_or_default(),
+            executed_quantity: order.executed_quantity,
+            executed_price: order.executed_price,
+            order_id: order.order_id.clone(),
+            currency: order.currency.clone(),
+            status: order.status,
+            submitted_at: order.submitted_at,
+            updated_at: order.updated_at.unwrap_or(order.submitted_at),
+            trigger_price: order.trigger_price,
+            msg: order.msg.clone(),
+            tag: order.tag,
+            trigger_status: order.trigger_status,
+            trigger_at: order.trigger_at,
+            trailing_amount: order.trailing_amount,
+            trailing_percent: order.trailing_percent,
+            limit_offset: order.limit_offset,
+            account_no: ACCOUNT_NO.to_string(),
+            last_share: last.map(|(quantity, _)| quantity),
+            last_price: last.map(|(_, price)| price),
+            remark: order.remark.clone(),
+        })
+    }
+}
+
+struct Position {
+    quantity: Decimal,
+    cost_price: Decimal,
+    market: Market,
+    currency: String,
+}
+
+struct LastPrice {
+    price: Decimal,
+    trade_session: TradeSession,
+}
+
+struct Simulator {
+    cash: BTreeMap<String, Decimal>,
+    orders: Vec<PaperOrder>,
+    positions: BTreeMap<String, Position>,
+    last_prices: HashMap<String, LastPrice>,
+    now: Option<OffsetDateTime>,
+    next_order_id: u64,
+    push_tx: mpsc::UnboundedSender<PushEvent>,
+    risk_manager: Option<RiskManager>,
+}
+
+impl Simulator {
+    #[inline]
+    fn now(&self) -> OffsetDateTime {
+        self.now.unwrap_or_else(OffsetDateTime::now_utc)
+    }
+
+    fn last_price(&self, symbol: &str) -> Option<Decimal> {
+        self.last_prices.get(symbol).map(|last| last.price)
+    }
+
+    fn frozen_cash(&self, currency: &str) -> Decimal {
+        self.orders
+            .iter()
+            .filter(|order| !order.order.status.is_terminal() && order.order.currency == currency)
+            .map(|order| order.reserved_cash)
+            .sum()
+    }
+
+    fn frozen_quantity(&self, symbol: &str) -> Decimal {
+        self.orders
+            .iter()
+            .filter(|order| {
+                !order.order.status.is_terminal()
+                    && order.order.side == OrderSide::Sell
+                    && order.order.symbol == symbol
+            })
+            .map(|order| order.order.quantity - order.order.executed_quantity)
+            .sum()
+    }
+
+    fn available_cash(&self, currency: &str) -> Decimal {
+        self.cash.get(currency).copied().unwrap_or_default() - self.frozen_cash(currency)
+    }
+
+    fn available_quantity(&self, symbol: &str) -> Decimal {
+        self.positions
+            .get(symbol)
+            .map(|position| position.quantity)
+            .unwrap_or_default()
+            - self.frozen_quantity(symbol)
+    }
+
+    /// Returns the open orders of the security except the specified one
+    fn open_orders(&self, symbol: &str, except_order_id: Option<&str>) -> Vec<Order> {
+        self.orders
+            .iter()
+            .filter(|order| {
+                order.order.symbol == symbol
+                    && !order.order.status.is_terminal()
+                    && Some(order.order.order_id.as_str()) != except_order_id
+            })
+            .map(|order| order.order.clone())
+            .collect()
+    }
+
+    fn find_order(&self, order_id: &str) -> Result<usize> {
+        self.orders
+            .iter()
+            .position(|order| order.order.order_id == order_id)
+            .ok_or_else(|| Error::PaperTrading(format!("order not found: {order_id}")))
+    }
+
+    /// Checks the buying power or the sellable quantity of the order and
+    /// reserves the cash of the buy orders
+    fn reserve(&self, order: &mut PaperOrder, released_cash: Decimal) -> Result<()> {
+        let quantity = order.order.quantity - order.order.executed_quantity;
+        match order.order.side {
+            OrderSide::Buy => {
+                order.reserved_cash = order
+                    .reference_price(self.last_price(&order.order.symbol))
+                    .map(|price| price * quantity)
+                    .unwrap_or_default();
+                if order.reserved_cash > self.available_cash(&order.order.currency) + released_cash
+                {
+                    return Err(Error::PaperTrading("insufficient buying power".to_string()));
+                }
+            }
+            _ => {
+                if quantity > self.available_quantity(&order.order.symbol) {
+                    return Err(Error::PaperTrading(
+                        "insufficient position quantity".to_string(),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn submit_order(&mut self, options: SubmitOrderOptions) -> Result<String> {
+        let market =
+            parse_market_from_symbol(&options.symbol).ok_or_else(|| Error::UnknownMarket {
+                symbol: options.symbol.clone(),
+            })?;
+        let (trigger_kind, is_market) =
+            classify_order_type(options.order_type).ok_or_else(|| {
+                Error::PaperTrading(format!("unsupported order type: {}", options.order_type))
+            })?;
+
+        if options.submitted_quantity <= Decimal::ZERO {
+            return Err(Error::PaperTrading("invalid quantity".to_string()));
+        }
+        if options.side == OrderSide::Unknown {
+            return Err(Error::PaperTrading("invalid order side".to_string()));
+        }
+        let missing = match trigger_kind {
+            TriggerKind::None | TriggerKind::Touched
+                if !is_market && options.submitted_price.is_none() =>
+            {
+                Some("submitted_price")
+            }
+            TriggerKind::Touched if options.trigger_price.is_none() => Some("trigger_price"),
+            TriggerKind::TrailingAmount if options.trailing_amount.is_none() => {
+                Some("trailing_amount")
+            }
+            TriggerKind::TrailingPercent if options.trailing_percent.is_none() => {
+                Some("trailing_percent")
+            }
+            TriggerKind::TrailingAmount | TriggerKind::TrailingPercent
+                if !is_market && options.limit_offset.is_none() =>
+            {
+                Some("limit_offset")
+            }
+            _ if options.time_in_force == TimeInForceType::GoodTilDate
+                && options.expire_date.is_none() =>
+            {
+                Some("expire_date")
+            }
+            _ => None,
+        };
+        if let Some(name) = missing {
+            return Err(Error::PaperTrading(format!("missing {name}")));
+        }
+        if let Some(risk_manager) = &self.risk_manager {
+            risk_manager.check_submit(&options, &self.open_orders(&options.symbol, None))?;
+        }
+
+        let now = self.now();
+        let order_id = self.next_order_id.to_string();
+        let mut order = PaperOrder {
+            order: Order {
+                order_id: order_id.clone(),
+                status: OrderStatus::New,
+                stock_name: String::new(),
+                quantity: options.submitted_quantity,
+                executed_quantity: Decimal::ZERO,
+                price: match trigger_kind {
+                    TriggerKind::None | TriggerKind::Touched if !is_market => {
+                        options.submitted_price
+                    }
+                    _ => None,
+                },
+                executed_price: None,
+                submitted_at: now,
+                side: options.side,
+                symbol: options.symbol.clone(),
+                order_type: options.order_type,
+                last_done: None,
+                trigger_price: options.trigger_price,
+                msg: String::new(),
+                tag: match options.time_in_force {
+                    TimeInForceType::GoodTilCanceled | TimeInForceType::GoodTilDate => {
+                        OrderTag::LongTerm
+                    }
+                    _ => OrderTag::Normal,
+                },
+                time_in_force: options.time_in_force,
+                expire_date: options.expire_date,
+                updated_at: Some(now),
+                trigger_at: None,
+                trailing_amount: options.trailing_amount,
+                trailing_percent: options.trailing_percent,
+                limit_offset: options.limit_offset,
+                trigger_status: (trigger_kind != TriggerKind::None)
+                    .then_some(TriggerStatus::Deactive),
+                currency: market_currency(market).to_string(),
+                outside_rth: options.outside_rth,
+                remark: options.remark.unwrap_or_default(),
+            },
+            market,
+            trigger_kind,
+            is_market,
+            triggered: false,
+            extreme_price: None,
+            reserved_cash: Decimal::ZERO,
+        };
+        self.reserve(&mut order, Decimal::ZERO)?;
+
+        self.next_order_id += 1;
+        let _ = self.push_tx.send(order.to_push_event(None));
+        self.orders.push(order);
+        self.match_with_last_price(self.orders.len() - 1);
+        Ok(order_id)
+    }
+
+    fn replace_order(&mut self, options: ReplaceOrderOptions) -> Result<()> {
+        let index = self.find_order(&options.order_id)?;
+        let order = &self.orders[index];
+        if order.order.status.is_terminal() {
+            return Err(Error::PaperTrading(format!(
+                "order cannot be replaced: {}",
+                options.order_id
+            )));
+        }
+        if options.quantity <= order.order.executed_quantity {
+            return Err(Error::PaperTrading("invalid quantity".to_string()));
+        }
+        if let Some(risk_manager) = &self.risk_manager {
+            risk_manager.check_replace(
+                &order.order.symbol,
+                order.order.side,
+                order.order.executed_quantity,
+                options.quantity,
+                options.price,
+                &self.open_orders(&order.order.symbol, Some(&options.order_id)),
+            )?;
+        }
+
+        let released_cash = order.reserved_cash;
+        let mut new_order = PaperOrder {
+            order: Order {
+                quantity: options.quantity,
+                price: options.price.or(order.order.price),
+                trigger_price: options.trigger_price.or(order.order.trigger_price),
+                limit_offset: options.limit_offset.or(order.order.limit_offset),
+                trailing_amount: options.trailing_amount.or(order.order.trailing_amount),
+                trailing_percent: options.trailing_percent.or(order.order.trailing_percent),
+                remark: options.remark.unwrap_or_else(|| order.order.remark.clone()),
+                ..order.order.clone()
+            },
+            ..*order
+        };
+        // the sell quantity of this order is not frozen against itself
+        if new_order.order.side == OrderSide::Sell {
+            let quantity = new_order.order.quantity - new_order.order.executed_quantity;
+            let frozen = order.order.quantity - order.order.executed_quantity;
+            if quantity > self.available_quantity(&new_order.order.symbol) + frozen {
+                return Err(Error::PaperTrading(
+                    "insufficient position quantity".to_string(),
+                ));
+            }
+        } else {
+            self.reserve(&mut new_order, released_cash)?;
+        }
+
+        let now = self.now();
+        new_order.order.status = OrderStatus::Replaced;
+        new_order.order.updated_at = Some(now);
+        let _ = self.push_tx.send(new_order.to_push_event(None));
+        self.orders[index] = new_order;
+        self.match_with_last_price(index);
+        Ok(())
+    }
+
+    fn cancel_order(&mut self, order_id: &str) -> Result<()> {
+        let index = self.find_order(order_id)?;
+        let now = self.now();
+        let order = &mut self.orders[index];
+        if order.order.status.is_terminal() {
+            return Err(Error::PaperTrading(format!(
+                "order cannot be canceled: {order_id}"
+            )));
+        }
+
+        order.order.status = OrderStatus::Canceled;
+        order.order.updated_at = Some(now);
+        order.reserved_cash = Decimal::ZERO;
+        let _ = self.push_tx.send(order.to_push_event(None));
+        Ok(())
+    }
+
+    fn expire_orders(&mut self) {
+        let now = self.now();
+        for order in &mut self.orders {
+            if !order.order.status.is_terminal() && order.is_expired(now) {
+                order.order.status = OrderStatus::Expired;
+                order.order.updated_at = Some(now);
+                order.reserved_cash = Decimal::ZERO;
+                let _ = self.push_tx.send(order.to_push_event(None));
+            }
+        }
+    }
+
+    fn match_with_last_price(&mut self, index: usize) {
+        let symbol = &self.orders[index].order.symbol;
+        if let Some(LastPrice {
+            price,
+            trade_session,
+        }) = self.last_prices.get(symbol)
+        {
+            self.match_order(index, *price, *trade_session);
+        }
+    }
+
+    fn match_order(&mut self, index: usize, price: Decimal, trade_session: TradeSession) {
+        let now = self.now();
+        let order = &mut self.orders[index];
+        if order.order.status.is_terminal() {
+            return;
+        }
+
+        order.order.last_done = Some(price);
+        if !session_allowed(order.order.outside_rth, trade_session) {
+            return;
+        }
+
+        if order.update_trigger(price, now) {
+            let _ = self.push_tx.send(order.to_push_event(None));
+        }
+        if !order.is_marketable(price) {
+            return;
+        }
+
+        let quantity = order.order.quantity - order.order.executed_quantity;
+        let amount = quantity * price;
+        let released_cash = order.reserved_cash;
+        let symbol = order.order.symbol.clone();
+        let currency = order.order.currency.clone();
+
+        if order.order.side == OrderSide::Buy
+            && amount > self.available_cash(&currency) + released_cash
+        {
+            let order = &mut self.orders[index];
+            order.order.status = OrderStatus::Rejected;
+            order.order.msg = "insufficient buying power".to_string();
+            order.order.updated_at = Some(now);
+            order.reserved_cash = Decimal::ZERO;
+            let _ = self.push_tx.send(order.to_push_event(None));
+            return;
+        }
+
+        let order = &mut self.orders[index];
+        let cash = self.cash.entry(currency.clone()).or_default();
+        let position = self
+            .positions
+            .entry(symbol.clone())
+            .or_insert_with(|| Position {
+                quantity: Decimal::ZERO,
+                cost_price: Decimal::ZERO,
+                market: order.market,
+                currency,
+            });
+
+        match order.order.side {
+            OrderSide::Buy => {
+                *cash -= amount;
+                position.cost_price = (position.quantity * position.cost_price + amount)
+                    / (position.quantity + quantity);
+                position.quantity += quantity;
+            }
+            _ => {
+                *cash += amount;
+                position.quantity -= quantity;
+            }
+        }
+        if position.quantity.is_zero() {
+            self.positions.remove(&symbol);
+        }
+
+        order.order.executed_price = Some(price);
+        order.order.executed_quantity = order.order.quantity;
+        order.order.status = OrderStatus::Filled;
+        order.order.updated_at = Some(now);
+        order.reserved_cash = Decimal::ZERO;
+        let _ = self
+            .push_tx
+            .send(order.to_push_event(Some((quantity, price))));
+    }
+
+    fn handle_price(
+        &mut self,
+        symbol: &str,
+        price: Decimal,
+        trade_session: TradeSession,
+        timestamp: OffsetDateTime,
+    ) {
+        if self.now.is_none_or(|now| timestamp > now) {
+            self.now = Some(timestamp);
+        }
+        self.expire_orders();
+        self.last_prices.insert(
+            symbol.to_string(),
+            LastPrice {
+                price,
+                trade_session,
+            },
+        );
+
+        for index in 0..self.orders.len() {
+            if self.orders[index].order.symbol == symbol {
+                self.match_order(index, price, trade_session);
+            }
+        }
+    }
+
+    fn handle_quote_event(&mut self, event: &quote::PushEvent) {
+        match &event.detail {
+            PushEventDetail::Quote(quote) if !quote.last_done.is_zero() => self.handle_price(
+                &event.symbol,
+                quote.last_done,
+                quote.trade_session,
+                quote.timestamp,
+            ),
+            PushEventDetail::Trade(trades) => {
+                for trade in &trades.trades {
+                    self.handle_price(
+                        &event.symbol,
+                        trade.price,
+                        trade.trade_session,
+                        trade.timestamp,
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn today_orders(&self, options: GetTodayOrdersOptions) -> Vec<Order> {
+        let now = self.now();
+        self.orders
+            .iter()
+            .filter(|order| {
+                !order.order.status.is_terminal()
+                    || order.local_date(order.order.submitted_at) == order.local_date(now)
+            })
+            .filter(|order| {
+                options
+                    .symbol
+                    .as_ref()
+                    .is_none_or(|symbol| &order.order.symbol == symbol)
+                    && (options.status.is_empty() || options.status.contains(&order.order.status))
+                    && options.side.is_none_or(|side| order.order.side == side)
+                    && options.market.is_none_or(|market| order.market == market)
+                    && options
+                        .order_id
+                        .as_ref()
+                        .is_none_or(|order_id| &order.order.order_id == order_id)
+            })
+            .map(|order| order.order.clone())
+            .collect()
+    }
+
+    fn order_detail(&self, order_id: &str) -> Result<OrderDetail> {
+        let order = self.orders[self.find_order(order_id)?].order.clone();
+        Ok(OrderDetail {
+            order_id: order.order_id,
+            status: order.status,
+            stock_name: order.stock_name,
+            quantity: order.quantity,
+            executed_quantity: order.executed_quantity,
+            price: order.price,
+            executed_price: order.executed_price,
+            submitted_at: order.submitted_at,
+            side: order.side,
+            symbol: order.symbol,
+            order_type: order.order_type,
+            last_done: order.last_done,
+            trigger_price: order.trigger_price,
+            msg: order.msg,
+            tag: order.tag,
+            time_in_force: order.time_in_force,
+            expire_date: order.expire_date,
+            updated_at: order.updated_at,
+            trigger_at: order.trigger_at,
+            trailing_amount: order.trailing_amount,
+            trailing_percent: order.trailing_percent,
+            limit_offset: order.limit_offset,
+            trigger_status: order.trigger_status,
+            outside_rth: order.outside_rth,
+            remark: order.remark,
+            free_status: CommissionFreeStatus::None,
+            free_amount: None,
+            free_currency: None,
+            deductions_status: DeductionStatus::None,
+            deductions_amount: None,
+            deductions_currency: None,
+            platform_deducted_status: DeductionStatus::None,
+            platform_deducted_amount: None,
+            platform_deducted_currency: None,
+            history: Vec::new(),
+            charge_detail: OrderChargeDetail {
+                total_amount: Decimal::ZERO,
+                currency: order.currency.clone(),
+                items: Vec::new(),
+            },
+            currency: order.currency,
+        })
+    }
+
+    fn stock_positions(&self, options: GetStockPositionsOptions) -> StockPositionsResponse {
+        let positions = self
+            .positions
+            .iter()
+            .filter(|(symbol, _)| options.symbols.is_empty() || options.symbols.contains(symbol))
+            .map(|(symbol, position)| StockPosition {
+                symbol: symbol.clone(),
+                symbol_name: String::new(),
+                quantity: position.quantity,
+                available_quantity: self.available_quantity(symbol),
+                currency: position.currency.clone(),
+                cost_price: position.cost_price,
+                market: position.market,
+                init_quantity: None,
+            })
+            .collect();
+        StockPositionsResponse {
+            channels: vec![StockPositionChannel {
+                account_channel: ACCOUNT_NO.to_string(),
+                positions,
+            }],
+        }
+    }
+
+    fn account_balance(&self, currency: Option<&str>) -> Vec<AccountBalance> {
+        self.cash
+            .iter()
+            .filter(|(cash_currency, _)| currency.is_none_or(|currency| currency == *cash_currency))
+            .map(|(currency, total_cash)| {
+                let frozen_cash = self.frozen_cash(currency);
+                let market_value: Decimal = self
+                    .positions
+                    .iter()
+                    .filter(|(_, position)| &position.currency == currency)
+                    .map(|(symbol, position)| {
+                        position.quantity * self.last_price(symbol).unwrap_or(position.cost_price)
+                    })
+                    .sum();
+                let available_cash = *total_cash - frozen_cash;
+                AccountBalance {
+                    total_cash: *total_cash,
+                    max_finance_amount: Decimal::ZERO,
+                    remaining_finance_amount: Decimal::ZERO,
+                    risk_level: 0,
+                    margin_call: Decimal::ZERO,
+                    currency: currency.clone(),
+                    cash_infos: vec![CashInfo {
+                        withdraw_cash: available_cash,
+                        available_cash,
+                        frozen_cash,
+                        settling_cash: Decimal::ZERO,
+                        currency: currency.clone(),
+                    }],
+                    net_assets: *total_cash + market_value,
+                    init_margin: Decimal::ZERO,
+                    maintenance_margin: Decimal::ZERO,
+                    buy_power: available_cash,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Paper trading context
+///
+/// A local simulator which exposes the order and asset methods of
+/// [`TradeContext`](crate::trade::TradeContext) and sends
+/// [`PushEvent::OrderChanged`] events, so that a strategy can be run against
+/// live or replayed quotes without placing real orders. It implements
+/// [`TradeApi`], so the code written against the trait runs unchanged.
+///
+/// Orders are matched against the latest prices of the [`quote::PushEvent`]s
+/// passed to [`PaperTradeContext::handle_quote_event`] or
+/// [`PaperTradeContext::attach`], and are always filled in full:
+///
+/// - `LO` (and `ELO`, `ALO`, `SLO`, `ODD`) is filled at the latest price once
+///   it reaches the submitted price
+/// - `MO` (and `AO`) is filled at the latest price
+/// - `LIT` / `MIT` are triggered when the price falls to the trigger price
+///   (buy) or rises to it (sell), then executed as `LO` / `MO`
+/// - `TSLPAMT` / `TSLPPCT` (and `TSMAMT` / `TSMPCT`) trail the lowest (buy) or
+///   highest (sell) price by the trailing amount or percent, once triggered the
+///   limit price is the trigger price plus (buy) or minus (sell) the limit
+///   offset. `trailing_percent` is in percent, `5` means `5%`
+///
+/// `Day` orders expire at the end of the trading day of the market and `GTD`
+/// orders after the expire date. Outside of the regular trading hours, orders
+/// are only matched if `outside_rth` is [`OutsideRTH::AnyTime`], or
+/// [`OutsideRTH::Overnight`] for the overnight session.
+///
+/// The simulated clock follows the timestamps of the quotes, so that replayed
+/// data behaves the same as live data.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use longport::{
+///     decimal,
+///     quote::{QuoteContext, SubFlags},
+///     trade::{OrderSide, OrderType, PaperTradeContext, SubmitOrderOptions, TimeInForceType},
+///     Config,
+/// };
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let config = Arc::new(Config::from_env()?);
+/// let (quote_ctx, quote_receiver) = QuoteContext::try_new(config).await?;
+/// quote_ctx
+///     .subscribe(["700.HK"], SubFlags::QUOTE, true)
+///     .await?;
+///
+/// let (ctx, mut receiver) = PaperTradeContext::new([("HKD", decimal!(1000000))]);
+/// let mut quote_receiver = ctx.attach(quote_receiver);
+///
+/// let opts = SubmitOrderOptions::new(
+///     "700.HK",
+///     OrderType::LO,
+///     OrderSide::Buy,
+///     decimal!(200),
+///     TimeInForceType::Day,
+/// )
+/// .submitted_price(decimal!(50i32));
+/// let resp = ctx.submit_order(opts).await?;
+/// println!("{:?}", resp);
+///
+/// while let Some(event) = receiver.recv().await {
+///     println!("{:?}", event);
+/// }
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// # });
+/// ```
+#[derive(Clone)]
+pub struct PaperTradeContext(Arc<Mutex<Simulator>>);
+
+impl PaperTradeContext {
+    /// Create a `PaperTradeContext` with the initial cash of each currency
+    pub fn new<I, T>(cash: I) -> (Self, mpsc::UnboundedReceiver<PushEvent>)
+    where
+        I: IntoIterator<Item = (T, Decimal)>,
+        T: Into<String>,
+    {
+        let (push_tx, push_rx) = mpsc::unbounded_channel();
+        let simulator = Simulator {
+            cash: cash
+                .into_iter()
+                .map(|(currency, amount)| (currency.into(), amount))
+                .collect(),
+            orders: Vec::new(),
+            positions: BTreeMap::new(),
+            last_prices: HashMap::new(),
+            now: None,
+            next_order_id: 1,
+            push_tx,
+            risk_manager: None,
+        };
+        (Self(Arc::new(Mutex::new(simulator))), push_rx)
+    }
+
+    /// Match the pending orders with the prices of a quote push event
+    ///
+    /// Only [`PushEventDetail::Quote`] and [`PushEventDetail::Trade`] events
+    /// are used.
+    pub fn handle_quote_event(&self, event: &quote::PushEvent) {
+        self.0.lock().unwrap().handle_quote_event(event);
+    }
+
+    /// Match the pending orders with all events of a quote push receiver, the
+    /// events are forwarded to the returned receiver after being matched
+    ///
+    /// The receiver can come from
+    /// [`QuoteContext::try_new`](crate::QuoteContext::try_new) or
+    /// [`PushReplayer::start`](crate::quote::PushReplayer::start).
+    pub fn attach(
+        &self,
+        mut receiver: mpsc::UnboundedReceiver<quote::PushEvent>,
+    ) -> mpsc::UnboundedReceiver<quote::PushEvent> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let ctx = self.clone();
+        tokio::spawn(async move {
+            while let Some(event) = receiver.recv().await {
+                ctx.handle_quote_event(&event);
+                let _ = tx.send(event);
+            }
+        });
+        rx
+    }
+
+    /// Set the risk manager which checks the orders before they are submitted
+    /// or replaced, `None` disables the checks
+    pub fn set_risk_manager(&self, risk_manager: Option<RiskManager>) {
+        self.0.lock().unwrap().risk_manager = risk_manager;
+    }
+
+    /// Returns the risk manager
+    pub fn risk_manager(&self) -> Option<RiskManager> {
+        self.0.lock().unwrap().risk_manager.clone()
+    }
+
+    /// Submit order
+    ///
+    /// If a risk manager is set, the order is checked before it is submitted
+    /// and [`Error::RiskCheckFailed`] is returned if it is rejected.
+    pub async fn submit_order(&self, options: SubmitOrderOptions) -> Result<SubmitOrderResponse> {
+        let order_id = self
+            .0
+            .lock()
+            .unwrap()
+            .submit_order(options.append_client_order_id()?)?;
+        Ok(SubmitOrderResponse { order_id })
+    }
+
+    /// Replace order
+    ///
+    /// If a risk manager is set, the order is checked before it is replaced
+    /// and [`Error::RiskCheckFailed`] is returned if it is rejected.
+    pub async fn replace_order(&self, options: ReplaceOrderOptions) -> Result<()> {
+        self.0.lock().unwrap().replace_order(options)
+    }
+
+    /// Cancel order
+    pub async fn cancel_order(&self, order_id: impl Into<String>) -> Result<()> {
+        self.0.lock().unwrap().cancel_order(&order_id.into())
+    }
+
+    /// Get today orders
+    ///
+    /// Returns the orders submitted on the current trading day and the orders
+    /// which are still pending.
+    pub async fn today_orders(
+        &self,
+        options: impl Into<Option<GetTodayOrdersOptions>>,
+    ) -> Result<Vec<Order>> {
+        Ok(self
+            .0
+            .lock()
+            .unwrap()
+            .today_orders(options.into().unwrap_or_default()))
+    }
+
+    /// Get order detail
+    ///
+    /// The paper orders have no charges and no history.
+    pub async fn order_detail(&self, order_id: impl Into<String>) -> Result<OrderDetail> {
+        self.0.lock().unwrap().order_detail(&order_id.into())
+    }
+
+    /// Get stock positions
+    pub async fn stock_positions(
+        &self,
+        opts: impl Into<Option<GetStockPositionsOptions>>,
+    ) -> Result<StockPositionsResponse> {
+        Ok(self
+            .0
+            .lock()
+            .unwrap()
+            .stock_positions(opts.into().unwrap_or_default()))
+    }
+
+    /// Get account balance
+    pub async fn account_balance(&self, currency: Option<&str>) -> Result<Vec<AccountBalance>> {
+        Ok(self.0.lock().unwrap().account_balance(currency))
+    }
+}
+
+/// The order changed pushes are always sent, the other methods of the trade
+/// context which can not be simulated return [`Error::Unsupported`].
+// the arguments are not `Send`, so the futures must not capture them
+#[allow(clippy::manual_async_fn)]
+impl TradeApi for PaperTradeContext {
+    #[inline]
+    fn log_subscriber(&self) -> Arc<dyn Subscriber + Send + Sync> {
+        Arc::new(NoSubscriber::default())
+    }
+
+    #[inline]
+    fn set_risk_manager(&self, risk_manager: Option<RiskManager>) {
+        PaperTradeContext::set_risk_manager(self, risk_manager)
+    }
+
+    #[inline]
+    fn risk_manager(&self) -> Option<RiskManager> {
+        PaperTradeContext::risk_manager(self)
+    }
+
+    /// Returns a closed receiver, the paper trading context has no connection
+    fn connection_events(&self) -> mpsc::UnboundedReceiver<ConnectionEvent> {
+        mpsc::unbounded_channel().1
+    }
+
+    fn subscribe<I>(&self, _topics: I) -> impl Future<Output = Result<()>> + Send
+    where
+        I: IntoIterator<Item = TopicType>,
+    {
+        async { Ok(()) }
+    }
+
+    fn unsubscribe<I>(&self, _topics: I) -> impl Future<Output = Result<()>> + Send
+    where
+        I: IntoIterator<Item = TopicType>,
+    {
+        async { Ok(()) }
+    }
+
+    fn history_executions(
+        &self,
+        _options: impl Into<Option<GetHistoryExecutionsOptions>>,
+    ) -> impl Future<Output = Result<Vec<Execution>>> + Send {
+        async {
+            Err(Error::Unsupported {
+                method: "history_executions",
+            })
+        }
+    }
+
+    fn today_executions(
+        &self,
+        _options: impl Into<Option<GetTodayExecutionsOptions>>,
+    ) -> impl Future<Output = Result<Vec<Execution>>> + Send {
+        async {
+            Err(Error::Unsupported {
+                method: "today_executions",
+            })
+        }
+    }
+
+    fn history_orders(
+        &self,
+        _options: impl Into<Option<GetHistoryOrdersOptions>>,
+    ) -> impl Future<Output = Result<Vec<Order>>> + Send {
+        async {
+            Err(Error::Unsupported {
+                method: "history_orders",
+            })
+        }
+    }
+
+    fn today_orders(
+        &self,
+        options: impl Into<Option<GetTodayOrdersOptions>>,
+    ) -> impl Future<Output = Result<Vec<Order>>> + Send {
+        PaperTradeContext::today_orders(self, options.into())
+    }
+
+    fn replace_order(
+        &self,
+        options: ReplaceOrderOptions,
+    ) -> impl Future<Output = Result<()>> + Send {
+        PaperTradeContext::replace_order(self, options)
+    }
+
+    fn submit_order(
+        &self,
+        options: SubmitOrderOptions,
+    ) -> impl Future<Output = Result<SubmitOrderResponse>> + Send {
+        PaperTradeContext::submit_order(self, options)
+    }
+
+    fn cancel_order(&self, order_id: impl Into<String>) -> impl Future<Output = Result<()>> + Send {
+        PaperTradeContext::cancel_order(self, order_id.into())
+    }
+
+    fn account_balance(
+        &self,
+        currency: Option<&str>,
+    ) -> impl Future<Output = Result<Vec<AccountBalance>>> + Send {
+        PaperTradeContext::account_balance(self, currency)
+    }
+
+    fn cash_flow(
+        &self,
+        _options: GetCashFlowOptions,
+    ) -> impl Future<Output = Result<Vec<CashFlow>>> + Send {
+        async {
+            Err(Error::Unsupported {
+                method: "cash_flow",
+            })
+        }
+    }
+
+    fn fund_positions(
+        &self,
+        _opts: impl Into<Option<GetFundPositionsOptions>>,
+    ) -> impl Future<Output = Result<FundPositionsResponse>> + Send {
+        async {
+            Err(Error::Unsupported {
+                method: "fund_positions",
+            })
+        }
+    }
+
+    fn stock_positions(
+        &self,
+        opts: impl Into<Option<GetStockPositionsOptions>>,
+    ) -> impl Future<Output = Result<StockPositionsResponse>> + Send {
+        PaperTradeContext::stock_positions(self, opts.into())
+    }
+
+    fn margin_ratio(
+        &self,
+        _symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<MarginRatio>> + Send {
+        async {
+            Err(Error::Unsupported {
+                method: "margin_ratio",
+            })
+        }
+    }
+
+    fn order_detail(
+        &self,
+        order_id: impl Into<String>,
+    ) -> impl Future<Output = Result<OrderDetail>> + Send {
+        PaperTradeContext::order_detail(self, order_id.into())
+    }
+
+    fn estimate_max_purchase_quantity(
+        &self,
+        _opts: EstimateMaxPurchaseQuantityOptions,
+    ) -> impl Future<Output = Result<EstimateMaxPurchaseQuantityResponse>> + Send {
+        async {
+            Err(Error::Unsupported {
+                method: "estimate_max_purchase_quantity",
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+    use crate::{
+        quote::PushQuote,
+        trade::{RiskLimits, RiskViolation},
+    };
+
+    fn quote_event(
+        price: Decimal,
+        session: TradeSession,
+        timestamp: OffsetDateTime,
+    ) -> quote::PushEvent {
+        quote::PushEvent {
+            sequence: 0,
+            symbol: "AAPL.US".to_string(),
+            detail: PushEventDetail::Quote(PushQuote {
+                last_done: price,
+                timestamp,
+                trade_session: session,
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn next_status(receiver: &mut mpsc::UnboundedReceiver<PushEvent>) -> OrderStatus {
+        let PushEvent::OrderChanged(order) = receiver.try_recv().unwrap() else {
+            panic!("unexpected push event");
+        };
+        order.status
+    }
+
+    #[tokio::test]
+    async fn test_paper_trading() {
+        let t = datetime!(2024-06-03 14:00 UTC);
+        let (ctx, mut receiver) = PaperTradeContext::new([("USD", Decimal::from(10000))]);
+        ctx.handle_quote_event(&quote_event(100.into(), TradeSession::NormalTrade, t));
+
+        // limit order
+        let order_id = ctx
+            .submit_order(
+                SubmitOrderOptions::new(
+                    "AAPL.US",
+                    OrderType::LO,
+                    OrderSide::Buy,
+                    10.into(),
+                    TimeInForceType::Day,
+                )
+                .submitted_price(99.into()),
+            )
+            .await
+            .unwrap()
+            .order_id;
+        assert_eq!(next_status(&mut receiver), OrderStatus::New);
+
+        // outside of the regular trading hours
+        ctx.handle_quote_event(&quote_event(98.into(), TradeSession::PostTrade, t));
+        assert!(receiver.try_recv().is_err());
+
+        ctx.handle_quote_event(&quote_event(98.into(), TradeSession::NormalTrade, t));
+        assert_eq!(next_status(&mut receiver), OrderStatus::Filled);
+
+        let orders = ctx.today_orders(None).await.unwrap();
+        assert_eq!(orders[0].order_id, order_id);
+        assert_eq!(orders[0].executed_price, Some(98.into()));
+
+        let balance = ctx.account_balance(Some("USD")).await.unwrap();
+        assert_eq!(balance[0].total_cash, Decimal::from(10000 - 980));
+
+        // trailing stop
+        ctx.submit_order(
+            SubmitOrderOptions::new(
+                "AAPL.US",
+                OrderType::TSLPAMT,
+                OrderSide::Sell,
+                10.into(),
+                TimeInForceType::GoodTilCanceled,
+            )
+            .trailing_amount(2.into())
+            .limit_offset(Decimal::ZERO),
+        )
+        .await
+        .unwrap();
+        assert_eq!(next_status(&mut receiver), OrderStatus::New);
+        assert!(ctx
+            .submit_order(SubmitOrderOptions::new(
+                "AAPL.US",
+                OrderType::MO,
+                OrderSide::Sell,
+                1.into(),
+                TimeInForceType::Day,
+            ))
+            .await
+            .is_err());
+
+        ctx.handle_quote_event(&quote_event(105.into(), TradeSession::NormalTrade, t));
+        ctx.handle_quote_event(&quote_event(102.into(), TradeSession::NormalTrade, t));
+        assert_eq!(next_status(&mut receiver), OrderStatus::New);
+        assert!(receiver.try_recv().is_err());
+
+        ctx.handle_quote_event(&quote_event(103.into(), TradeSession::NormalTrade, t));
+        assert_eq!(next_status(&mut receiver), OrderStatus::Filled);
+        let resp = ctx.stock_positions(None).await.unwrap();
+        assert!(resp.channels[0].positions.is_empty());
+
+        // day order expires on the next trading day
+        ctx.submit_order(
+            SubmitOrderOptions::new(
+                "AAPL.US",
+                OrderType::LIT,
+                OrderSide::Buy,
+                10.into(),
+                TimeInForceType::Day,
+            )
+            .trigger_price(90.into())
+            .submitted_price(90.into()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(next_status(&mut receiver), OrderStatus::New);
+        ctx.handle_quote_event(&quote_event(
+            103.into(),
+            TradeSession::NormalTrade,
+            datetime!(2024-06-04 14:00 UTC),
+        ));
+        assert_eq!(next_status(&mut receiver), OrderStatus::Expired);
+    }
+
+    #[tokio::test]
+    async fn test_trade_api() {
+        let t = datetime!(2024-06-03 14:00 UTC);
+        let (ctx, _receiver) = PaperTradeContext::new([("USD", Decimal::from(10000))]);
+        ctx.handle_quote_event(&quote_event(100.into(), TradeSession::NormalTrade, t));
+
+        let order_id = TradeApi::submit_order(
+            &ctx,
+            SubmitOrderOptions::new(
+                "AAPL.US",
+                OrderType::LO,
+                OrderSide::Buy,
+                10.into(),
+                TimeInForceType::Day,
+            )
+            .submitted_price(99.into()),
+        )
+        .await
+        .unwrap()
+        .order_id;
+        assert_eq!(TradeApi::open_orders(&ctx).await.unwrap().len(), 1);
+
+        ctx.handle_quote_event(&quote_event(98.into(), TradeSession::NormalTrade, t));
+        let detail = TradeApi::wait_for_order(
+            &ctx,
+            order_id.clone(),
+            OrderStatus::is_terminal,
+            std::time::Duration::from_secs(1),
+        )
+        .await
+        .unwrap();
+        assert_eq!(detail.status, OrderStatus::Filled);
+        assert_eq!(detail.executed_price, Some(98.into()));
+        assert!(TradeApi::open_orders(&ctx).await.unwrap().is_empty());
+        assert!(TradeApi::order(&ctx, order_id).await.unwrap().is_some());
+        assert!(matches!(
+            TradeApi::cash_flow(&ctx, GetCashFlowOptions::new(t, t)).await,
+            Err(Error::Unsupported {
+                method: "cash_flow"
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_risk_manager() {
+        let t = datetime!(2024-06-03 14:00 UTC);
+        let (ctx, _receiver) = PaperTradeContext::new([("USD", Decimal::from(10000))]);
+        ctx.handle_quote_event(&quote_event(100.into(), TradeSession::NormalTrade, t));
+        TradeApi::set_risk_manager(
+            &ctx,
+            Some(RiskManager::new(
+                RiskLimits::new().max_position_quantity(15.into()),
+            )),
+        );
+        let buy = SubmitOrderOptions::new(
+            "AAPL.US",
+            OrderType::LO,
+            OrderSide::Buy,
+            10.into(),
+            TimeInForceType::Day,
+        )
+        .submitted_price(99.into());
+
+        // the open orders are added to the position
+        let order_id = ctx.submit_order(buy.clone()).await.unwrap().order_id;
+        assert!(matches!(
+            ctx.submit_order(buy).await,
+            Err(Error::RiskCheckFailed {
+                violation: RiskViolation::PositionQuantity { .. },
+                ..
+            })
+        ));
+        ctx.replace_order(ReplaceOrderOptions::new(order_id.clone(), 15.into()))
+            .await
+            .unwrap();
+
+        ctx.risk_manager().unwrap().set_kill_switch(true);
+        assert!(matches!(
+            ctx.replace_order(ReplaceOrderOptions::new(order_id, 10.into()))
+                .await,
+            Err(Error::RiskCheckFailed {
+                violation: RiskViolation::KillSwitch,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,693 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use rust_decimal::Decimal;
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+
+use crate::{
+    quote::{self, PushEventDetail},
+    trade::{
+        AccountBalance, Order, OrderSide, PushEvent, PushOrderChanged, StockPositionsResponse,
+        TradeContext,
+    },
+    types::parse_market_from_symbol,
+    Market, Result,
+};
+
+/// Position with the profit and loss
+#[derive(Debug, Clone)]
+pub struct PositionPnl {
+    /// Security code
+    pub symbol: String,
+    /// Market
+    pub market: Market,
+    /// Currency
+    pub currency: String,
+    /// Quantity, negative for the short positions
+    pub quantity: Decimal,
+    /// Average cost price
+    pub cost_price: Decimal,
+    /// Latest price, `None` if no quote has been received
+    pub last_price: Option<Decimal>,
+    /// Market value, valued at the cost price if no quote has been received
+    pub market_value: Decimal,
+    /// Unrealized profit and loss
+    pub unrealized_pnl: Decimal,
+    /// Realized profit and loss since the tracker was seeded
+    pub realized_pnl: Decimal,
+    /// Start-of-day mark, the last price when the tracker was seeded or the
+    /// first price received after it, `None` if no quote has been received
+    pub mark_price: Option<Decimal>,
+    /// Profit and loss since the tracker was seeded, the seeded quantity is
+    /// valued from the start-of-day mark and the fills from their prices
+    pub daily_pnl: Decimal,
+}
+
+/// Profit and loss of the positions in a currency
+#[derive(Debug, Clone)]
+pub struct CurrencyPnl {
+    /// Currency
+    pub currency: String,
+    /// Cash, seeded with the available and the frozen cash, and updated with
+    /// the fills
+    pub cash: Decimal,
+    /// Net market value of the positions
+    pub market_value: Decimal,
+    /// Gross exposure, the sum of the absolute market values
+    pub gross_exposure: Decimal,
+    /// Unrealized profit and loss
+    pub unrealized_pnl: Decimal,
+    /// Realized profit and loss since the tracker was seeded
+    pub realized_pnl: Decimal,
+    /// Profit and loss since the tracker was seeded, measured from the
+    /// start-of-day marks of the positions
+    pub daily_pnl: Decimal,
+}
+
+#[derive(Debug)]
+struct Position {
+    market: Market,
+    currency: String,
+    quantity: Decimal,
+    cost_price: Decimal,
+    last_price: Option<Decimal>,
+    realized_pnl: Decimal,
+    /// The quantity when the tracker was seeded
+    seed_quantity: Decimal,
+    mark_price: Option<Decimal>,
+    /// The cash flow of the fills since the tracker was seeded
+    fill_cash: Decimal,
+}
+
+impl Position {
+    /// Applies a fill with the signed quantity with the average cost method
+    fn fill(&mut self, quantity: Decimal, price: Decimal) {
+        if self.quantity.is_zero()
+            || self.quantity.is_sign_positive() == quantity.is_sign_positive()
+        {
+            let total = self.quantity.abs() + quantity.abs();
+            self.cost_price =
+                (self.cost_price * self.quantity.abs() + price * quantity.abs()) / total;
+            self.quantity += quantity;
+            return;
+        }
+
+        let closed = quantity.abs().min(self.quantity.abs());
+        let direction = if self.quantity.is_sign_positive() {
+            Decimal::ONE
+        } else {
+            Decimal::NEGATIVE_ONE
+        };
+        self.realized_pnl += (price - self.cost_price) * closed * direction;
+        self.quantity += quantity;
+        if !self.quantity.is_zero()
+            && self.quantity.is_sign_positive() == quantity.is_sign_positive()
+        {
+            // the position is reversed
+            self.cost_price = price;
+        }
+    }
+
+    fn new(market: Market, currency: String, last_price: Option<Decimal>) -> Self {
+        Self {
+            market,
+            currency,
+            quantity: Decimal::ZERO,
+            cost_price: Decimal::ZERO,
+            last_price,
+            realized_pnl: Decimal::ZERO,
+            seed_quantity: Decimal::ZERO,
+            mark_price: last_price,
+            fill_cash: Decimal::ZERO,
+        }
+    }
+
+    fn pnl(&self, symbol: &str) -> PositionPnl {
+        let price = self.last_price.unwrap_or(self.cost_price);
+        let mark_price = self.mark_price.unwrap_or(price);
+        PositionPnl {
+            symbol: symbol.to_string(),
+            market: self.market,
+            currency: self.currency.clone(),
+            quantity: self.quantity,
+            cost_price: self.cost_price,
+            last_price: self.last_price,
+            market_value: price * self.quantity,
+            unrealized_pnl: (price - self.cost_price) * self.quantity,
+            realized_pnl: self.realized_pnl,
+            mark_price: self.mark_price,
+            daily_pnl: price * self.quantity - mark_price * self.seed_quantity + self.fill_cash,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Portfolio {
+    positions: BTreeMap<String, Position>,
+    cash: BTreeMap<String, Decimal>,
+    /// The executed quantities of the orders
+    executed: HashMap<String, Decimal>,
+    /// The time when the positions and the cash were seeded
+    seeded_at: Option<OffsetDateTime>,
+}
+
+impl Portfolio {
+    fn handle_order_changed(&mut self, order: &PushOrderChanged) {
+        self.fill_order(
+            &order.order_id,
+            &order.symbol,
+            &order.currency,
+            order.side,
+            order.executed_quantity,
+            // the last share of an unknown order is the only fill after seeding
+            order.last_share.unwrap_or_default(),
+            order.last_price.or(order.executed_price),
+            order.updated_at,
+        );
+    }
+
+    fn handle_reconciled_order(&mut self, order: &Order) {
+        // all fills of an unknown order submitted after the seeding are new,
+        // the fills of an earlier order can not be told apart from the seeded
+        // positions
+        let is_submitted_after_seeding = self
+            .seeded_at
+            .is_none_or(|seeded_at| order.submitted_at > seeded_at);
+        self.fill_order(
+            &order.order_id,
+            &order.symbol,
+            &order.currency,
+            order.side,
+            order.executed_quantity,
+            if is_submitted_after_seeding {
+                order.executed_quantity
+            } else {
+                Decimal::ZERO
+            },
+            order.executed_price,
+            order.updated_at.unwrap_or(order.submitted_at),
+        );
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn fill_order(
+        &mut self,
+        order_id: &str,
+        symbol: &str,
+        currency: &str,
+        side: OrderSide,
+        executed_quantity: Decimal,
+        unknown_quantity: Decimal,
+        price: Option<Decimal>,
+        updated_at: OffsetDateTime,
+    ) {
+        let sign = match side {
+            OrderSide::Buy => Decimal::ONE,
+            OrderSide::Sell => Decimal::NEGATIVE_ONE,
+            OrderSide::Unknown => return,
+        };
+
+        // the executed quantity is used to skip the duplicate pushes, and
+        // `unknown_quantity` is the fill of an order which is not tracked yet
+        let quantity = match self.executed.get(order_id) {
+            Some(executed) => executed_quantity - *executed,
+            None => unknown_quantity,
+        };
+        let executed = self
+            .executed
+            .entry(order_id.to_string())
+            .or_insert(executed_quantity);
+        *executed = (*executed).max(executed_quantity);
+        // the fills before the seeding are included in the seeded positions
+        let is_seeded = self
+            .seeded_at
+            .is_some_and(|seeded_at| updated_at <= seeded_at);
+        if quantity <= Decimal::ZERO || is_seeded {
+            return;
+        }
+        let Some(price) = price else {
+            return;
+        };
+
+        let position = self.positions.entry(symbol.to_string()).or_insert_with(|| {
+            Position::new(
+                parse_market_from_symbol(symbol).unwrap_or_default(),
+                currency.to_string(),
+                None,
+            )
+        });
+        position.fill(quantity * sign, price);
+        position.fill_cash -= quantity * sign * price;
+        *self.cash.entry(currency.to_string()).or_default() -= quantity * sign * price;
+    }
+
+    fn handle_quote_event(&mut self, event: &quote::PushEvent) {
+        let Some(position) = self.positions.get_mut(&event.symbol) else {
+            return;
+        };
+        let price = match &event.detail {
+            PushEventDetail::Quote(quote) => quote.last_done,
+            PushEventDetail::Trade(trades) => match trades.trades.last() {
+                Some(trade) => trade.price,
+                None => return,
+            },
+            _ => return,
+        };
+        if !price.is_zero() {
+            position.last_price = Some(price);
+            position.mark_price.get_or_insert(price);
+        }
+    }
+}
+
+/// Realtime position and profit and loss tracker
+///
+/// Seeded with the stock positions and the cash of the account, the
+/// quantities are updated with the fills of the [`PushEvent::OrderChanged`]
+/// and [`PushEvent::OrdersReconciled`] events and the positions are marked to market with the prices of the
+/// [`quote::PushEvent`]s. The fills updated at or before the seeding are
+/// skipped, because they are included in the seeded positions. The cost
+/// prices are averaged, and the realized profit and loss is accumulated since
+/// the tracker was seeded.
+///
+/// The daily profit and loss is measured from a start-of-day mark of each
+/// position, the last price carried over when the tracker is seeded again, or
+/// the first price received after the seeding.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use longport::{
+///     quote::{QuoteContext, SubFlags},
+///     trade::{PortfolioTracker, TopicType, TradeContext},
+///     Config,
+/// };
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let config = Arc::new(Config::from_env()?);
+/// let (quote_ctx, quote_receiver) = QuoteContext::try_new(config.clone()).await?;
+/// let (trade_ctx, trade_receiver) = TradeContext::try_new(config).await?;
+/// trade_ctx.subscribe([TopicType::Private]).await?;
+///
+/// let tracker = PortfolioTracker::try_new(&trade_ctx).await?;
+/// let _trade_receiver = tracker.attach_trade(trade_receiver);
+/// let _quote_receiver = tracker.attach_quote(quote_receiver);
+/// let symbols = tracker
+///     .positions()
+///     .into_iter()
+///     .map(|position| position.symbol)
+///     .collect::<Vec<_>>();
+/// quote_ctx.subscribe(symbols, SubFlags::QUOTE, true).await?;
+///
+/// println!("{:?}", tracker.currency_pnl());
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// # });
+/// ```
+#[derive(Clone, Default)]
+pub struct PortfolioTracker(Arc<Mutex<Portfolio>>);
+
+impl PortfolioTracker {
+    /// Create an empty `PortfolioTracker`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a `PortfolioTracker` seeded with the stock positions and the
+    /// account balance of the trade context
+    pub async fn try_new(ctx: &TradeContext) -> Result<Self> {
+        // the fills pushed while fetching may be included in the positions, so
+        // the seeding time is taken before fetching
+        let seeded_at = OffsetDateTime::now_utc();
+        let positions = ctx.stock_positions(None).await?;
+        let balances = ctx.account_balance(None).await?;
+        let tracker = Self::new();
+        tracker.seed_at(&positions, &balances, seeded_at);
+        Ok(tracker)
+    }
+
+    /// Replaces the positions and the cash, the realized and the daily profit
+    /// and loss are reset
+    ///
+    /// The last prices of the previous positions are kept as the start-of-day
+    /// marks, so the tracker should be seeded at the start of every trading
+    /// day.
+    ///
+    /// The cash of each currency is the available cash plus the frozen cash,
+    /// so the cash frozen by the open orders is deducted when they are filled.
+    ///
+    /// The fills updated at or before the call are skipped, so the positions
+    /// and the balances should be fetched right before.
+    pub fn seed(&self, positions: &StockPositionsResponse, balances: &[AccountBalance]) {
+        self.seed_at(positions, balances, OffsetDateTime::now_utc());
+    }
+
+    fn seed_at(
+        &self,
+        positions: &StockPositionsResponse,
+        balances: &[AccountBalance],
+        seeded_at: OffsetDateTime,
+    ) {
+        let mut portfolio = self.0.lock().unwrap();
+        let last_prices = portfolio
+            .positions
+            .iter()
+            .filter_map(|(symbol, position)| Some((symbol.clone(), position.last_price?)))
+            .collect::<HashMap<_, _>>();
+
+        portfolio.positions.clear();
+        for position in positions
+            .channels
+            .iter()
+            .flat_map(|channel| &channel.positions)
+        {
+            let entry = portfolio
+                .positions
+                .entry(position.symbol.clone())
+                .or_insert_with(|| {
+                    Position::new(
+                        position.market,
+                        position.currency.clone(),
+                        last_prices.get(&position.symbol).copied(),
+                    )
+                });
+            entry.fill(position.quantity, position.cost_price);
+            entry.seed_quantity += position.quantity;
+        }
+
+        portfolio.cash.clear();
+        for cash_info in balances.iter().flat_map(|balance| &balance.cash_infos) {
+            portfolio
+                .cash
+                .entry(cash_info.currency.clone())
+                .or_insert(cash_info.available_cash + cash_info.frozen_cash);
+        }
+        portfolio.seeded_at = Some(seeded_at);
+    }
+
+    /// Update the positions with the fills of a trade push event
+    ///
+    /// The fills of the reconciled orders are valued at their average
+    /// executed prices.
+    pub fn handle_trade_event(&self, event: &PushEvent) {
+        let mut portfolio = self.0.lock().unwrap();
+        match event {
+            PushEvent::OrderChanged(order) => portfolio.handle_order_changed(order),
+            PushEvent::OrdersReconciled(reconciled) => {
+                for order in &reconciled.orders {
+                    portfolio.handle_reconciled_order(order);
+                }
+            }
+        }
+    }
+
+    /// Mark the positions to market with the prices of a quote push event
+    ///
+    /// Only [`PushEventDetail::Quote`] and [`PushEventDetail::Trade`] events
+    /// are used.
+    pub fn handle_quote_event(&self, event: &quote::PushEvent) {
+        self.0.lock().unwrap().handle_quote_event(event);
+    }
+
+    /// Update the positions with all events of a trade push receiver, the
+    /// events are forwarded to the returned receiver after being handled
+    pub fn attach_trade(
+        &self,
+        mut receiver: mpsc::UnboundedReceiver<PushEvent>,
+    ) -> mpsc::UnboundedReceiver<PushEvent> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let tracker = self.clone();
+        tokio::spawn(async move {
+            while let Some(event) = receiver.recv().await {
+                tracker.handle_trade_event(&event);
+                let _ = tx.send(event);
+            }
+        });
+        rx
+    }
+
+    /// Mark the positions to market with all events of a quote push receiver,
+    /// the events are forwarded to the returned receiver after being handled
+    pub fn attach_quote(
+        &self,
+        mut receiver: mpsc::UnboundedReceiver<quote::PushEvent>,
+    ) -> mpsc::UnboundedReceiver<quote::PushEvent> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let tracker = self.clone();
+        tokio::spawn(async move {
+            while let Some(event) = receiver.recv().await {
+                tracker.handle_quote_event(&event);
+                let _ = tx.send(event);
+            }
+        });
+        rx
+    }
+
+    /// Returns the positions sorted by the symbols, including the closed
+    /// positions with the realized profit and loss
+    pub fn positions(&self) -> Vec<PositionPnl> {
+        self.0
+            .lock()
+            .unwrap()
+            .positions
+            .iter()
+            .map(|(symbol, position)| position.pnl(symbol))
+            .collect()
+    }
+
+    /// Returns the position of the security
+    pub fn position(&self, symbol: &str) -> Option<PositionPnl> {
+        self.0
+            .lock()
+            .unwrap()
+            .positions
+            .get(symbol)
+            .map(|position| position.pnl(symbol))
+    }
+
+    /// Returns the profit and loss of each currency sorted by the currencies
+    pub fn currency_pnl(&self) -> Vec<CurrencyPnl> {
+        let portfolio = self.0.lock().unwrap();
+        let mut res: BTreeMap<&str, CurrencyPnl> = portfolio
+            .cash
+            .iter()
+            .map(|(currency, cash)| {
+                (
+                    currency.as_str(),
+                    CurrencyPnl {
+                        currency: currency.clone(),
+                        cash: *cash,
+                        market_value: Decimal::ZERO,
+                        gross_exposure: Decimal::ZERO,
+                        unrealized_pnl: Decimal::ZERO,
+                        realized_pnl: Decimal::ZERO,
+                        daily_pnl: Decimal::ZERO,
+                    },
+                )
+            })
+            .collect();
+
+        for (symbol, position) in &portfolio.positions {
+            let pnl = position.pnl(symbol);
+            let entry = res
+                .entry(position.currency.as_str())
+                .or_insert_with(|| CurrencyPnl {
+                    currency: position.currency.clone(),
+                    cash: Decimal::ZERO,
+                    market_value: Decimal::ZERO,
+                    gross_exposure: Decimal::ZERO,
+                    unrealized_pnl: Decimal::ZERO,
+                    realized_pnl: Decimal::ZERO,
+                    daily_pnl: Decimal::ZERO,
+                });
+            entry.market_value += pnl.market_value;
+            entry.gross_exposure += pnl.market_value.abs();
+            entry.unrealized_pnl += pnl.unrealized_pnl;
+            entry.realized_pnl += pnl.realized_pnl;
+            entry.daily_pnl += pnl.daily_pnl;
+        }
+
+        res.into_values().collect()
+    }
+
+    /// Returns the net market value of the positions of each market, in the
+    /// currencies of the positions
+    pub fn market_exposure(&self) -> HashMap<Market, Decimal> {
+        let mut res = HashMap::new();
+        for (symbol, position) in &self.0.lock().unwrap().positions {
+            *res.entry(position.market).or_default() += position.pnl(symbol).market_value;
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{quote::PushQuote, trade::PushOrdersReconciled};
+
+    fn order_changed(order_id: &str, side: &str, executed: i64, price: i64) -> PushEvent {
+        serde_json::from_value(json!({
+            "event": "order_changed_lb",
+            "data": {
+                "side": side,
+                "stock_name": "Tencent",
+                "submitted_quantity": "1000",
+                "symbol": "700.HK",
+                "order_type": "LO",
+                "submitted_price": price.to_string(),
+                "executed_quantity": executed.to_string(),
+                "executed_price": price.to_string(),
+                "order_id": order_id,
+                "currency": "HKD",
+                "status": "PartialFilledStatus",
+                "submitted_at": "1700000000",
+                "updated_at": "4102444800",
+                "trigger_price": "",
+                "msg": "",
+                "tag": "Normal",
+                "trigger_status": "NOT_USED",
+                "trigger_at": "0",
+                "trailing_amount": "",
+                "trailing_percent": "",
+                "limit_offset": "",
+                "account_no": "ACC",
+                "last_share": "100",
+                "last_price": price.to_string(),
+                "remark": ""
+            }
+        }))
+        .unwrap()
+    }
+
+    fn orders_reconciled(order_id: &str, side: &str, executed: i64, price: i64) -> PushEvent {
+        PushEvent::OrdersReconciled(PushOrdersReconciled {
+            orders: vec![serde_json::from_value(json!({
+                "currency": "HKD",
+                "executed_price": price.to_string(),
+                "executed_quantity": executed.to_string(),
+                "expire_date": "",
+                "last_done": "",
+                "limit_offset": "",
+                "msg": "",
+                "order_id": order_id,
+                "order_type": "LO",
+                "outside_rth": "UnknownOutsideRth",
+                "price": price.to_string(),
+                "quantity": "1000",
+                "side": side,
+                "status": "PartialFilledStatus",
+                "stock_name": "Tencent",
+                "submitted_at": "4102444800",
+                "symbol": "700.HK",
+                "tag": "Normal",
+                "time_in_force": "Day",
+                "trailing_amount": "",
+                "trailing_percent": "",
+                "trigger_at": "0",
+                "trigger_price": "",
+                "trigger_status": "NOT_USED",
+                "updated_at": "4102444800",
+                "remark": ""
+            }))
+            .unwrap()],
+            removed: vec![],
+        })
+    }
+
+    fn quote_event(last_done: i64) -> quote::PushEvent {
+        quote::PushEvent {
+            sequence: 0,
+            symbol: "700.HK".to_string(),
+            detail: PushEventDetail::Quote(PushQuote {
+                last_done: last_done.into(),
+                ..Default::default()
+            }),
+        }
+    }
+
+    #[test]
+    fn test_portfolio_tracker() {
+        let tracker = PortfolioTracker::new();
+        let positions: StockPositionsResponse = serde_json::from_value(json!({
+            "list": [{
+                "account_channel": "lb",
+                "stock_info": [{
+                    "symbol": "700.HK",
+                    "symbol_name": "Tencent",
+                    "quantity": "200",
+                    "available_quantity": "200",
+                    "currency": "HKD",
+                    "cost_price": "300",
+                    "market": "HK",
+                    "init_quantity": "200"
+                }]
+            }]
+        }))
+        .unwrap();
+        tracker.seed(&positions, &[]);
+
+        // the fills before the seeding are skipped
+        let mut event = order_changed("0", "Buy", 100, 320);
+        if let PushEvent::OrderChanged(order) = &mut event {
+            order.updated_at = OffsetDateTime::UNIX_EPOCH;
+        }
+        tracker.handle_trade_event(&event);
+        assert_eq!(
+            tracker.position("700.HK").unwrap().quantity,
+            Decimal::from(200)
+        );
+
+        tracker.handle_quote_event(&quote_event(310));
+        let position = tracker.position("700.HK").unwrap();
+        assert_eq!(position.market_value, Decimal::from(62000));
+        assert_eq!(position.unrealized_pnl, Decimal::from(2000));
+        assert_eq!(position.mark_price, Some(Decimal::from(310)));
+        assert_eq!(position.daily_pnl, Decimal::ZERO);
+
+        // buy 100 at 320, the duplicate push is skipped
+        tracker.handle_trade_event(&order_changed("1", "Buy", 100, 320));
+        tracker.handle_trade_event(&order_changed("1", "Buy", 100, 320));
+        let position = tracker.position("700.HK").unwrap();
+        assert_eq!(position.quantity, Decimal::from(300));
+        assert_eq!(position.cost_price.round_dp(4), Decimal::new(3066667, 4));
+
+        // sell 200 at 330
+        tracker.handle_trade_event(&order_changed("2", "Sell", 100, 330));
+        tracker.handle_trade_event(&order_changed("2", "Sell", 200, 330));
+        tracker.handle_quote_event(&quote_event(330));
+        let position = tracker.position("700.HK").unwrap();
+        assert_eq!(position.quantity, Decimal::from(100));
+        assert_eq!(position.realized_pnl.round_dp(2), Decimal::new(466667, 2));
+        assert_eq!(position.unrealized_pnl.round_dp(2), Decimal::new(233333, 2));
+        // 100 * 330 - 200 * 310 - 100 * 320 + 200 * 330
+        assert_eq!(position.daily_pnl, Decimal::from(5000));
+
+        let currency_pnl = tracker.currency_pnl();
+        assert_eq!(currency_pnl.len(), 1);
+        assert_eq!(currency_pnl[0].cash, Decimal::from(66000 - 32000));
+        assert_eq!(currency_pnl[0].market_value, Decimal::from(33000));
+        assert_eq!(currency_pnl[0].daily_pnl, Decimal::from(5000));
+        assert_eq!(
+            tracker.market_exposure().get(&Market::HK),
+            Some(&Decimal::from(33000))
+        );
+
+        // the reconciled orders are deduplicated with the executed quantities
+        tracker.handle_trade_event(&orders_reconciled("2", "Sell", 200, 330));
+        tracker.handle_trade_event(&orders_reconciled("1", "Buy", 200, 320));
+        tracker.handle_trade_event(&orders_reconciled("3", "Buy", 100, 340));
+        let position = tracker.position("700.HK").unwrap();
+        assert_eq!(position.quantity, Decimal::from(300));
+        assert_eq!(
+            tracker.currency_pnl()[0].cash,
+            Decimal::from(66000 - 32000 - 32000 - 34000)
+        );
+    }
+}
@@ -9,7 +9,7 @@ use time::OffsetDateTime;
 
 use crate::{
     serde_utils,
-    trade::{cmd_code, OrderSide, OrderStatus, OrderTag, OrderType, TriggerStatus},
+    trade::{cmd_code, Order, OrderSide, OrderStatus, OrderTag, OrderType, TriggerStatus},
     Error, Result,
 };
 
@@ -22,7 +22,7 @@ pub enum TopicType {
 }
 
 /// Order changed message
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct PushOrderChanged {
     /// Order side
     pub side: OrderSide,
@@ -87,13 +87,33 @@ pub struct PushOrderChanged {
     pub remark: String,
 }
 
+/// Orders reconciled message
+///
+/// Sent after reconnecting to the trade server or refreshing the snapshot of
+/// today's orders periodically, when the snapshot reveals the changes missed
+/// by the pushes.
+#[derive(Debug)]
+pub struct PushOrdersReconciled {
+    /// The orders which are changed or added, sorted by the updated time
+    pub orders: Vec<Order>,
+    /// The orders which are missing from the snapshot (e.g. the orders of the
+    /// previous day) and removed from the order cache, sorted by the updated
+    /// time
+    pub removed: Vec<Order>,
+}
+
 /// Push event
 #[derive(Debug, Deserialize)]
 #[serde(tag = "event", content = "data")]
+#[allow(clippy::large_enum_variant)]
+#[non_exhaustive]
 pub enum PushEvent {
     /// Order changed
     #[serde(rename = "order_changed_lb")]
     OrderChanged(PushOrderChanged),
+    /// Orders reconciled after reconnecting
+    #[serde(skip)]
+    OrdersReconciled(PushOrdersReconciled),
 }
 
 impl PushEvent {
@@ -4,7 +4,7 @@ use serde::Serialize;
 #[derive(Debug, Serialize, Default)]
 pub struct GetStockPositionsOptions {
     #[serde(skip_serializing_if = "<[_]>::is_empty", rename = "symbol")]
-    symbols: Vec<String>,
+    pub(crate) symbols: Vec<String>,
 }
 
 impl GetStockPositionsOptions {
@@ -4,20 +4,20 @@ use serde::Serialize;
 /// Options for replace order request
 #[derive(Debug, Serialize, Clone)]
 pub struct ReplaceOrderOptions {
-    order_id: String,
-    quantity: Decimal,
+    pub(crate) order_id: String,
+    pub(crate) quantity: Decimal,
     #[serde(skip_serializing_if = "Option::is_none")]
-    price: Option<Decimal>,
+    pub(crate) price: Option<Decimal>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    trigger_price: Option<Decimal>,
+    pub(crate) trigger_price: Option<Decimal>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    limit_offset: Option<Decimal>,
+    pub(crate) limit_offset: Option<Decimal>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    trailing_amount: Option<Decimal>,
+    pub(crate) trailing_amount: Option<Decimal>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    trailing_percent: Option<Decimal>,
+    pub(crate) trailing_percent: Option<Decimal>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    remark: Option<String>,
+    pub(crate) remark: Option<String>,
 }
 
 impl ReplaceOrderOptions {
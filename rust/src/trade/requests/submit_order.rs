@@ -5,32 +5,41 @@ use time::Date;
 use crate::{
     serde_utils,
     trade::{OrderSide, OrderType, OutsideRTH, TimeInForceType},
+    Error, Result,
 };
 
+/// The prefix of the client order id in the remark of an order
+const CLIENT_ORDER_ID_PREFIX: &str = "lpcoid:";
+
+/// Maximum number of the characters of the remark of an order
+const MAX_REMARK_LEN: usize = 64;
+
 /// Options for submit order request
 #[derive(Debug, Serialize, Clone)]
 pub struct SubmitOrderOptions {
-    symbol: String,
-    order_type: OrderType,
-    side: OrderSide,
-    submitted_quantity: Decimal,
-    time_in_force: TimeInForceType,
+    pub(crate) symbol: String,
+    pub(crate) order_type: OrderType,
+    pub(crate) side: OrderSide,
+    pub(crate) submitted_quantity: Decimal,
+    pub(crate) time_in_force: TimeInForceType,
     #[serde(skip_serializing_if = "Option::is_none")]
-    submitted_price: Option<Decimal>,
+    pub(crate) submitted_price: Option<Decimal>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    trigger_price: Option<Decimal>,
+    pub(crate) trigger_price: Option<Decimal>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    limit_offset: Option<Decimal>,
+    pub(crate) limit_offset: Option<Decimal>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    trailing_amount: Option<Decimal>,
+    pub(crate) trailing_amount: Option<Decimal>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    trailing_percent: Option<Decimal>,
+    pub(crate) trailing_percent: Option<Decimal>,
     #[serde(with = "serde_utils::date_opt")]
-    expire_date: Option<Date>,
+    pub(crate) expire_date: Option<Date>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    outside_rth: Option<OutsideRTH>,
+    pub(crate) outside_rth: Option<OutsideRTH>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    remark: Option<String>,
+    pub(crate) remark: Option<String>,
+    #[serde(skip)]
+    pub(crate) client_order_id: Option<String>,
 }
 
 impl SubmitOrderOptions {
@@ -57,6 +66,7 @@ impl SubmitOrderOptions {
             expire_date: None,
             outside_rth: None,
             remark: None,
+            client_order_id: None,
         }
     }
 
@@ -139,4 +149,40 @@ impl SubmitOrderOptions {
             ..self
         }
     }
+
+    /// Set the client order id, which identifies the order when the
+    /// submission is retried by
+    /// [`TradeContext::submit_order_with_retry`](crate::trade::TradeContext::submit_order_with_retry)
+    ///
+    /// The client order id prefixed with `lpcoid:` is appended to the remark
+    /// of the order, separated by a space, and is returned in
+    /// [`Order::remark`](crate::trade::Order::remark). The order is rejected
+    /// with [`Error::RemarkTooLong`](crate::Error::RemarkTooLong) if the
+    /// remark exceeds 64 characters. It should be unique among the orders of
+    /// the day.
+    #[inline]
+    #[must_use]
+    pub fn client_order_id(self, client_order_id: impl Into<String>) -> Self {
+        Self {
+            client_order_id: Some(client_order_id.into()),
+            ..self
+        }
+    }
+
+    /// Appends the client order id to the remark
+    pub(crate) fn append_client_order_id(mut self) -> Result<Self> {
+        let Some(client_order_id) = self.client_order_id.take() else {
+            return Ok(self);
+        };
+        let tag = format!("{CLIENT_ORDER_ID_PREFIX}{client_order_id}");
+        let remark = match self.remark.take().filter(|remark| !remark.is_empty()) {
+            Some(remark) => format!("{remark} {tag}"),
+            None => tag,
+        };
+        if remark.chars().count() > MAX_REMARK_LEN {
+            return Err(Error::RemarkTooLong { remark });
+        }
+        self.remark = Some(remark);
+        Ok(self)
+    }
 }
@@ -0,0 +1,956 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+use rust_decimal::Decimal;
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+
+use crate::{
+    quote::{self, PushEventDetail, SecurityStaticInfo},
+    trade::{Order, OrderSide, OrderStatus, PortfolioTracker, SubmitOrderOptions},
+    types::{market_currency, parse_market_from_symbol},
+    Error, Result,
+};
+
+/// Reason of a rejection of the pre-trade risk checks
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RiskViolation {
+    /// The kill switch is enabled
+    #[error("kill switch is enabled")]
+    KillSwitch,
+    /// The security is restricted
+    #[error("restricted symbol")]
+    RestrictedSymbol,
+    /// The notional of the order exceeds the limit
+    #[error("order notional {notional} exceeds {limit}")]
+    OrderNotional {
+        /// Notional of the order
+        notional: Decimal,
+        /// Limit
+        limit: Decimal,
+    },
+    /// The quantity of the order exceeds the limit
+    #[error("order quantity {quantity} exceeds {limit}")]
+    OrderQuantity {
+        /// Quantity of the order
+        quantity: Decimal,
+        /// Limit
+        limit: Decimal,
+    },
+    /// The position after the order is filled exceeds the limit
+    #[error("position {position} exceeds {limit}")]
+    PositionQuantity {
+        /// Position after the order is filled
+        position: Decimal,
+        /// Limit
+        limit: Decimal,
+    },
+    /// The price of the order is out of the collar of the last price
+    #[error("price {price} is out of the collar {collar} of the last price {last_price}")]
+    PriceCollar {
+        /// Price of the order
+        price: Decimal,
+        /// Last price
+        last_price: Decimal,
+        /// Collar, the ratio of the price to the last price
+        collar: Decimal,
+    },
+    /// The quantity of the order is not a multiple of the lot size
+    #[error("quantity {quantity} is not a multiple of the lot size {lot_size}")]
+    LotSize {
+        /// Quantity of the order
+        quantity: Decimal,
+        /// Lot size
+        lot_size: i32,
+    },
+    /// The daily loss exceeds the limit
+    #[error("daily loss {loss} of {currency} exceeds {limit}")]
+    DailyLoss {
+        /// Currency
+        currency: String,
+        /// Loss
+        loss: Decimal,
+        /// Limit
+        limit: Decimal,
+    },
+    /// The orders are not tracked by the trade context, because
+    /// [`TopicType::Private`](crate::trade::TopicType::Private) is not
+    /// subscribed
+    #[error("orders are not tracked, subscribe to the private topic")]
+    OrdersNotTracked,
+}
+
+/// Limits of the pre-trade risk checks
+///
+/// All limits are disabled by default.
+#[derive(Debug, Clone, Default)]
+pub struct RiskLimits {
+    pub(crate) max_order_notional: Option<Decimal>,
+    pub(crate) max_order_quantity: Option<Decimal>,
+    pub(crate) max_position_quantity: Option<Decimal>,
+    pub(crate) price_collar: Option<Decimal>,
+    pub(crate) max_daily_loss: HashMap<String, Decimal>,
+    pub(crate) restricted_symbols: HashSet<String>,
+}
+
+impl RiskLimits {
+    /// Create a new `RiskLimits`
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum notional of an order, in the currency of the security
+    ///
+    /// The notional of a market order is valued at the last price, and it is
+    /// not checked if the last price is unknown.
+    #[inline]
+    #[must_use]
+    pub fn max_order_notional(self, notional: Decimal) -> Self {
+        Self {
+            max_order_notional: Some(notional),
+            ..self
+        }
+    }
+
+    /// Set the maximum quantity of an order
+    #[inline]
+    #[must_use]
+    pub fn max_order_quantity(self, quantity: Decimal) -> Self {
+        Self {
+            max_order_quantity: Some(quantity),
+            ..self
+        }
+    }
+
+    /// Set the maximum absolute position of a security after the order and
+    /// the other open orders of the security are filled
+    ///
+    /// The positions are read from the [`PortfolioTracker`] of the
+    /// [`RiskManager`], the orders reducing the positions are always allowed.
+    #[inline]
+    #[must_use]
+    pub fn max_position_quantity(self, quantity: Decimal) -> Self {
+        Self {
+            max_position_quantity: Some(quantity),
+            ..self
+        }
+    }
+
+    /// Set the price collar, the maximum ratio of the difference between the
+    /// price of an order and the last price to the last price
+    ///
+    /// For example, `0.05` rejects the orders whose prices are more than 5%
+    /// away from the last price.
+    #[inline]
+    #[must_use]
+    pub fn price_collar(self, collar: Decimal) -> Self {
+        Self {
+            price_collar: Some(collar),
+            ..self
+        }
+    }
+
+    /// Set the maximum daily loss of a currency, measured from the
+    /// start-of-day marks of the positions of the [`PortfolioTracker`] of the
+    /// [`RiskManager`], see [`PositionPnl::daily_pnl`]
+    ///
+    /// The tracker should be seeded at the start of every trading day. Only
+    /// the loss in the currency of the order is checked, and once the limit is
+    /// reached, only the orders reducing the positions are allowed.
+    ///
+    /// [`PositionPnl::daily_pnl`]: crate::trade::PositionPnl::daily_pnl
+    #[must_use]
+    pub fn max_daily_loss(mut self, currency: impl Into<String>, loss: Decimal) -> Self {
+        self.max_daily_loss.insert(currency.into(), loss);
+        self
+    }
+
+    /// Set the restricted securities
+    #[must_use]
+    pub fn restricted_symbols<I, T>(self, symbols: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        Self {
+            restricted_symbols: symbols.into_iter().map(Into::into).collect(),
+            ..self
+        }
+    }
+}
+
+/// The quantity reserved by an order which passed the checks, until the
+/// change of the order is found in the orders of the trade context
+struct Reservation {
+    symbol: String,
+    /// The signed quantity which is not executed yet
+    quantity: Decimal,
+    /// The id of the submitted or replaced order, `None` while it is being
+    /// submitted
+    order_id: Option<String>,
+    /// The updated time of the replaced order
+    updated_at: Option<OffsetDateTime>,
+}
+
+#[derive(Default)]
+struct RiskState {
+    limits: RiskLimits,
+    kill_switch: bool,
+    last_prices: HashMap<String, Decimal>,
+    lot_sizes: HashMap<String, i32>,
+    portfolio: Option<PortfolioTracker>,
+    reservations: HashMap<u64, Reservation>,
+    next_reservation_id: u64,
+}
+
+/// An order to be checked
+pub(crate) struct OrderRequest<'a> {
+    symbol: &'a str,
+    side: OrderSide,
+    quantity: Decimal,
+    /// The quantity which is not executed yet
+    open_quantity: Decimal,
+    price: Option<Decimal>,
+    /// The id of the order to be replaced
+    order_id: Option<&'a str>,
+}
+
+impl<'a> OrderRequest<'a> {
+    pub(crate) fn submit(options: &'a SubmitOrderOptions) -> Self {
+        Self {
+            symbol: &options.symbol,
+            side: options.side,
+            quantity: options.submitted_quantity,
+            open_quantity: options.submitted_quantity,
+            price: options.submitted_price,
+            order_id: None,
+        }
+    }
+
+    pub(crate) fn replace(
+        order_id: Option<&'a str>,
+        symbol: &'a str,
+        side: OrderSide,
+        executed_quantity: Decimal,
+        quantity: Decimal,
+        price: Option<Decimal>,
+    ) -> Self {
+        Self {
+            symbol,
+            side,
+            quantity,
+            open_quantity: (quantity - executed_quantity).max(Decimal::ZERO),
+            price,
+            order_id,
+        }
+    }
+
+    /// Returns the signed quantity which is not executed yet
+    fn signed_open_quantity(&self) -> Decimal {
+        match self.side {
+            OrderSide::Buy => self.open_quantity,
+            OrderSide::Sell => -self.open_quantity,
+            OrderSide::Unknown => Decimal::ZERO,
+        }
+    }
+}
+
+#[inline]
+fn updated_at(order: &Order) -> OffsetDateTime {
+    order.updated_at.unwrap_or(order.submitted_at)
+}
+
+impl RiskState {
+    /// Returns the signed quantity of the other open orders and the
+    /// reservations of the security which is not executed yet
+    ///
+    /// An open order with a reservation is counted with the reserved quantity.
+    fn pending_quantity(&self, order: &OrderRequest, orders: &[Order]) -> Decimal {
+        let is_other =
+            |order_id: Option<&str>| order.order_id.is_none() || order_id != order.order_id;
+        let is_reserved = |order_id: &str| {
+            self.reservations
+                .values()
+                .any(|reservation| reservation.order_id.as_deref() == Some(order_id))
+        };
+
+        let open_quantity: Decimal = orders
+            .iter()
+            .filter(|open_order| {
+                open_order.symbol == order.symbol
+                    && open_order.status != OrderStatus::Unknown
+                    && !open_order.status.is_terminal()
+                    && is_other(Some(&open_order.order_id))
+                    && !is_reserved(&open_order.order_id)
+            })
+            .map(|open_order| {
+                let open = (open_order.quantity - open_order.executed_quantity).max(Decimal::ZERO);
+                match open_order.side {
+                    OrderSide::Buy => open,
+                    OrderSide::Sell => -open,
+                    OrderSide::Unknown => Decimal::ZERO,
+                }
+            })
+            .sum();
+        let reserved_quantity: Decimal = self
+            .reservations
+            .values()
+            .filter(|reservation| {
+                reservation.symbol == order.symbol && is_other(reservation.order_id.as_deref())
+            })
+            .map(|reservation| reservation.quantity)
+            .sum();
+        open_quantity + reserved_quantity
+    }
+
+    /// Removes the reservations of the security whose orders are changed
+    fn release_reservations(&mut self, symbol: &str, orders: &[Order]) {
+        self.reservations.retain(|_, reservation| {
+            if reservation.symbol != symbol {
+                return true;
+            }
+            let Some(order_id) = &reservation.order_id else {
+                return true;
+            };
+            match orders.iter().find(|order| &order.order_id == order_id) {
+                Some(order) => reservation.updated_at == Some(updated_at(order)),
+                None => true,
+            }
+        });
+    }
+
+    fn check(
+        &self,
+        order: &OrderRequest,
+        orders: &[Order],
+    ) -> std::result::Result<(), RiskViolation> {
+        if self.kill_switch {
+            return Err(RiskViolation::KillSwitch);
+        }
+        if self.limits.restricted_symbols.contains(order.symbol) {
+            return Err(RiskViolation::RestrictedSymbol);
+        }
+
+        if let Some(limit) = self.limits.max_order_quantity {
+            if order.quantity > limit {
+                return Err(RiskViolation::OrderQuantity {
+                    quantity: order.quantity,
+                    limit,
+                });
+            }
+        }
+
+        if let Some(lot_size) = self.lot_sizes.get(order.symbol).copied() {
+            if lot_size > 0 && !(order.quantity % Decimal::from(lot_size)).is_zero() {
+                return Err(RiskViolation::LotSize {
+                    quantity: order.quantity,
+                    lot_size,
+                });
+            }
+        }
+
+        let position = self
+            .portfolio
+            .as_ref()
+            .and_then(|portfolio| portfolio.position(order.symbol));
+        let last_price = self
+            .last_prices
+            .get(order.symbol)
+            .copied()
+            .or_else(|| position.as_ref().and_then(|position| position.last_price));
+
+        if let (Some(collar), Some(price), Some(last_price)) =
+            (self.limits.price_collar, order.price, last_price)
+        {
+            if !last_price.is_zero() && ((price - last_price) / last_price).abs() > collar {
+                return Err(RiskViolation::PriceCollar {
+                    price,
+                    last_price,
+                    collar,
+                });
+            }
+        }
+
+        if let (Some(limit), Some(price)) =
+            (self.limits.max_order_notional, order.price.or(last_price))
+        {
+            let notional = price * order.quantity;
+            if notional > limit {
+                return Err(RiskViolation::OrderNotional { notional, limit });
+            }
+        }
+
+        // the other open orders are assumed to be filled
+        let current = position
+            .as_ref()
+            .map(|position| position.quantity)
+            .unwrap_or_default()
+            + self.pending_quantity(order, orders);
+        let target = current + order.signed_open_quantity();
+        let is_reducing = target.abs() <= current.abs() && target * current >= Decimal::ZERO;
+        if is_reducing {
+            return Ok(());
+        }
+
+        if let Some(limit) = self.limits.max_position_quantity {
+            if target.abs() > limit {
+                return Err(RiskViolation::PositionQuantity {
+                    position: target,
+                    limit,
+                });
+            }
+        }
+
+        if let Some(portfolio) = &self.portfolio {
+            let currency = match &position {
+                Some(position) => position.currency.as_str(),
+                None => market_currency(parse_market_from_symbol(order.symbol).unwrap_or_default()),
+            };
+            if let Some(limit) = self.limits.max_daily_loss.get(currency).copied() {
+                let loss = portfolio
+                    .currency_pnl()
+                    .into_iter()
+                    .find(|pnl| pnl.currency == currency)
+                    .map(|pnl| -pnl.daily_pnl)
+                    .unwrap_or_default();
+                if loss >= limit {
+                    return Err(RiskViolation::DailyLoss {
+                        currency: currency.to_string(),
+                        loss,
+                        limit,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Client-side pre-trade risk checks
+///
+/// The orders submitted or replaced by a [`TradeContext`] with a risk manager
+/// are checked before they are sent, and the rejected orders return
+/// [`Error::RiskCheckFailed`]. The last prices are updated with the quote
+/// pushes, the lot sizes with the static info of the securities, and the
+/// positions and the daily loss are read from a [`PortfolioTracker`].
+///
+/// The open orders are read from the order cache of the [`TradeContext`],
+/// which requires [`TopicType::Private`] to be subscribed. The quantity of an
+/// order which passes the checks is reserved until the order is found in the
+/// cache, so the orders submitted back to back are checked against each
+/// other.
+///
+/// [`TradeContext`]: crate::trade::TradeContext
+/// [`TopicType::Private`]: crate::trade::TopicType::Private
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use longport::{
+///     decimal,
+///     quote::QuoteContext,
+///     trade::{
+///         OrderSide, OrderType, PortfolioTracker, RiskLimits, RiskManager, SubmitOrderOptions,
+///         TimeInForceType, TradeContext,
+///     },
+///     Config,
+/// };
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let config = Arc::new(Config::from_env()?);
+/// let (quote_ctx, quote_receiver) = QuoteContext::try_new(config.clone()).await?;
+/// let (ctx, _) = TradeContext::try_new(config).await?;
+///
+/// let portfolio = PortfolioTracker::try_new(&ctx).await?;
+/// let risk = RiskManager::new(
+///     RiskLimits::new()
+///         .max_order_notional(decimal!(1000000))
+///         .price_collar(decimal!(0.05)),
+/// )
+/// .portfolio(portfolio);
+/// risk.update_static_info(&quote_ctx.static_info(["700.HK"]).await?);
+/// let _quote_receiver = risk.attach_quote(quote_receiver);
+/// ctx.set_risk_manager(Some(risk));
+///
+/// let opts = SubmitOrderOptions::new(
+///     "700.HK",
+///     OrderType::LO,
+///     OrderSide::Buy,
+///     decimal!(200),
+///     TimeInForceType::Day,
+/// )
+/// .submitted_price(decimal!(50i32));
+/// let resp = ctx.submit_order(opts).await?;
+/// println!("{:?}", resp);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// # });
+/// ```
+#[derive(Clone, Default)]
+pub struct RiskManager(Arc<Mutex<RiskState>>);
+
+impl RiskManager {
+    /// Create a new `RiskManager`
+    pub fn new(limits: RiskLimits) -> Self {
+        Self(Arc::new(Mutex::new(RiskState {
+            limits,
+            ..Default::default()
+        })))
+    }
+
+    /// Set the portfolio tracker used to check the positions and the daily
+    /// loss
+    #[must_use]
+    pub fn portfolio(self, portfolio: PortfolioTracker) -> Self {
+        self.0.lock().unwrap().portfolio = Some(portfolio);
+        self
+    }
+
+    /// Returns the limits
+    pub fn limits(&self) -> RiskLimits {
+        self.0.lock().unwrap().limits.clone()
+    }
+
+    /// Replace the limits
+    pub fn set_limits(&self, limits: RiskLimits) {
+        self.0.lock().unwrap().limits = limits;
+    }
+
+    /// Enable or disable the kill switch, all orders are rejected when it is
+    /// enabled
+    pub fn set_kill_switch(&self, enabled: bool) {
+        self.0.lock().unwrap().kill_switch = enabled;
+    }
+
+    /// Returns `true` if the kill switch is enabled
+    pub fn is_kill_switch_enabled(&self) -> bool {
+        self.0.lock().unwrap().kill_switch
+    }
+
+    /// Update the lot sizes with the static info of the securities
+    pub fn update_static_info(&self, infos: &[SecurityStaticInfo]) {
+        let mut state = self.0.lock().unwrap();
+        for info in infos {
+            state.lot_sizes.insert(info.symbol.clone(), info.lot_size);
+        }
+    }
+
+    /// Set the last price of a security
+    pub fn set_last_price(&self, symbol: impl Into<String>, price: Decimal) {
+        self.0
+            .lock()
+            .unwrap()
+            .last_prices
+            .insert(symbol.into(), price);
+    }
+
+    /// Update the last prices with a quote push event
+    ///
+    /// Only [`PushEventDetail::Quote`] and [`PushEventDetail::Trade`] events
+    /// are used.
+    pub fn handle_quote_event(&self, event: &quote::PushEvent) {
+        let price = match &event.detail {
+            PushEventDetail::Quote(quote) => quote.last_done,
+            PushEventDetail::Trade(trades) => match trades.trades.last() {
+                Some(trade) => trade.price,
+                None => return,
+            },
+            _ => return,
+        };
+        if !price.is_zero() {
+            self.set_last_price(event.symbol.clone(), price);
+        }
+    }
+
+    /// Update the last prices with all events of a quote push receiver, the
+    /// events are forwarded to the returned receiver after being handled
+    pub fn attach_quote(
+        &self,
+        mut receiver: mpsc::UnboundedReceiver<quote::PushEvent>,
+    ) -> mpsc::UnboundedReceiver<quote::PushEvent> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let manager = self.clone();
+        tokio::spawn(async move {
+            while let Some(event) = receiver.recv().await {
+                manager.handle_quote_event(&event);
+                let _ = tx.send(event);
+            }
+        });
+        rx
+    }
+
+    fn check(&self, order: &OrderRequest, orders: &[Order]) -> Result<()> {
+        self.0
+            .lock()
+            .unwrap()
+            .check(order, orders)
+            .map_err(|violation| Error::RiskCheckFailed {
+                symbol: order.symbol.to_string(),
+                violation,
+            })
+    }
+
+    /// Checks an order and reserves its quantity if it passes, `orders` are
+    /// today's orders of the security
+    ///
+    /// The reservations whose orders are changed in `orders` are released
+    /// first. `updated_at` is the updated time of the order to be replaced.
+    pub(crate) fn reserve(
+        &self,
+        order: &OrderRequest,
+        orders: &[Order],
+        updated_at: Option<OffsetDateTime>,
+    ) -> Result<RiskReservation> {
+        let mut state = self.0.lock().unwrap();
+        state.release_reservations(order.symbol, orders);
+        state
+            .check(order, orders)
+            .map_err(|violation| Error::RiskCheckFailed {
+                symbol: order.symbol.to_string(),
+                violation,
+            })?;
+
+        let id = state.next_reservation_id;
+        state.next_reservation_id += 1;
+        state.reservations.insert(
+            id,
+            Reservation {
+                symbol: order.symbol.to_string(),
+                quantity: order.signed_open_quantity(),
+                order_id: order.order_id.map(ToString::to_string),
+                updated_at,
+            },
+        );
+        Ok(RiskReservation {
+            manager: self.clone(),
+            id,
+            is_kept: false,
+        })
+    }
+
+    /// Check an order to be submitted, the quantities of the open orders of
+    /// the security which are not executed yet are added to the position
+    pub fn check_submit(&self, options: &SubmitOrderOptions, open_orders: &[Order]) -> Result<()> {
+        self.check(&OrderRequest::submit(options), open_orders)
+    }
+
+    /// Check an order to be replaced with the new quantity and price, the
+    /// quantities of the open orders of the security which are not executed
+    /// yet are added to the position
+    ///
+    /// The `open_orders` should not include the order to be replaced.
+    pub fn check_replace(
+        &self,
+        symbol: &str,
+        side: OrderSide,
+        executed_quantity: Decimal,
+        quantity: Decimal,
+        price: Option<Decimal>,
+        open_orders: &[Order],
+    ) -> Result<()> {
+        self.check(
+            &OrderRequest::replace(None, symbol, side, executed_quantity, quantity, price),
+            open_orders,
+        )
+    }
+}
+
+/// The reserved quantity of an order which passed the checks of a
+/// [`RiskManager`], released when dropped unless it is kept after the order is
+/// sent
+pub(crate) struct RiskReservation {
+    manager: RiskManager,
+    id: u64,
+    is_kept: bool,
+}
+
+impl RiskReservation {
+    /// Keeps the reservation until the change of the order is found in the
+    /// orders of the trade context
+    pub(crate) fn keep(mut self, order_id: &str) {
+        if let Some(reservation) = self
+            .manager
+            .0
+            .lock()
+            .unwrap()
+            .reservations
+            .get_mut(&self.id)
+        {
+            reservation.order_id = Some(order_id.to_string());
+        }
+        self.is_kept = true;
+    }
+}
+
+impl Drop for RiskReservation {
+    fn drop(&mut self) {
+        if !self.is_kept {
+            self.manager.0.lock().unwrap().reservations.remove(&self.id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        quote::PushQuote,
+        trade::{OrderType, StockPositionsResponse, TimeInForceType},
+    };
+
+    fn buy(symbol: &str, quantity: Decimal, price: Decimal) -> SubmitOrderOptions {
+        SubmitOrderOptions::new(
+            symbol,
+            OrderType::LO,
+            OrderSide::Buy,
+            quantity,
+            TimeInForceType::Day,
+        )
+        .submitted_price(price)
+    }
+
+    fn open_order(side: &str, quantity: i64, executed: i64) -> Order {
+        serde_json::from_value(json!({
+            "currency": "HKD",
+            "executed_price": "0",
+            "executed_quantity": executed.to_string(),
+            "expire_date": "",
+            "last_done": "",
+            "limit_offset": "",
+            "msg": "",
+            "order_id": "1",
+            "order_type": "LO",
+            "outside_rth": "UnknownOutsideRth",
+            "price": "300",
+            "quantity": quantity.to_string(),
+            "side": side,
+            "status": "NewStatus",
+            "stock_name": "Tencent",
+            "submitted_at": "1700000000",
+            "symbol": "700.HK",
+            "tag": "Normal",
+            "time_in_force": "Day",
+            "trailing_amount": "",
+            "trailing_percent": "",
+            "trigger_at": "0",
+            "trigger_price": "",
+            "trigger_status": "NOT_USED",
+            "updated_at": "1700000000",
+            "remark": ""
+        }))
+        .unwrap()
+    }
+
+    fn quote_event(last_done: i64) -> quote::PushEvent {
+        quote::PushEvent {
+            sequence: 0,
+            symbol: "700.HK".to_string(),
+            detail: PushEventDetail::Quote(PushQuote {
+                last_done: last_done.into(),
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn violation(res: Result<()>) -> RiskViolation {
+        match res {
+            Err(Error::RiskCheckFailed { violation, .. }) => violation,
+            res => panic!("unexpected result: {res:?}"),
+        }
+    }
+
+    #[test]
+    fn test_check_submit() {
+        let manager = RiskManager::new(
+            RiskLimits::new()
+                .max_order_notional(decimal!(100000))
+                .max_order_quantity(decimal!(1000))
+                .price_collar(decimal!(0.1))
+                .restricted_symbols(["9988.HK"]),
+        );
+        manager.set_last_price("700.HK", decimal!(300));
+        manager
+            .0
+            .lock()
+            .unwrap()
+            .lot_sizes
+            .insert("700.HK".to_string(), 100);
+
+        assert!(manager
+            .check_submit(&buy("700.HK", decimal!(200), decimal!(310)), &[])
+            .is_ok());
+        assert_eq!(
+            violation(manager.check_submit(&buy("9988.HK", decimal!(100), decimal!(80)), &[])),
+            RiskViolation::RestrictedSymbol
+        );
+        assert_eq!(
+            violation(manager.check_submit(&buy("700.HK", decimal!(2000), decimal!(300)), &[])),
+            RiskViolation::OrderQuantity {
+                quantity: decimal!(2000),
+                limit: decimal!(1000)
+            }
+        );
+        assert_eq!(
+            violation(manager.check_submit(&buy("700.HK", decimal!(150), decimal!(300)), &[])),
+            RiskViolation::LotSize {
+                quantity: decimal!(150),
+                lot_size: 100
+            }
+        );
+        assert!(matches!(
+            violation(manager.check_submit(&buy("700.HK", decimal!(100), decimal!(340)), &[])),
+            RiskViolation::PriceCollar { .. }
+        ));
+        assert_eq!(
+            violation(manager.check_submit(&buy("700.HK", decimal!(400), decimal!(300)), &[])),
+            RiskViolation::OrderNotional {
+                notional: decimal!(120000),
+                limit: decimal!(100000)
+            }
+        );
+
+        manager.set_kill_switch(true);
+        assert_eq!(
+            violation(manager.check_submit(&buy("700.HK", decimal!(100), decimal!(300)), &[])),
+            RiskViolation::KillSwitch
+        );
+    }
+
+    #[test]
+    fn test_position_and_daily_loss() {
+        let portfolio = PortfolioTracker::new();
+        let positions: StockPositionsResponse = serde_json::from_value(json!({
+            "list": [{
+                "account_channel": "lb",
+                "stock_info": [{
+                    "symbol": "700.HK",
+                    "symbol_name": "Tencent",
+                    "quantity": "200",
+                    "available_quantity": "200",
+                    "currency": "HKD",
+                    "cost_price": "300",
+                    "market": "HK",
+                    "init_quantity": "200"
+                }]
+            }]
+        }))
+        .unwrap();
+        portfolio.seed(&positions, &[]);
+        portfolio.handle_quote_event(&quote_event(310));
+        let manager = RiskManager::new(
+            RiskLimits::new()
+                .max_position_quantity(decimal!(300))
+                .max_daily_loss("HKD", decimal!(1000)),
+        )
+        .portfolio(portfolio.clone());
+
+        // the open orders are added to the position
+        assert!(manager
+            .check_submit(&buy("700.HK", decimal!(100), decimal!(310)), &[])
+            .is_ok());
+        assert_eq!(
+            violation(manager.check_submit(
+                &buy("700.HK", decimal!(100), decimal!(310)),
+                &[open_order("Buy", 200, 100)]
+            )),
+            RiskViolation::PositionQuantity {
+                position: decimal!(400),
+                limit: decimal!(300)
+            }
+        );
+        assert!(manager
+            .check_submit(
+                &buy("700.HK", decimal!(200), decimal!(310)),
+                &[open_order("Sell", 100, 0)]
+            )
+            .is_ok());
+
+        // the loss is measured from the start-of-day mark
+        portfolio.handle_quote_event(&quote_event(300));
+        assert_eq!(
+            violation(manager.check_submit(&buy("700.HK", decimal!(100), decimal!(300)), &[])),
+            RiskViolation::DailyLoss {
+                currency: "HKD".to_string(),
+                loss: decimal!(2000),
+                limit: decimal!(1000)
+            }
+        );
+        let sell = SubmitOrderOptions::new(
+            "700.HK",
+            OrderType::LO,
+            OrderSide::Sell,
+            decimal!(100),
+            TimeInForceType::Day,
+        )
+        .submitted_price(decimal!(300));
+        assert!(manager.check_submit(&sell, &[]).is_ok());
+
+        // only the loss in the currency of the order is checked
+        assert!(manager
+            .check_submit(&buy("AAPL.US", decimal!(100), decimal!(200)), &[])
+            .is_ok());
+    }
+
+    #[test]
+    fn test_reservations() {
+        let manager = RiskManager::new(RiskLimits::new().max_position_quantity(decimal!(300)));
+        let options = buy("700.HK", decimal!(200), decimal!(300));
+        let reserve = |orders: &[Order]| {
+            manager
+                .reserve(&OrderRequest::submit(&options), orders, None)
+                .map(|_| ())
+        };
+
+        // the reservation is released if the order is not sent
+        reserve(&[]).unwrap();
+        reserve(&[]).unwrap();
+
+        // the reservation is kept until the order is found in the orders
+        manager
+            .reserve(&OrderRequest::submit(&options), &[], None)
+            .unwrap()
+            .keep("1");
+        assert!(matches!(
+            violation(reserve(&[])),
+            RiskViolation::PositionQuantity { .. }
+        ));
+        let mut order = open_order("Buy", 200, 0);
+        order.status = OrderStatus::Canceled;
+        reserve(&[order.clone()]).unwrap();
+
+        // the replaced quantity is reserved until the order is updated
+        order.status = OrderStatus::New;
+        manager
+            .reserve(
+                &OrderRequest::replace(
+                    Some("1"),
+                    "700.HK",
+                    OrderSide::Buy,
+                    Decimal::ZERO,
+                    decimal!(300),
+                    None,
+                ),
+                &[order.clone()],
+                Some(updated_at(&order)),
+            )
+            .unwrap()
+            .keep("1");
+        assert!(matches!(
+            violation(manager.check_submit(
+                &buy("700.HK", decimal!(100), decimal!(300)),
+                &[order.clone()]
+            )),
+            RiskViolation::PositionQuantity { .. }
+        ));
+        order.updated_at = Some(order.submitted_at + time::Duration::SECOND);
+        reserve(&[order]).unwrap_err();
+        reserve(&[]).unwrap();
+    }
+}
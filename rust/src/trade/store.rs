@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+
+use time::OffsetDateTime;
+
+use crate::trade::{Order, OrderStatus, PushOrderChanged, TimeInForceType};
+
+#[inline]
+fn updated_at(order: &Order) -> OffsetDateTime {
+    order.updated_at.unwrap_or(order.submitted_at)
+}
+
+/// Returns `true` if the state of the order is changed
+fn is_changed(a: &Order, b: &Order) -> bool {
+    a.status != b.status
+        || a.executed_quantity != b.executed_quantity
+        || a.executed_price != b.executed_price
+        || a.quantity != b.quantity
+        || a.price != b.price
+        || a.trigger_status != b.trigger_status
+        || a.updated_at != b.updated_at
+}
+
+/// Today's orders, maintained from the snapshots of
+/// [`TradeContext::today_orders`](crate::trade::TradeContext::today_orders)
+/// and the order changed pushes
+#[derive(Debug, Default)]
+pub(crate) struct OrderStore {
+    orders: HashMap<String, Order>,
+}
+
+impl OrderStore {
+    /// Replaces the orders with a snapshot of today's orders, returns the
+    /// orders which are changed or added by the snapshot and the orders which
+    /// are removed
+    ///
+    /// The orders updated by the pushes later than the snapshot are kept, the
+    /// other orders missing from the snapshot (e.g. the orders of the previous
+    /// day) are removed.
+    pub(crate) fn replace_snapshot(&mut self, orders: Vec<Order>) -> (Vec<Order>, Vec<Order>) {
+        let snapshot_updated_at = orders.iter().map(updated_at).max();
+        let mut current = std::mem::take(&mut self.orders);
+        let mut changed = Vec::new();
+
+        for order in orders {
+            match current.remove(&order.order_id) {
+                Some(current) if updated_at(&current) > updated_at(&order) => {
+                    self.orders.insert(current.order_id.clone(), current);
+                }
+                Some(current) if !is_changed(&current, &order) => {
+                    self.orders.insert(current.order_id.clone(), current);
+                }
+                _ => {
+                    changed.push(order.clone());
+                    self.orders.insert(order.order_id.clone(), order);
+                }
+            }
+        }
+
+        let mut removed = Vec::new();
+        for (order_id, order) in current {
+            if snapshot_updated_at
+                .is_some_and(|snapshot_updated_at| updated_at(&order) > snapshot_updated_at)
+            {
+                self.orders.insert(order_id, order);
+            } else {
+                removed.push(order);
+            }
+        }
+
+        changed.sort_by_key(updated_at);
+        removed.sort_by_key(updated_at);
+        (changed, removed)
+    }
+
+    /// Applies an order changed push, returns `false` if the push is older
+    /// than the stored order
+    pub(crate) fn apply(&mut self, push: &PushOrderChanged) -> bool {
+        match self.orders.get_mut(&push.order_id) {
+            Some(order) => {
+                if updated_at(order) > push.updated_at {
+                    return false;
+                }
+                order.status = push.status;
+                order.stock_name.clone_from(&push.stock_name);
+                order.quantity = push.submitted_quantity;
+                order.executed_quantity = push.executed_quantity;
+                order.price = Some(push.submitted_price);
+                order.executed_price = push.executed_price;
+                order.last_done = push.last_price.or(order.last_done);
+                order.trigger_price = push.trigger_price;
+                order.msg.clone_from(&push.msg);
+                order.tag = push.tag;
+                order.updated_at = Some(push.updated_at);
+                order.trigger_at = push.trigger_at;
+                order.trailing_amount = push.trailing_amount;
+                order.trailing_percent = push.trailing_percent;
+                order.limit_offset = push.limit_offset;
+                order.trigger_status = push.trigger_status;
+                order.remark.clone_from(&push.remark);
+            }
+            None => {
+                // the time in force and the outside RTH option are not pushed,
+                // they are filled by the next snapshot
+                self.orders.insert(
+                    push.order_id.clone(),
+                    Order {
+                        order_id: push.order_id.clone(),
+                        status: push.status,
+                        stock_name: push.stock_name.clone(),
+                        quantity: push.submitted_quantity,
+                        executed_quantity: push.executed_quantity,
+                        price: Some(push.submitted_price),
+                        executed_price: push.executed_price,
+                        submitted_at: push.submitted_at,
+                        side: push.side,
+                        symbol: push.symbol.clone(),
+                        order_type: push.order_type,
+                        last_done: push.last_price,
+                        trigger_price: push.trigger_price,
+                        msg: push.msg.clone(),
+                        tag: push.tag,
+                        time_in_force: TimeInForceType::Unknown,
+                        expire_date: None,
+                        updated_at: Some(push.updated_at),
+                        trigger_at: push.trigger_at,
+                        trailing_amount: push.trailing_amount,
+                        trailing_percent: push.trailing_percent,
+                        limit_offset: push.limit_offset,
+                        trigger_status: push.trigger_status,
+                        currency: push.currency.clone(),
+                        outside_rth: None,
+                        remark: push.remark.clone(),
+                    },
+                );
+            }
+        }
+        true
+    }
+
+    #[inline]
+    pub(crate) fn order(&self, order_id: &str) -> Option<Order> {
+        self.orders.get(order_id).cloned()
+    }
+
+    /// Returns the orders sorted by the submitted time, filtered by the symbol
+    /// and the open status
+    pub(crate) fn orders(&self, symbol: Option<&str>, open_only: bool) -> Vec<Order> {
+        let mut orders = self
+            .orders
+            .values()
+            .filter(|order| symbol.is_none_or(|symbol| order.symbol == symbol))
+            .filter(|order| {
+                // the orders with an unknown status are not open
+                !open_only || (order.status != OrderStatus::Unknown && !order.status.is_terminal())
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        orders.sort_by(|a, b| {
+            a.submitted_at
+                .cmp(&b.submitted_at)
+                .then_with(|| a.order_id.cmp(&b.order_id))
+        });
+        orders
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn order(order_id: &str, status: &str, updated_at: i64) -> Order {
+        serde_json::from_value(json!({
+            "currency": "HKD",
+            "executed_price": "0",
+            "executed_quantity": "0",
+            "expire_date": "",
+            "last_done": "",
+            "limit_offset": "",
+            "msg": "",
+            "order_id": order_id,
+            "order_type": "LO",
+            "outside_rth": "UnknownOutsideRth",
+            "price": "300",
+            "quantity": "100",
+            "side": "Buy",
+            "status": status,
+            "stock_name": "Tencent",
+            "submitted_at": "1700000000",
+            "symbol": "700.HK",
+            "tag": "Normal",
+            "time_in_force": "Day",
+            "trailing_amount": "",
+            "trailing_percent": "",
+            "trigger_at": "0",
+            "trigger_price": "",
+            "trigger_status": "NOT_USED",
+            "updated_at": updated_at.to_string(),
+            "remark": ""
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_replace_snapshot() {
+        let mut store = OrderStore::default();
+        let (changed, removed) = store.replace_snapshot(vec![
+            order("1", "NewStatus", 1700000000),
+            order("2", "FilledStatus", 1700000000),
+        ]);
+        assert_eq!(changed.len(), 2);
+        assert!(removed.is_empty());
+        assert_eq!(store.orders(Some("700.HK"), true).len(), 1);
+
+        // unchanged orders are not reported
+        let (changed, removed) = store.replace_snapshot(vec![
+            order("1", "CanceledStatus", 1700000010),
+            order("2", "FilledStatus", 1700000000),
+        ]);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].status, OrderStatus::Canceled);
+        assert!(removed.is_empty());
+        assert!(store.orders(None, true).is_empty());
+
+        // the snapshot older than the stored order is ignored
+        let (changed, removed) = store.replace_snapshot(vec![
+            order("1", "NewStatus", 1700000005),
+            order("2", "FilledStatus", 1700000000),
+        ]);
+        assert!(changed.is_empty());
+        assert!(removed.is_empty());
+        assert_eq!(store.order("1").unwrap().status, OrderStatus::Canceled);
+
+        // the orders missing from the snapshot are removed, unless they are
+        // updated later than the snapshot
+        store.replace_snapshot(vec![order("3", "NewStatus", 1700000100)]);
+        store
+            .orders
+            .insert("4".to_string(), order("4", "NewStatus", 1700000200));
+        let (changed, removed) = store.replace_snapshot(vec![
+            order("3", "NewStatus", 1700000100),
+            order("5", "NewStatus", 1700000150),
+        ]);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].order_id, "5");
+        assert!(removed.is_empty());
+        assert!(store.order("4").is_some());
+
+        // the orders of the previous day are removed
+        let (changed, removed) = store.replace_snapshot(vec![]);
+        assert!(changed.is_empty());
+        assert_eq!(
+            removed
+                .iter()
+                .map(|order| order.order_id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["3", "5", "4"]
+        );
+        assert!(store.orders(None, false).is_empty());
+    }
+}
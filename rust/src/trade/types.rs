@@ -111,6 +111,21 @@ pub enum OrderStatus {
     PartialWithdrawal,
 }
 
+impl OrderStatus {
+    /// Returns `true` if the order is filled, rejected, canceled, expired or
+    /// partially withdrawn, and will not be changed anymore
+    pub fn is_terminal(self) -> bool {
+        matches!(
+            self,
+            OrderStatus::Filled
+                | OrderStatus::Rejected
+                | OrderStatus::Canceled
+                | OrderStatus::Expired
+                | OrderStatus::PartialWithdrawal
+        )
+    }
+}
+
 /// Execution
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Execution {
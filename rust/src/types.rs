@@ -17,3 +17,23 @@ pub enum Market {
 
 impl_default_for_enum_string!(Market);
 impl_serde_for_enum_string!(Market);
+
+pub(crate) fn parse_market_from_symbol(symbol: &str) -> Option<Market> {
+    let market = symbol.rfind('.').map(|idx| &symbol[idx + 1..])?;
+    Some(match market {
+        "US" => Market::US,
+        "HK" => Market::HK,
+        "SG" => Market::SG,
+        "SH" | "SZ" => Market::CN,
+        _ => return None,
+    })
+}
+
+pub(crate) fn market_currency(market: Market) -> &'static str {
+    match market {
+        Market::US | Market::Unknown => "USD",
+        Market::HK => "HKD",
+        Market::CN => "CNH",
+        Market::SG => "SGD",
+    }
+}
@@ -0,0 +1,828 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures_util::{stream, Stream};
+use longport::{
+    decimal,
+    quote::{
+        AdjustType, BarType, CalcIndex, Candlestick, CapitalDistributionResponse, CapitalFlowLine,
+        CustomPeriod, Depth, FilterWarrantExpiryDate, FilterWarrantInOutBoundsType, Indicator,
+        IndicatorValue, IntradayLine, IssuerInfo, MarketTradingDays, MarketTradingSession,
+        OptionQuote, ParticipantInfo, Period, PushEvent, QuoteApi, QuotePackageDetail,
+        RealtimeQuote, RequestCreateWatchlistGroup, RequestUpdateWatchlistGroup, Security,
+        SecurityBrokers, SecurityCalcIndex, SecurityDepth, SecurityListCategory, SecurityQuote,
+        SecurityStaticInfo, SortOrderType, StrikePriceInfo, SubFlags, Subscription, Trade,
+        TradeSession, TradeSessions, WarrantInfo, WarrantQuote, WarrantSortBy, WarrantStatus,
+        WarrantType, WatchlistGroup,
+    },
+    trade::{
+        AccountBalance, CashFlow, EstimateMaxPurchaseQuantityOptions,
+        EstimateMaxPurchaseQuantityResponse, Execution, FundPositionsResponse, GetCashFlowOptions,
+        GetFundPositionsOptions, GetHistoryExecutionsOptions, GetHistoryOrdersOptions,
+        GetStockPositionsOptions, GetTodayExecutionsOptions, GetTodayOrdersOptions, MarginRatio,
+        Order, OrderDetail, OrderSide, OrderStatus, OrderType, ReplaceOrderOptions, RiskManager,
+        StockPositionsResponse, SubmitOrderOptions, SubmitOrderResponse, TimeInForceType,
+        TopicType, TradeApi,
+    },
+    ConnectionEvent, Decimal, Error, Market, Result,
+};
+use serde_json::json;
+use time::{
+    macros::{date, datetime},
+    Date, OffsetDateTime, PrimitiveDateTime,
+};
+use tokio::sync::mpsc;
+use tracing::Subscriber;
+
+/// A quote API implemented with the required methods only
+#[derive(Clone)]
+struct FakeQuote;
+
+fn daily_candlestick(timestamp: OffsetDateTime, close: Decimal) -> Candlestick {
+    Candlestick {
+        close,
+        open: close,
+        low: close,
+        high: close,
+        volume: 100,
+        turnover: close * decimal!(100),
+        timestamp,
+        trade_session: TradeSession::NormalTrade,
+    }
+}
+
+// the generic arguments are not `Send`, so the futures do not capture them
+#[allow(clippy::manual_async_fn)]
+impl QuoteApi for FakeQuote {
+    fn log_subscriber(&self) -> Arc<dyn Subscriber + Send + Sync> {
+        unimplemented!()
+    }
+
+    fn connection_events(&self) -> mpsc::UnboundedReceiver<ConnectionEvent> {
+        unimplemented!()
+    }
+
+    fn member_id(&self) -> i64 {
+        unimplemented!()
+    }
+
+    fn quote_level(&self) -> &str {
+        unimplemented!()
+    }
+
+    fn quote_package_details(&self) -> &[QuotePackageDetail] {
+        unimplemented!()
+    }
+
+    fn subscribe<I, T>(
+        &self,
+        _symbols: I,
+        _sub_types: impl Into<SubFlags>,
+        _is_first_push: bool,
+    ) -> impl Future<Output = Result<()>> + Send
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        async { unimplemented!() }
+    }
+
+    fn unsubscribe<I, T>(
+        &self,
+        _symbols: I,
+        _sub_types: impl Into<SubFlags>,
+    ) -> impl Future<Output = Result<()>> + Send
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        async { unimplemented!() }
+    }
+
+    fn subscribe_candlesticks<T>(
+        &self,
+        _symbol: T,
+        _period: Period,
+        _trade_sessions: TradeSessions,
+    ) -> impl Future<Output = Result<Vec<Candlestick>>> + Send
+    where
+        T: AsRef<str>,
+    {
+        async { unimplemented!() }
+    }
+
+    fn unsubscribe_candlesticks<T>(
+        &self,
+        _symbol: T,
+        _period: Period,
+    ) -> impl Future<Output = Result<()>> + Send
+    where
+        T: AsRef<str>,
+    {
+        async { unimplemented!() }
+    }
+
+    fn subscribe_custom_candlesticks<T>(
+        &self,
+        _symbol: T,
+        _period: CustomPeriod,
+        _trade_sessions: TradeSessions,
+    ) -> impl Future<Output = Result<Vec<Candlestick>>> + Send
+    where
+        T: AsRef<str>,
+    {
+        async { unimplemented!() }
+    }
+
+    fn unsubscribe_custom_candlesticks<T>(
+        &self,
+        _symbol: T,
+        _period: CustomPeriod,
+    ) -> impl Future<Output = Result<()>> + Send
+    where
+        T: AsRef<str>,
+    {
+        async { unimplemented!() }
+    }
+
+    fn subscribe_bars<T>(
+        &self,
+        _symbol: T,
+        _bar_type: BarType,
+        _trade_sessions: TradeSessions,
+    ) -> impl Future<Output = Result<()>> + Send
+    where
+        T: AsRef<str>,
+    {
+        async { unimplemented!() }
+    }
+
+    fn unsubscribe_bars<T>(
+        &self,
+        _symbol: T,
+        _bar_type: BarType,
+    ) -> impl Future<Output = Result<()>> + Send
+    where
+        T: AsRef<str>,
+    {
+        async { unimplemented!() }
+    }
+
+    fn attach_indicator<T>(
+        &self,
+        _symbol: T,
+        _period: Period,
+        _indicator: Indicator,
+    ) -> impl Future<Output = Result<Option<IndicatorValue>>> + Send
+    where
+        T: AsRef<str>,
+    {
+        async { unimplemented!() }
+    }
+
+    fn detach_indicator<T>(
+        &self,
+        _symbol: T,
+        _period: Period,
+        _indicator: Indicator,
+    ) -> impl Future<Output = Result<()>> + Send
+    where
+        T: AsRef<str>,
+    {
+        async { unimplemented!() }
+    }
+
+    fn subscriptions(&self) -> impl Future<Output = Result<Vec<Subscription>>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn push_stream(&self) -> Result<impl Stream<Item = PushEvent> + Send + Unpin + 'static> {
+        Err::<stream::Empty<PushEvent>, _>(Error::Unsupported {
+            method: "push_stream",
+        })
+    }
+
+    fn static_info<I, T>(
+        &self,
+        _symbols: I,
+    ) -> impl Future<Output = Result<Vec<SecurityStaticInfo>>> + Send
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        async { unimplemented!() }
+    }
+
+    fn quote<I, T>(&self, _symbols: I) -> impl Future<Output = Result<Vec<SecurityQuote>>> + Send
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        async { unimplemented!() }
+    }
+
+    fn option_quote<I, T>(
+        &self,
+        _symbols: I,
+    ) -> impl Future<Output = Result<Vec<OptionQuote>>> + Send
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        async { unimplemented!() }
+    }
+
+    fn warrant_quote<I, T>(
+        &self,
+        _symbols: I,
+    ) -> impl Future<Output = Result<Vec<WarrantQuote>>> + Send
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        async { unimplemented!() }
+    }
+
+    fn depth(
+        &self,
+        _symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<SecurityDepth>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn brokers(
+        &self,
+        _symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<SecurityBrokers>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn participants(&self) -> impl Future<Output = Result<Vec<ParticipantInfo>>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn trades(
+        &self,
+        _symbol: impl Into<String>,
+        _count: usize,
+    ) -> impl Future<Output = Result<Vec<Trade>>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn intraday(
+        &self,
+        _symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<Vec<IntradayLine>>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn candlesticks(
+        &self,
+        _symbol: impl Into<String>,
+        _period: Period,
+        _count: usize,
+        _adjust_type: AdjustType,
+        _trade_sessions: TradeSessions,
+    ) -> impl Future<Output = Result<Vec<Candlestick>>> + Send {
+        async { unimplemented!() }
+    }
+
+    /// Returns the daily candlesticks of 2024-01-02..=2024-01-05 from the
+    /// cursor, the prices are halved by a split on 2024-01-04
+    fn history_candlesticks_by_offset(
+        &self,
+        _symbol: impl Into<String>,
+        _period: Period,
+        adjust_type: AdjustType,
+        _forward: bool,
+        time: Option<PrimitiveDateTime>,
+        count: usize,
+        _trade_sessions: TradeSessions,
+    ) -> impl Future<Output = Result<Vec<Candlestick>>> + Send {
+        let candlesticks = [
+            (datetime!(2024-01-02 00:00 +8), decimal!(200)),
+            (datetime!(2024-01-03 00:00 +8), decimal!(210)),
+            (datetime!(2024-01-04 00:00 +8), decimal!(100)),
+            (datetime!(2024-01-05 00:00 +8), decimal!(110)),
+        ]
+        .into_iter()
+        .filter(|(timestamp, _)| time.is_none_or(|time| timestamp.date() >= time.date()))
+        .take(count)
+        .map(|(timestamp, close)| {
+            let close = match adjust_type {
+                AdjustType::ForwardAdjust if timestamp.date() < date!(2024 - 01 - 04) => {
+                    close / decimal!(2)
+                }
+                _ => close,
+            };
+            daily_candlestick(timestamp, close)
+        })
+        .collect();
+        async move { Ok(candlesticks) }
+    }
+
+    fn history_candlesticks_by_date(
+        &self,
+        _symbol: impl Into<String>,
+        _period: Period,
+        _adjust_type: AdjustType,
+        _start: Option<Date>,
+        _end: Option<Date>,
+        _trade_sessions: TradeSessions,
+    ) -> impl Future<Output = Result<Vec<Candlestick>>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn option_chain_expiry_date_list(
+        &self,
+        _symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<Vec<Date>>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn option_chain_info_by_date(
+        &self,
+        _symbol: impl Into<String>,
+        _expiry_date: Date,
+    ) -> impl Future<Output = Result<Vec<StrikePriceInfo>>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn warrant_issuers(&self) -> impl Future<Output = Result<Vec<IssuerInfo>>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn warrant_list(
+        &self,
+        _symbol: impl Into<String>,
+        _sort_by: WarrantSortBy,
+        _sort_order: SortOrderType,
+        _warrant_type: Option<&[WarrantType]>,
+        _issuer: Option<&[i32]>,
+        _expiry_date: Option<&[FilterWarrantExpiryDate]>,
+        _price_type: Option<&[FilterWarrantInOutBoundsType]>,
+        _status: Option<&[WarrantStatus]>,
+    ) -> impl Future<Output = Result<Vec<WarrantInfo>>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn trading_session(&self) -> impl Future<Output = Result<Vec<MarketTradingSession>>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn trading_days(
+        &self,
+        market: Market,
+        begin: Date,
+        _end: Date,
+    ) -> impl Future<Output = Result<MarketTradingDays>> + Send {
+        let trading_days = match market {
+            Market::HK => vec![begin],
+            _ => vec![],
+        };
+        async move {
+            Ok(MarketTradingDays {
+                trading_days,
+                half_trading_days: vec![],
+            })
+        }
+    }
+
+    fn capital_flow(
+        &self,
+        _symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<Vec<CapitalFlowLine>>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn capital_distribution(
+        &self,
+        _symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<CapitalDistributionResponse>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn calc_indexes<I, T, J>(
+        &self,
+        _symbols: I,
+        _indexes: J,
+    ) -> impl Future<Output = Result<Vec<SecurityCalcIndex>>> + Send
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+        J: IntoIterator<Item = CalcIndex>,
+    {
+        async { unimplemented!() }
+    }
+
+    fn watchlist(&self) -> impl Future<Output = Result<Vec<WatchlistGroup>>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn create_watchlist_group(
+        &self,
+        _req: RequestCreateWatchlistGroup,
+    ) -> impl Future<Output = Result<i64>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn delete_watchlist_group(
+        &self,
+        _id: i64,
+        _purge: bool,
+    ) -> impl Future<Output = Result<()>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn update_watchlist_group(
+        &self,
+        _req: RequestUpdateWatchlistGroup,
+    ) -> impl Future<Output = Result<()>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn security_list(
+        &self,
+        _market: Market,
+        _category: SecurityListCategory,
+    ) -> impl Future<Output = Result<Vec<Security>>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn realtime_quote<I, T>(
+        &self,
+        _symbols: I,
+    ) -> impl Future<Output = Result<Vec<RealtimeQuote>>> + Send
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        async { unimplemented!() }
+    }
+
+    fn realtime_depth(
+        &self,
+        _symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<SecurityDepth>> + Send {
+        let depth = |position, price, volume| Depth {
+            position,
+            price: Some(price),
+            volume,
+            order_num: 1,
+        };
+        let depth = SecurityDepth {
+            asks: vec![depth(2, decimal!(101), 200), depth(1, decimal!(100.5), 100)],
+            bids: vec![depth(1, decimal!(100), 300)],
+        };
+        async move { Ok(depth) }
+    }
+
+    fn realtime_trades(
+        &self,
+        _symbol: impl Into<String>,
+        _count: usize,
+    ) -> impl Future<Output = Result<Vec<Trade>>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn realtime_brokers(
+        &self,
+        _symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<SecurityBrokers>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn realtime_candlesticks(
+        &self,
+        _symbol: impl Into<String>,
+        _period: Period,
+        _count: usize,
+    ) -> impl Future<Output = Result<Vec<Candlestick>>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn realtime_custom_candlesticks(
+        &self,
+        _symbol: impl Into<String>,
+        _period: CustomPeriod,
+        _count: usize,
+    ) -> impl Future<Output = Result<Vec<Candlestick>>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn realtime_bars(
+        &self,
+        _symbol: impl Into<String>,
+        _bar_type: BarType,
+        _count: usize,
+    ) -> impl Future<Output = Result<Vec<Candlestick>>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn indicator(
+        &self,
+        _symbol: impl Into<String>,
+        _period: Period,
+        _indicator: Indicator,
+    ) -> impl Future<Output = Result<Option<IndicatorValue>>> + Send {
+        async { unimplemented!() }
+    }
+}
+
+#[tokio::test]
+async fn test_quote_api_defaults() {
+    let api = FakeQuote;
+
+    let candlesticks = api
+        .history_candlesticks_paginator(
+            "700.HK",
+            Period::Day,
+            AdjustType::NoAdjust,
+            date!(2024 - 01 - 03),
+            date!(2024 - 01 - 04),
+            TradeSessions::Normal,
+        )
+        .collect()
+        .await
+        .unwrap();
+    assert_eq!(
+        candlesticks
+            .iter()
+            .map(|candlestick| candlestick.close)
+            .collect::<Vec<_>>(),
+        vec![decimal!(210), decimal!(100)]
+    );
+
+    let resp = api
+        .bulk_history_candlesticks(
+            ["700.HK", "9988.HK"],
+            Period::Day,
+            AdjustType::NoAdjust,
+            date!(2024 - 01 - 02),
+            date!(2024 - 01 - 05),
+            TradeSessions::Normal,
+            2,
+        )
+        .await;
+    assert_eq!(resp.len(), 2);
+    assert_eq!(resp["9988.HK"].as_ref().unwrap().len(), 4);
+
+    let factors = api
+        .adjustment_factors("700.HK", date!(2024 - 01 - 02), date!(2024 - 01 - 05))
+        .await
+        .unwrap();
+    assert_eq!(factors.events().len(), 1);
+    assert_eq!(factors.events()[0].ex_date, date!(2024 - 01 - 04));
+    assert_eq!(factors.events()[0].ratio, decimal!(0.5));
+
+    let calendar = api.trading_calendar().await.unwrap();
+    let begin = OffsetDateTime::now_utc().date() - time::Duration::days(5);
+    assert!(calendar.is_trading_day(Market::HK, begin));
+    assert!(!calendar.is_trading_day(Market::US, begin));
+
+    let order_book = api.realtime_order_book("700.HK").await.unwrap();
+    assert_eq!(order_book.best_ask().unwrap().price, decimal!(100.5));
+    assert_eq!(order_book.spread(), Some(decimal!(0.5)));
+
+    assert!(matches!(
+        api.quote_stream("700.HK"),
+        Err(Error::Unsupported {
+            method: "push_stream"
+        })
+    ));
+}
+
+/// A trade API implemented with the required methods only
+#[derive(Default)]
+struct FakeTrade {
+    orders: Mutex<HashMap<String, serde_json::Value>>,
+}
+
+fn order_json(order_id: &str, status: &str) -> serde_json::Value {
+    json!({
+        "currency": "HKD",
+        "executed_price": "0",
+        "executed_quantity": "0",
+        "expire_date": "",
+        "last_done": "",
+        "limit_offset": "",
+        "msg": "",
+        "order_id": order_id,
+        "order_type": "LO",
+        "outside_rth": "UnknownOutsideRth",
+        "price": "300",
+        "quantity": "100",
+        "side": "Buy",
+        "status": status,
+        "stock_name": "Tencent",
+        "submitted_at": "1700000000",
+        "symbol": "700.HK",
+        "tag": "Normal",
+        "time_in_force": "Day",
+        "trailing_amount": "",
+        "trailing_percent": "",
+        "trigger_at": "0",
+        "trigger_price": "",
+        "trigger_status": "NOT_USED",
+        "updated_at": "1700000000",
+        "remark": ""
+    })
+}
+
+#[allow(clippy::manual_async_fn)]
+impl TradeApi for FakeTrade {
+    fn log_subscriber(&self) -> Arc<dyn Subscriber + Send + Sync> {
+        unimplemented!()
+    }
+
+    fn set_risk_manager(&self, _risk_manager: Option<RiskManager>) {
+        unimplemented!()
+    }
+
+    fn risk_manager(&self) -> Option<RiskManager> {
+        None
+    }
+
+    fn connection_events(&self) -> mpsc::UnboundedReceiver<ConnectionEvent> {
+        unimplemented!()
+    }
+
+    fn subscribe<I>(&self, _topics: I) -> impl Future<Output = Result<()>> + Send
+    where
+        I: IntoIterator<Item = TopicType>,
+    {
+        async { unimplemented!() }
+    }
+
+    fn unsubscribe<I>(&self, _topics: I) -> impl Future<Output = Result<()>> + Send
+    where
+        I: IntoIterator<Item = TopicType>,
+    {
+        async { unimplemented!() }
+    }
+
+    fn history_executions(
+        &self,
+        _options: impl Into<Option<GetHistoryExecutionsOptions>>,
+    ) -> impl Future<Output = Result<Vec<Execution>>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn today_executions(
+        &self,
+        _options: impl Into<Option<GetTodayExecutionsOptions>>,
+    ) -> impl Future<Output = Result<Vec<Execution>>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn history_orders(
+        &self,
+        _options: impl Into<Option<GetHistoryOrdersOptions>>,
+    ) -> impl Future<Output = Result<Vec<Order>>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn today_orders(
+        &self,
+        _options: impl Into<Option<GetTodayOrdersOptions>>,
+    ) -> impl Future<Output = Result<Vec<Order>>> + Send {
+        let mut orders = self
+            .orders
+            .lock()
+            .unwrap()
+            .values()
+            .map(|order| serde_json::from_value::<Order>(order.clone()).unwrap())
+            .collect::<Vec<_>>();
+        orders.sort_by(|a, b| a.order_id.cmp(&b.order_id));
+        async move { Ok(orders) }
+    }
+
+    fn replace_order(
+        &self,
+        _options: ReplaceOrderOptions,
+    ) -> impl Future<Output = Result<()>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn submit_order(
+        &self,
+        _options: SubmitOrderOptions,
+    ) -> impl Future<Output = Result<SubmitOrderResponse>> + Send {
+        let mut orders = self.orders.lock().unwrap();
+        let order_id = (orders.len() + 1).to_string();
+        orders.insert(order_id.clone(), order_json(&order_id, "FilledStatus"));
+        async move { Ok(SubmitOrderResponse { order_id }) }
+    }
+
+    fn cancel_order(
+        &self,
+        _order_id: impl Into<String>,
+    ) -> impl Future<Output = Result<()>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn account_balance(
+        &self,
+        _currency: Option<&str>,
+    ) -> impl Future<Output = Result<Vec<AccountBalance>>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn cash_flow(
+        &self,
+        _options: GetCashFlowOptions,
+    ) -> impl Future<Output = Result<Vec<CashFlow>>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn fund_positions(
+        &self,
+        _opts: impl Into<Option<GetFundPositionsOptions>>,
+    ) -> impl Future<Output = Result<FundPositionsResponse>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn stock_positions(
+        &self,
+        _opts: impl Into<Option<GetStockPositionsOptions>>,
+    ) -> impl Future<Output = Result<StockPositionsResponse>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn margin_ratio(
+        &self,
+        _symbol: impl Into<String>,
+    ) -> impl Future<Output = Result<MarginRatio>> + Send {
+        async { unimplemented!() }
+    }
+
+    fn order_detail(
+        &self,
+        order_id: impl Into<String>,
+    ) -> impl Future<Output = Result<OrderDetail>> + Send {
+        let mut detail = self.orders.lock().unwrap()[&order_id.into()].clone();
+        detail.as_object_mut().unwrap().extend(
+            json!({
+                "free_status": "None",
+                "free_amount": "",
+                "free_currency": "",
+                "deductions_status": "NONE",
+                "deductions_amount": "",
+                "deductions_currency": "",
+                "platform_deducted_status": "NONE",
+                "platform_deducted_amount": "",
+                "platform_deducted_currency": "",
+                "history": [],
+                "charge_detail": { "items": [], "total_amount": "0", "currency": "HKD" }
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        );
+        async move { Ok(serde_json::from_value(detail).unwrap()) }
+    }
+
+    fn estimate_max_purchase_quantity(
+        &self,
+        _opts: EstimateMaxPurchaseQuantityOptions,
+    ) -> impl Future<Output = Result<EstimateMaxPurchaseQuantityResponse>> + Send {
+        async { unimplemented!() }
+    }
+}
+
+#[tokio::test]
+async fn test_trade_api_defaults() {
+    let api = FakeTrade::default();
+    api.orders
+        .lock()
+        .unwrap()
+        .insert("1".to_string(), order_json("1", "NewStatus"));
+
+    let options = SubmitOrderOptions::new(
+        "700.HK",
+        OrderType::LO,
+        OrderSide::Buy,
+        decimal!(100),
+        TimeInForceType::Day,
+    )
+    .submitted_price(decimal!(300));
+    let detail = api
+        .submit_and_wait(options.clone(), Duration::from_secs(1))
+        .await
+        .unwrap();
+    assert_eq!(detail.order_id, "2");
+    assert_eq!(detail.status, OrderStatus::Filled);
+
+    let resp = api.submit_order_with_retry(options, 3).await.unwrap();
+    assert_eq!(resp.order_id, "3");
+
+    let open_orders = api.open_orders().await.unwrap();
+    assert_eq!(open_orders.len(), 1);
+    assert_eq!(open_orders[0].order_id, "1");
+    assert_eq!(api.symbol_orders("700.HK").await.unwrap().len(), 3);
+    assert!(api.risk_manager().is_none());
+}
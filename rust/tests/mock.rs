@@ -0,0 +1,1068 @@
+use std::{
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use longport::{
+    quote::{
+        AdjustType, BarType, CustomPeriod, Indicator, IndicatorValue, Period, PushEventDetail,
+        SubFlags, TradeSessions,
+    },
+    trade::{
+        OrderSide, OrderStatus, OrderType, PushEvent, RiskLimits, RiskManager, RiskViolation,
+        SubmitOrderOptions, TimeInForceType, TopicType, TradeApi,
+    },
+    AccessTokenEvent, Config, ConnectionEvent, Error, QuoteContext, ReconnectPolicy, TradeContext,
+};
+use longport_mock::{
+    proto::quote::{
+        Candlestick, PushQuote, PushTrade, SecurityCandlestickResponse,
+        SecurityHistoryCandlestickRequest, SecurityQuote, SecurityQuoteResponse, Trade,
+    },
+    proto::trade::Notification,
+    HttpResponse, Method, MockServer, StatusCode, WsResponse,
+};
+use prost::Message;
+use serde_json::json;
+use time::{macros::date, OffsetDateTime};
+use tokio::time::timeout;
+
+const GET_REALTIME_QUOTE: u8 = 11;
+const PUSH_REALTIME_QUOTE: u8 = 101;
+const PUSH_REALTIME_TRADES: u8 = 104;
+const GET_SECURITY_CANDLESTICKS: u8 = 19;
+const GET_SECURITY_HISTORY_CANDLESTICKS: u8 = 27;
+const PUSH_NOTIFICATION: u8 = 18;
+
+/// 2023-11-15 10:00:00 HKT
+const TRADING_TIME: i64 = 1700013600;
+
+fn create_config(server: &MockServer) -> Arc<Config> {
+    Arc::new(
+        Config::new(server.app_key(), server.app_secret(), server.access_token())
+            .http_url(server.http_url())
+            .quote_ws_url(server.quote_ws_url())
+            .trade_ws_url(server.trade_ws_url())
+            .reconnect_policy(ReconnectPolicy::fixed(Duration::from_millis(100)))
+            .dont_print_quote_packages(),
+    )
+}
+
+fn push_quote(sequence: i64, last_done: &str) -> PushQuote {
+    PushQuote {
+        symbol: "700.HK".to_string(),
+        sequence,
+        last_done: last_done.to_string(),
+        timestamp: 1700000000,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_quote_push_and_resume() {
+    let server = MockServer::start().await.unwrap();
+    let (ctx, mut receiver) = QuoteContext::try_new(create_config(&server)).await.unwrap();
+    let mut events = ctx.connection_events();
+
+    ctx.subscribe(["700.HK"], SubFlags::QUOTE, false)
+        .await
+        .unwrap();
+    assert!(server.quote().subscriptions().contains_key("700.HK"));
+
+    server
+        .quote()
+        .push(PUSH_REALTIME_QUOTE, &push_quote(1, "500"));
+    let event = timeout(Duration::from_secs(5), receiver.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(event.symbol, "700.HK");
+    assert!(
+        matches!(event.detail, PushEventDetail::Quote(quote) if quote.last_done.to_string() == "500")
+    );
+
+    // the context resumes the session and restores the subscriptions
+    server.quote().disconnect();
+    loop {
+        let event = timeout(Duration::from_secs(5), events.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        if event == ConnectionEvent::Resumed {
+            break;
+        }
+    }
+
+    server
+        .quote()
+        .push(PUSH_REALTIME_QUOTE, &push_quote(2, "501"));
+    let event = timeout(Duration::from_secs(5), receiver.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(
+        matches!(event.detail, PushEventDetail::Quote(quote) if quote.last_done.to_string() == "501")
+    );
+    assert!(server.quote().subscriptions().contains_key("700.HK"));
+}
+
+#[tokio::test]
+async fn test_quote_sequence_gap_resync() {
+    let server = MockServer::start().await.unwrap();
+    server.quote().on_request(GET_REALTIME_QUOTE, |_| {
+        WsResponse::ok(&SecurityQuoteResponse {
+            secu_quote: vec![SecurityQuote {
+                symbol: "700.HK".to_string(),
+                last_done: "510".to_string(),
+                prev_close: "490".to_string(),
+                open: "500".to_string(),
+                high: "510".to_string(),
+                low: "490".to_string(),
+                timestamp: TRADING_TIME,
+                volume: 1000,
+                turnover: "500000".to_string(),
+                ..Default::default()
+            }],
+        })
+    });
+
+    let config = Arc::into_inner(create_config(&server))
+        .unwrap()
+        .enable_push_resync();
+    let (ctx, mut receiver) = QuoteContext::try_new(Arc::new(config)).await.unwrap();
+    ctx.subscribe(["700.HK"], SubFlags::QUOTE, false)
+        .await
+        .unwrap();
+
+    // the sequence 2 is missed
+    server
+        .quote()
+        .push(PUSH_REALTIME_QUOTE, &push_quote(1, "500"));
+    server
+        .quote()
+        .push(PUSH_REALTIME_QUOTE, &push_quote(3, "502"));
+
+    async fn next_event(
+        receiver: &mut tokio::sync::mpsc::UnboundedReceiver<longport::quote::PushEvent>,
+    ) -> longport::quote::PushEvent {
+        timeout(Duration::from_secs(5), receiver.recv())
+            .await
+            .unwrap()
+            .unwrap()
+    }
+    assert!(matches!(
+        next_event(&mut receiver).await.detail,
+        PushEventDetail::Quote(_)
+    ));
+    let event = next_event(&mut receiver).await;
+    let PushEventDetail::SequenceGap(gap) = event.detail else {
+        panic!("unexpected event: {event:?}");
+    };
+    assert_eq!((gap.last_sequence, gap.sequence), (1, 3));
+    assert!(matches!(
+        next_event(&mut receiver).await.detail,
+        PushEventDetail::Quote(_)
+    ));
+
+    // the quote is re-fetched and pushed before the resynced message
+    let event = next_event(&mut receiver).await;
+    let PushEventDetail::Quote(quote) = event.detail else {
+        panic!("unexpected event: {event:?}");
+    };
+    assert_eq!(quote.last_done.to_string(), "510");
+    assert_eq!(quote.volume, 1000);
+    let event = next_event(&mut receiver).await;
+    let PushEventDetail::Resynced(resynced) = event.detail else {
+        panic!("unexpected event: {event:?}");
+    };
+    assert_eq!(resynced.sub_types, SubFlags::QUOTE);
+}
+
+#[tokio::test]
+async fn test_quote_resync_retry() {
+    let server = MockServer::start().await.unwrap();
+    let requests = Arc::new(AtomicI64::new(0));
+    server.quote().on_request(GET_REALTIME_QUOTE, {
+        let requests = requests.clone();
+        move |_| {
+            // the first resync request fails
+            if requests.fetch_add(1, Ordering::SeqCst) == 0 {
+                return WsResponse::error(500, "internal error");
+            }
+            WsResponse::ok(&SecurityQuoteResponse {
+                secu_quote: vec![SecurityQuote {
+                    symbol: "700.HK".to_string(),
+                    last_done: "510".to_string(),
+                    timestamp: TRADING_TIME,
+                    ..Default::default()
+                }],
+            })
+        }
+    });
+
+    let config = Arc::into_inner(create_config(&server))
+        .unwrap()
+        .enable_push_resync();
+    let (ctx, mut receiver) = QuoteContext::try_new(Arc::new(config)).await.unwrap();
+    ctx.subscribe(["700.HK"], SubFlags::QUOTE, false)
+        .await
+        .unwrap();
+    server
+        .quote()
+        .push(PUSH_REALTIME_QUOTE, &push_quote(1, "500"));
+    server
+        .quote()
+        .push(PUSH_REALTIME_QUOTE, &push_quote(3, "502"));
+
+    // the failed resync is retried
+    let resynced = timeout(Duration::from_secs(10), async {
+        while let Some(event) = receiver.recv().await {
+            if let PushEventDetail::Resynced(resynced) = event.detail {
+                return resynced;
+            }
+        }
+        panic!("receiver closed");
+    })
+    .await
+    .unwrap();
+    assert_eq!(resynced.sub_types, SubFlags::QUOTE);
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_quote_streams() {
+    let server = MockServer::start().await.unwrap();
+    let (ctx, mut receiver) = QuoteContext::try_new(create_config(&server)).await.unwrap();
+    let mut quotes = ctx.quote_stream("700.HK").unwrap();
+    let mut other_quotes = ctx.quote_stream("AAPL.US").unwrap();
+    let mut events = ctx.push_stream().unwrap();
+
+    ctx.subscribe(["700.HK"], SubFlags::QUOTE, false)
+        .await
+        .unwrap();
+    server
+        .quote()
+        .push(PUSH_REALTIME_QUOTE, &push_quote(1, "500"));
+
+    let quote = timeout(Duration::from_secs(5), quotes.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(quote.last_done.to_string(), "500");
+    let event = timeout(Duration::from_secs(5), events.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(event.symbol, "700.HK");
+    // the receiver of the context keeps receiving the events
+    let event = timeout(Duration::from_secs(5), receiver.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(event.detail, PushEventDetail::Quote(_)));
+    assert!(timeout(Duration::from_millis(100), other_quotes.recv())
+        .await
+        .is_err());
+
+    // the streams end when the context is closed
+    drop(ctx);
+    assert!(timeout(Duration::from_secs(5), quotes.recv())
+        .await
+        .unwrap()
+        .is_none());
+}
+
+fn push_trades(sequence: i64, trades: &[(i64, &str)]) -> PushTrade {
+    PushTrade {
+        symbol: "700.HK".to_string(),
+        sequence,
+        trade: trades
+            .iter()
+            .map(|(timestamp, price)| Trade {
+                price: price.to_string(),
+                volume: 100,
+                timestamp: *timestamp,
+                ..Default::default()
+            })
+            .collect(),
+    }
+}
+
+/// Responds three 1 minute candlesticks from [`TRADING_TIME`]
+fn mock_candlesticks(server: &MockServer) {
+    server.quote().on_request(GET_SECURITY_CANDLESTICKS, |_| {
+        WsResponse::ok(&SecurityCandlestickResponse {
+            symbol: "700.HK".to_string(),
+            candlesticks: (0..3)
+                .map(|idx| Candlestick {
+                    close: "500".to_string(),
+                    open: "500".to_string(),
+                    low: "500".to_string(),
+                    high: "500".to_string(),
+                    volume: 1000,
+                    turnover: "500000".to_string(),
+                    timestamp: TRADING_TIME + idx * 60,
+                    trade_session: 0,
+                })
+                .collect(),
+        })
+    });
+}
+
+#[tokio::test]
+async fn test_quote_custom_candlesticks() {
+    let server = MockServer::start().await.unwrap();
+    mock_candlesticks(&server);
+    let (ctx, _) = QuoteContext::try_new(create_config(&server)).await.unwrap();
+    let period = CustomPeriod::Minutes(2);
+    let mut candlesticks = ctx.custom_candlestick_stream("700.HK", period).unwrap();
+
+    // seeded from the 1 minute history candlesticks
+    let history = ctx
+        .subscribe_custom_candlesticks("700.HK", period, TradeSessions::Normal)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].volume, 2000);
+    assert_eq!(history[1].volume, 1000);
+    assert!(server.quote().subscriptions().contains_key("700.HK"));
+
+    server.quote().push(
+        PUSH_REALTIME_TRADES,
+        &push_trades(
+            1,
+            &[(TRADING_TIME + 150, "510"), (TRADING_TIME + 240, "520")],
+        ),
+    );
+    let confirmed = loop {
+        let candlestick = timeout(Duration::from_secs(5), candlesticks.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        if candlestick.is_confirmed {
+            break candlestick.candlestick;
+        }
+    };
+    assert_eq!(confirmed.high.to_string(), "510");
+    assert_eq!(confirmed.volume, 1100);
+    assert_eq!(
+        ctx.realtime_custom_candlesticks("700.HK", period, 10)
+            .await
+            .unwrap()
+            .len(),
+        3
+    );
+
+    ctx.unsubscribe_custom_candlesticks("700.HK", period)
+        .await
+        .unwrap();
+    assert!(!server.quote().subscriptions().contains_key("700.HK"));
+}
+
+#[tokio::test]
+async fn test_quote_indicators() {
+    let server = MockServer::start().await.unwrap();
+    mock_candlesticks(&server);
+    let (ctx, _) = QuoteContext::try_new(create_config(&server)).await.unwrap();
+    let indicator = Indicator::Sma(2);
+    let mut values = ctx
+        .indicator_stream("700.HK", Period::OneMinute, indicator)
+        .unwrap();
+
+    assert!(ctx
+        .attach_indicator("700.HK", Period::OneMinute, indicator)
+        .await
+        .is_err());
+    ctx.subscribe_candlesticks("700.HK", Period::OneMinute, TradeSessions::Normal)
+        .await
+        .unwrap();
+    // calculated from the candlesticks in the local storage
+    assert_eq!(
+        ctx.attach_indicator("700.HK", Period::OneMinute, indicator)
+            .await
+            .unwrap(),
+        Some(IndicatorValue::Value(500.into()))
+    );
+
+    server.quote().push(
+        PUSH_REALTIME_TRADES,
+        &push_trades(
+            1,
+            &[(TRADING_TIME + 150, "510"), (TRADING_TIME + 240, "520")],
+        ),
+    );
+    let confirmed = loop {
+        let value = timeout(Duration::from_secs(5), values.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        if value.is_confirmed {
+            break value.value;
+        }
+    };
+    assert_eq!(confirmed, IndicatorValue::Value(505.into()));
+    assert_eq!(
+        ctx.indicator("700.HK", Period::OneMinute, indicator)
+            .await
+            .unwrap(),
+        Some(IndicatorValue::Value(515.into()))
+    );
+
+    ctx.detach_indicator("700.HK", Period::OneMinute, indicator)
+        .await
+        .unwrap();
+    assert_eq!(
+        ctx.indicator("700.HK", Period::OneMinute, indicator)
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+/// Responds five 1 minute candlesticks from [`TRADING_TIME`] to the history
+/// requests, the pages include the candlestick at the offset time
+fn mock_history_candlesticks(
+    server: &MockServer,
+    price: impl Fn() -> String + Send + Sync + 'static,
+) {
+    server
+        .quote()
+        .on_request(GET_SECURITY_HISTORY_CANDLESTICKS, move |body| {
+            let req = SecurityHistoryCandlestickRequest::decode(body).unwrap();
+            let offset = req.offset_request.unwrap();
+            let minute = offset.minute.parse::<i64>().unwrap();
+            let price = price();
+            WsResponse::ok(&SecurityCandlestickResponse {
+                symbol: req.symbol,
+                candlesticks: (0..5)
+                    .filter(|idx| 1000 + idx >= minute)
+                    .take(offset.count as usize)
+                    .map(|idx| Candlestick {
+                        close: price.clone(),
+                        open: price.clone(),
+                        low: price.clone(),
+                        high: price.clone(),
+                        volume: 1000,
+                        turnover: "500000".to_string(),
+                        timestamp: TRADING_TIME + idx * 60,
+                        trade_session: 0,
+                    })
+                    .collect(),
+            })
+        });
+}
+
+#[tokio::test]
+async fn test_quote_history_candlesticks_paginator() {
+    let server = MockServer::start().await.unwrap();
+    mock_history_candlesticks(&server, || "500".to_string());
+    let (ctx, _) = QuoteContext::try_new(create_config(&server)).await.unwrap();
+    let paginator = ctx
+        .history_candlesticks_paginator(
+            "700.HK",
+            Period::OneMinute,
+            AdjustType::NoAdjust,
+            date!(2023 - 11 - 15),
+            date!(2023 - 11 - 15),
+            TradeSessions::Normal,
+        )
+        .page_size(2);
+
+    let mut first = paginator.clone();
+    let page = first.next_page().await.unwrap().unwrap();
+    assert_eq!(page.len(), 2);
+    let checkpoint = first.checkpoint().unwrap();
+    assert_eq!(checkpoint.unix_timestamp(), TRADING_TIME + 60);
+
+    let candlesticks = paginator.collect().await.unwrap();
+    assert_eq!(candlesticks.len(), 5);
+    assert!(candlesticks
+        .windows(2)
+        .all(|w| w[0].timestamp < w[1].timestamp));
+
+    // resumed from the checkpoint
+    let candlesticks = ctx
+        .history_candlesticks_paginator(
+            "700.HK",
+            Period::OneMinute,
+            AdjustType::NoAdjust,
+            date!(2023 - 11 - 15),
+            date!(2023 - 11 - 15),
+            TradeSessions::Normal,
+        )
+        .resume_from(checkpoint)
+        .collect()
+        .await
+        .unwrap();
+    assert_eq!(candlesticks.len(), 3);
+
+    let resp = ctx
+        .bulk_history_candlesticks(
+            ["700.HK", "9988.HK"],
+            Period::OneMinute,
+            AdjustType::NoAdjust,
+            date!(2023 - 11 - 15),
+            date!(2023 - 11 - 15),
+            TradeSessions::Normal,
+            2,
+        )
+        .await;
+    assert_eq!(resp.len(), 2);
+    assert!(resp.values().all(|res| res.as_ref().unwrap().len() == 5));
+}
+
+#[tokio::test]
+async fn test_quote_candlestick_cache() {
+    let server = MockServer::start().await.unwrap();
+    let price = Arc::new(AtomicI64::new(500));
+    mock_history_candlesticks(&server, {
+        let price = price.clone();
+        move || price.load(Ordering::SeqCst).to_string()
+    });
+    let cache_dir = std::env::temp_dir().join(format!("longport-cache-{}", std::process::id()));
+    let config = Config::new(server.app_key(), server.app_secret(), server.access_token())
+        .http_url(server.http_url())
+        .quote_ws_url(server.quote_ws_url())
+        .dont_print_quote_packages()
+        .candlestick_cache_dir(&cache_dir);
+    let (ctx, _) = QuoteContext::try_new(Arc::new(config)).await.unwrap();
+    let history_requests = || {
+        server
+            .quote()
+            .requests()
+            .iter()
+            .filter(|req| req.command_code == GET_SECURITY_HISTORY_CANDLESTICKS)
+            .count()
+    };
+    let fetch = |adjust_type| {
+        ctx.history_candlesticks_by_date(
+            "700.HK",
+            Period::OneMinute,
+            adjust_type,
+            Some(date!(2023 - 11 - 15)),
+            Some(date!(2023 - 11 - 15)),
+            TradeSessions::Normal,
+        )
+    };
+
+    assert_eq!(fetch(AdjustType::NoAdjust).await.unwrap().len(), 5);
+    let count = history_requests();
+    assert_eq!(fetch(AdjustType::NoAdjust).await.unwrap().len(), 5);
+    assert_eq!(history_requests(), count);
+
+    // the forward adjusted prices are validated once per day
+    let candlesticks = fetch(AdjustType::ForwardAdjust).await.unwrap();
+    assert_eq!(candlesticks[0].close.to_string(), "500");
+    let count = history_requests();
+    fetch(AdjustType::ForwardAdjust).await.unwrap();
+    assert_eq!(history_requests(), count);
+
+    // the cache is invalidated when the prices are shifted on the next day
+    price.store(250, Ordering::SeqCst);
+    let path = cache_dir.join("700.HK_OneMinute_ForwardAdjust_Normal.json");
+    let mut file: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    file["validated_at"] = serde_json::Value::Null;
+    std::fs::write(&path, serde_json::to_vec(&file).unwrap()).unwrap();
+    let candlesticks = fetch(AdjustType::ForwardAdjust).await.unwrap();
+    assert_eq!(candlesticks[0].close.to_string(), "250");
+
+    let _ = std::fs::remove_dir_all(cache_dir);
+}
+
+#[tokio::test]
+async fn test_quote_bars() {
+    let server = MockServer::start().await.unwrap();
+    let (ctx, _) = QuoteContext::try_new(create_config(&server)).await.unwrap();
+    let mut bars = ctx.bar_stream("700.HK", BarType::Tick(2)).unwrap();
+
+    ctx.subscribe_bars("700.HK", BarType::Tick(2), TradeSessions::Normal)
+        .await
+        .unwrap();
+    assert!(server.quote().subscriptions().contains_key("700.HK"));
+
+    server.quote().push(
+        PUSH_REALTIME_TRADES,
+        &push_trades(
+            1,
+            &[
+                (TRADING_TIME, "500"),
+                (TRADING_TIME + 1, "502"),
+                (TRADING_TIME + 2, "501"),
+            ],
+        ),
+    );
+
+    let confirmed = loop {
+        let bar = timeout(Duration::from_secs(5), bars.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        if bar.is_confirmed {
+            break bar.candlestick;
+        }
+    };
+    assert_eq!(confirmed.open.to_string(), "500");
+    assert_eq!(confirmed.high.to_string(), "502");
+    assert_eq!(confirmed.volume, 200);
+    assert_eq!(
+        ctx.realtime_bars("700.HK", BarType::Tick(2), 10)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+
+    ctx.unsubscribe_bars("700.HK", BarType::Tick(2))
+        .await
+        .unwrap();
+    assert!(!server.quote().subscriptions().contains_key("700.HK"));
+}
+
+#[tokio::test]
+async fn test_trade_http_and_subscribe() {
+    let server = MockServer::start().await.unwrap();
+    server.on_http(Method::GET, "/v1/asset/account", |_| {
+        HttpResponse::ok(json!({ "list": [] }))
+    });
+
+    let (ctx, _) = TradeContext::try_new(create_config(&server)).await.unwrap();
+
+    assert!(ctx.account_balance(Some("HKD")).await.unwrap().is_empty());
+    assert!(TradeApi::account_balance(&ctx, None)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(server
+        .http_requests()
+        .iter()
+        .any(|req| req.path == "/v1/asset/account" && req.query == "currency=HKD"));
+
+    ctx.subscribe([TopicType::Private]).await.unwrap();
+    assert!(server.trade().subscriptions().contains_key("private"));
+}
+
+fn order_json(order_id: &str, status: &str, updated_at: i64) -> serde_json::Value {
+    json!({
+        "currency": "HKD",
+        "executed_price": "0",
+        "executed_quantity": "0",
+        "expire_date": "",
+        "last_done": "",
+        "limit_offset": "",
+        "msg": "",
+        "order_id": order_id,
+        "order_type": "LO",
+        "outside_rth": "UnknownOutsideRth",
+        "price": "300",
+        "quantity": "100",
+        "side": "Buy",
+        "status": status,
+        "stock_name": "Tencent",
+        "submitted_at": "1700000000",
+        "symbol": "700.HK",
+        "tag": "Normal",
+        "time_in_force": "Day",
+        "trailing_amount": "",
+        "trailing_percent": "",
+        "trigger_at": "0",
+        "trigger_price": "",
+        "trigger_status": "NOT_USED",
+        "updated_at": updated_at.to_string(),
+        "remark": ""
+    })
+}
+
+fn push_order_changed(order_id: &str, status: &str, updated_at: i64) -> Notification {
+    let data = json!({
+        "event": "order_changed_lb",
+        "data": {
+            "side": "Buy",
+            "stock_name": "Tencent",
+            "submitted_quantity": "100",
+            "symbol": "700.HK",
+            "order_type": "LO",
+            "submitted_price": "300",
+            "executed_quantity": "100",
+            "executed_price": "300",
+            "order_id": order_id,
+            "currency": "HKD",
+            "status": status,
+            "submitted_at": "1700000000",
+            "updated_at": updated_at.to_string(),
+            "trigger_price": "",
+            "msg": "",
+            "tag": "Normal",
+            "trigger_status": "NOT_USED",
+            "trigger_at": "0",
+            "trailing_amount": "",
+            "trailing_percent": "",
+            "limit_offset": "",
+            "account_no": "ACC",
+            "last_share": "100",
+            "last_price": "300",
+            "remark": ""
+        }
+    });
+    Notification {
+        topic: "private".to_string(),
+        data: serde_json::to_vec(&data).unwrap(),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_trade_order_cache() {
+    let server = MockServer::start().await.unwrap();
+    let orders = Arc::new(Mutex::new(vec![
+        order_json("1", "NewStatus", 1700000000),
+        order_json("2", "FilledStatus", 1700000000),
+    ]));
+    server.on_http(Method::GET, "/v1/trade/order/today", {
+        let orders = orders.clone();
+        move |_| HttpResponse::ok(json!({ "orders": *orders.lock().unwrap() }))
+    });
+
+    let (ctx, mut receiver) = TradeContext::try_new(create_config(&server)).await.unwrap();
+    assert!(ctx.open_orders().await.unwrap().is_empty());
+
+    // snapshot on subscribe
+    ctx.subscribe([TopicType::Private]).await.unwrap();
+    let open_orders = ctx.open_orders().await.unwrap();
+    assert_eq!(open_orders.len(), 1);
+    assert_eq!(open_orders[0].order_id, "1");
+    assert_eq!(ctx.symbol_orders("700.HK").await.unwrap().len(), 2);
+
+    // the pushes are applied in order
+    server.trade().push(
+        PUSH_NOTIFICATION,
+        &push_order_changed("1", "FilledStatus", 1700000100),
+    );
+    server.trade().push(
+        PUSH_NOTIFICATION,
+        &push_order_changed("1", "NewStatus", 1700000050),
+    );
+    for _ in 0..2 {
+        let event = timeout(Duration::from_secs(5), receiver.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(event, PushEvent::OrderChanged(_)));
+    }
+    let order = ctx.order("1").await.unwrap().unwrap();
+    assert_eq!(order.status, OrderStatus::Filled);
+    assert_eq!(order.executed_quantity, 100.into());
+    assert!(ctx.open_orders().await.unwrap().is_empty());
+
+    // the changes missed while disconnected are reconciled
+    {
+        let mut orders = orders.lock().unwrap();
+        orders.retain(|order| order["order_id"] != "2");
+        orders.push(order_json("3", "NewStatus", 1700000200));
+    }
+    server.trade().disconnect();
+    let event = timeout(Duration::from_secs(5), receiver.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    let PushEvent::OrdersReconciled(reconciled) = event else {
+        panic!("unexpected event: {event:?}");
+    };
+    assert_eq!(reconciled.orders.len(), 1);
+    assert_eq!(reconciled.orders[0].order_id, "3");
+    assert_eq!(reconciled.removed.len(), 1);
+    assert_eq!(reconciled.removed[0].order_id, "2");
+    assert_eq!(ctx.open_orders().await.unwrap()[0].order_id, "3");
+    assert!(ctx.order("2").await.unwrap().is_none());
+    assert_eq!(
+        ctx.order("1").await.unwrap().unwrap().status,
+        OrderStatus::Filled
+    );
+}
+
+#[tokio::test]
+async fn test_submit_order_with_retry() {
+    let server = MockServer::start().await.unwrap();
+    let orders = Arc::new(Mutex::new(Vec::new()));
+    let submitted = Arc::new(AtomicI64::new(0));
+    server.on_http(Method::POST, "/v1/trade/order", {
+        let orders = orders.clone();
+        let submitted = submitted.clone();
+        move |req| {
+            let body: serde_json::Value = req.json().unwrap();
+            match submitted.fetch_add(1, Ordering::SeqCst) {
+                // the first order reaches the server, but the response is lost
+                0 => {
+                    assert_eq!(body["remark"], "note lpcoid:a");
+                    let mut order = order_json("1", "NewStatus", 1700000000);
+                    order["remark"] = body["remark"].clone();
+                    orders.lock().unwrap().push(order);
+                    HttpResponse::status(StatusCode::BAD_GATEWAY)
+                }
+                // the second order does not reach the server, and the orders
+                // with the same remark but another quantity or side are not
+                // matched
+                1 => {
+                    let mut order = order_json("3", "NewStatus", 1700000000);
+                    order["remark"] = body["remark"].clone();
+                    order["quantity"] = "200".into();
+                    orders.lock().unwrap().push(order);
+                    let mut order = order_json("4", "NewStatus", 1700000000);
+                    order["remark"] = body["remark"].clone();
+                    order["side"] = "Sell".into();
+                    orders.lock().unwrap().push(order);
+                    HttpResponse::status(StatusCode::BAD_GATEWAY)
+                }
+                _ => {
+                    assert_eq!(body["remark"], "lpcoid:b");
+                    HttpResponse::ok(json!({ "order_id": "2" }))
+                }
+            }
+        }
+    });
+    server.on_http(Method::GET, "/v1/trade/order/today", {
+        let orders = orders.clone();
+        move |_| HttpResponse::ok(json!({ "orders": *orders.lock().unwrap() }))
+    });
+
+    let (ctx, _) = TradeContext::try_new(create_config(&server)).await.unwrap();
+    let options = SubmitOrderOptions::new(
+        "700.HK",
+        OrderType::LO,
+        OrderSide::Buy,
+        100.into(),
+        TimeInForceType::Day,
+    )
+    .submitted_price(300.into());
+
+    // the order found in today's orders is not submitted again
+    let resp = ctx
+        .submit_order_with_retry(options.clone().remark("note").client_order_id("a"), 3)
+        .await
+        .unwrap();
+    assert_eq!(resp.order_id, "1");
+    assert_eq!(submitted.load(Ordering::SeqCst), 1);
+
+    // the missing order is submitted again
+    let resp = ctx
+        .submit_order_with_retry(options.clone().client_order_id("b"), 3)
+        .await
+        .unwrap();
+    assert_eq!(resp.order_id, "2");
+    assert_eq!(submitted.load(Ordering::SeqCst), 3);
+
+    // the client order id is also appended by a plain submission
+    ctx.submit_order(options.clone().client_order_id("b"))
+        .await
+        .unwrap();
+    assert_eq!(submitted.load(Ordering::SeqCst), 4);
+
+    // the remark with the client order id must not exceed 64 characters
+    assert!(matches!(
+        ctx.submit_order(options.remark("x".repeat(60)).client_order_id("c"))
+            .await,
+        Err(Error::RemarkTooLong { .. })
+    ));
+    assert_eq!(submitted.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn test_submit_order_risk_reservation() {
+    let server = MockServer::start().await.unwrap();
+    server.on_http(Method::POST, "/v1/trade/order", |_| {
+        HttpResponse::ok(json!({ "order_id": "1" }))
+    });
+    server.on_http(Method::GET, "/v1/trade/order/today", |_| {
+        HttpResponse::ok(json!({ "orders": [] }))
+    });
+
+    let (ctx, mut receiver) = TradeContext::try_new(create_config(&server)).await.unwrap();
+    ctx.set_risk_manager(Some(RiskManager::new(
+        RiskLimits::new().max_position_quantity(150.into()),
+    )));
+    let options = SubmitOrderOptions::new(
+        "700.HK",
+        OrderType::LO,
+        OrderSide::Buy,
+        100.into(),
+        TimeInForceType::Day,
+    )
+    .submitted_price(300.into());
+
+    // the orders are not tracked without the private topic
+    assert!(matches!(
+        ctx.submit_order(options.clone()).await,
+        Err(Error::RiskCheckFailed {
+            violation: RiskViolation::OrdersNotTracked,
+            ..
+        })
+    ));
+
+    // the quantity of the submitted order is reserved before it is pushed
+    ctx.subscribe([TopicType::Private]).await.unwrap();
+    ctx.submit_order(options.clone()).await.unwrap();
+    assert!(matches!(
+        ctx.submit_order(options.clone()).await,
+        Err(Error::RiskCheckFailed {
+            violation: RiskViolation::PositionQuantity { .. },
+            ..
+        })
+    ));
+
+    // the reservation is released after the order is canceled
+    server.trade().push(
+        PUSH_NOTIFICATION,
+        &push_order_changed("1", "CanceledStatus", 1700000100),
+    );
+    timeout(Duration::from_secs(5), receiver.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    ctx.submit_order(options).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_submit_and_wait() {
+    let server = MockServer::start().await.unwrap();
+    let status = Arc::new(Mutex::new("NewStatus"));
+    server.on_http(Method::POST, "/v1/trade/order", |_| {
+        HttpResponse::ok(json!({ "order_id": "1" }))
+    });
+    server.on_http(Method::GET, "/v1/trade/order", {
+        let status = status.clone();
+        move |_| {
+            let mut detail = order_json("1", &status.lock().unwrap(), 1700000000);
+            detail.as_object_mut().unwrap().extend(
+                json!({
+                    "free_status": "None",
+                    "free_amount": "",
+                    "free_currency": "",
+                    "deductions_status": "NONE",
+                    "deductions_amount": "",
+                    "deductions_currency": "",
+                    "platform_deducted_status": "NONE",
+                    "platform_deducted_amount": "",
+                    "platform_deducted_currency": "",
+                    "history": [],
+                    "charge_detail": { "items": [], "total_amount": "0", "currency": "HKD" }
+                })
+                .as_object()
+                .unwrap()
+                .clone(),
+            );
+            HttpResponse::ok(detail)
+        }
+    });
+
+    let (ctx, _) = TradeContext::try_new(create_config(&server)).await.unwrap();
+    ctx.subscribe([TopicType::Private]).await.unwrap();
+
+    // the push arrives before the order detail is polled
+    let start = std::time::Instant::now();
+    let options = SubmitOrderOptions::new(
+        "700.HK",
+        OrderType::LO,
+        OrderSide::Buy,
+        100.into(),
+        TimeInForceType::Day,
+    )
+    .submitted_price(300.into());
+    let (detail, _) = tokio::join!(
+        ctx.submit_and_wait(options, Duration::from_secs(10)),
+        async {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            *status.lock().unwrap() = "FilledStatus";
+            server.trade().push(
+                PUSH_NOTIFICATION,
+                &push_order_changed("1", "FilledStatus", 1700000100),
+            );
+        }
+    );
+    let detail = detail.unwrap();
+    assert_eq!(detail.status, OrderStatus::Filled);
+    assert!(start.elapsed() < Duration::from_millis(1500));
+
+    // the latest order detail is returned when the timeout elapses
+    let detail = ctx
+        .wait_for_order(
+            "1",
+            |status| status == OrderStatus::Canceled,
+            Duration::from_millis(100),
+        )
+        .await
+        .unwrap();
+    assert_eq!(detail.status, OrderStatus::Filled);
+}
+
+#[tokio::test]
+async fn test_trade_gave_up() {
+    let server = MockServer::start().await.unwrap();
+    let config = Config::new(server.app_key(), server.app_secret(), server.access_token())
+        .http_url(server.http_url())
+        .trade_ws_url(server.trade_ws_url())
+        .reconnect_policy(ReconnectPolicy::never());
+    let (ctx, _) = TradeContext::try_new(Arc::new(config)).await.unwrap();
+    let mut events = ctx.connection_events();
+
+    server.trade().disconnect();
+    loop {
+        let event = timeout(Duration::from_secs(5), events.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        if matches!(event, ConnectionEvent::GaveUp { .. }) {
+            break;
+        }
+    }
+
+    // the requests return the cause instead of a generic closed error
+    let err = ctx.subscribe([TopicType::Private]).await.unwrap_err();
+    assert!(
+        matches!(err, longport::Error::ConnectionGaveUp { attempts: 0, .. }),
+        "unexpected error: {err:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_credentials() {
+    let server = MockServer::start().await.unwrap();
+    let config = Config::new(server.app_key(), server.app_secret(), "invalid")
+        .http_url(server.http_url())
+        .quote_ws_url(server.quote_ws_url());
+    assert!(QuoteContext::try_new(Arc::new(config)).await.is_err());
+}
+
+#[tokio::test]
+async fn test_rotate_access_token() {
+    let server = MockServer::start().await.unwrap();
+    server.on_http(Method::GET, "/v1/asset/account", |_| {
+        HttpResponse::ok(json!({ "list": [] }))
+    });
+    let access_token = server.access_token().to_string();
+    server.on_http(Method::GET, "/v1/token/refresh", move |_| {
+        HttpResponse::ok(json!({ "token": access_token }))
+    });
+
+    let config = create_config(&server);
+    let (ctx, _) = TradeContext::try_new(config.clone()).await.unwrap();
+
+    // the running context picks up the rotated token
+    config.set_access_token("invalid");
+    assert!(ctx.account_balance(None).await.is_err());
+
+    let mut events = config.spawn_access_token_refresher(OffsetDateTime::now_utc());
+    let event = timeout(Duration::from_secs(5), events.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(event, AccessTokenEvent::RefreshFailed { .. }));
+
+    config.set_access_token(server.access_token());
+    assert!(ctx.account_balance(None).await.unwrap().is_empty());
+    let token = config.refresh_access_token(None).await.unwrap();
+    config.set_access_token(token);
+    assert!(ctx.account_balance(None).await.unwrap().is_empty());
+}